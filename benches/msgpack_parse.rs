@@ -0,0 +1,211 @@
+//! Msgpack decoding throughput on a corpus shaped like production data: mostly small ACL and
+//! membership individuals, and documents with labels in two languages, links and a long text.
+//! `legacy_full` is the decoder before the single-pass rewrite, kept here as the baseline.
+//! Inputs are set up per iteration: batched setup clones the corpus hundreds of times ahead of the
+//! timed loop, so `full` would read cold buffers while the other decoders read the warm corpus.
+
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion, Throughput};
+use v_individual_model::onto::datatype::Lang;
+use v_individual_model::onto::individual::{Individual, RawObj};
+use v_individual_model::onto::individual2msgpack::to_msgpack;
+use v_individual_model::onto::parser::{parse_raw, parse_raw_slice};
+
+fn permission(n: usize) -> Individual {
+    let mut indv = Individual::default();
+    indv.set_id(&format!("d:permission_{}", n));
+    indv.add_uri("rdf:type", "v-s:PermissionStatement").unwrap();
+    indv.add_uri("v-s:permissionObject", &format!("d:document_{}", n / 4)).unwrap();
+    indv.add_uri("v-s:permissionSubject", &format!("cfg:Group_{}", n % 17)).unwrap();
+    indv.add_bool("v-s:canRead", true);
+    if n.is_multiple_of(3) {
+        indv.add_bool("v-s:canUpdate", true);
+    }
+    indv.add_datetime("v-s:created", 1_600_000_000 + n as i64 * 60);
+    indv
+}
+
+fn membership(n: usize) -> Individual {
+    let mut indv = Individual::default();
+    indv.set_id(&format!("d:membership_{}", n));
+    indv.add_uri("rdf:type", "v-s:Membership").unwrap();
+    indv.add_uri("v-s:resource", &format!("d:employee_{}", n)).unwrap();
+    indv.add_uri("v-s:memberOf", &format!("d:org_{}", n % 11)).unwrap();
+    indv.add_uri("v-s:memberOf", &format!("cfg:Group_{}", n % 17)).unwrap();
+    indv
+}
+
+fn document(n: usize) -> Individual {
+    let mut indv = Individual::default();
+    indv.set_id(&format!("d:document_{}", n));
+    indv.add_uri("rdf:type", "v-s:Contract").unwrap();
+    indv.add_string("rdfs:label", &format!("Договор поставки № {}", n), Lang::new_from_str("ru"));
+    indv.add_string("rdfs:label", &format!("Supply contract No. {}", n), Lang::new_from_str("en"));
+    indv.add_string("v-s:description", &"Поставка оборудования согласно спецификации. ".repeat(6), Lang::new_from_str("ru"));
+    indv.add_uri("v-s:creator", &format!("d:appointment_{}", n % 50)).unwrap();
+    indv.add_uri("v-s:lastEditor", &format!("d:appointment_{}", n % 30)).unwrap();
+    indv.add_datetime("v-s:created", 1_600_000_000 + n as i64 * 3600);
+    indv.add_datetime("v-s:edited", 1_600_100_000 + n as i64 * 3600);
+    indv.add_integer("v-s:updateCounter", (n % 9) as i64 + 1);
+    indv.add_decimal_d("v-s:sum", 1_250_000 + n as i64, -2);
+    indv.add_bool("v-s:deleted", false);
+    for f in 0..n % 6 {
+        indv.add_uri("v-s:attachment", &format!("d:file_{}_{}", n, f)).unwrap();
+    }
+    indv.add_uri("v-s:hasStatus", "v-s:StatusAccepted").unwrap();
+    indv
+}
+
+fn corpus() -> Vec<Vec<u8>> {
+    let mut res = Vec::new();
+    for n in 0..500 {
+        let indv = match n % 5 {
+            0 | 1 => permission(n),
+            2 | 3 => membership(n),
+            _ => document(n),
+        };
+        let mut buf = Vec::new();
+        to_msgpack(&indv, &mut buf).expect("serialize corpus");
+        res.push(buf);
+    }
+    res
+}
+
+fn bench(c: &mut Criterion) {
+    let corpus = corpus();
+    let mut group = c.benchmark_group("msgpack_parse");
+    group.throughput(Throughput::Bytes(corpus.iter().map(|b| b.len() as u64).sum()));
+
+    group.bench_function("full", |b| {
+        b.iter_batched(
+            || corpus.iter().map(|buf| Individual::new_raw(RawObj::new(buf.clone()))).collect::<Vec<Individual>>(),
+            |mut indvs| {
+                for indv in indvs.iter_mut() {
+                    parse_raw(indv).expect("parse");
+                    indv.parse_all();
+                }
+                indvs
+            },
+            BatchSize::PerIteration,
+        )
+    });
+
+    group.bench_function("first_predicate", |b| {
+        b.iter_batched(
+            || corpus.iter().map(|buf| Individual::new_raw(RawObj::new(buf.clone()))).collect::<Vec<Individual>>(),
+            |mut indvs| {
+                for indv in indvs.iter_mut() {
+                    parse_raw(indv).expect("parse");
+                    criterion::black_box(indv.get_first_literal("rdf:type"));
+                }
+                indvs
+            },
+            BatchSize::PerIteration,
+        )
+    });
+
+    group.bench_function("slice_full", |b| {
+        b.iter_batched(
+            || (),
+            |_| {
+                corpus
+                    .iter()
+                    .map(|buf| {
+                        let mut indv = Individual::default();
+                        parse_raw_slice(buf, &mut indv).expect("parse");
+                        indv
+                    })
+                    .collect::<Vec<Individual>>()
+            },
+            BatchSize::PerIteration,
+        )
+    });
+
+    group.bench_function("legacy_full", |b| {
+        b.iter_batched(|| (), |_| corpus.iter().map(|buf| legacy::parse(buf).expect("parse")).collect::<Vec<Individual>>(), BatchSize::PerIteration)
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, bench);
+criterion_main!(benches);
+
+/// The decoder before the rewrite: `rmp` reads through a `Cursor`, every string is copied to a
+/// scratch buffer, validated, then copied again, and every value looks its predicate up anew.
+mod legacy {
+    use rmp::decode::*;
+    use rmp::Marker;
+    use std::io::Cursor;
+    use v_individual_model::onto::datatype::{DataType, Lang};
+    use v_individual_model::onto::individual::Individual;
+
+    pub fn parse(data: &[u8]) -> Result<Individual, String> {
+        let mut cur = Cursor::new(data);
+        match read_marker(&mut cur) {
+            Ok(Marker::FixArray(2)) => {},
+            _ => return Err("header".to_owned()),
+        }
+        let mut indv = Individual::default();
+        indv.set_id(&read_string(&mut cur)?);
+        let len = read_map_len(&mut cur).map_err(|e| e.to_string())?;
+
+        for _ in 0..len {
+            let predicate = read_string(&mut cur)?;
+            let size = read_array_len(&mut cur).map_err(|e| e.to_string())?;
+            for _ in 0..size {
+                let size = match read_marker(&mut cur) {
+                    Ok(Marker::FixArray(size)) => size,
+                    m => return Err(format!("{:?}", m)),
+                };
+                let v_type: u8 = read_int(&mut cur).map_err(|e| e.to_string())?;
+                if size == 2 {
+                    if v_type == DataType::Boolean as u8 {
+                        indv.add_bool(&predicate, read_bool(&mut cur).map_err(|e| e.to_string())?);
+                    } else if v_type == DataType::Datetime as u8 {
+                        indv.add_datetime(&predicate, read_int(&mut cur).map_err(|e| e.to_string())?);
+                    } else if v_type == DataType::Integer as u8 {
+                        indv.add_integer(&predicate, read_int(&mut cur).map_err(|e| e.to_string())?);
+                    } else if v_type == DataType::Uri as u8 {
+                        indv.add_uri_unchecked(&predicate, &read_string(&mut cur)?);
+                    } else if v_type == DataType::String as u8 {
+                        indv.add_string(&predicate, &read_string(&mut cur)?, Lang::none());
+                    } else {
+                        return Err(format!("type {}", v_type));
+                    }
+                } else if v_type == DataType::Decimal as u8 {
+                    let mantissa = read_int(&mut cur).map_err(|e| e.to_string())?;
+                    let exponent = read_int(&mut cur).map_err(|e| e.to_string())?;
+                    indv.add_decimal_d(&predicate, mantissa, exponent);
+                } else {
+                    let s = read_string(&mut cur)?;
+                    let lang = match read_marker(&mut cur.clone()) {
+                        Ok(Marker::I8 | Marker::U8 | Marker::FixPos(_)) => Lang::new_from_i64(read_int(&mut cur).map_err(|e| e.to_string())?),
+                        _ => Lang::new_from_str(&read_string(&mut cur)?),
+                    };
+                    indv.add_string(&predicate, &s, lang);
+                }
+            }
+        }
+        Ok(indv)
+    }
+
+    fn read_string(cur: &mut Cursor<&[u8]>) -> Result<String, String> {
+        let m_pos = cur.position();
+        let size = match read_marker(cur).map_err(|e| format!("{:?}", e))? {
+            Marker::FixStr(s) => u32::from(s),
+            Marker::Str8 | Marker::Str16 | Marker::Str32 => {
+                cur.set_position(m_pos);
+                read_str_len(cur).map_err(|e| e.to_string())?
+            },
+            Marker::Null => return Ok(String::new()),
+            m => return Err(format!("{:?}", m)),
+        };
+        cur.set_position(m_pos);
+        let mut out = vec![0u8; size as usize];
+        match read_str(cur, &mut out) {
+            Ok(v) => Ok(v.to_string()),
+            Err(DecodeStringError::InvalidUtf8(buf, _)) => Ok(String::from_utf8_lossy(buf).to_string()),
+            Err(e) => Err(e.to_string()),
+        }
+    }
+}
@@ -0,0 +1,62 @@
+/*
+ * C interface of v-individual-model, available with the `ffi` cargo feature.
+ *
+ * Build a shared library with:
+ *   cargo rustc --release --features ffi --crate-type cdylib
+ *
+ * Return codes: 0 - ok, -1 - error, 1 - value not found.
+ */
+
+#ifndef V_INDIVIDUAL_MODEL_H
+#define V_INDIVIDUAL_MODEL_H
+
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+typedef struct Individual Individual;
+
+typedef struct VBuffer {
+    uint8_t *data;
+    size_t len;
+} VBuffer;
+
+Individual *v_individual_new(void);
+void v_individual_free(Individual *indv);
+
+Individual *v_individual_parse_raw(const uint8_t *data, size_t len);
+Individual *v_individual_parse_json(const char *json);
+
+char *v_individual_get_id(const Individual *indv);
+int v_individual_set_id(Individual *indv, const char *id);
+
+char *v_individual_get_first_literal(Individual *indv, const char *predicate);
+int v_individual_get_first_integer(Individual *indv, const char *predicate, int64_t *out);
+int v_individual_get_first_datetime(Individual *indv, const char *predicate, int64_t *out);
+int v_individual_get_first_bool(Individual *indv, const char *predicate, bool *out);
+
+int v_individual_set_string(Individual *indv, const char *predicate, const char *value, const char *lang);
+int v_individual_add_string(Individual *indv, const char *predicate, const char *value, const char *lang);
+int v_individual_set_uri(Individual *indv, const char *predicate, const char *value);
+int v_individual_add_uri(Individual *indv, const char *predicate, const char *value);
+int v_individual_set_integer(Individual *indv, const char *predicate, int64_t value);
+int v_individual_add_integer(Individual *indv, const char *predicate, int64_t value);
+int v_individual_set_datetime(Individual *indv, const char *predicate, int64_t value);
+int v_individual_set_bool(Individual *indv, const char *predicate, bool value);
+int v_individual_remove(Individual *indv, const char *predicate);
+
+VBuffer v_individual_to_msgpack(Individual *indv);
+char *v_individual_to_json(Individual *indv);
+
+void v_string_free(char *s);
+void v_buffer_free(VBuffer buf);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif
@@ -0,0 +1,59 @@
+//! Converts a dump between record formats, see `dump::convert_dump`:
+//!
+//! ```text
+//! v-dump-convert <input> <output> <from> <to> [parallelism]
+//! ```
+//!
+//! Formats by name (`msgpack`, `cbor`, `json`, `turtle`, `ntriples`) or media type.
+
+use std::env;
+use std::process::ExitCode;
+use std::time::Instant;
+use v_individual_model::onto::dump::convert_dump;
+use v_individual_model::onto::media_type::MediaFormat;
+
+const USAGE: &str = "usage: v-dump-convert <input> <output> <from> <to> [parallelism]";
+
+fn parse_format(s: &str) -> Option<MediaFormat> {
+    match s.to_ascii_lowercase().as_str() {
+        "msgpack" => Some(MediaFormat::Msgpack),
+        "cbor" => Some(MediaFormat::Cbor),
+        "json" => Some(MediaFormat::Json),
+        "turtle" | "ttl" => Some(MediaFormat::Turtle),
+        "ntriples" | "nt" => Some(MediaFormat::NTriples),
+        media_type => MediaFormat::from_media_type(media_type),
+    }
+}
+
+fn main() -> ExitCode {
+    let args: Vec<String> = env::args().skip(1).collect();
+    if args.len() < 4 || args.len() > 5 {
+        eprintln!("{}", USAGE);
+        return ExitCode::from(2);
+    }
+
+    let (Some(from), Some(to)) = (parse_format(&args[2]), parse_format(&args[3])) else {
+        eprintln!("unknown format, {}", USAGE);
+        return ExitCode::from(2);
+    };
+    let parallelism = match args.get(4).map(|p| p.parse::<usize>()) {
+        None => 0,
+        Some(Ok(n)) => n,
+        Some(Err(_)) => {
+            eprintln!("parallelism is not a number, {}", USAGE);
+            return ExitCode::from(2);
+        },
+    };
+
+    let start = Instant::now();
+    match convert_dump(&args[0], &args[1], from, to, parallelism) {
+        Ok(report) => {
+            println!("{} records converted, {} -> {} bytes in {:.1?}", report.records, report.bytes_read, report.bytes_written, start.elapsed());
+            ExitCode::SUCCESS
+        },
+        Err(e) => {
+            eprintln!("{}: {}", args[0], e);
+            ExitCode::FAILURE
+        },
+    }
+}
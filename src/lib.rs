@@ -1,4 +1,25 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+#[cfg(not(feature = "std"))]
+extern crate alloc;
+#[cfg(feature = "std")]
+extern crate std as alloc;
+
+#[cfg(feature = "alloc")]
 #[macro_use]
 extern crate log;
 
+/// Error event with structured fields: `log_error!(uri = id, predicate = p, format = "cbor"; "unknown type {:?}", t)`.
+/// With the `tracing` feature this is a `tracing` event carrying the fields (forwarded to `log` when no subscriber
+/// is installed), otherwise a `log` record with the fields appended to the message.
+#[cfg(feature = "alloc")]
+macro_rules! log_error {
+    ($($k:ident = $v:expr),+ ; $($arg:tt)+) => {{
+        #[cfg(feature = "tracing")]
+        ::tracing::error!($($k = %$v),+, $($arg)+);
+        #[cfg(not(feature = "tracing"))]
+        error!(concat!("{}" $(, ", ", stringify!($k), "={}")+), format_args!($($arg)+), $($v),+);
+    }};
+}
+
 pub mod onto;
@@ -0,0 +1,87 @@
+use crate::onto::onto_impl::Onto;
+use arc_swap::ArcSwapOption;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+static ALIASES: ArcSwapOption<PredicateAliases> = ArcSwapOption::const_empty();
+/// Lets reads skip `ALIASES` while no aliases are set.
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+/// Groups of predicates that mean the same, e.g. the old and the new name of a property while stored
+/// individuals are migrated. Once set by `set_predicate_aliases`, the `get_*`, `is_exists` and
+/// `any_exists` methods of `Individual` read the first alias having values when the predicate asked
+/// for has none.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PredicateAliases {
+    /// The other predicates of the group of each predicate, in the order they were added.
+    aliases: HashMap<String, Vec<String>>,
+}
+
+impl PredicateAliases {
+    pub fn new() -> Self {
+        PredicateAliases::default()
+    }
+
+    /// The `owl:equivalentProperty` declarations the ontology was given, see `Onto::equivalent_properties`.
+    pub fn from_onto(onto: &Onto) -> Self {
+        let mut res = PredicateAliases::new();
+        let mut properties: Vec<(&String, &Vec<String>)> = onto.equivalent_properties.iter().collect();
+        properties.sort();
+        for (a, equivalent) in properties {
+            for b in equivalent {
+                res.add(a, b);
+            }
+        }
+        res
+    }
+
+    /// Makes `a` and `b` equivalent, together with the predicates already equivalent to either.
+    pub fn add(&mut self, a: &str, b: &str) -> &mut Self {
+        if a == b {
+            return self;
+        }
+        let mut group = vec![a.to_owned()];
+        for p in [a, b] {
+            for alias in std::iter::once(p).chain(self.get(p).iter().map(|s| s.as_str())) {
+                if !group.iter().any(|g| g == alias) {
+                    group.push(alias.to_owned());
+                }
+            }
+        }
+        for p in group.iter() {
+            let others = group.iter().filter(|g| *g != p).cloned().collect();
+            self.aliases.insert(p.to_owned(), others);
+        }
+        self
+    }
+
+    pub fn with_alias(mut self, a: &str, b: &str) -> Self {
+        self.add(a, b);
+        self
+    }
+
+    /// The predicates equivalent to `predicate`, in the order they are tried.
+    pub fn get(&self, predicate: &str) -> &[String] {
+        self.aliases.get(predicate).map_or(&[], |v| v.as_slice())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.aliases.is_empty()
+    }
+}
+
+/// Sets the aliases used by all reads of the process, None to stop using them. Can be called again,
+/// e.g. after an ontology reload: `set_predicate_aliases(Some(PredicateAliases::from_onto(&onto)))`.
+pub fn set_predicate_aliases(aliases: Option<PredicateAliases>) {
+    let aliases = aliases.filter(|a| !a.is_empty());
+    ENABLED.store(aliases.is_some(), Ordering::Release);
+    ALIASES.store(aliases.map(Arc::new));
+}
+
+pub fn predicate_aliases() -> Option<Arc<PredicateAliases>> {
+    if !ENABLED.load(Ordering::Acquire) {
+        return None;
+    }
+    ALIASES.load_full()
+}
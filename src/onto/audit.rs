@@ -0,0 +1,101 @@
+use crate::onto::individual::{Individual, IndividualObj};
+use crate::onto::resource::Resource;
+
+pub const UPDATE_TYPE: &str = "v-s:Update";
+pub const PREDICATE_CHANGE_TYPE: &str = "v-s:PredicateChange";
+
+/// Builds audit records of a change: a `v-s:Update` individual with the document, actor and time,
+/// linked by `v-s:hasChange` to one `v-s:PredicateChange` per changed predicate with its old and new values.
+#[derive(Debug, Clone)]
+pub struct AuditBuilder {
+    actor: String,
+    timestamp: i64,
+    id: Option<String>,
+    ignore: Vec<String>,
+}
+
+fn same_values(a: &[Resource], b: &[Resource]) -> bool {
+    a.len() == b.len() && a.iter().all(|r| b.contains(r)) && b.iter().all(|r| a.contains(r))
+}
+
+impl AuditBuilder {
+    /// `timestamp` is the time of the change in seconds, as stored in datetime values.
+    pub fn new(actor: &str, timestamp: i64) -> Self {
+        AuditBuilder {
+            actor: actor.to_owned(),
+            timestamp,
+            id: None,
+            ignore: Vec::new(),
+        }
+    }
+
+    /// Uri of the update individual, by default `<document uri>_upd_<timestamp>`.
+    pub fn with_id(mut self, id: &str) -> Self {
+        self.id = Some(id.to_owned());
+        self
+    }
+
+    /// Predicates not reported as changes, e.g. `v-s:updateCounter`.
+    pub fn ignore(mut self, predicates: &[&str]) -> Self {
+        self.ignore.extend(predicates.iter().map(|p| (*p).to_owned()));
+        self
+    }
+
+    /// Predicates whose values differ between the versions, sorted; value order is not a change.
+    pub fn changed_predicates(&self, prev: Option<&IndividualObj>, new: &IndividualObj) -> Vec<String> {
+        let mut res: Vec<String> = Vec::new();
+        let empty = IndividualObj::default();
+        let prev = prev.unwrap_or(&empty);
+
+        for predicate in prev.resources.keys().chain(new.resources.keys()) {
+            if self.ignore.contains(predicate) || res.contains(predicate) {
+                continue;
+            }
+            let a = prev.resources.get(predicate).map(|v| v.as_slice()).unwrap_or_default();
+            let b = new.resources.get(predicate).map(|v| v.as_slice()).unwrap_or_default();
+            if !same_values(a, b) {
+                res.push(predicate.to_owned());
+            }
+        }
+        res.sort();
+        res
+    }
+
+    /// Returns the update individual followed by the predicate changes, empty if nothing changed.
+    pub fn build(&self, prev: Option<&IndividualObj>, new: &IndividualObj) -> Vec<Individual> {
+        let changed = self.changed_predicates(prev, new);
+        if changed.is_empty() {
+            return Vec::new();
+        }
+
+        let id = self.id.clone().unwrap_or_else(|| format!("{}_upd_{}", new.uri, self.timestamp));
+        let mut update = Individual::default();
+        update.set_id(&id);
+        // uris of stored individuals and predicates, kept as they are
+        update.add_uri_unchecked("rdf:type", UPDATE_TYPE);
+        update.add_uri_unchecked("v-s:onDocument", &new.uri);
+        update.add_uri_unchecked("v-s:creator", &self.actor);
+        update.add_datetime("v-s:created", self.timestamp);
+
+        let mut res = Vec::with_capacity(changed.len() + 1);
+        for (idx, predicate) in changed.iter().enumerate() {
+            let mut change = Individual::default();
+            change.set_id(&format!("{}_{}", id, idx + 1));
+            change.add_uri_unchecked("rdf:type", PREDICATE_CHANGE_TYPE);
+            change.add_uri_unchecked("v-s:predicate", predicate);
+            if let Some(values) = prev.and_then(|p| p.resources.get(predicate)) {
+                change.add_resources("v-s:oldValue", values);
+            }
+            if let Some(values) = new.resources.get(predicate) {
+                change.add_resources("v-s:newValue", values);
+            }
+
+            update.add_uri_unchecked("v-s:hasChange", change.get_id());
+            update.add_uri_unchecked("v-s:changedPredicate", predicate);
+            res.push(change);
+        }
+
+        res.insert(0, update);
+        res
+    }
+}
@@ -0,0 +1,150 @@
+use crate::onto::individual::{Individual, IndividualObj};
+use crate::onto::isomorphism::{is_blank_node, BLANK_NODE_PREFIX};
+use crate::onto::resource::Value;
+use std::collections::HashMap;
+
+/// Path of skolem IRIs under an authority, RDF 1.1 section 3.5.
+pub const GENID_PATH: &str = ".well-known/genid/";
+
+/// Allocates the labels blank nodes get in an export: `_:b0`, `_:b1`, … in the order they are first
+/// met, or skolem IRIs `<base>.well-known/genid/b0`. The same input label always gets the same id
+/// and input labels never reach the output, so labels of several individuals or batches written
+/// with one `BlankNodeIds` cannot collide.
+#[derive(Debug, Default)]
+pub struct BlankNodeIds {
+    ids: HashMap<String, String>,
+    next: usize,
+    skolem_base: Option<String>,
+}
+
+impl BlankNodeIds {
+    pub fn new() -> Self {
+        BlankNodeIds::default()
+    }
+
+    /// Allocates skolem IRIs under `base`, e.g. `http://example.org/`.
+    pub fn skolemizing(base: &str) -> Self {
+        BlankNodeIds {
+            ids: HashMap::new(),
+            next: 0,
+            skolem_base: Some(base.to_owned()),
+        }
+    }
+
+    /// The id of the blank node `label` (`_:x`), allocated on first use.
+    pub fn get(&mut self, label: &str) -> &str {
+        if !self.ids.contains_key(label) {
+            let id = self.fresh();
+            self.ids.insert(label.to_owned(), id);
+        }
+        &self.ids[label]
+    }
+
+    /// A new id not bound to an input label, for nodes the export adds itself such as list cells.
+    pub fn fresh(&mut self) -> String {
+        let n = self.next;
+        self.next += 1;
+        match &self.skolem_base {
+            Some(base) => format!("{}{}b{}", base, GENID_PATH, n),
+            None => format!("_:b{}", n),
+        }
+    }
+
+    /// Number of allocated ids.
+    pub fn len(&self) -> usize {
+        self.next
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.next == 0
+    }
+
+    /// Replaces the blank node uri and `Value::Uri` values of `obj` by their ids, for callers writing
+    /// individuals themselves. Returns the number of replaced terms.
+    pub fn relabel(&mut self, obj: &mut IndividualObj) -> usize {
+        rewrite_terms(obj, |term| {
+            if is_blank_node(term) {
+                Some(self.get(term).to_owned())
+            } else {
+                None
+            }
+        })
+    }
+}
+
+/// Replaces the uri and `Value::Uri` values of `obj` for which `f` gives a new term.
+fn rewrite_terms<F>(obj: &mut IndividualObj, mut f: F) -> usize
+where
+    F: FnMut(&str) -> Option<String>,
+{
+    let mut count = 0;
+    if let Some(uri) = f(&obj.uri) {
+        obj.uri = uri;
+        count += 1;
+    }
+    for values in obj.resources.values_mut() {
+        for r in values.iter_mut() {
+            if let Value::Uri(u) = &mut r.value {
+                if let Some(n) = f(u) {
+                    *u = n;
+                    count += 1;
+                }
+            }
+        }
+    }
+    count
+}
+
+/// The skolem IRI of the blank node `label` (`_:x`) under `base`: `<base>.well-known/genid/x`.
+pub fn skolem_iri(base: &str, label: &str) -> String {
+    format!("{}{}{}", base, GENID_PATH, label.strip_prefix(BLANK_NODE_PREFIX).unwrap_or(label))
+}
+
+/// The blank node label of a skolem IRI under `base`, None for other uris.
+pub fn deskolem_label(base: &str, uri: &str) -> Option<String> {
+    let id = uri.strip_prefix(base)?.strip_prefix(GENID_PATH)?;
+    if id.is_empty() {
+        return None;
+    }
+    Some(format!("{}{}", BLANK_NODE_PREFIX, id))
+}
+
+impl IndividualObj {
+    /// Replaces blank nodes in the uri and `Value::Uri` values by skolem IRIs under `base`, e.g.
+    /// `http://example.org/`. The IRI keeps the label, so individuals skolemized one by one still
+    /// reference each other and `deskolemize` restores the input. Returns the number of replaced terms.
+    pub fn skolemize(&mut self, base: &str) -> usize {
+        rewrite_terms(self, |term| {
+            if is_blank_node(term) {
+                Some(skolem_iri(base, term))
+            } else {
+                None
+            }
+        })
+    }
+
+    /// Replaces skolem IRIs under `base` by blank nodes, the inverse of `skolemize`.
+    pub fn deskolemize(&mut self, base: &str) -> usize {
+        rewrite_terms(self, |term| deskolem_label(base, term))
+    }
+}
+
+impl Individual {
+    pub fn skolemize(&mut self, base: &str) -> usize {
+        self.modify_any(|obj| obj.skolemize(base))
+    }
+
+    pub fn deskolemize(&mut self, base: &str) -> usize {
+        self.modify_any(|obj| obj.deskolemize(base))
+    }
+}
+
+/// `skolemize` over a set, e.g. before sending it as a SPARQL update to a store that rejects blank nodes.
+pub fn skolemize_all(indvs: &mut [Individual], base: &str) -> usize {
+    indvs.iter_mut().map(|indv| indv.skolemize(base)).sum()
+}
+
+/// `deskolemize` over a set, e.g. on the data read back from such a store.
+pub fn deskolemize_all(indvs: &mut [Individual], base: &str) -> usize {
+    indvs.iter_mut().map(|indv| indv.deskolemize(base)).sum()
+}
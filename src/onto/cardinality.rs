@@ -0,0 +1,239 @@
+use crate::onto::datatype::{in_range, Lang};
+use crate::onto::individual::{Individual, IndividualObj};
+use crate::onto::iri::normalize_iri;
+use crate::onto::onto_impl::Onto;
+use crate::onto::resource::{Resource, Value};
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CardinalityError {
+    pub uri: String,
+    pub predicate: String,
+    pub max: usize,
+    pub count: usize,
+}
+
+impl fmt::Display for CardinalityError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "[{}]: predicate [{}] allows at most {} values, found {}", self.uri, self.predicate, self.max, self.count)
+    }
+}
+
+impl std::error::Error for CardinalityError {}
+
+/// A numeric value outside the XSD integer type (`xsd:nonNegativeInteger`, `xsd:int`, ...) the ontology declares as range of the predicate.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RangeError {
+    pub uri: String,
+    pub predicate: String,
+    pub datatype: String,
+    pub value: String,
+}
+
+impl fmt::Display for RangeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "[{}]: predicate [{}], value {} is out of range of {}", self.uri, self.predicate, self.value, self.datatype)
+    }
+}
+
+impl std::error::Error for RangeError {}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AddError {
+    Cardinality(CardinalityError),
+    Range(RangeError),
+}
+
+impl fmt::Display for AddError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            AddError::Cardinality(e) => e.fmt(f),
+            AddError::Range(e) => e.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for AddError {}
+
+impl From<CardinalityError> for AddError {
+    fn from(e: CardinalityError) -> Self {
+        AddError::Cardinality(e)
+    }
+}
+
+impl From<RangeError> for AddError {
+    fn from(e: RangeError) -> Self {
+        AddError::Range(e)
+    }
+}
+
+fn value_to_string(r: &Resource) -> String {
+    match &r.value {
+        Value::Int(i) => i.to_string(),
+        Value::Num(m, e) => format!("{}e{}", m, e),
+        v => format!("{:?}", v),
+    }
+}
+
+/// The first range of the predicate the value does not fit.
+fn check_range(uri: &str, predicate: &str, r: &Resource, onto: &Onto) -> Result<(), RangeError> {
+    match onto.get_ranges(predicate).iter().find(|range| !in_range(&r.value, range)) {
+        Some(range) => Err(RangeError {
+            uri: uri.to_owned(),
+            predicate: predicate.to_owned(),
+            datatype: range.to_owned(),
+            value: value_to_string(r),
+        }),
+        None => Ok(()),
+    }
+}
+
+impl IndividualObj {
+    /// Replaces all values of the predicate by one value, returns the previous values.
+    pub fn set_single(&mut self, predicate: &str, mut r: Resource) -> Vec<Resource> {
+        r.order = 0;
+        self.resources.insert(predicate.to_owned(), vec![r]).unwrap_or_default()
+    }
+
+    /// Error if the uri is malformed, the values are left unchanged then.
+    pub fn set_single_uri(&mut self, predicate: &str, s: &str) -> Result<Vec<Resource>, String> {
+        let v = normalize_iri(s)?;
+        Ok(self.set_single(predicate, Resource::new_uri(&v)))
+    }
+
+    pub fn set_single_string(&mut self, predicate: &str, s: &str, lang: Lang) -> Vec<Resource> {
+        self.set_single(predicate, Resource::new_string(s, lang))
+    }
+
+    pub fn set_single_integer(&mut self, predicate: &str, i: i64) -> Vec<Resource> {
+        self.set_single(predicate, Resource::new_integer(i))
+    }
+
+    pub fn set_single_decimal_d(&mut self, predicate: &str, mantissa: i64, exponent: i64) -> Vec<Resource> {
+        self.set_single(predicate, Resource::new_decimal(mantissa, exponent))
+    }
+
+    pub fn set_single_datetime(&mut self, predicate: &str, i: i64) -> Vec<Resource> {
+        self.set_single(predicate, Resource::new_datetime(i))
+    }
+
+    pub fn set_single_bool(&mut self, predicate: &str, b: bool) -> Vec<Resource> {
+        self.set_single(predicate, Resource::new_bool(b))
+    }
+
+    /// Appends the value if the predicate stays within the maxCardinality declared in the ontology
+    /// and a numeric value fits the XSD integer type of its range (`xsd:nonNegativeInteger`, ...);
+    /// a deprecated predicate is reported to the ontology's deprecation callback.
+    pub fn try_add(&mut self, predicate: &str, r: Resource, onto: &Onto) -> Result<(), AddError> {
+        self.check_add(predicate, &r, onto)?;
+        self.push_value(predicate, r);
+        Ok(())
+    }
+
+    fn check_add(&self, predicate: &str, r: &Resource, onto: &Onto) -> Result<(), AddError> {
+        onto.warn_deprecated(&self.uri, predicate);
+        let count = self.resources.get(predicate).map(|v| v.len()).unwrap_or(0);
+        if let Some(max) = onto.get_max_cardinality(predicate) {
+            if count >= max {
+                return Err(AddError::Cardinality(CardinalityError {
+                    uri: self.uri.to_owned(),
+                    predicate: predicate.to_owned(),
+                    max,
+                    count: count + 1,
+                }));
+            }
+        }
+        check_range(&self.uri, predicate, r, onto)?;
+        Ok(())
+    }
+
+    fn push_value(&mut self, predicate: &str, mut r: Resource) {
+        let values = self.resources.entry(predicate.to_owned()).or_default();
+        r.order = values.len() as u16;
+        values.push(r);
+    }
+
+    /// Returns the predicates holding more values than the ontology allows.
+    pub fn check_cardinality(&self, onto: &Onto) -> Vec<CardinalityError> {
+        let mut res = Vec::new();
+        for (predicate, values) in self.resources.iter() {
+            if let Some(max) = onto.get_max_cardinality(predicate) {
+                if values.len() > max {
+                    res.push(CardinalityError {
+                        uri: self.uri.to_owned(),
+                        predicate: predicate.to_owned(),
+                        max,
+                        count: values.len(),
+                    });
+                }
+            }
+        }
+        res
+    }
+
+    /// Returns the numeric values outside the XSD integer types declared as ranges of their predicates.
+    pub fn check_ranges(&self, onto: &Onto) -> Vec<RangeError> {
+        let mut res = Vec::new();
+        for (predicate, values) in self.resources.iter() {
+            for r in values {
+                if let Err(e) = check_range(&self.uri, predicate, r, onto) {
+                    res.push(e);
+                }
+            }
+        }
+        res
+    }
+}
+
+impl Individual {
+    pub fn set_single(&mut self, predicate: &str, r: Resource) -> Vec<Resource> {
+        self.is_exists(predicate);
+        let old = self.obj_mut().set_single(predicate, r);
+        self.modified(predicate);
+        old
+    }
+
+    pub fn set_single_uri(&mut self, predicate: &str, s: &str) -> Result<Vec<Resource>, String> {
+        let v = normalize_iri(s)?;
+        Ok(self.set_single(predicate, Resource::new_uri(&v)))
+    }
+
+    pub fn set_single_string(&mut self, predicate: &str, s: &str, lang: Lang) -> Vec<Resource> {
+        self.set_single(predicate, Resource::new_string(s, lang))
+    }
+
+    pub fn set_single_integer(&mut self, predicate: &str, i: i64) -> Vec<Resource> {
+        self.set_single(predicate, Resource::new_integer(i))
+    }
+
+    pub fn set_single_decimal_d(&mut self, predicate: &str, mantissa: i64, exponent: i64) -> Vec<Resource> {
+        self.set_single(predicate, Resource::new_decimal(mantissa, exponent))
+    }
+
+    pub fn set_single_datetime(&mut self, predicate: &str, i: i64) -> Vec<Resource> {
+        self.set_single(predicate, Resource::new_datetime(i))
+    }
+
+    pub fn set_single_bool(&mut self, predicate: &str, b: bool) -> Vec<Resource> {
+        self.set_single(predicate, Resource::new_bool(b))
+    }
+
+    pub fn try_add(&mut self, predicate: &str, r: Resource, onto: &Onto) -> Result<(), AddError> {
+        self.is_exists(predicate);
+        // a rejected value leaves the individual clean
+        self.get_obj().check_add(predicate, &r, onto)?;
+        self.obj_mut().push_value(predicate, r);
+        self.modified(predicate);
+        Ok(())
+    }
+
+    pub fn check_cardinality(&mut self, onto: &Onto) -> Vec<CardinalityError> {
+        self.parse_all();
+        self.obj.check_cardinality(onto)
+    }
+
+    pub fn check_ranges(&mut self, onto: &Onto) -> Vec<RangeError> {
+        self.parse_all();
+        self.obj.check_ranges(onto)
+    }
+}
@@ -1,9 +1,13 @@
+#![deny(clippy::unwrap_used, clippy::expect_used, clippy::indexing_slicing, clippy::panic)]
+
 use crate::onto::datatype::Lang;
 use crate::onto::individual::{Individual, IndividualObj, RawObj};
 use crate::onto::parser::RawType;
+use crate::onto::trace::{value_detail, ParseTrace, Token};
 use cbor::types::Type;
 use cbor::{Config, Decoder};
-use std::io::Cursor;
+use std::cell::Cell;
+use std::io::{self, Cursor, Read};
 
 #[derive(Eq, PartialEq, Debug)]
 pub enum TagId {
@@ -19,22 +23,34 @@ pub enum TagId {
     Uri = 32,
 }
 
-pub fn parse_cbor(raw: &mut RawObj) -> Result<String, i8> {
-    if raw.data.is_empty() || raw.raw_type != RawType::Cbor {
-        return Err(-1);
-    }
-
-    let input = Cursor::new(raw.data.to_owned());
+/// Values nest only inside decimal fractions and the per-predicate array, deeper input is rejected
+/// instead of recursing on it.
+const MAX_DEPTH: usize = 4;
 
+/// Limits for decoding `len` bytes: a text, byte string or array can not be longer than the input,
+/// so a corrupted length fails without allocating it.
+fn decoder_config(len: usize) -> Config {
     let mut config = Config::default();
-    if raw.data.len() > 200_000 {
+    if len > 200_000 {
         config.max_len_array = 10000;
     }
+    config.max_len_array = config.max_len_array.min(len);
+    config.max_len_bytes = config.max_len_bytes.min(len);
+    config.max_len_text = config.max_len_text.min(len);
+    config
+}
+
+pub fn parse_cbor(raw: &mut RawObj) -> Result<String, i8> {
+    if raw.data.is_empty() || raw.raw_type != RawType::Cbor {
+        return Err(-1);
+    }
 
-    let mut d = Decoder::new(config, input);
+    let input = Cursor::new(raw.data.as_slice());
+    let mut d = Decoder::new(decoder_config(raw.data.len()), input);
 
     if let Ok(len) = d.object() {
-        raw.len_predicates = len as u32;
+        // the map holds the "@" entry besides the predicates
+        raw.len_predicates = (len as u32).saturating_sub(1);
         if let Ok(type_info) = d.typeinfo() {
             if let Ok(predicate) = d._text(&type_info) {
                 if predicate == "@" {
@@ -63,25 +79,24 @@ pub fn parse_cbor_to_predicate(expect_predicate: &str, iraw: &mut Individual) ->
     let mut cur = Cursor::new(iraw.raw.data.as_slice());
     cur.set_position(iraw.raw.cur);
 
-    let mut config = Config::default();
-    let len = iraw.raw.data.len();
-    if len > 200_000 {
-        config.max_len_array = 10000;
-    }
-    let mut d = Decoder::new(config, cur);
+    let mut d = Decoder::new(decoder_config(iraw.raw.data.len()), cur);
 
-    for _ in iraw.raw.cur_predicates..iraw.raw.len_predicates {
-        if let Ok(type_info) = d.typeinfo() {
-            if let Ok(predicate) = d._text(&type_info) {
-                if predicate == expect_predicate {
-                    is_found = true;
-                }
-                if !add_value(&predicate, &mut d, &mut iraw.obj) {
-                    iraw.raw.cur = d.into_reader().position();
-                    return Err(String::default());
-                }
-            }
+    for i in iraw.raw.cur_predicates..iraw.raw.len_predicates {
+        let predicate = match d.typeinfo().and_then(|type_info| d._text(&type_info)) {
+            Ok(p) => p,
+            Err(e) => {
+                iraw.raw.cur = d.into_reader().position();
+                return Err(format!("fail read predicate, err={:?}", e));
+            },
+        };
+        if predicate == expect_predicate {
+            is_found = true;
         }
+        if let Err(e) = add_value(&predicate, &mut d, &mut iraw.obj, 0, None) {
+            iraw.raw.cur = d.into_reader().position();
+            return Err(e);
+        }
+        iraw.raw.cur_predicates = i + 1;
 
         if is_found {
             iraw.raw.cur = d.into_reader().position();
@@ -93,97 +108,159 @@ pub fn parse_cbor_to_predicate(expect_predicate: &str, iraw: &mut Individual) ->
     Err(String::default())
 }
 
-fn add_value(predicate: &str, d: &mut Decoder<Cursor<&[u8]>>, indv: &mut IndividualObj) -> bool {
-    if let Ok((type_info, tag)) = d.typeinfo_and_tag() {
-        match type_info.0 {
-            Type::Bool => {
-                if let Ok(b) = d._bool(&type_info) {
-                    indv.add_bool(predicate, b);
-                }
-            },
-            Type::Bytes => {
-                if let Ok(t) = d._text(&type_info) {
-                    if tag == TagId::Uri as u64 {
-                        indv.add_uri(predicate, &t);
-                    } else {
-                        let mut lang = Lang::none();
-
-                        if tag == TagId::TextRu as u64 || tag == TagId::TextEn as u64 {
-                            if tag == TagId::TextRu as u64 {
-                                lang = Lang::new_from_str("RU");
-                            } else if tag == TagId::TextEn as u64 {
-                                lang = Lang::new_from_str("EN");
-                            }
-                        }
+/// A cursor that publishes its position, the decoder keeps its reader until `into_reader`.
+struct TracedReader<'a> {
+    cur: Cursor<&'a [u8]>,
+    pos: &'a Cell<u64>,
+}
 
-                        indv.add_string(predicate, &t, lang);
-                    }
-                }
-            },
-            Type::Text => {
-                if let Ok(t) = d._text(&type_info) {
-                    if tag == TagId::Uri as u64 {
-                        indv.add_uri(predicate, &t);
-                    } else {
-                        let mut lang = Lang::none();
-
-                        if tag == TagId::TextRu as u64 || tag == TagId::TextEn as u64 {
-                            if tag == TagId::TextRu as u64 {
-                                lang = Lang::new_from_str("ru");
-                            } else if tag == TagId::TextEn as u64 {
-                                lang = Lang::new_from_str("en");
-                            }
-                        }
+impl Read for TracedReader<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.cur.read(buf)?;
+        self.pos.set(self.cur.position());
+        Ok(n)
+    }
+}
 
-                        indv.add_string(predicate, &t, lang);
-                    }
-                }
-            },
-            Type::UInt8 | Type::Int8 | Type::UInt16 | Type::Int16 | Type::UInt32 | Type::Int32 | Type::Int64 | Type::UInt64 => {
-                if let Ok(mut i) = d._i64(&type_info) {
-                    if i < 0 {
-                        i += 1; // ?! this cbor decoder returned not correct negative number
-                    }
+/// Where `add_value` records the values it decodes, when tracing.
+struct ValueTrace<'a> {
+    pos: &'a Cell<u64>,
+    trace: &'a mut ParseTrace,
+}
 
-                    if tag == TagId::EpochDateTime as u64 {
-                        indv.add_datetime(predicate, i);
-                    } else {
-                        indv.add_integer(predicate, i);
-                    }
-                }
-            },
-            Type::Array => {
-                if let Ok(len) = d._array(&type_info) {
-                    if tag == TagId::DecimalFraction as u64 {
-                        if let Ok((type_info, _tag)) = d.typeinfo_and_tag() {
-                            if let Ok(mut m) = d._i64(&type_info) {
-                                if m < 0 {
-                                    m += 1; // ?! this cbor decoder returned not correct negative number
-                                }
-                                if let Ok((type_info, _tag)) = d.typeinfo_and_tag() {
-                                    if let Ok(mut e) = d._i64(&type_info) {
-                                        if e < 0 {
-                                            e += 1; // ?! this cbor decoder returned not correct negative number
-                                        }
-                                        indv.add_decimal_d(predicate, m, e);
-                                    }
-                                }
-                            }
-                        }
-                    } else {
-                        for _x in 0..len {
-                            add_value(predicate, d, indv);
-                        }
-                    }
+/// Reads a CBOR individual token by token, see `trace::trace_raw`.
+pub(crate) fn trace_cbor(data: &[u8]) -> ParseTrace {
+    let mut trace = ParseTrace::new(RawType::Cbor.as_str());
+    let pos = Cell::new(0);
+    let mut d = Decoder::new(
+        decoder_config(data.len()),
+        TracedReader {
+            cur: Cursor::new(data),
+            pos: &pos,
+        },
+    );
+    let mut obj = IndividualObj::default();
+    let mut predicate = String::new();
+    if let Err(e) = trace_tokens(&mut d, &pos, &mut obj, &mut predicate, &mut trace) {
+        trace.push(pos.get(), Token::Error, &predicate, e);
+    } else if pos.get() < data.len() as u64 {
+        trace.push(pos.get(), Token::Trailing, "", format!("{} bytes", data.len() as u64 - pos.get()));
+    }
+    trace
+}
+
+fn trace_tokens<R: Read>(d: &mut Decoder<R>, pos: &Cell<u64>, obj: &mut IndividualObj, predicate: &mut String, trace: &mut ParseTrace) -> Result<(), String> {
+    let len = d.object().map_err(|e| format!("fail read map, err={:?}", e))?;
+    trace.push(0, Token::Header, "", format!("map of {}", len));
+
+    for i in 0..len {
+        let at = pos.get();
+        let key = d.typeinfo().and_then(|type_info| d._text(&type_info)).map_err(|e| format!("fail read predicate, err={:?}", e))?;
+        if i == 0 {
+            if key != "@" {
+                return Err(format!("expected @, found {:?}", key));
+            }
+            let at = pos.get();
+            let uri = d.typeinfo().and_then(|type_info| d._text(&type_info)).map_err(|e| format!("fail read uri, err={:?}", e))?;
+            trace.push(at, Token::Uri, "", uri);
+            continue;
+        }
+        *predicate = key;
+        trace.push(at, Token::Predicate, predicate, String::new());
+        add_value(
+            predicate,
+            d,
+            obj,
+            0,
+            Some(&mut ValueTrace {
+                pos,
+                trace: &mut *trace,
+            }),
+        )?;
+    }
+    predicate.clear();
+    Ok(())
+}
+
+/// Decodes the value or the array of values of `predicate` into `indv`; with `trace` each of them
+/// is recorded.
+fn add_value<R: Read>(predicate: &str, d: &mut Decoder<R>, indv: &mut IndividualObj, depth: usize, mut trace: Option<&mut ValueTrace>) -> Result<(), String> {
+    if depth > MAX_DEPTH {
+        return Err(format!("values nested deeper than {}", MAX_DEPTH));
+    }
+    let at = trace.as_ref().map_or(0, |t| t.pos.get());
+
+    let (type_info, tag) = d.typeinfo_and_tag().map_err(|e| format!("fail read type, err={:?}", e))?;
+    match type_info.0 {
+        Type::Bool => {
+            let b = d._bool(&type_info).map_err(|e| format!("fail read bool, err={:?}", e))?;
+            indv.add_bool(predicate, b);
+        },
+        Type::Bytes | Type::Text => {
+            let t = d._text(&type_info).map_err(|e| format!("fail read text, err={:?}", e))?;
+            if tag == TagId::Uri as u64 {
+                indv.add_uri_unchecked(predicate, &t);
+            } else {
+                let lang = if tag == TagId::TextRu as u64 {
+                    Lang::new_from_str("ru")
+                } else if tag == TagId::TextEn as u64 {
+                    Lang::new_from_str("en")
                 } else {
-                    return false;
+                    Lang::none()
+                };
+
+                indv.add_string(predicate, &t, lang);
+            }
+        },
+        Type::UInt8 | Type::Int8 | Type::UInt16 | Type::Int16 | Type::UInt32 | Type::Int32 | Type::Int64 | Type::UInt64 => {
+            let i = read_i64(d, &type_info)?;
+            if tag == TagId::EpochDateTime as u64 {
+                indv.add_datetime(predicate, i);
+            } else {
+                indv.add_integer(predicate, i);
+            }
+        },
+        Type::Array => {
+            let len = d._array(&type_info).map_err(|e| format!("fail read array, err={:?}", e))?;
+            if tag == TagId::DecimalFraction as u64 {
+                if len != 2 {
+                    return Err(format!("decimal fraction of {} items", len));
                 }
-            },
-            _ => {
-                error!("parse cbor:unknown type {:?}, predicate={}, id={}", type_info.0, predicate, indv.uri);
-                return false;
-            },
+                let (type_info, _tag) = d.typeinfo_and_tag().map_err(|e| format!("fail read mantissa, err={:?}", e))?;
+                let m = read_i64(d, &type_info)?;
+                let (type_info, _tag) = d.typeinfo_and_tag().map_err(|e| format!("fail read exponent, err={:?}", e))?;
+                let e = read_i64(d, &type_info)?;
+                indv.add_decimal_d(predicate, m, e);
+            } else {
+                if let Some(t) = trace.as_deref_mut() {
+                    t.trace.push(at, Token::Values, predicate, format!("{} values", len));
+                }
+                for _x in 0..len {
+                    add_value(predicate, d, indv, depth + 1, trace.as_deref_mut())?;
+                }
+                return Ok(());
+            }
+        },
+        _ => {
+            log_error!(uri = indv.uri, predicate = predicate, format = "cbor"; "unknown type {:?}", type_info.0);
+            return Err(match trace {
+                Some(_) => format!("unknown type {:?}", type_info.0),
+                None => String::default(),
+            });
+        },
+    }
+    if let Some(t) = trace {
+        if let Some(r) = indv.resources.get(predicate).and_then(|values| values.last()) {
+            t.trace.push(at, Token::Value, predicate, value_detail(r));
         }
     }
-    true
+    Ok(())
+}
+
+fn read_i64<R: Read>(d: &mut Decoder<R>, type_info: &(Type, u8)) -> Result<i64, String> {
+    let mut i = d._i64(type_info).map_err(|e| format!("fail read integer, err={:?}", e))?;
+    if i < 0 {
+        i += 1; // ?! this cbor decoder returned not correct negative number
+    }
+    Ok(i)
 }
@@ -0,0 +1,236 @@
+use crate::onto::individual::IndividualObj;
+use crate::onto::media_type::{decode, encode, MediaError, MediaFormat};
+#[cfg(feature = "tokio-util")]
+use bytes::{Buf, BufMut, BytesMut};
+use std::collections::HashMap;
+use std::fmt;
+#[cfg(feature = "tokio-util")]
+use std::sync::Arc;
+#[cfg(feature = "tokio-util")]
+use tokio_util::codec::{Decoder, Encoder};
+
+/// Prefix of a message, followed by the format id, the schema version (u16 big-endian) and the payload.
+pub const MESSAGE_MAGIC: [u8; 3] = [0xc1, b'V', b'M'];
+
+const HEADER_LEN: usize = MESSAGE_MAGIC.len() + 3;
+
+/// Frames longer than this are rejected by `MessageCodec` unless configured otherwise, 16 MiB.
+pub const DEFAULT_MAX_FRAME: usize = 16 * 1024 * 1024;
+
+impl MediaFormat {
+    /// Format id in message headers.
+    pub fn message_id(self) -> u8 {
+        match self {
+            MediaFormat::Msgpack => 1,
+            MediaFormat::Cbor => 2,
+            MediaFormat::Json => 3,
+            MediaFormat::Turtle => 4,
+            MediaFormat::NTriples => 5,
+            MediaFormat::JsonLd => 6,
+        }
+    }
+
+    pub fn from_message_id(id: u8) -> Option<MediaFormat> {
+        MediaFormat::ALL.into_iter().find(|f| f.message_id() == id)
+    }
+}
+
+/// What a message carries: the payload format and a schema version defined by the application,
+/// so consumers can tell messages of an older or newer producer apart.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MessageHeader {
+    pub format: MediaFormat,
+    pub schema_version: u16,
+}
+
+impl MessageHeader {
+    pub fn new(format: MediaFormat, schema_version: u16) -> Self {
+        MessageHeader {
+            format,
+            schema_version,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CodecError {
+    Truncated,
+    /// The data does not start with `MESSAGE_MAGIC`.
+    NotMessage,
+    UnknownFormat(u8),
+    FrameTooLong {
+        len: usize,
+        max: usize,
+    },
+    Media(MediaError),
+    Io(String),
+}
+
+impl fmt::Display for CodecError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CodecError::Truncated => write!(f, "message is truncated"),
+            CodecError::NotMessage => write!(f, "data is not a tagged message"),
+            CodecError::UnknownFormat(id) => write!(f, "unknown message format {}", id),
+            CodecError::FrameTooLong {
+                len,
+                max,
+            } => write!(f, "frame of {} bytes exceeds the limit of {}", len, max),
+            CodecError::Media(e) => write!(f, "{}", e),
+            CodecError::Io(e) => write!(f, "io error: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for CodecError {}
+
+impl From<MediaError> for CodecError {
+    fn from(e: MediaError) -> Self {
+        CodecError::Media(e)
+    }
+}
+
+impl From<std::io::Error> for CodecError {
+    fn from(e: std::io::Error) -> Self {
+        CodecError::Io(e.to_string())
+    }
+}
+
+pub fn is_message(data: &[u8]) -> bool {
+    data.starts_with(&MESSAGE_MAGIC)
+}
+
+/// Writes the header of a message, the payload follows.
+pub fn write_message_header(header: MessageHeader, out: &mut Vec<u8>) {
+    out.extend_from_slice(&MESSAGE_MAGIC);
+    out.push(header.format.message_id());
+    out.extend_from_slice(&header.schema_version.to_be_bytes());
+}
+
+/// The header of a message and its payload, e.g. to route by format or version before parsing.
+pub fn split_message(data: &[u8]) -> Result<(MessageHeader, &[u8]), CodecError> {
+    if !is_message(data) {
+        return Err(if data.len() < MESSAGE_MAGIC.len() && MESSAGE_MAGIC.starts_with(data) {
+            CodecError::Truncated
+        } else {
+            CodecError::NotMessage
+        });
+    }
+    if data.len() < HEADER_LEN {
+        return Err(CodecError::Truncated);
+    }
+    let id = data[MESSAGE_MAGIC.len()];
+    let format = MediaFormat::from_message_id(id).ok_or(CodecError::UnknownFormat(id))?;
+    let schema_version = u16::from_be_bytes([data[HEADER_LEN - 2], data[HEADER_LEN - 1]]);
+    Ok((MessageHeader::new(format, schema_version), &data[HEADER_LEN..]))
+}
+
+/// A message of one individual in `header.format`, see `media_type::encode`.
+pub fn encode_message(obj: &IndividualObj, header: MessageHeader, all_prefixes: &HashMap<String, String>, out: &mut Vec<u8>) -> Result<(), CodecError> {
+    let payload = encode(obj, header.format, all_prefixes)?;
+    out.reserve(HEADER_LEN + payload.len());
+    write_message_header(header, out);
+    out.extend_from_slice(&payload);
+    Ok(())
+}
+
+/// The individual of a message; of Turtle and N-Triples payloads the first subject.
+pub fn decode_message(data: &[u8], all_prefixes: &HashMap<String, String>) -> Result<(MessageHeader, IndividualObj), CodecError> {
+    let (header, payload) = split_message(data)?;
+    let obj = decode(payload, header.format, all_prefixes)?.into_iter().next().ok_or_else(|| {
+        CodecError::Media(MediaError::Decode {
+            msg: "no individual".to_owned(),
+        })
+    })?;
+    Ok((header, obj))
+}
+
+/// Appends a frame: u32 big-endian length and the data, as in the `Journal` log.
+pub fn write_delimited(data: &[u8], out: &mut Vec<u8>) {
+    out.reserve(4 + data.len());
+    out.extend_from_slice(&(data.len() as u32).to_be_bytes());
+    out.extend_from_slice(data);
+}
+
+/// The first frame of `buf` and the number of bytes it takes, None while it is incomplete.
+pub fn read_delimited(buf: &[u8], max_len: usize) -> Result<Option<(&[u8], usize)>, CodecError> {
+    if buf.len() < 4 {
+        return Ok(None);
+    }
+    let len = u32::from_be_bytes([buf[0], buf[1], buf[2], buf[3]]) as usize;
+    if len > max_len {
+        return Err(CodecError::FrameTooLong {
+            len,
+            max: max_len,
+        });
+    }
+    Ok(buf.get(4..4 + len).map(|frame| (frame, 4 + len)))
+}
+
+/// Length-delimited messages for `tokio_util::codec::Framed` and friends: encodes individuals with
+/// one header, decodes messages of any format and version.
+#[cfg(feature = "tokio-util")]
+#[derive(Debug, Clone)]
+pub struct MessageCodec {
+    header: MessageHeader,
+    max_len: usize,
+    prefixes: Arc<HashMap<String, String>>,
+}
+
+#[cfg(feature = "tokio-util")]
+impl MessageCodec {
+    pub fn new(header: MessageHeader) -> Self {
+        MessageCodec {
+            header,
+            max_len: DEFAULT_MAX_FRAME,
+            prefixes: Arc::new(HashMap::new()),
+        }
+    }
+
+    pub fn with_max_len(mut self, max_len: usize) -> Self {
+        self.max_len = max_len;
+        self
+    }
+
+    /// Prefixes declared in Turtle payloads and compacted when reading them.
+    pub fn with_prefixes(mut self, prefixes: Arc<HashMap<String, String>>) -> Self {
+        self.prefixes = prefixes;
+        self
+    }
+}
+
+#[cfg(feature = "tokio-util")]
+impl Encoder<&IndividualObj> for MessageCodec {
+    type Error = CodecError;
+
+    fn encode(&mut self, obj: &IndividualObj, dst: &mut BytesMut) -> Result<(), Self::Error> {
+        let mut msg = Vec::new();
+        encode_message(obj, self.header, &self.prefixes, &mut msg)?;
+        if msg.len() > self.max_len {
+            return Err(CodecError::FrameTooLong {
+                len: msg.len(),
+                max: self.max_len,
+            });
+        }
+        dst.reserve(4 + msg.len());
+        dst.put_u32(msg.len() as u32);
+        dst.extend_from_slice(&msg);
+        Ok(())
+    }
+}
+
+#[cfg(feature = "tokio-util")]
+impl Decoder for MessageCodec {
+    type Item = (MessageHeader, IndividualObj);
+    type Error = CodecError;
+
+    fn decode(&mut self, src: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
+        let (item, used) = match read_delimited(src, self.max_len)? {
+            Some((frame, used)) => (decode_message(frame, &self.prefixes), used),
+            None => return Ok(None),
+        };
+        // a malformed message is skipped, the next frame can still be read
+        src.advance(used);
+        item.map(Some)
+    }
+}
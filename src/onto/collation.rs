@@ -0,0 +1,123 @@
+use crate::onto::datatype::Lang;
+use crate::onto::decimal;
+use crate::onto::individual::Individual;
+use crate::onto::resource::{Resource, Value};
+#[cfg(feature = "collation")]
+use icu_collator::{Collator, CollatorOptions};
+#[cfg(feature = "collation")]
+use icu_locid::Locale;
+use rust_decimal::Decimal;
+use std::cmp::Ordering;
+
+/// Compares strings by the rules of a locale. With the `collation` feature this is ICU collation,
+/// otherwise a fallback that ignores case first and then compares code points.
+pub struct ValueCollator {
+    #[cfg(feature = "collation")]
+    collator: Collator,
+}
+
+impl ValueCollator {
+    /// `locale` is a BCP 47 tag like `ru` or `en-US`, an empty string selects the root collation.
+    pub fn new(locale: &str) -> Result<Self, String> {
+        #[cfg(feature = "collation")]
+        {
+            let locale: Locale = if locale.is_empty() {
+                Locale::UND
+            } else {
+                locale.parse().map_err(|e| format!("invalid locale [{}]: {:?}", locale, e))?
+            };
+            let collator = Collator::try_new(&(&locale).into(), CollatorOptions::new()).map_err(|e| format!("no collation for [{}]: {:?}", locale, e))?;
+            Ok(ValueCollator {
+                collator,
+            })
+        }
+        #[cfg(not(feature = "collation"))]
+        {
+            let _ = locale;
+            Ok(ValueCollator {})
+        }
+    }
+
+    pub fn compare_str(&self, a: &str, b: &str) -> Ordering {
+        #[cfg(feature = "collation")]
+        {
+            self.collator.compare(a, b)
+        }
+        #[cfg(not(feature = "collation"))]
+        {
+            a.to_lowercase().cmp(&b.to_lowercase()).then_with(|| a.cmp(b))
+        }
+    }
+
+    /// Strings are collated, numbers compared by value (integers and decimals together), values of
+    /// different kinds are ordered by their data type.
+    pub fn compare(&self, a: &Resource, b: &Resource) -> Ordering {
+        match (&a.value, &b.value) {
+            (Value::Str(x, _), Value::Str(y, _)) => self.compare_str(x, y),
+            (Value::Uri(x), Value::Uri(y)) => x.cmp(y),
+            (Value::Bool(x), Value::Bool(y)) => x.cmp(y),
+            (Value::Datetime(x), Value::Datetime(y)) => x.cmp(y),
+            (Value::Binary(x), Value::Binary(y)) => x.cmp(y),
+            (Value::Int(_) | Value::Num(..), Value::Int(_) | Value::Num(..)) => numeric(&a.value).cmp(&numeric(&b.value)),
+            _ => (a.rtype.clone() as u8).cmp(&(b.rtype.clone() as u8)),
+        }
+    }
+}
+
+fn numeric(v: &Value) -> Decimal {
+    match v {
+        Value::Int(i) => Decimal::from(*i),
+        Value::Num(m, e) => decimal::to_decimal(*m, *e).unwrap_or(if *m < 0 {
+            Decimal::MIN
+        } else {
+            Decimal::MAX
+        }),
+        _ => Decimal::ZERO,
+    }
+}
+
+fn sort_key(indv: &mut Individual, predicate: &str, lang: &Lang) -> Option<Resource> {
+    let values = indv.get_resources(predicate)?;
+    let preferred = values.iter().find(|r| matches!(&r.value, Value::Str(_, l) if l == lang));
+    preferred.or_else(|| values.first()).map(|r| r.get_copy())
+}
+
+/// Sorts by the value of `predicate` in `lang` (or its first value) using the collation of `locale`;
+/// individuals without the predicate go last. An unknown locale falls back to the root collation.
+pub fn sort_individuals_by(indvs: &mut [Individual], predicate: &str, lang: &Lang, locale: &str) {
+    let collator = match ValueCollator::new(locale) {
+        Ok(c) => c,
+        Err(e) => {
+            warn!("{}, use root collation", e);
+            match ValueCollator::new("") {
+                Ok(c) => c,
+                Err(_) => return,
+            }
+        },
+    };
+
+    let mut keys: Vec<(Option<Resource>, usize)> = indvs.iter_mut().enumerate().map(|(idx, indv)| (sort_key(indv, predicate, lang), idx)).collect();
+    keys.sort_by(|(a, _), (b, _)| match (a, b) {
+        (Some(a), Some(b)) => collator.compare(a, b),
+        (Some(_), None) => Ordering::Less,
+        (None, Some(_)) => Ordering::Greater,
+        (None, None) => Ordering::Equal,
+    });
+
+    let order: Vec<usize> = keys.into_iter().map(|(_, idx)| idx).collect();
+    apply_permutation(indvs, order);
+}
+
+/// Moves `indvs[order[i]]` to position `i`.
+fn apply_permutation(indvs: &mut [Individual], mut order: Vec<usize>) {
+    for i in 0..order.len() {
+        let mut cur = i;
+        while order[cur] != i {
+            let next = order[cur];
+            indvs.swap(cur, next);
+            order[cur] = cur;
+            cur = next;
+        }
+        order[cur] = cur;
+    }
+}
@@ -0,0 +1,131 @@
+use crate::onto::individual::IndividualObj;
+use crate::onto::resource::{Resource, Value};
+use std::collections::{HashMap, HashSet};
+
+pub const RDF_NIL: &str = "rdf:nil";
+pub const LIST_TYPE: &str = "rdf:List";
+pub const SEQ_TYPE: &str = "rdf:Seq";
+
+/// RDF structure carrying the order of a multivalued predicate.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Container {
+    /// A collection, `( a b c )`: cells with `rdf:first` and `rdf:rest`, ending with `rdf:nil`.
+    List,
+    /// An `rdf:Seq` node with the values as `rdf:_1`, `rdf:_2`, …
+    Seq,
+}
+
+/// Position of a `rdf:_n` membership predicate.
+fn member_index(predicate: &str) -> Option<u32> {
+    predicate.strip_prefix("rdf:_")?.parse().ok().filter(|n| *n > 0)
+}
+
+fn has_only_type(obj: &IndividualObj, rtype: &str) -> bool {
+    match obj.resources.get("rdf:type").map(|v| v.as_slice()) {
+        None => true,
+        Some([r]) => matches!(&r.value, Value::Uri(u) if u == rtype),
+        Some(_) => false,
+    }
+}
+
+fn is_seq(obj: &IndividualObj) -> bool {
+    obj.resources.get("rdf:type").is_some_and(|v| v.iter().any(|r| matches!(&r.value, Value::Uri(u) if u == SEQ_TYPE)))
+        && has_only_type(obj, SEQ_TYPE)
+        && obj.resources.keys().all(|p| p == "rdf:type" || member_index(p).is_some())
+}
+
+/// (first, rest) of a list cell: exactly one `rdf:first` and `rdf:rest`, nothing else but `rdf:type rdf:List`.
+fn list_cell(obj: &IndividualObj) -> Option<(&Resource, &str)> {
+    if !has_only_type(obj, LIST_TYPE) || obj.resources.keys().any(|p| p != "rdf:type" && p != "rdf:first" && p != "rdf:rest") {
+        return None;
+    }
+    match (obj.resources.get("rdf:first")?.as_slice(), obj.resources.get("rdf:rest")?.as_slice()) {
+        (
+            [first],
+            [Resource {
+                value: Value::Uri(rest),
+                ..
+            }],
+        ) => Some((first, rest.as_str())),
+        _ => None,
+    }
+}
+
+/// Members of the container `head` and the positions of its nodes, None if `head` is not a well-formed
+/// list or seq of the set.
+fn members<'a>(objs: &'a [IndividualObj], index: &HashMap<&str, usize>, head: &str) -> Option<(Vec<&'a Resource>, Vec<usize>)> {
+    let pos = *index.get(head)?;
+    if is_seq(&objs[pos]) {
+        let mut items: Vec<(u32, &Resource)> = vec![];
+        for (predicate, values) in &objs[pos].resources {
+            if let Some(n) = member_index(predicate) {
+                items.extend(values.iter().map(|r| (n, r)));
+            }
+        }
+        items.sort_by_key(|(n, _)| *n);
+        return Some((items.into_iter().map(|(_, r)| r).collect(), vec![pos]));
+    }
+
+    let (mut values, mut nodes) = (vec![], vec![]);
+    let mut node = head;
+    while node != RDF_NIL {
+        let pos = *index.get(node)?;
+        if nodes.contains(&pos) {
+            return None;
+        }
+        let (first, rest) = list_cell(&objs[pos])?;
+        values.push(first);
+        nodes.push(pos);
+        node = rest;
+    }
+    Some((values, nodes))
+}
+
+/// Reads containers back into ordered values, e.g. after parsing Turtle into individuals: a
+/// `Value::Uri` naming an `rdf:List` or `rdf:Seq` of the set is replaced by the members, in order,
+/// and the cells are removed from the set. Malformed containers and `rdf:nil` are left alone.
+/// Returns the number of folded containers.
+pub fn fold_lists(objs: &mut Vec<IndividualObj>) -> usize {
+    let index: HashMap<&str, usize> = objs.iter().enumerate().map(|(i, obj)| (obj.uri.as_str(), i)).collect();
+    let is_node: Vec<bool> = objs.iter().map(|obj| is_seq(obj) || list_cell(obj).is_some()).collect();
+
+    // (individual, predicate, value position, members)
+    let mut replace: Vec<(usize, String, usize, Vec<Resource>)> = vec![];
+    let mut consumed = HashSet::new();
+    for (i, obj) in objs.iter().enumerate() {
+        if is_node[i] {
+            continue;
+        }
+        for (predicate, values) in &obj.resources {
+            for (j, r) in values.iter().enumerate() {
+                if let Value::Uri(head) = &r.value {
+                    if let Some((items, nodes)) = members(objs, &index, head) {
+                        if nodes.iter().any(|n| consumed.contains(n)) {
+                            continue;
+                        }
+                        consumed.extend(nodes);
+                        replace.push((i, predicate.to_owned(), j, items.into_iter().map(|r| r.get_copy()).collect()));
+                    }
+                }
+            }
+        }
+    }
+
+    let count = replace.len();
+    // from the last value on, positions of the earlier ones stay valid
+    for (i, predicate, j, items) in replace.into_iter().rev() {
+        if let Some(values) = objs[i].resources.get_mut(&predicate) {
+            values.splice(j..=j, items);
+            for (order, r) in values.iter_mut().enumerate() {
+                r.order = order as u16;
+            }
+        }
+    }
+
+    let mut pos = 0;
+    objs.retain(|_| {
+        pos += 1;
+        !consumed.contains(&(pos - 1))
+    });
+    count
+}
@@ -0,0 +1,235 @@
+use crate::onto::datatype::Lang;
+use crate::onto::individual::{Individual, IndividualObj};
+use crate::onto::resource::Resource;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+
+/// The merge state of a designated predicate is kept as strings in `<predicate>_crdt`,
+/// the predicate itself holds the materialized values, so ordinary readers see plain data.
+pub const CRDT_STATE_SUFFIX: &str = "_crdt";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CrdtKind {
+    /// Grow-only counter, state entries `<replica>=<count>`, the predicate holds the sum.
+    GCounter,
+    /// Last writer wins, state entry `<timestamp>=<replica>`, ties are broken by the replica id.
+    LwwRegister,
+    /// Observed-remove set, state entries `+<tag> <value key>` for adds and `-<tag>` for removed tags.
+    OrSet,
+}
+
+pub fn state_predicate(predicate: &str) -> String {
+    format!("{}{}", predicate, CRDT_STATE_SUFFIX)
+}
+
+fn get_state(obj: &IndividualObj, predicate: &str) -> Vec<String> {
+    obj.resources.get(&state_predicate(predicate)).map(|v| v.iter().map(|r| r.get_str().to_owned()).collect()).unwrap_or_default()
+}
+
+fn set_state(obj: &mut IndividualObj, predicate: &str, entries: impl IntoIterator<Item = String>) {
+    let values: Vec<Resource> = entries.into_iter().map(|s| Resource::new_string(&s, Lang::none())).collect();
+    obj.resources.shift_remove(&state_predicate(predicate));
+    obj.add_resources(&state_predicate(predicate), &values);
+}
+
+fn set_values(obj: &mut IndividualObj, predicate: &str, values: &[Resource]) {
+    obj.resources.shift_remove(predicate);
+    if !values.is_empty() {
+        obj.add_resources(predicate, values);
+    }
+}
+
+fn counter_state(state: &[String]) -> BTreeMap<String, u64> {
+    state.iter().filter_map(|s| s.rsplit_once('=')).filter_map(|(replica, n)| Some((replica.to_owned(), n.parse().ok()?))).collect()
+}
+
+fn write_counter(obj: &mut IndividualObj, predicate: &str, counts: &BTreeMap<String, u64>) {
+    set_state(obj, predicate, counts.iter().map(|(replica, n)| format!("{}={}", replica, n)));
+    obj.set_integer(predicate, counts.values().sum::<u64>() as i64);
+}
+
+fn lww_stamp(state: &[String]) -> Option<(i64, String)> {
+    let (ts, replica) = state.first()?.split_once('=')?;
+    Some((ts.parse().ok()?, replica.to_owned()))
+}
+
+/// Identity of a set element, independent of its position.
+fn value_key(r: &Resource) -> String {
+    format!("{:?}:{:?}", r.rtype, r.value)
+}
+
+#[derive(Default)]
+struct OrSetState {
+    adds: BTreeSet<(String, String)>,
+    removed: BTreeSet<String>,
+}
+
+impl OrSetState {
+    fn new(state: &[String]) -> Self {
+        let mut res = OrSetState::default();
+        for s in state.iter() {
+            if let Some(tag) = s.strip_prefix('-') {
+                res.removed.insert(tag.to_owned());
+            } else if let Some((tag, key)) = s.strip_prefix('+').and_then(|s| s.split_once(' ')) {
+                res.adds.insert((tag.to_owned(), key.to_owned()));
+            }
+        }
+        res
+    }
+
+    fn next_tag(&self, replica: &str) -> String {
+        let prefix = format!("{}.", replica);
+        let last = self.adds.iter().map(|(tag, _)| tag).chain(self.removed.iter()).filter_map(|tag| tag.strip_prefix(&prefix)?.parse::<u64>().ok()).max();
+        format!("{}{}", prefix, last.unwrap_or(0) + 1)
+    }
+
+    /// Writes the state and keeps the values of `candidates` whose adds are not removed.
+    fn write<'a>(mut self, obj: &mut IndividualObj, predicate: &str, candidates: impl Iterator<Item = &'a Resource>) {
+        let removed = &self.removed;
+        self.adds.retain(|(tag, _)| !removed.contains(tag));
+
+        let visible: HashSet<&str> = self.adds.iter().map(|(_, key)| key.as_str()).collect();
+        let mut seen = HashSet::new();
+        let mut values = Vec::new();
+        for r in candidates {
+            let key = value_key(r);
+            if visible.contains(key.as_str()) && seen.insert(key) {
+                values.push(r.get_copy());
+            }
+        }
+
+        let state = self.adds.iter().map(|(tag, key)| format!("+{} {}", tag, key)).chain(self.removed.iter().map(|tag| format!("-{}", tag)));
+        let state: Vec<String> = state.collect();
+        set_state(obj, predicate, state);
+        set_values(obj, predicate, &values);
+    }
+}
+
+/// Designated predicates and their merge semantics, other predicates are left as they are on merge.
+#[derive(Debug, Clone, Default)]
+pub struct CrdtSchema {
+    kinds: HashMap<String, CrdtKind>,
+}
+
+impl CrdtSchema {
+    pub fn new() -> Self {
+        CrdtSchema::default()
+    }
+
+    pub fn with(mut self, predicate: &str, kind: CrdtKind) -> Self {
+        self.kinds.insert(predicate.to_owned(), kind);
+        self
+    }
+
+    pub fn get(&self, predicate: &str) -> Option<CrdtKind> {
+        self.kinds.get(predicate).copied()
+    }
+
+    /// Merges the designated predicates of a replica into `obj`; merging is commutative and idempotent.
+    pub fn merge(&self, obj: &mut IndividualObj, other: &IndividualObj) {
+        for (predicate, kind) in self.kinds.iter() {
+            match kind {
+                CrdtKind::GCounter => {
+                    let mut counts = counter_state(&get_state(obj, predicate));
+                    for (replica, n) in counter_state(&get_state(other, predicate)) {
+                        let c = counts.entry(replica).or_insert(0);
+                        *c = (*c).max(n);
+                    }
+                    if !counts.is_empty() {
+                        write_counter(obj, predicate, &counts);
+                    }
+                },
+                CrdtKind::LwwRegister => {
+                    let theirs = lww_stamp(&get_state(other, predicate));
+                    if theirs.is_some() && theirs > lww_stamp(&get_state(obj, predicate)) {
+                        let values = other.resources.get(predicate).map(|v| v.as_slice()).unwrap_or_default();
+                        set_values(obj, predicate, values);
+                        set_state(obj, predicate, get_state(other, predicate));
+                    }
+                },
+                CrdtKind::OrSet => {
+                    let mut state = OrSetState::new(&get_state(obj, predicate));
+                    let theirs = OrSetState::new(&get_state(other, predicate));
+                    if state.adds.is_empty() && state.removed.is_empty() && theirs.adds.is_empty() && theirs.removed.is_empty() {
+                        continue;
+                    }
+                    state.adds.extend(theirs.adds);
+                    state.removed.extend(theirs.removed);
+
+                    let ours = obj.resources.shift_remove(predicate).unwrap_or_default();
+                    let candidates = ours.iter().chain(other.resources.get(predicate).into_iter().flatten());
+                    state.write(obj, predicate, candidates);
+                },
+            }
+        }
+    }
+}
+
+impl IndividualObj {
+    /// Adds `delta` to the count of `replica` in a G-counter, returns the new total.
+    pub fn crdt_increment(&mut self, predicate: &str, replica: &str, delta: u64) -> u64 {
+        let mut counts = counter_state(&get_state(self, predicate));
+        *counts.entry(replica.to_owned()).or_insert(0) += delta;
+        write_counter(self, predicate, &counts);
+        counts.values().sum()
+    }
+
+    /// Sets an LWW register, ignored (returns false) if the current value was written later.
+    pub fn crdt_lww_set(&mut self, predicate: &str, r: Resource, timestamp: i64, replica: &str) -> bool {
+        let stamp = (timestamp, replica.to_owned());
+        if lww_stamp(&get_state(self, predicate)).is_some_and(|cur| cur > stamp) {
+            return false;
+        }
+        set_values(self, predicate, &[r]);
+        set_state(self, predicate, [format!("{}={}", timestamp, replica)]);
+        true
+    }
+
+    /// Adds an element to an OR-set with a new tag of `replica`.
+    pub fn crdt_set_add(&mut self, predicate: &str, r: Resource, replica: &str) {
+        let mut state = OrSetState::new(&get_state(self, predicate));
+        let tag = state.next_tag(replica);
+        state.adds.insert((tag, value_key(&r)));
+
+        let mut values = self.resources.shift_remove(predicate).unwrap_or_default();
+        values.push(r);
+        state.write(self, predicate, values.iter());
+    }
+
+    /// Removes an element from an OR-set, concurrent adds not yet seen by this replica survive a merge.
+    pub fn crdt_set_remove(&mut self, predicate: &str, r: &Resource) -> bool {
+        let mut state = OrSetState::new(&get_state(self, predicate));
+        let key = value_key(r);
+        let tags: Vec<String> = state.adds.iter().filter(|(_, k)| *k == key).map(|(tag, _)| tag.to_owned()).collect();
+        if tags.is_empty() {
+            return false;
+        }
+        state.removed.extend(tags);
+
+        let values = self.resources.shift_remove(predicate).unwrap_or_default();
+        state.write(self, predicate, values.iter());
+        true
+    }
+}
+
+impl Individual {
+    pub fn crdt_increment(&mut self, predicate: &str, replica: &str, delta: u64) -> u64 {
+        self.modify_any(|obj| obj.crdt_increment(predicate, replica, delta))
+    }
+
+    pub fn crdt_lww_set(&mut self, predicate: &str, r: Resource, timestamp: i64, replica: &str) -> bool {
+        self.modify_any(|obj| obj.crdt_lww_set(predicate, r, timestamp, replica))
+    }
+
+    pub fn crdt_set_add(&mut self, predicate: &str, r: Resource, replica: &str) {
+        self.modify_any(|obj| obj.crdt_set_add(predicate, r, replica))
+    }
+
+    pub fn crdt_set_remove(&mut self, predicate: &str, r: &Resource) -> bool {
+        self.modify_any(|obj| obj.crdt_set_remove(predicate, r))
+    }
+
+    pub fn crdt_merge(&mut self, other: &mut Individual, schema: &CrdtSchema) {
+        other.parse_all();
+        self.modify_any(|obj| schema.merge(obj, &other.obj));
+    }
+}
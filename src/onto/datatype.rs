@@ -1,3 +1,16 @@
+#[cfg(feature = "chrono")]
+use crate::onto::datetime::parse_datetime;
+#[cfg(feature = "std")]
+use crate::onto::decimal::{to_decimal, DecimalConfig};
+#[cfg(feature = "std")]
+use crate::onto::resource::Value;
+#[cfg(feature = "std")]
+use crate::onto::*;
+use alloc::borrow::ToOwned;
+use alloc::string::String;
+#[cfg(feature = "std")]
+use rust_decimal::Decimal;
+
 #[derive(Eq, PartialEq, Debug, Clone)]
 #[repr(u8)]
 pub enum DataType {
@@ -102,3 +115,105 @@ pub fn exponent_to_scale(m: &i64, e: &i64) -> (i64, u32) {
 
     (num, scale)
 }
+
+/// Full XSD IRI of a full IRI or `xsd:` name.
+#[cfg(feature = "std")]
+pub(crate) fn xsd_iri(datatype_iri: &str) -> String {
+    let local = datatype_iri.strip_prefix("http://www.w3.org/2001/XMLSchema#").or_else(|| datatype_iri.strip_prefix("xsd:")).unwrap_or(datatype_iri);
+    format!("http://www.w3.org/2001/XMLSchema#{}", local)
+}
+
+/// Inclusive bounds of the XSD integer types, limited to 64 bit; None for other datatypes.
+#[cfg(feature = "std")]
+pub fn integer_bounds(datatype_iri: &str) -> Option<(i64, i64)> {
+    match xsd_iri(datatype_iri).as_str() {
+        XSD_LONG | XSD_INTEGER => Some((i64::MIN, i64::MAX)),
+        XSD_INT => Some((i32::MIN as i64, i32::MAX as i64)),
+        XSD_NEGATIVE_INTEGER => Some((i64::MIN, -1)),
+        XSD_NON_POSITIVE_INTEGER => Some((i64::MIN, 0)),
+        XSD_NON_NEGATIVE_INTEGER => Some((0, i64::MAX)),
+        XSD_POSITIVE_INTEGER => Some((1, i64::MAX)),
+        _ => None,
+    }
+}
+
+/// Whether the value fits an XSD integer type: an integer, or a decimal without fraction, within its bounds.
+/// Other datatypes and non-numeric values are not checked.
+#[cfg(feature = "std")]
+pub fn in_range(v: &Value, datatype_iri: &str) -> bool {
+    let (lo, hi) = match integer_bounds(datatype_iri) {
+        Some(b) => b,
+        None => return true,
+    };
+    match v {
+        Value::Int(i) => lo <= *i && *i <= hi,
+        Value::Num(m, e) => match to_decimal(*m, *e) {
+            Ok(d) => d.fract().is_zero() && d >= Decimal::from(lo) && d <= Decimal::from(hi),
+            Err(_) => false,
+        },
+        _ => true,
+    }
+}
+
+#[cfg(feature = "std")]
+fn parse_int(s: &str, datatype: &str, (lo, hi): (i64, i64)) -> Result<Value, String> {
+    match s.parse::<i64>() {
+        Ok(v) if lo <= v && v <= hi => Ok(Value::Int(v)),
+        Ok(v) => Err(format!("{} is out of range of {}", v, datatype)),
+        Err(e) => Err(format!("[{}] is not a valid {}: {}", s, datatype, e)),
+    }
+}
+
+/// `v` is whether the parsed number is finite and its shortest decimal form.
+#[cfg(feature = "std")]
+fn parse_float(s: &str, datatype: &str, v: Option<(bool, String)>) -> Result<Value, String> {
+    match v {
+        Some((finite, v)) => match DecimalConfig::default().parse(&v) {
+            Ok((m, e)) if finite => Ok(Value::Num(m, e)),
+            _ => Err(format!("{} [{}] is not representable as a decimal", datatype, s)),
+        },
+        None => Err(format!("[{}] is not a valid {}", s, datatype)),
+    }
+}
+
+/// Converts the lexical form of a value of an XSD datatype (full IRI or `xsd:` name) into a `Value`,
+/// checking the range of the type. Integers are limited to 64 bit, `xsd:float` and `xsd:double` are stored
+/// as decimals, so `INF` and `NaN` are an error; `xsd:dateTime` requires the `chrono` feature.
+#[cfg(feature = "std")]
+pub fn parse_literal(value: &str, datatype_iri: &str) -> Result<Value, String> {
+    let iri = xsd_iri(datatype_iri);
+    // whitespace is collapsed for all types except the strings
+    let s = value.trim();
+
+    if let Some(bounds) = integer_bounds(&iri) {
+        return parse_int(s, &iri.replace("http://www.w3.org/2001/XMLSchema#", "xsd:"), bounds);
+    }
+    match iri.as_str() {
+        XSD_STRING => Ok(Value::Str(value.to_owned(), Lang::none())),
+        XSD_NORMALIZED_STRING => {
+            if value.contains(['\r', '\n', '\t']) {
+                Err(format!("[{}] contains line breaks or tabs, not a valid xsd:normalizedString", value))
+            } else {
+                Ok(Value::Str(value.to_owned(), Lang::none()))
+            }
+        },
+        XSD_BOOLEAN => match s {
+            "true" | "1" => Ok(Value::Bool(true)),
+            "false" | "0" => Ok(Value::Bool(false)),
+            _ => Err(format!("[{}] is not a valid xsd:boolean", s)),
+        },
+        XSD_DECIMAL => {
+            if s.contains(['e', 'E']) {
+                return Err(format!("[{}] is not a valid xsd:decimal, exponents are not allowed", s));
+            }
+            DecimalConfig::default().parse(s).map(|(m, e)| Value::Num(m, e)).map_err(|e| e.to_string())
+        },
+        XSD_FLOAT => parse_float(s, "xsd:float", s.parse::<f32>().ok().map(|v| (v.is_finite(), v.to_string()))),
+        XSD_DOUBLE => parse_float(s, "xsd:double", s.parse::<f64>().ok().map(|v| (v.is_finite(), v.to_string()))),
+        #[cfg(feature = "chrono")]
+        XSD_DATE_TIME => parse_datetime(s).map(Value::Datetime).map_err(|e| e.to_string()),
+        #[cfg(not(feature = "chrono"))]
+        XSD_DATE_TIME => Err("xsd:dateTime needs the chrono feature".to_owned()),
+        _ => Err(format!("unsupported datatype {}", datatype_iri)),
+    }
+}
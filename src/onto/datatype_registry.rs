@@ -0,0 +1,329 @@
+use crate::onto::datatype::{parse_literal, DataType, Lang};
+#[cfg(feature = "chrono")]
+use crate::onto::datetime::{format_datetime, parse_datetime};
+use crate::onto::decimal::{to_decimal, DecimalConfig};
+use crate::onto::individual::{Individual, IndividualObj};
+use crate::onto::onto_impl::Onto;
+use crate::onto::resource::{Resource, Value};
+use crate::onto::*;
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{Arc, OnceLock};
+
+pub(crate) const XSD_NAMESPACE: &str = "http://www.w3.org/2001/XMLSchema#";
+
+pub type ParseFn = dyn Fn(&str) -> Result<Value, String> + Send + Sync;
+pub type FormatFn = dyn Fn(&Value) -> Result<String, String> + Send + Sync;
+pub type ValidateFn = dyn Fn(&Value) -> Result<(), String> + Send + Sync;
+
+/// `http://www.w3.org/2001/XMLSchema#long` -> `xsd:long`, other IRIs as is.
+fn short_iri(iri: &str) -> Cow<'_, str> {
+    match iri.strip_prefix(XSD_NAMESPACE) {
+        Some(local) => Cow::Owned(format!("xsd:{}", local)),
+        None => Cow::Borrowed(iri),
+    }
+}
+
+fn base_name(base: &DataType) -> &'static str {
+    match base {
+        DataType::Uri => "Uri",
+        DataType::String => "String",
+        DataType::Integer => "Integer",
+        DataType::Datetime => "Datetime",
+        DataType::Decimal => "Decimal",
+        DataType::Boolean => "Boolean",
+        DataType::Binary => "Binary",
+    }
+}
+
+/// Lexical form -> value of the storage type.
+fn parse_base(base: &DataType, s: &str) -> Result<Value, String> {
+    match base {
+        DataType::Uri => Ok(Value::Uri(s.to_owned())),
+        DataType::String => Ok(Value::Str(s.to_owned(), Lang::none())),
+        DataType::Integer => s.trim().parse::<i64>().map(Value::Int).map_err(|e| format!("[{}] is not an integer: {}", s, e)),
+        DataType::Decimal => DecimalConfig::default().parse(s.trim()).map(|(m, e)| Value::Num(m, e)).map_err(|e| e.to_string()),
+        DataType::Boolean => match s.trim() {
+            "true" | "1" => Ok(Value::Bool(true)),
+            "false" | "0" => Ok(Value::Bool(false)),
+            _ => Err(format!("[{}] is not a boolean", s)),
+        },
+        #[cfg(feature = "chrono")]
+        DataType::Datetime => parse_datetime(s.trim()).map(Value::Datetime).map_err(|e| e.to_string()),
+        #[cfg(not(feature = "chrono"))]
+        DataType::Datetime => Err("datetime literals need the chrono feature".to_owned()),
+        DataType::Binary => Ok(Value::Binary(s.as_bytes().to_vec())),
+    }
+}
+
+/// Value -> lexical form.
+fn format_base(v: &Value) -> Result<String, String> {
+    match v {
+        Value::Int(i) => Ok(i.to_string()),
+        Value::Str(s, _) | Value::Uri(s) => Ok(s.to_owned()),
+        Value::Bool(b) => Ok(b.to_string()),
+        Value::Num(m, e) => to_decimal(*m, *e).map(|d| d.to_string()).map_err(|e| e.to_string()),
+        #[cfg(feature = "chrono")]
+        Value::Datetime(ts) => format_datetime(*ts).map_err(|e| e.to_string()),
+        #[cfg(not(feature = "chrono"))]
+        Value::Datetime(ts) => Ok(ts.to_string()),
+        Value::Binary(b) => String::from_utf8(b.clone()).map_err(|_| "binary value is not utf-8".to_owned()),
+    }
+}
+
+/// A datatype IRI with the storage type of its values and optional hooks replacing the lexical
+/// conversions of the storage type and adding a check.
+#[derive(Clone)]
+pub struct DatatypeDef {
+    iri: String,
+    base: DataType,
+    parse: Option<Arc<ParseFn>>,
+    format: Option<Arc<FormatFn>>,
+    validate: Option<Arc<ValidateFn>>,
+}
+
+impl fmt::Debug for DatatypeDef {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("DatatypeDef").field("iri", &self.iri).field("base", &self.base).field("validate", &self.validate.is_some()).finish()
+    }
+}
+
+impl DatatypeDef {
+    /// `DatatypeDef::new("v-s:PhoneNumber", DataType::String)` stores phone numbers as strings.
+    pub fn new(iri: &str, base: DataType) -> Self {
+        DatatypeDef {
+            iri: short_iri(iri).into_owned(),
+            base,
+            parse: None,
+            format: None,
+            validate: None,
+        }
+    }
+
+    pub fn with_parse<F>(mut self, f: F) -> Self
+    where
+        F: Fn(&str) -> Result<Value, String> + Send + Sync + 'static,
+    {
+        self.parse = Some(Arc::new(f));
+        self
+    }
+
+    pub fn with_format<F>(mut self, f: F) -> Self
+    where
+        F: Fn(&Value) -> Result<String, String> + Send + Sync + 'static,
+    {
+        self.format = Some(Arc::new(f));
+        self
+    }
+
+    pub fn with_validate<F>(mut self, f: F) -> Self
+    where
+        F: Fn(&Value) -> Result<(), String> + Send + Sync + 'static,
+    {
+        self.validate = Some(Arc::new(f));
+        self
+    }
+
+    pub fn iri(&self) -> &str {
+        &self.iri
+    }
+
+    pub fn base(&self) -> &DataType {
+        &self.base
+    }
+
+    /// The value must have the storage type and pass the validate hook.
+    pub fn validate(&self, v: &Value) -> Result<(), String> {
+        let t = v.data_type();
+        if t != self.base {
+            return Err(format!("{} value for {}, expected {}", base_name(&t), self.iri, base_name(&self.base)));
+        }
+        match &self.validate {
+            Some(f) => f(v),
+            None => Ok(()),
+        }
+    }
+
+    /// Lexical form to a validated resource.
+    pub fn parse(&self, s: &str) -> Result<Resource, String> {
+        let value = match &self.parse {
+            Some(f) => f(s)?,
+            None => parse_base(&self.base, s)?,
+        };
+        self.validate(&value)?;
+        Ok(Resource {
+            rtype: value.data_type(),
+            value,
+            order: 0,
+        })
+    }
+
+    pub fn format(&self, v: &Value) -> Result<String, String> {
+        match &self.format {
+            Some(f) => f(v),
+            None => format_base(v),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DatatypeError {
+    pub uri: String,
+    pub predicate: String,
+    pub message: String,
+}
+
+impl fmt::Display for DatatypeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "[{}]: predicate [{}], {}", self.uri, self.predicate, self.message)
+    }
+}
+
+impl std::error::Error for DatatypeError {}
+
+/// Datatypes by IRI and the datatype of predicates. XSD IRIs may be given in full or as `xsd:` names.
+#[derive(Debug, Clone)]
+pub struct DatatypeRegistry {
+    types: HashMap<String, DatatypeDef>,
+    predicates: HashMap<String, String>,
+}
+
+impl Default for DatatypeRegistry {
+    /// The XSD datatypes of the `onto::XSD_*` constants, parsed and range checked by `parse_literal`.
+    fn default() -> Self {
+        let mut res = DatatypeRegistry {
+            types: HashMap::new(),
+            predicates: HashMap::new(),
+        };
+        let xsd = [
+            (XSD_STRING, DataType::String),
+            (XSD_NORMALIZED_STRING, DataType::String),
+            (XSD_BOOLEAN, DataType::Boolean),
+            (XSD_INTEGER, DataType::Integer),
+            (XSD_LONG, DataType::Integer),
+            (XSD_INT, DataType::Integer),
+            (XSD_NEGATIVE_INTEGER, DataType::Integer),
+            (XSD_NON_POSITIVE_INTEGER, DataType::Integer),
+            (XSD_NON_NEGATIVE_INTEGER, DataType::Integer),
+            (XSD_POSITIVE_INTEGER, DataType::Integer),
+            (XSD_DECIMAL, DataType::Decimal),
+            (XSD_FLOAT, DataType::Decimal),
+            (XSD_DOUBLE, DataType::Decimal),
+            (XSD_DATE_TIME, DataType::Datetime),
+        ];
+        for (iri, base) in xsd {
+            // values of bound predicates get the same range checks as parsed literals
+            let def =
+                DatatypeDef::new(iri, base).with_parse(move |s| parse_literal(s, iri)).with_validate(move |v| parse_literal(&format_base(v)?, iri).map(|_| ()));
+            res.register(def);
+        }
+        res
+    }
+}
+
+impl DatatypeRegistry {
+    pub fn new() -> Self {
+        DatatypeRegistry::default()
+    }
+
+    /// The built-in datatypes only, shared.
+    pub fn builtin() -> &'static DatatypeRegistry {
+        static BUILTIN: OnceLock<DatatypeRegistry> = OnceLock::new();
+        BUILTIN.get_or_init(DatatypeRegistry::default)
+    }
+
+    /// Adds or replaces a datatype, returns the replaced one.
+    pub fn register(&mut self, def: DatatypeDef) -> Option<DatatypeDef> {
+        self.types.insert(def.iri.clone(), def)
+    }
+
+    pub fn get(&self, iri: &str) -> Option<&DatatypeDef> {
+        self.types.get(short_iri(iri).as_ref())
+    }
+
+    /// Values of `predicate` are of datatype `iri`; false if it is not registered.
+    pub fn bind(&mut self, predicate: &str, iri: &str) -> bool {
+        let iri = short_iri(iri);
+        if !self.types.contains_key(iri.as_ref()) {
+            return false;
+        }
+        self.predicates.insert(predicate.to_owned(), iri.into_owned());
+        true
+    }
+
+    /// Binds every property whose `rdfs:range` is a registered datatype.
+    pub fn bind_onto(&mut self, onto: &Onto) {
+        for (property, ranges) in onto.ranges.iter() {
+            if let Some(def) = ranges.iter().find_map(|r| self.get(r)) {
+                let iri = def.iri.clone();
+                self.predicates.insert(property.to_owned(), iri);
+            }
+        }
+    }
+
+    /// The datatype bound to `predicate`, if any.
+    pub fn get_bound(&self, predicate: &str) -> Option<&DatatypeDef> {
+        self.predicates.get(predicate).and_then(|iri| self.types.get(iri))
+    }
+
+    /// The bound datatype when the value has its storage type, else the XSD type of the storage type.
+    /// None for URIs and binaries without a binding.
+    pub fn datatype_of(&self, predicate: &str, r: &Resource) -> Option<&DatatypeDef> {
+        if let Some(def) = self.get_bound(predicate) {
+            if def.base == r.rtype {
+                return Some(def);
+            }
+        }
+        let iri = match r.rtype {
+            DataType::String => "xsd:string",
+            DataType::Integer => "xsd:integer",
+            DataType::Decimal => "xsd:decimal",
+            DataType::Boolean => "xsd:boolean",
+            DataType::Datetime => "xsd:dateTime",
+            DataType::Uri | DataType::Binary => return None,
+        };
+        self.types.get(iri)
+    }
+
+    /// Parses a lexical form of the datatype `iri`.
+    pub fn parse(&self, iri: &str, s: &str) -> Result<Resource, String> {
+        match self.get(iri) {
+            Some(def) => def.parse(s),
+            None => Err(format!("unknown datatype {}", iri)),
+        }
+    }
+
+    /// Checks values of bound predicates, other predicates pass.
+    pub fn validate(&self, predicate: &str, r: &Resource) -> Result<(), String> {
+        match self.get_bound(predicate) {
+            Some(def) => def.validate(&r.value),
+            None => Ok(()),
+        }
+    }
+}
+
+impl IndividualObj {
+    pub fn validate_datatypes(&self, registry: &DatatypeRegistry) -> Vec<DatatypeError> {
+        let mut res = Vec::new();
+        for (predicate, values) in self.resources.iter() {
+            for r in values {
+                if let Err(message) = registry.validate(predicate, r) {
+                    res.push(DatatypeError {
+                        uri: self.uri.clone(),
+                        predicate: predicate.to_owned(),
+                        message,
+                    });
+                }
+            }
+        }
+        res
+    }
+}
+
+impl Individual {
+    pub fn validate_datatypes(&mut self, registry: &DatatypeRegistry) -> Vec<DatatypeError> {
+        self.parse_all();
+        self.obj.validate_datatypes(registry)
+    }
+}
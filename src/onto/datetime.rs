@@ -0,0 +1,109 @@
+use chrono::offset::LocalResult::Single;
+use chrono::{DateTime, Datelike, Local, NaiveDateTime, TimeZone, Timelike, Utc};
+use std::fmt;
+use std::ops::Sub;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DatetimeError {
+    /// The timestamp (seconds) is outside the range chrono can represent (about ±262000 years).
+    OutOfRange(i64),
+    Parse(String),
+}
+
+impl fmt::Display for DatetimeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DatetimeError::OutOfRange(ts) => write!(f, "timestamp {} is out of range", ts),
+            DatetimeError::Parse(s) => write!(f, "fail parse [{}] to datetime", s),
+        }
+    }
+}
+
+impl std::error::Error for DatetimeError {}
+
+pub fn to_utc(ts: i64) -> Result<DateTime<Utc>, DatetimeError> {
+    Utc.timestamp_opt(ts, 0).single().ok_or(DatetimeError::OutOfRange(ts))
+}
+
+/// `xsd:dateTime` in UTC, e.g. `2020-01-01T00:00:00Z`. Years before 1 are written with a minus
+/// sign (`-0044-03-15T00:00:00Z`, year 0 is 1 BC), years after 9999 with more digits and no plus sign.
+pub fn format_datetime(ts: i64) -> Result<String, DatetimeError> {
+    let dt = to_utc(ts)?;
+    let year = dt.year();
+    let sign = if year < 0 {
+        "-"
+    } else {
+        ""
+    };
+    Ok(format!("{}{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z", sign, year.unsigned_abs(), dt.month(), dt.day(), dt.hour(), dt.minute(), dt.second()))
+}
+
+fn parse_offset(s: &str) -> Option<i64> {
+    let b = s.as_bytes();
+    if b.len() != 6 || b[3] != b':' {
+        return None;
+    }
+    let sign = match b[0] {
+        b'+' => 1,
+        b'-' => -1,
+        _ => return None,
+    };
+    let h: i64 = s.get(1..3)?.parse().ok()?;
+    let m: i64 = s.get(4..6)?.parse().ok()?;
+    Some(sign * (h * 3600 + m * 60))
+}
+
+/// Parses a datetime with a timezone (`Z` or `±hh:mm`), fractions of a second are dropped.
+/// Accepts signed and more than four digit years, so everything written by `format_datetime`
+/// and by chrono's `Debug` reads back.
+pub fn parse_datetime(s: &str) -> Result<i64, DatetimeError> {
+    let err = || DatetimeError::Parse(s.to_owned());
+    let (local, offset) = if let Some(v) = s.strip_suffix('Z').or_else(|| s.strip_suffix('z')) {
+        (v, 0)
+    } else if s.len() > 6 && s.is_char_boundary(s.len() - 6) {
+        let (v, tz) = s.split_at(s.len() - 6);
+        (v, parse_offset(tz).ok_or_else(err)?)
+    } else {
+        return Err(err());
+    };
+
+    // chrono requires a sign on years with more than four digits
+    let year_digits = local.trim_start_matches(['+', '-']).find('-').unwrap_or(0);
+    let local = if year_digits > 4 && local.as_bytes()[0].is_ascii_digit() {
+        format!("+{}", local)
+    } else {
+        local.to_owned()
+    };
+
+    let ndt = NaiveDateTime::parse_from_str(&local, "%Y-%m-%dT%H:%M:%S%.f").map_err(|_| err())?;
+    Ok(ndt.and_utc().timestamp() - offset)
+}
+
+/// Like `parse_datetime`, values without a timezone (`2020-01-01T10:00:00`, `2020-01-01`, `01.01.2020`)
+/// are taken in the local timezone.
+pub fn parse_datetime_local(value: &str) -> Result<i64, DatetimeError> {
+    if let Ok(v) = parse_datetime(value) {
+        return Ok(v);
+    }
+
+    let ndt = if value.len() == 10 {
+        if value.contains('.') {
+            NaiveDateTime::parse_from_str(&(value.to_owned() + "T00:00:00"), "%d.%m.%YT%H:%M:%S")
+        } else {
+            NaiveDateTime::parse_from_str(&(value.to_owned() + "T00:00:00"), "%Y-%m-%dT%H:%M:%S")
+        }
+    } else {
+        NaiveDateTime::parse_from_str(value, "%Y-%m-%dT%H:%M:%S")
+    };
+
+    match ndt {
+        Ok(v) => {
+            if let Single(offset) = Local.offset_from_local_datetime(&v) {
+                Ok(v.sub(offset).and_utc().timestamp())
+            } else {
+                Ok(v.and_utc().timestamp())
+            }
+        },
+        Err(_) => Err(DatetimeError::Parse(value.to_owned())),
+    }
+}
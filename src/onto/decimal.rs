@@ -0,0 +1,224 @@
+use num::FromPrimitive;
+use rust_decimal::{Decimal, RoundingStrategy};
+use std::fmt;
+use std::str::FromStr;
+
+/// Largest scale `rust_decimal` can hold.
+pub const MAX_SCALE: u32 = 28;
+
+/// What to do with digits beyond the configured scale.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Rounding {
+    /// Half to even (banker's rounding).
+    #[default]
+    HalfEven,
+    /// Half away from zero.
+    HalfUp,
+    /// Toward zero (truncation).
+    Down,
+    /// Refuse to drop digits, `DecimalError::Inexact`.
+    Exact,
+}
+
+impl Rounding {
+    fn strategy(self) -> Option<RoundingStrategy> {
+        match self {
+            Rounding::HalfEven => Some(RoundingStrategy::MidpointNearestEven),
+            Rounding::HalfUp => Some(RoundingStrategy::MidpointAwayFromZero),
+            Rounding::Down => Some(RoundingStrategy::ToZero),
+            Rounding::Exact => None,
+        }
+    }
+}
+
+/// How serializers write `Value::Num`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DecimalFormat {
+    /// Plain notation, `12.50`; a string in JSON.
+    #[default]
+    Plain,
+    /// A JSON number. Integral values are exact, others go through `f64` and keep 15-17 significant digits.
+    /// Turtle writes the plain form.
+    Number,
+    /// Canonical `xsd:double` notation, `1.25E1`; a string in JSON, typed `xsd:double` in Turtle.
+    Scientific,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DecimalError {
+    /// The value does not fit the mantissa (i64 in individuals, 96 bits in `Decimal`).
+    Overflow(String),
+    /// Digits beyond `max_scale` with `Rounding::Exact`.
+    Inexact {
+        value: String,
+        max_scale: u32,
+    },
+    Parse(String),
+}
+
+impl fmt::Display for DecimalError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DecimalError::Overflow(v) => write!(f, "decimal {} overflows", v),
+            DecimalError::Inexact {
+                value,
+                max_scale,
+            } => write!(f, "decimal {} has more than {} fraction digits", value, max_scale),
+            DecimalError::Parse(v) => write!(f, "fail parse [{}] to decimal", v),
+        }
+    }
+}
+
+impl std::error::Error for DecimalError {}
+
+/// Scale limit and rounding for converting between `Value::Num(mantissa, exponent)` and `Decimal`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DecimalConfig {
+    pub max_scale: u32,
+    pub rounding: Rounding,
+}
+
+impl Default for DecimalConfig {
+    fn default() -> Self {
+        DecimalConfig {
+            max_scale: MAX_SCALE,
+            rounding: Rounding::HalfEven,
+        }
+    }
+}
+
+/// `m / 10^excess` rounded to an integer.
+fn round_int(m: i128, excess: u64, rounding: Rounding) -> Option<i128> {
+    if excess > 38 {
+        // |m| < 10^19, far below half of 10^excess
+        return if m == 0 || rounding != Rounding::Exact {
+            Some(0)
+        } else {
+            None
+        };
+    }
+    let p = 10_i128.pow(excess as u32);
+    let (q, r) = (m / p, (m % p).abs());
+    if r == 0 {
+        return Some(q);
+    }
+    let sign = m.signum();
+    let half = r.cmp(&(p - r));
+    match rounding {
+        Rounding::Exact => None,
+        Rounding::Down => Some(q),
+        Rounding::HalfUp => Some(if half.is_ge() {
+            q + sign
+        } else {
+            q
+        }),
+        Rounding::HalfEven => Some(if half.is_gt() || (half.is_eq() && q % 2 != 0) {
+            q + sign
+        } else {
+            q
+        }),
+    }
+}
+
+impl DecimalConfig {
+    pub fn new(max_scale: u32, rounding: Rounding) -> Self {
+        DecimalConfig {
+            max_scale: max_scale.min(MAX_SCALE),
+            rounding,
+        }
+    }
+
+    /// Rounds to at most `max_scale` fraction digits.
+    pub fn round(&self, d: Decimal) -> Result<Decimal, DecimalError> {
+        if d.scale() <= self.max_scale {
+            return Ok(d);
+        }
+        match self.rounding.strategy() {
+            Some(s) => Ok(d.round_dp_with_strategy(self.max_scale, s)),
+            None => Err(DecimalError::Inexact {
+                value: d.to_string(),
+                max_scale: self.max_scale,
+            }),
+        }
+    }
+
+    /// `mantissa * 10^exponent`, positive exponents that overflow are an error instead of wrapping.
+    pub fn to_decimal(&self, mantissa: i64, exponent: i64) -> Result<Decimal, DecimalError> {
+        let overflow = || DecimalError::Overflow(format!("{}e{}", mantissa, exponent));
+        if exponent >= 0 {
+            let p = u32::try_from(exponent).ok().and_then(|e| 10_i128.checked_pow(e)).ok_or_else(overflow)?;
+            let v = (mantissa as i128).checked_mul(p).ok_or_else(overflow)?;
+            return Decimal::try_from_i128_with_scale(v, 0).map_err(|_| overflow());
+        }
+
+        let scale = exponent.unsigned_abs();
+        if scale <= self.max_scale as u64 {
+            return Decimal::try_from_i128_with_scale(mantissa as i128, scale as u32).map_err(|_| overflow());
+        }
+        let m = round_int(mantissa as i128, scale - self.max_scale as u64, self.rounding).ok_or_else(|| DecimalError::Inexact {
+            value: format!("{}e{}", mantissa, exponent),
+            max_scale: self.max_scale,
+        })?;
+        Decimal::try_from_i128_with_scale(m, self.max_scale).map_err(|_| overflow())
+    }
+
+    /// Mantissa and exponent for `Value::Num`, after rounding to `max_scale`.
+    pub fn to_parts(&self, d: Decimal) -> Result<(i64, i64), DecimalError> {
+        let d = self.round(d)?;
+        let m = i64::try_from(d.mantissa()).map_err(|_| DecimalError::Overflow(d.to_string()))?;
+        Ok((m, -(d.scale() as i64)))
+    }
+
+    pub fn parse(&self, s: &str) -> Result<(i64, i64), DecimalError> {
+        let d = Decimal::from_str(s).or_else(|_| Decimal::from_scientific(s)).map_err(|_| DecimalError::Parse(s.to_owned()))?;
+        self.to_parts(d)
+    }
+
+    pub fn from_f64(&self, v: f64) -> Result<(i64, i64), DecimalError> {
+        let d = Decimal::from_f64(v).ok_or_else(|| DecimalError::Parse(v.to_string()))?;
+        self.to_parts(d)
+    }
+}
+
+/// `Value::Num` as `Decimal` with the default config: exact up to 28 fraction digits, then half to even.
+pub fn to_decimal(mantissa: i64, exponent: i64) -> Result<Decimal, DecimalError> {
+    DecimalConfig::default().to_decimal(mantissa, exponent)
+}
+
+/// Mantissa and exponent of `d`, keeping its scale. A mantissa beyond `i64` loses its last digits,
+/// rounded half to even, the exponent grows instead.
+pub fn to_parts_nearest(d: Decimal) -> (i64, i64) {
+    let mut m = d.mantissa();
+    let mut e = -(d.scale() as i64);
+    loop {
+        match i64::try_from(m) {
+            Ok(m) => return (m, e),
+            Err(_) => {
+                m = round_int(m, 1, Rounding::HalfEven).unwrap_or_default();
+                e += 1;
+            },
+        }
+    }
+}
+
+/// `mantissa * 10^exponent` in canonical `xsd:double` notation: `1.25E1`, `-5.0E-3`, `0.0E0`.
+/// Works on the parts directly, so values beyond the range of `Decimal` are written too.
+pub fn format_scientific(mantissa: i64, exponent: i64) -> String {
+    if mantissa == 0 {
+        return "0.0E0".to_owned();
+    }
+    let all = mantissa.unsigned_abs().to_string();
+    let exp = exponent as i128 + all.len() as i128 - 1;
+    let digits = all.trim_end_matches('0');
+    let sign = if mantissa < 0 {
+        "-"
+    } else {
+        ""
+    };
+    let fraction = if digits.len() > 1 {
+        &digits[1..]
+    } else {
+        "0"
+    };
+    format!("{}{}.{}E{}", sign, &digits[..1], fraction, exp)
+}
@@ -0,0 +1,264 @@
+use crate::onto::media_type::{decode, encode, MediaError, MediaFormat};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, ErrorKind, Read, Seek, SeekFrom, Write};
+use std::path::Path;
+use std::sync::mpsc::sync_channel;
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+/// Records a worker converts at once.
+const BATCH_SIZE: usize = 256;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DumpError {
+    Io(String),
+    /// The dump ends inside the record after `records` complete ones.
+    Truncated {
+        records: usize,
+    },
+    /// A format this build does not read or write.
+    Media(MediaError),
+    /// The record at `index` (0-based) could not be converted.
+    Record {
+        index: usize,
+        error: MediaError,
+    },
+    /// The output does not have a record for each record of the input.
+    CountMismatch {
+        read: usize,
+        written: usize,
+    },
+}
+
+impl fmt::Display for DumpError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DumpError::Io(e) => write!(f, "io error: {}", e),
+            DumpError::Truncated {
+                records,
+            } => write!(f, "dump is truncated after {} records", records),
+            DumpError::Media(e) => write!(f, "{}", e),
+            DumpError::Record {
+                index,
+                error,
+            } => write!(f, "record {}: {}", index, error),
+            DumpError::CountMismatch {
+                read,
+                written,
+            } => write!(f, "{} records read, {} written", read, written),
+        }
+    }
+}
+
+impl std::error::Error for DumpError {}
+
+impl From<io::Error> for DumpError {
+    fn from(e: io::Error) -> Self {
+        DumpError::Io(e.to_string())
+    }
+}
+
+/// Result of `convert_dump`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ConvertReport {
+    pub records: usize,
+    pub bytes_read: u64,
+    pub bytes_written: u64,
+}
+
+/// The next record of a dump, a snapshot or a journal (u32 big-endian length and the body), None at
+/// the end. A record cut off is an `UnexpectedEof` error.
+pub fn read_record<R: Read>(src: &mut R) -> io::Result<Option<Vec<u8>>> {
+    let mut len_buf = [0u8; 4];
+    let mut got = 0;
+    while got < len_buf.len() {
+        match src.read(&mut len_buf[got..]) {
+            Ok(0) => break,
+            Ok(n) => got += n,
+            Err(e) if e.kind() == ErrorKind::Interrupted => {},
+            Err(e) => return Err(e),
+        }
+    }
+    if got == 0 {
+        return Ok(None);
+    }
+
+    let len = u32::from_be_bytes(len_buf) as u64;
+    let mut data = Vec::new();
+    // the body is read as it comes, a damaged length does not allocate 4 GiB up front
+    if got < len_buf.len() || src.take(len).read_to_end(&mut data)? as u64 != len {
+        return Err(io::Error::new(ErrorKind::UnexpectedEof, "input ends inside a record"));
+    }
+    Ok(Some(data))
+}
+
+pub fn write_record<W: Write>(out: &mut W, data: &[u8]) -> io::Result<()> {
+    let len = u32::try_from(data.len()).map_err(|_| io::Error::new(ErrorKind::InvalidInput, "record is longer than 4 GiB"))?;
+    out.write_all(&len.to_be_bytes())?;
+    out.write_all(data)
+}
+
+/// The number of records in a dump file, read by skipping the bodies.
+pub fn count_records<P: AsRef<Path>>(path: P) -> Result<usize, DumpError> {
+    let file = File::open(path)?;
+    let len = file.metadata()?.len();
+    let mut src = BufReader::new(file);
+    let (mut count, mut pos) = (0, 0u64);
+    let mut len_buf = [0u8; 4];
+    while pos < len {
+        if pos + 4 > len {
+            return Err(DumpError::Truncated {
+                records: count,
+            });
+        }
+        src.read_exact(&mut len_buf)?;
+        let body = u32::from_be_bytes(len_buf) as u64;
+        pos += 4 + body;
+        if pos > len {
+            return Err(DumpError::Truncated {
+                records: count,
+            });
+        }
+        src.seek(SeekFrom::Start(pos))?;
+        count += 1;
+    }
+    Ok(count)
+}
+
+fn convert_record(data: &[u8], from: MediaFormat, to: MediaFormat, prefixes: &HashMap<String, String>) -> Result<Vec<u8>, MediaError> {
+    match decode(data, from, prefixes)?.as_slice() {
+        [obj] => encode(obj, to, prefixes),
+        objs => Err(MediaError::Decode {
+            msg: format!("{} individuals in the record", objs.len()),
+        }),
+    }
+}
+
+/// Converts a dump whose records are in format `from` into a dump of records in format `to`, as
+/// done for storage migrations. The input is read as a stream, `parallelism` threads (0 for one
+/// per CPU) convert batches of records and the output keeps the input order. The output is then
+/// counted again: it must have one record per input record. Turtle records are written without
+/// prefix declarations and read without prefixes.
+///
+/// ```text
+/// let report = convert_dump("individuals.cbor.dump", "individuals.msgpack.dump", MediaFormat::Cbor, MediaFormat::Msgpack, 0)?;
+/// ```
+pub fn convert_dump<P: AsRef<Path>, Q: AsRef<Path>>(
+    input_path: P,
+    output_path: Q,
+    from: MediaFormat,
+    to: MediaFormat,
+    parallelism: usize,
+) -> Result<ConvertReport, DumpError> {
+    if !from.can_decode() || !to.can_encode() {
+        let format = if from.can_decode() {
+            to
+        } else {
+            from
+        };
+        return Err(DumpError::Media(MediaError::Unsupported {
+            media_type: format.media_type().to_owned(),
+        }));
+    }
+    let workers = if parallelism == 0 {
+        thread::available_parallelism().map(|n| n.get()).unwrap_or(1)
+    } else {
+        parallelism
+    };
+
+    let input = File::open(input_path)?;
+    let bytes_read = input.metadata()?.len();
+    let mut src = BufReader::new(input);
+    let mut out = BufWriter::new(File::create(&output_path)?);
+    let prefixes = HashMap::new();
+
+    // bounded channels keep a few batches per worker in memory, not the dump
+    let (batch_tx, batch_rx) = sync_channel::<(usize, Vec<Vec<u8>>)>(workers * 2);
+    let batch_rx = Arc::new(Mutex::new(batch_rx));
+    let (res_tx, res_rx) = sync_channel::<(usize, Result<Vec<Vec<u8>>, DumpError>)>(workers * 2);
+
+    let (read, written) = thread::scope(|s| {
+        for _ in 0..workers {
+            let (batch_rx, res_tx, prefixes) = (batch_rx.clone(), res_tx.clone(), &prefixes);
+            s.spawn(move || loop {
+                let Ok(Ok((first, batch))) = batch_rx.lock().map(|rx| rx.recv()) else {
+                    break;
+                };
+                let res = batch
+                    .iter()
+                    .enumerate()
+                    .map(|(i, data)| {
+                        convert_record(data, from, to, prefixes).map_err(|error| DumpError::Record {
+                            index: first + i,
+                            error,
+                        })
+                    })
+                    .collect();
+                if res_tx.send((first, res)).is_err() {
+                    break;
+                }
+            });
+        }
+        // the workers own the channels now: when the writer stops they stop, then the reader
+        drop((batch_rx, res_tx));
+
+        let reader = s.spawn(move || -> Result<usize, DumpError> {
+            let mut count = 0;
+            loop {
+                let mut batch = Vec::with_capacity(BATCH_SIZE);
+                while batch.len() < BATCH_SIZE {
+                    match read_record(&mut src) {
+                        Ok(Some(data)) => batch.push(data),
+                        Ok(None) => break,
+                        Err(e) if e.kind() == ErrorKind::UnexpectedEof => {
+                            return Err(DumpError::Truncated {
+                                records: count + batch.len(),
+                            })
+                        },
+                        Err(e) => return Err(e.into()),
+                    }
+                }
+                let len = batch.len();
+                if len == 0 || batch_tx.send((count, batch)).is_err() {
+                    return Ok(count);
+                }
+                count += len;
+            }
+        });
+
+        let write = move || -> Result<usize, DumpError> {
+            let mut pending = BTreeMap::new();
+            let mut written = 0;
+            for (first, res) in res_rx.iter() {
+                pending.insert(first, res);
+                while let Some(records) = pending.remove(&written) {
+                    for data in records? {
+                        write_record(&mut out, &data)?;
+                        written += 1;
+                    }
+                }
+            }
+            out.into_inner().map_err(|e| e.into_error())?.sync_all()?;
+            Ok(written)
+        };
+        let written = write();
+        let read = reader.join().unwrap_or_else(|_| Err(DumpError::Io("reader thread panicked".to_owned())));
+        (read, written)
+    });
+    let (read, written) = (read?, written?);
+
+    let count = count_records(&output_path)?;
+    if read != written || count != read {
+        return Err(DumpError::CountMismatch {
+            read,
+            written: count,
+        });
+    }
+    Ok(ConvertReport {
+        records: read,
+        bytes_read,
+        bytes_written: std::fs::metadata(&output_path)?.len(),
+    })
+}
@@ -0,0 +1,495 @@
+use std::fmt;
+
+/// First bytes of an enveloped raw individual, `0xc1` is never used by msgpack and is not a valid CBOR map start.
+pub const ENVELOPE_MAGIC: [u8; 3] = [0xc1, b'V', b'E'];
+
+/// magic, codec, flags, length of the inner data (u32 big endian)
+const HEADER_LEN: usize = 9;
+
+/// The payload is encrypted with XChaCha20-Poly1305, the header is followed by the key id (u8 length + bytes).
+pub const FLAG_ENCRYPTED: u8 = 0x01;
+/// The envelope ends with a CRC32C (u32 big endian) of all preceding bytes.
+pub const FLAG_CRC32C: u8 = 0x02;
+/// The envelope ends with an XXH3-64 (u64 big endian) of all preceding bytes.
+pub const FLAG_XXH3: u8 = 0x04;
+/// The header is followed by extensions: u8 count, then per extension u8 tag, u16 big endian length and the data.
+pub const FLAG_EXTENSIONS: u8 = 0x08;
+const CHECKSUM_FLAGS: u8 = FLAG_CRC32C | FLAG_XXH3;
+const KNOWN_FLAGS: u8 = FLAG_ENCRYPTED | CHECKSUM_FLAGS | FLAG_EXTENSIONS;
+
+/// Extension with the rights masks of predicates, see `rights::encode_rights`.
+pub const EXT_RIGHTS: u8 = 1;
+
+/// Largest unpacked size accepted by `unpack` and `unpack_with`, the size is read from the header
+/// before decompressing, so it is not trusted.
+pub const DEFAULT_MAX_INNER_LEN: usize = 64 * 1024 * 1024;
+
+#[cfg(feature = "encryption")]
+const NONCE_LEN: usize = 24;
+
+/// 256-bit XChaCha20-Poly1305 key.
+pub type Key = [u8; 32];
+
+/// Returns the key for a key id.
+pub type KeyProvider = dyn Fn(&str) -> Option<Key>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum Codec {
+    None = 0,
+    Zstd = 1,
+    Lz4 = 2,
+}
+
+impl Codec {
+    pub fn from_u8(v: u8) -> Option<Codec> {
+        match v {
+            0 => Some(Codec::None),
+            1 => Some(Codec::Zstd),
+            2 => Some(Codec::Lz4),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Checksum {
+    None,
+    Crc32c,
+    Xxh3,
+}
+
+impl Checksum {
+    fn from_flags(flags: u8) -> Result<Checksum, EnvelopeError> {
+        match flags & CHECKSUM_FLAGS {
+            0 => Ok(Checksum::None),
+            FLAG_CRC32C => Ok(Checksum::Crc32c),
+            FLAG_XXH3 => Ok(Checksum::Xxh3),
+            _ => Err(EnvelopeError::UnsupportedFlags(flags)),
+        }
+    }
+
+    fn flag(self) -> u8 {
+        match self {
+            Checksum::None => 0,
+            Checksum::Crc32c => FLAG_CRC32C,
+            Checksum::Xxh3 => FLAG_XXH3,
+        }
+    }
+
+    fn len(self) -> usize {
+        match self {
+            Checksum::None => 0,
+            Checksum::Crc32c => 4,
+            Checksum::Xxh3 => 8,
+        }
+    }
+
+    fn compute(self, data: &[u8]) -> u64 {
+        match self {
+            Checksum::None => 0,
+            Checksum::Crc32c => crc32c::crc32c(data) as u64,
+            Checksum::Xxh3 => xxhash_rust::xxh3::xxh3_64(data),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EnvelopeError {
+    Truncated,
+    UnknownCodec(u8),
+    UnsupportedFlags(u8),
+    /// The codec is known but the crate was built without it.
+    CodecNotEnabled(Codec),
+    Codec(String),
+    /// The envelope is encrypted and the crate was built without the encryption feature.
+    EncryptionNotEnabled,
+    /// The key provider has no key with this id.
+    MissingKey(String),
+    /// Wrong key or the data was tampered with.
+    Decrypt,
+    InvalidKeyId,
+    /// More than 255 extensions or one longer than 65535 bytes.
+    InvalidExtension,
+    /// Extensions of an encrypted envelope are authenticated, they are given to `pack_encrypted_with`.
+    Sealed,
+    /// The header gives an unpacked size above the accepted maximum.
+    TooLarge {
+        inner_len: u32,
+        max: usize,
+    },
+    /// The stored checksum does not match the data.
+    Corrupted {
+        expected: u64,
+        actual: u64,
+    },
+}
+
+impl fmt::Display for EnvelopeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            EnvelopeError::Truncated => write!(f, "envelope is truncated"),
+            EnvelopeError::UnknownCodec(c) => write!(f, "unknown envelope codec {}", c),
+            EnvelopeError::UnsupportedFlags(v) => write!(f, "unsupported envelope flags {:#04x}", v),
+            EnvelopeError::CodecNotEnabled(c) => write!(f, "codec {:?} is not enabled, build with the compression feature", c),
+            EnvelopeError::Codec(e) => write!(f, "codec error: {}", e),
+            EnvelopeError::EncryptionNotEnabled => write!(f, "envelope is encrypted, build with the encryption feature"),
+            EnvelopeError::MissingKey(id) => write!(f, "no key [{}] to decrypt envelope", id),
+            EnvelopeError::Decrypt => write!(f, "envelope decryption failed"),
+            EnvelopeError::InvalidKeyId => write!(f, "key id must be 1..255 bytes"),
+            EnvelopeError::InvalidExtension => write!(f, "envelope extensions must be at most 255 of at most 65535 bytes"),
+            EnvelopeError::Sealed => write!(f, "extensions of an encrypted envelope are set when it is packed"),
+            EnvelopeError::TooLarge {
+                inner_len,
+                max,
+            } => write!(f, "envelope holds {} bytes, at most {} are accepted", inner_len, max),
+            EnvelopeError::Corrupted {
+                expected,
+                actual,
+            } => write!(f, "envelope is corrupted, checksum {:#x} expected, {:#x} found", expected, actual),
+        }
+    }
+}
+
+impl std::error::Error for EnvelopeError {}
+
+/// Wrapper around a msgpack or CBOR individual: `ENVELOPE_MAGIC`, codec id, flags, inner length, the key id
+/// of encrypted envelopes, extensions, the payload and an optional checksum.
+/// `parse_raw` recognizes it, verifies the checksum and unpacks the individual before parsing.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RawEnvelope {
+    pub codec: Codec,
+    pub flags: u8,
+    /// Length of the data before compression.
+    pub inner_len: u32,
+    /// Set for encrypted envelopes.
+    pub key_id: Option<String>,
+    /// Metadata kept outside the payload by tag, e.g. `EXT_RIGHTS`.
+    pub extensions: Vec<(u8, Vec<u8>)>,
+    /// Compressed data, prefixed with the nonce when encrypted.
+    pub payload: Vec<u8>,
+}
+
+pub fn is_envelope(data: &[u8]) -> bool {
+    data.starts_with(&ENVELOPE_MAGIC)
+}
+
+impl RawEnvelope {
+    /// Compresses a raw individual with the given codec.
+    pub fn pack(data: &[u8], codec: Codec) -> Result<RawEnvelope, EnvelopeError> {
+        Ok(RawEnvelope {
+            codec,
+            flags: 0,
+            inner_len: data.len() as u32,
+            key_id: None,
+            extensions: Vec::new(),
+            payload: compress(data, codec)?,
+        })
+    }
+
+    /// Compresses and encrypts a raw individual, `key_id` is stored in clear to find the key on decryption.
+    pub fn pack_encrypted(data: &[u8], codec: Codec, key_id: &str, key: &Key) -> Result<RawEnvelope, EnvelopeError> {
+        RawEnvelope::pack_encrypted_with(data, codec, key_id, key, Vec::new())
+    }
+
+    /// Same as `pack_encrypted` with extensions, which are authenticated together with the header.
+    pub fn pack_encrypted_with(data: &[u8], codec: Codec, key_id: &str, key: &Key, extensions: Vec<(u8, Vec<u8>)>) -> Result<RawEnvelope, EnvelopeError> {
+        if key_id.is_empty() || key_id.len() > u8::MAX as usize {
+            return Err(EnvelopeError::InvalidKeyId);
+        }
+
+        let mut res = RawEnvelope {
+            codec,
+            flags: FLAG_ENCRYPTED,
+            inner_len: data.len() as u32,
+            key_id: Some(key_id.to_owned()),
+            extensions: Vec::new(),
+            payload: Vec::new(),
+        };
+        for (tag, ext) in extensions {
+            res.push_extension(tag, ext)?;
+        }
+        res.payload = encrypt(&compress(data, codec)?, key, &res.authenticated_header())?;
+        Ok(res)
+    }
+
+    /// Sets the checksum written by `encode`. It is not part of the authenticated data,
+    /// so it can be added to or removed from an encrypted envelope without the key.
+    pub fn with_checksum(mut self, checksum: Checksum) -> Self {
+        self.flags = (self.flags & !CHECKSUM_FLAGS) | checksum.flag();
+        self
+    }
+
+    /// Adds or replaces an extension of a not encrypted envelope. Extensions of an encrypted one are
+    /// part of the authenticated data and are given to `pack_encrypted_with`, here they are `Sealed`.
+    pub fn with_extension(mut self, tag: u8, data: Vec<u8>) -> Result<Self, EnvelopeError> {
+        if self.is_encrypted() {
+            return Err(EnvelopeError::Sealed);
+        }
+        self.push_extension(tag, data)?;
+        Ok(self)
+    }
+
+    fn push_extension(&mut self, tag: u8, data: Vec<u8>) -> Result<(), EnvelopeError> {
+        self.extensions.retain(|(t, _)| *t != tag);
+        if data.len() > u16::MAX as usize || self.extensions.len() >= u8::MAX as usize {
+            return Err(EnvelopeError::InvalidExtension);
+        }
+        self.extensions.push((tag, data));
+        self.flags |= FLAG_EXTENSIONS;
+        Ok(())
+    }
+
+    pub fn extension(&self, tag: u8) -> Option<&[u8]> {
+        self.extensions.iter().find(|(t, _)| *t == tag).map(|(_, data)| data.as_slice())
+    }
+
+    pub fn checksum(&self) -> Checksum {
+        Checksum::from_flags(self.flags).unwrap_or(Checksum::None)
+    }
+
+    pub fn is_encrypted(&self) -> bool {
+        self.flags & FLAG_ENCRYPTED != 0
+    }
+
+    /// Parses the envelope and verifies its checksum, the payload is not unpacked.
+    pub fn decode(data: &[u8]) -> Result<RawEnvelope, EnvelopeError> {
+        if data.len() < HEADER_LEN || !is_envelope(data) {
+            return Err(EnvelopeError::Truncated);
+        }
+
+        let codec = Codec::from_u8(data[3]).ok_or(EnvelopeError::UnknownCodec(data[3]))?;
+        let flags = data[4];
+        if flags & !KNOWN_FLAGS != 0 {
+            return Err(EnvelopeError::UnsupportedFlags(flags));
+        }
+
+        let checksum = Checksum::from_flags(flags)?;
+        if data.len() < HEADER_LEN + checksum.len() {
+            return Err(EnvelopeError::Truncated);
+        }
+        let (data, trailer) = data.split_at(data.len() - checksum.len());
+        if checksum != Checksum::None {
+            let expected = trailer.iter().fold(0u64, |acc, b| (acc << 8) | *b as u64);
+            let actual = checksum.compute(data);
+            if expected != actual {
+                return Err(EnvelopeError::Corrupted {
+                    expected,
+                    actual,
+                });
+            }
+        }
+
+        let mut pos = HEADER_LEN;
+        let mut key_id = None;
+        if flags & FLAG_ENCRYPTED != 0 {
+            let len = *data.get(pos).ok_or(EnvelopeError::Truncated)? as usize;
+            let id = data.get(pos + 1..pos + 1 + len).ok_or(EnvelopeError::Truncated)?;
+            key_id = Some(String::from_utf8(id.to_vec()).map_err(|_| EnvelopeError::InvalidKeyId)?);
+            pos += 1 + len;
+        }
+
+        let mut extensions = Vec::new();
+        if flags & FLAG_EXTENSIONS != 0 {
+            let count = *data.get(pos).ok_or(EnvelopeError::Truncated)?;
+            pos += 1;
+            for _ in 0..count {
+                let head = data.get(pos..pos + 3).ok_or(EnvelopeError::Truncated)?;
+                let len = u16::from_be_bytes([head[1], head[2]]) as usize;
+                let ext = data.get(pos + 3..pos + 3 + len).ok_or(EnvelopeError::Truncated)?;
+                extensions.push((head[0], ext.to_vec()));
+                pos += 3 + len;
+            }
+        }
+
+        Ok(RawEnvelope {
+            codec,
+            flags,
+            inner_len: u32::from_be_bytes([data[5], data[6], data[7], data[8]]),
+            key_id,
+            extensions,
+            payload: data[pos..].to_vec(),
+        })
+    }
+
+    /// The header with the key id and the extensions, without checksum flag: the authenticated data
+    /// of encryption.
+    fn authenticated_header(&self) -> Vec<u8> {
+        self.header(self.flags & !CHECKSUM_FLAGS)
+    }
+
+    /// Everything before the payload.
+    fn header(&self, flags: u8) -> Vec<u8> {
+        let mut res = Vec::with_capacity(HEADER_LEN);
+        res.extend_from_slice(&ENVELOPE_MAGIC);
+        res.push(self.codec as u8);
+        res.push(flags);
+        res.extend_from_slice(&self.inner_len.to_be_bytes());
+        if let Some(id) = &self.key_id {
+            res.push(id.len() as u8);
+            res.extend_from_slice(id.as_bytes());
+        }
+        if flags & FLAG_EXTENSIONS != 0 {
+            res.push(self.extensions.len() as u8);
+            for (tag, data) in self.extensions.iter() {
+                res.push(*tag);
+                res.extend_from_slice(&(data.len() as u16).to_be_bytes());
+                res.extend_from_slice(data);
+            }
+        }
+        res
+    }
+
+    pub fn encode(&self, out: &mut Vec<u8>) {
+        let start = out.len();
+        out.extend_from_slice(&self.header(self.flags));
+        out.extend_from_slice(&self.payload);
+
+        let checksum = self.checksum();
+        if checksum != Checksum::None {
+            let value = checksum.compute(&out[start..]).to_be_bytes();
+            out.extend_from_slice(&value[8 - checksum.len()..]);
+        }
+    }
+
+    /// Returns the raw individual held by a not encrypted envelope.
+    pub fn unpack(&self) -> Result<Vec<u8>, EnvelopeError> {
+        self.unpack_with(&|_| None)
+    }
+
+    /// Returns the raw individual, `keys` gives the key for the key id of an encrypted envelope.
+    pub fn unpack_with(&self, keys: &KeyProvider) -> Result<Vec<u8>, EnvelopeError> {
+        self.unpack_limited(keys, DEFAULT_MAX_INNER_LEN)
+    }
+
+    /// Same as `unpack_with`, an envelope that unpacks to more than `max_inner_len` bytes is `TooLarge`.
+    pub fn unpack_limited(&self, keys: &KeyProvider, max_inner_len: usize) -> Result<Vec<u8>, EnvelopeError> {
+        if self.inner_len as usize > max_inner_len {
+            return Err(EnvelopeError::TooLarge {
+                inner_len: self.inner_len,
+                max: max_inner_len,
+            });
+        }
+        let res = if self.is_encrypted() {
+            let id = self.key_id.as_deref().unwrap_or_default();
+            let key = keys(id).ok_or_else(|| EnvelopeError::MissingKey(id.to_owned()))?;
+            let data = decrypt(&self.payload, &key, &self.authenticated_header())?;
+            decompress(&data, self.codec, self.inner_len as usize)?
+        } else {
+            decompress(&self.payload, self.codec, self.inner_len as usize)?
+        };
+        if res.len() != self.inner_len as usize {
+            return Err(EnvelopeError::Codec(format!("expected {} bytes, got {}", self.inner_len, res.len())));
+        }
+        Ok(res)
+    }
+}
+
+/// Writes `data` into `out` as an envelope compressed with `codec`.
+pub fn pack(data: &[u8], codec: Codec, out: &mut Vec<u8>) -> Result<(), EnvelopeError> {
+    RawEnvelope::pack(data, codec)?.encode(out);
+    Ok(())
+}
+
+pub fn pack_encrypted(data: &[u8], codec: Codec, key_id: &str, key: &Key, out: &mut Vec<u8>) -> Result<(), EnvelopeError> {
+    RawEnvelope::pack_encrypted(data, codec, key_id, key)?.encode(out);
+    Ok(())
+}
+
+/// Returns the raw individual from an envelope.
+pub fn unpack(data: &[u8]) -> Result<Vec<u8>, EnvelopeError> {
+    RawEnvelope::decode(data)?.unpack()
+}
+
+/// Checks the envelope header and checksum without unpacking, e.g. when scrubbing storage.
+pub fn verify(data: &[u8]) -> Result<(), EnvelopeError> {
+    RawEnvelope::decode(data).map(|_| ())
+}
+
+pub fn unpack_with(data: &[u8], keys: &KeyProvider) -> Result<Vec<u8>, EnvelopeError> {
+    RawEnvelope::decode(data)?.unpack_with(keys)
+}
+
+/// Returns the nonce followed by the ciphertext.
+#[cfg(feature = "encryption")]
+fn encrypt(data: &[u8], key: &Key, aad: &[u8]) -> Result<Vec<u8>, EnvelopeError> {
+    use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng, Payload};
+    use chacha20poly1305::XChaCha20Poly1305;
+
+    let cipher = XChaCha20Poly1305::new(key.into());
+    let nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+    let encrypted = cipher
+        .encrypt(
+            &nonce,
+            Payload {
+                msg: data,
+                aad,
+            },
+        )
+        .map_err(|_| EnvelopeError::Codec("encryption failed".to_owned()))?;
+
+    let mut res = nonce.to_vec();
+    res.extend_from_slice(&encrypted);
+    Ok(res)
+}
+
+#[cfg(feature = "encryption")]
+fn decrypt(data: &[u8], key: &Key, aad: &[u8]) -> Result<Vec<u8>, EnvelopeError> {
+    use chacha20poly1305::aead::{Aead, KeyInit, Payload};
+    use chacha20poly1305::{XChaCha20Poly1305, XNonce};
+
+    if data.len() < NONCE_LEN {
+        return Err(EnvelopeError::Truncated);
+    }
+    let (nonce, msg) = data.split_at(NONCE_LEN);
+    XChaCha20Poly1305::new(key.into())
+        .decrypt(
+            XNonce::from_slice(nonce),
+            Payload {
+                msg,
+                aad,
+            },
+        )
+        .map_err(|_| EnvelopeError::Decrypt)
+}
+
+#[cfg(not(feature = "encryption"))]
+fn encrypt(_data: &[u8], _key: &Key, _aad: &[u8]) -> Result<Vec<u8>, EnvelopeError> {
+    Err(EnvelopeError::EncryptionNotEnabled)
+}
+
+#[cfg(not(feature = "encryption"))]
+fn decrypt(_data: &[u8], _key: &Key, _aad: &[u8]) -> Result<Vec<u8>, EnvelopeError> {
+    Err(EnvelopeError::EncryptionNotEnabled)
+}
+
+#[cfg(feature = "compression")]
+fn compress(data: &[u8], codec: Codec) -> Result<Vec<u8>, EnvelopeError> {
+    match codec {
+        Codec::None => Ok(data.to_vec()),
+        Codec::Zstd => zstd::bulk::compress(data, 0).map_err(|e| EnvelopeError::Codec(e.to_string())),
+        Codec::Lz4 => Ok(lz4_flex::block::compress(data)),
+    }
+}
+
+#[cfg(feature = "compression")]
+fn decompress(data: &[u8], codec: Codec, inner_len: usize) -> Result<Vec<u8>, EnvelopeError> {
+    match codec {
+        Codec::None => Ok(data.to_vec()),
+        Codec::Zstd => zstd::bulk::decompress(data, inner_len).map_err(|e| EnvelopeError::Codec(e.to_string())),
+        Codec::Lz4 => lz4_flex::block::decompress(data, inner_len).map_err(|e| EnvelopeError::Codec(e.to_string())),
+    }
+}
+
+#[cfg(not(feature = "compression"))]
+fn compress(data: &[u8], codec: Codec) -> Result<Vec<u8>, EnvelopeError> {
+    match codec {
+        Codec::None => Ok(data.to_vec()),
+        c => Err(EnvelopeError::CodecNotEnabled(c)),
+    }
+}
+
+#[cfg(not(feature = "compression"))]
+fn decompress(data: &[u8], codec: Codec, _inner_len: usize) -> Result<Vec<u8>, EnvelopeError> {
+    compress(data, codec)
+}
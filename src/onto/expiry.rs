@@ -0,0 +1,89 @@
+use crate::onto::individual::{Individual, IndividualObj};
+use crate::onto::individual_store::IndividualStoreInMem;
+use crate::onto::resource::Value;
+
+/// Time after which an individual is stale, a datetime in seconds.
+pub const EXPIRES_AT: &str = "v-s:expiresAt";
+
+impl IndividualObj {
+    /// Replaces the expiry time, `timestamp` in seconds like datetime values.
+    pub fn set_expires_at(&mut self, timestamp: i64) {
+        self.set_datetime(EXPIRES_AT, timestamp);
+    }
+
+    /// Expires `ttl` seconds after `now`.
+    pub fn set_ttl(&mut self, now: i64, ttl: i64) {
+        self.set_expires_at(now.saturating_add(ttl));
+    }
+
+    pub fn clear_expiry(&mut self) -> bool {
+        self.remove(EXPIRES_AT)
+    }
+
+    /// The earliest `v-s:expiresAt`, None if the individual does not expire.
+    pub fn get_expires_at(&self) -> Option<i64> {
+        self.resources
+            .get(EXPIRES_AT)?
+            .iter()
+            .filter_map(|r| match r.value {
+                Value::Datetime(t) => Some(t),
+                _ => None,
+            })
+            .min()
+    }
+
+    /// True from the expiry time on.
+    pub fn is_expired(&self, now: i64) -> bool {
+        self.get_expires_at().is_some_and(|t| t <= now)
+    }
+}
+
+impl Individual {
+    pub fn set_expires_at(&mut self, timestamp: i64) {
+        self.set_datetime(EXPIRES_AT, timestamp);
+    }
+
+    pub fn set_ttl(&mut self, now: i64, ttl: i64) {
+        self.set_expires_at(now.saturating_add(ttl));
+    }
+
+    pub fn clear_expiry(&mut self) -> bool {
+        self.remove(EXPIRES_AT)
+    }
+
+    pub fn get_expires_at(&mut self) -> Option<i64> {
+        self.parse_all();
+        self.get_obj().get_expires_at()
+    }
+
+    pub fn is_expired(&mut self, now: i64) -> bool {
+        self.parse_all();
+        self.get_obj().is_expired(now)
+    }
+}
+
+/// Individuals of a stream that have not expired at `now`.
+pub fn drop_expired<I>(indvs: I, now: i64) -> impl Iterator<Item = Individual>
+where
+    I: IntoIterator<Item = Individual>,
+{
+    indvs.into_iter().filter_map(move |mut indv| (!indv.is_expired(now)).then_some(indv))
+}
+
+/// Removes the individuals expired at `now`, returns how many.
+pub fn retain_unexpired(indvs: &mut Vec<Individual>, now: i64) -> usize {
+    let len = indvs.len();
+    indvs.retain_mut(|indv| !indv.is_expired(now));
+    len - indvs.len()
+}
+
+impl IndividualStoreInMem {
+    /// Removes the individuals expired at `now` and returns their uris.
+    pub fn purge_expired(&mut self, now: i64) -> Vec<String> {
+        let expired: Vec<String> = self.iter().filter(|indv| indv.get_obj().is_expired(now)).map(|indv| indv.get_id().to_owned()).collect();
+        for uri in expired.iter() {
+            self.remove(uri);
+        }
+        expired
+    }
+}
@@ -0,0 +1,97 @@
+use crate::onto::individual::IndividualError;
+use std::fmt;
+
+/// Everything `extract!` found wrong with an individual, one error per field.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExtractError(pub Vec<IndividualError>);
+
+impl fmt::Display for ExtractError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (idx, e) in self.0.iter().enumerate() {
+            if idx > 0 {
+                write!(f, "; ")?;
+            }
+            write!(f, "{}", e)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ExtractError {}
+
+#[doc(hidden)]
+pub fn required<T>(res: Result<T, IndividualError>, errors: &mut Vec<IndividualError>) -> Option<T> {
+    res.map_err(|e| errors.push(e)).ok()
+}
+
+/// A missing predicate is None, a value of another datatype is still an error.
+#[doc(hidden)]
+pub fn optional<T>(res: Result<T, IndividualError>, errors: &mut Vec<IndividualError>) -> Option<Option<T>> {
+    match res {
+        Ok(v) => Some(Some(v)),
+        Err(IndividualError::MissingPredicate {
+            ..
+        }) => Some(None),
+        Err(e) => {
+            errors.push(e);
+            None
+        },
+    }
+}
+
+/// Reads the first values of several predicates of an `&mut Individual` at once into a struct,
+/// checking all of them before failing: `Ok` with a field per predicate, or `Err(ExtractError)`
+/// with every missing or mistyped one.
+///
+/// ```text
+/// let doc = extract!(&mut indv, {
+///     label: str["rdfs:label"],
+///     created: datetime["v-s:created"],
+///     author?: uri["v-s:author"],
+/// })?;
+/// println!("{} {} {:?}", doc.label, doc.created, doc.author);
+/// ```
+///
+/// The kinds are `str` (string or uri, as `get_first_literal`), `uri`, `integer`, `bool`,
+/// `datetime`, `number` (mantissa, exponent) and `binobj`, read by the `try_get_first_*` accessor
+/// of the same name. A field marked `?` is an `Option`, None when the predicate is missing.
+#[macro_export]
+macro_rules! extract {
+    (@field $indv:ident, $errors:ident, [$($done:tt)*] $field:ident ? : $kind:ident [$predicate:expr] $(, $($rest:tt)*)?) => {{
+        let $field = $crate::onto::extract::optional($crate::extract!(@get $indv, $kind, $predicate), &mut $errors);
+        $crate::extract!(@field $indv, $errors, [$($done)* $field: ::std::option::Option<$crate::extract!(@type $kind)>,] $($($rest)*)?)
+    }};
+    (@field $indv:ident, $errors:ident, [$($done:tt)*] $field:ident : $kind:ident [$predicate:expr] $(, $($rest:tt)*)?) => {{
+        let $field = $crate::onto::extract::required($crate::extract!(@get $indv, $kind, $predicate), &mut $errors);
+        $crate::extract!(@field $indv, $errors, [$($done)* $field: $crate::extract!(@type $kind),] $($($rest)*)?)
+    }};
+    (@field $indv:ident, $errors:ident, [$($field:ident : $ty:ty,)*]) => {{
+        #[derive(Debug)]
+        struct Extracted {
+            $($field: $ty),*
+        }
+        match ($($field,)*) {
+            ($(::std::option::Option::Some($field),)*) => ::std::result::Result::Ok(Extracted { $($field),* }),
+            _ => ::std::result::Result::Err($crate::onto::extract::ExtractError($errors)),
+        }
+    }};
+    (@get $indv:ident, str, $predicate:expr) => { $indv.try_get_first_literal($predicate) };
+    (@get $indv:ident, uri, $predicate:expr) => { $indv.try_get_first_uri($predicate) };
+    (@get $indv:ident, integer, $predicate:expr) => { $indv.try_get_first_integer($predicate) };
+    (@get $indv:ident, bool, $predicate:expr) => { $indv.try_get_first_bool($predicate) };
+    (@get $indv:ident, datetime, $predicate:expr) => { $indv.try_get_first_datetime($predicate) };
+    (@get $indv:ident, number, $predicate:expr) => { $indv.try_get_first_number($predicate) };
+    (@get $indv:ident, binobj, $predicate:expr) => { $indv.try_get_first_binobj($predicate) };
+    (@type str) => { ::std::string::String };
+    (@type uri) => { ::std::string::String };
+    (@type integer) => { i64 };
+    (@type bool) => { bool };
+    (@type datetime) => { i64 };
+    (@type number) => { (i64, i64) };
+    (@type binobj) => { ::std::vec::Vec<u8> };
+    ($indv:expr, { $($fields:tt)* }) => {{
+        let indv: &mut $crate::onto::individual::Individual = $indv;
+        let mut errors = ::std::vec::Vec::new();
+        $crate::extract!(@field indv, errors, [] $($fields)*)
+    }};
+}
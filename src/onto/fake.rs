@@ -0,0 +1,105 @@
+use crate::onto::datatype::{integer_bounds, xsd_iri, Lang};
+use crate::onto::individual::IndividualObj;
+use crate::onto::onto_impl::Onto;
+use crate::onto::{XSD_BOOLEAN, XSD_DATE_TIME, XSD_DECIMAL, XSD_DOUBLE, XSD_FLOAT, XSD_NORMALIZED_STRING, XSD_STRING};
+use rand::Rng;
+
+/// Values generated for a predicate without max cardinality.
+const MAX_FAKE_VALUES: usize = 3;
+
+/// 2000-01-01 … 2030-01-01.
+const FAKE_DATETIME_RANGE: (i64, i64) = (946_684_800, 1_893_456_000);
+
+const FAKE_WORDS: [&str; 24] = [
+    "lorem",
+    "ipsum",
+    "dolor",
+    "sit",
+    "amet",
+    "consectetur",
+    "adipiscing",
+    "elit",
+    "sed",
+    "do",
+    "eiusmod",
+    "tempor",
+    "incididunt",
+    "ut",
+    "labore",
+    "et",
+    "dolore",
+    "magna",
+    "aliqua",
+    "enim",
+    "ad",
+    "minim",
+    "veniam",
+    "quis",
+];
+
+fn fake_uri<R: Rng + ?Sized>(rng: &mut R) -> String {
+    format!("d:fake_{:016x}", rng.gen::<u64>())
+}
+
+fn fake_text<R: Rng + ?Sized>(rng: &mut R) -> String {
+    let words: Vec<&str> = (0..rng.gen_range(2..=6)).map(|_| FAKE_WORDS[rng.gen_range(0..FAKE_WORDS.len())]).collect();
+    let text = words.join(" ");
+    let mut chars = text.chars();
+    chars.next().map(|c| c.to_uppercase().chain(chars).collect()).unwrap_or_default()
+}
+
+/// Adds a random value fitting `range`: the XSD datatypes get literals, `rdfs:Literal` and
+/// unknown datatypes a text, any other range a link to a fake individual.
+fn add_fake_value<R: Rng + ?Sized>(obj: &mut IndividualObj, predicate: &str, range: Option<&str>, rng: &mut R) {
+    let Some(range) = range else {
+        obj.add_string(predicate, &fake_text(rng), Lang::none());
+        return;
+    };
+    if let Some((lo, hi)) = integer_bounds(range) {
+        // small numbers, as in real data
+        let (lo, hi) = (lo.max(-1000), hi.min(1000));
+        obj.add_integer(predicate, rng.gen_range(lo..=hi));
+        return;
+    }
+    let is_xsd = range.starts_with("xsd:") || range.starts_with("http://www.w3.org/2001/XMLSchema#");
+    match xsd_iri(range).as_str() {
+        XSD_BOOLEAN => obj.add_bool(predicate, rng.gen()),
+        XSD_DATE_TIME => obj.add_datetime(predicate, rng.gen_range(FAKE_DATETIME_RANGE.0..FAKE_DATETIME_RANGE.1)),
+        XSD_DECIMAL | XSD_FLOAT | XSD_DOUBLE => obj.add_decimal_d(predicate, rng.gen_range(0..1_000_000), -2),
+        XSD_STRING | XSD_NORMALIZED_STRING => obj.add_string(predicate, &fake_text(rng), Lang::none()),
+        _ if is_xsd || range == "rdfs:Literal" => obj.add_string(predicate, &fake_text(rng), Lang::none()),
+        _ => obj.add_uri_unchecked(predicate, &fake_uri(rng)),
+    }
+}
+
+/// Random individual of the class for load testing, `d:fake_…` uri. It has the predicates
+/// `Onto::instantiate` gives the class: the declared defaults, and for each required predicate
+/// one to three values of one of its ranges (`Onto::get_ranges`), no more than its max cardinality.
+/// Links point to fake uris, they are not generated.
+pub fn generate_fake<R: Rng + ?Sized>(class_uri: &str, onto: &Onto, rng: &mut R) -> IndividualObj {
+    let mut obj = onto.instantiate(class_uri);
+    obj.uri = fake_uri(rng);
+
+    let predicates: Vec<String> = obj.resources.keys().filter(|p| *p != "rdf:type").cloned().collect();
+    for predicate in predicates {
+        let max = onto.get_max_cardinality(&predicate);
+        let Some(values) = obj.resources.get_mut(&predicate) else {
+            continue;
+        };
+        if !values.is_empty() {
+            values.truncate(max.unwrap_or(usize::MAX));
+            continue;
+        }
+        let limit = max.unwrap_or(MAX_FAKE_VALUES).min(MAX_FAKE_VALUES);
+        if limit == 0 {
+            continue;
+        }
+
+        let ranges = onto.get_ranges(&predicate);
+        for _ in 0..rng.gen_range(1..=limit) {
+            let range = (!ranges.is_empty()).then(|| ranges[rng.gen_range(0..ranges.len())].as_str());
+            add_fake_value(&mut obj, &predicate, range, rng);
+        }
+    }
+    obj
+}
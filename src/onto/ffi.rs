@@ -0,0 +1,420 @@
+//! Stable C ABI over [`Individual`].
+//!
+//! All functions take and return raw pointers; ownership rules:
+//! - an `Individual` created by `v_individual_new`/`v_individual_parse_*` must be released with `v_individual_free`,
+//! - every `char *` returned by this module must be released with `v_string_free`,
+//! - every `VBuffer` returned by this module must be released with `v_buffer_free`.
+//!
+//! Integer return codes follow the crate convention: `0` on success, `-1` on error, `1` when a value was not found.
+
+use crate::onto::datatype::Lang;
+use crate::onto::individual::{Individual, RawObj};
+use crate::onto::individual2msgpack::to_msgpack;
+use crate::onto::json2individual::{json_from_slice, parse_json_to_individual};
+use crate::onto::parser::parse_raw;
+use std::ffi::{CStr, CString};
+use std::os::raw::{c_char, c_int};
+use std::ptr;
+use std::slice;
+
+const FFI_OK: c_int = 0;
+const FFI_ERR: c_int = -1;
+const FFI_NOT_FOUND: c_int = 1;
+
+#[repr(C)]
+pub struct VBuffer {
+    pub data: *mut u8,
+    pub len: usize,
+}
+
+impl VBuffer {
+    fn null() -> Self {
+        VBuffer {
+            data: ptr::null_mut(),
+            len: 0,
+        }
+    }
+
+    fn from_vec(v: Vec<u8>) -> Self {
+        let mut b = v.into_boxed_slice();
+        let res = VBuffer {
+            data: b.as_mut_ptr(),
+            len: b.len(),
+        };
+        std::mem::forget(b);
+        res
+    }
+}
+
+unsafe fn to_str<'a>(s: *const c_char) -> Option<&'a str> {
+    if s.is_null() {
+        return None;
+    }
+    CStr::from_ptr(s).to_str().ok()
+}
+
+fn to_c_string(s: &str) -> *mut c_char {
+    match CString::new(s) {
+        Ok(cs) => cs.into_raw(),
+        Err(_) => ptr::null_mut(),
+    }
+}
+
+unsafe fn to_lang(lang: *const c_char) -> Lang {
+    match to_str(lang) {
+        Some(l) if !l.is_empty() => Lang::new_from_str(l),
+        _ => Lang::none(),
+    }
+}
+
+/// Creates an empty individual.
+#[no_mangle]
+pub extern "C" fn v_individual_new() -> *mut Individual {
+    Box::into_raw(Box::default())
+}
+
+/// Releases an individual.
+///
+/// # Safety
+/// `indv` must be null or a pointer returned by this module that was not freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn v_individual_free(indv: *mut Individual) {
+    if !indv.is_null() {
+        drop(Box::from_raw(indv));
+    }
+}
+
+/// Parses a CBOR or msgpack buffer, returns null on error.
+///
+/// # Safety
+/// `data` must point to `len` readable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn v_individual_parse_raw(data: *const u8, len: usize) -> *mut Individual {
+    if data.is_null() || len == 0 {
+        return ptr::null_mut();
+    }
+
+    let mut indv = Individual::new_raw(RawObj::new(slice::from_raw_parts(data, len).to_vec()));
+    if parse_raw(&mut indv).is_err() {
+        return ptr::null_mut();
+    }
+    indv.parse_all();
+
+    Box::into_raw(Box::new(indv))
+}
+
+/// Parses a JSON individual, returns null on error.
+///
+/// # Safety
+/// `json` must be a valid nul-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn v_individual_parse_json(json: *const c_char) -> *mut Individual {
+    let src = match to_str(json).and_then(|s| json_from_slice(s.as_bytes()).ok()) {
+        Some(v) => v,
+        None => return ptr::null_mut(),
+    };
+
+    let mut indv = Individual::default();
+    if !parse_json_to_individual(&src, &mut indv) {
+        return ptr::null_mut();
+    }
+
+    Box::into_raw(Box::new(indv))
+}
+
+/// Returns the uri of individual.
+///
+/// # Safety
+/// `indv` must be a valid individual pointer.
+#[no_mangle]
+pub unsafe extern "C" fn v_individual_get_id(indv: *const Individual) -> *mut c_char {
+    match indv.as_ref() {
+        Some(indv) => to_c_string(indv.get_id()),
+        None => ptr::null_mut(),
+    }
+}
+
+/// Sets the uri of individual.
+///
+/// # Safety
+/// `indv` must be a valid individual pointer, `id` a valid nul-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn v_individual_set_id(indv: *mut Individual, id: *const c_char) -> c_int {
+    match (indv.as_mut(), to_str(id)) {
+        (Some(indv), Some(id)) => {
+            indv.set_id(id);
+            FFI_OK
+        },
+        _ => FFI_ERR,
+    }
+}
+
+/// Returns the first string or uri value of predicate, null if absent.
+///
+/// # Safety
+/// `indv` must be a valid individual pointer, `predicate` a valid nul-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn v_individual_get_first_literal(indv: *mut Individual, predicate: *const c_char) -> *mut c_char {
+    match (indv.as_mut(), to_str(predicate)) {
+        (Some(indv), Some(predicate)) => match indv.get_first_literal(predicate) {
+            Some(v) => to_c_string(&v),
+            None => ptr::null_mut(),
+        },
+        _ => ptr::null_mut(),
+    }
+}
+
+/// Writes the first integer value of predicate into `out`.
+///
+/// # Safety
+/// `indv` must be a valid individual pointer, `predicate` a valid nul-terminated string, `out` writable.
+#[no_mangle]
+pub unsafe extern "C" fn v_individual_get_first_integer(indv: *mut Individual, predicate: *const c_char, out: *mut i64) -> c_int {
+    if out.is_null() {
+        return FFI_ERR;
+    }
+    match (indv.as_mut(), to_str(predicate)) {
+        (Some(indv), Some(predicate)) => match indv.get_first_integer(predicate) {
+            Some(v) => {
+                *out = v;
+                FFI_OK
+            },
+            None => FFI_NOT_FOUND,
+        },
+        _ => FFI_ERR,
+    }
+}
+
+/// Writes the first datetime value (unix seconds) of predicate into `out`.
+///
+/// # Safety
+/// `indv` must be a valid individual pointer, `predicate` a valid nul-terminated string, `out` writable.
+#[no_mangle]
+pub unsafe extern "C" fn v_individual_get_first_datetime(indv: *mut Individual, predicate: *const c_char, out: *mut i64) -> c_int {
+    if out.is_null() {
+        return FFI_ERR;
+    }
+    match (indv.as_mut(), to_str(predicate)) {
+        (Some(indv), Some(predicate)) => match indv.get_first_datetime(predicate) {
+            Some(v) => {
+                *out = v;
+                FFI_OK
+            },
+            None => FFI_NOT_FOUND,
+        },
+        _ => FFI_ERR,
+    }
+}
+
+/// Writes the first boolean value of predicate into `out`.
+///
+/// # Safety
+/// `indv` must be a valid individual pointer, `predicate` a valid nul-terminated string, `out` writable.
+#[no_mangle]
+pub unsafe extern "C" fn v_individual_get_first_bool(indv: *mut Individual, predicate: *const c_char, out: *mut bool) -> c_int {
+    if out.is_null() {
+        return FFI_ERR;
+    }
+    match (indv.as_mut(), to_str(predicate)) {
+        (Some(indv), Some(predicate)) => match indv.get_first_bool(predicate) {
+            Some(v) => {
+                *out = v;
+                FFI_OK
+            },
+            None => FFI_NOT_FOUND,
+        },
+        _ => FFI_ERR,
+    }
+}
+
+/// Replaces the values of predicate by one string, `lang` may be null.
+///
+/// # Safety
+/// `indv` must be a valid individual pointer, string arguments valid nul-terminated strings or null.
+#[no_mangle]
+pub unsafe extern "C" fn v_individual_set_string(indv: *mut Individual, predicate: *const c_char, value: *const c_char, lang: *const c_char) -> c_int {
+    match (indv.as_mut(), to_str(predicate), to_str(value)) {
+        (Some(indv), Some(predicate), Some(value)) => {
+            indv.set_string(predicate, value, to_lang(lang));
+            FFI_OK
+        },
+        _ => FFI_ERR,
+    }
+}
+
+/// Appends a string value to predicate, `lang` may be null.
+///
+/// # Safety
+/// `indv` must be a valid individual pointer, string arguments valid nul-terminated strings or null.
+#[no_mangle]
+pub unsafe extern "C" fn v_individual_add_string(indv: *mut Individual, predicate: *const c_char, value: *const c_char, lang: *const c_char) -> c_int {
+    match (indv.as_mut(), to_str(predicate), to_str(value)) {
+        (Some(indv), Some(predicate), Some(value)) => {
+            indv.add_string(predicate, value, to_lang(lang));
+            FFI_OK
+        },
+        _ => FFI_ERR,
+    }
+}
+
+/// Replaces the values of predicate by one uri, returns FFI_ERR for a malformed uri.
+///
+/// # Safety
+/// `indv` must be a valid individual pointer, string arguments valid nul-terminated strings.
+#[no_mangle]
+pub unsafe extern "C" fn v_individual_set_uri(indv: *mut Individual, predicate: *const c_char, value: *const c_char) -> c_int {
+    match (indv.as_mut(), to_str(predicate), to_str(value)) {
+        (Some(indv), Some(predicate), Some(value)) => match indv.set_uri(predicate, value) {
+            Ok(()) => FFI_OK,
+            Err(_) => FFI_ERR,
+        },
+        _ => FFI_ERR,
+    }
+}
+
+/// Appends a uri value to predicate, returns FFI_ERR for a malformed uri.
+///
+/// # Safety
+/// `indv` must be a valid individual pointer, string arguments valid nul-terminated strings.
+#[no_mangle]
+pub unsafe extern "C" fn v_individual_add_uri(indv: *mut Individual, predicate: *const c_char, value: *const c_char) -> c_int {
+    match (indv.as_mut(), to_str(predicate), to_str(value)) {
+        (Some(indv), Some(predicate), Some(value)) => match indv.add_uri(predicate, value) {
+            Ok(()) => FFI_OK,
+            Err(_) => FFI_ERR,
+        },
+        _ => FFI_ERR,
+    }
+}
+
+/// Replaces the values of predicate by one integer.
+///
+/// # Safety
+/// `indv` must be a valid individual pointer, `predicate` a valid nul-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn v_individual_set_integer(indv: *mut Individual, predicate: *const c_char, value: i64) -> c_int {
+    match (indv.as_mut(), to_str(predicate)) {
+        (Some(indv), Some(predicate)) => {
+            indv.set_integer(predicate, value);
+            FFI_OK
+        },
+        _ => FFI_ERR,
+    }
+}
+
+/// Appends an integer value to predicate.
+///
+/// # Safety
+/// `indv` must be a valid individual pointer, `predicate` a valid nul-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn v_individual_add_integer(indv: *mut Individual, predicate: *const c_char, value: i64) -> c_int {
+    match (indv.as_mut(), to_str(predicate)) {
+        (Some(indv), Some(predicate)) => {
+            indv.add_integer(predicate, value);
+            FFI_OK
+        },
+        _ => FFI_ERR,
+    }
+}
+
+/// Replaces the values of predicate by one datetime (unix seconds).
+///
+/// # Safety
+/// `indv` must be a valid individual pointer, `predicate` a valid nul-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn v_individual_set_datetime(indv: *mut Individual, predicate: *const c_char, value: i64) -> c_int {
+    match (indv.as_mut(), to_str(predicate)) {
+        (Some(indv), Some(predicate)) => {
+            indv.set_datetime(predicate, value);
+            FFI_OK
+        },
+        _ => FFI_ERR,
+    }
+}
+
+/// Replaces the values of predicate by one boolean.
+///
+/// # Safety
+/// `indv` must be a valid individual pointer, `predicate` a valid nul-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn v_individual_set_bool(indv: *mut Individual, predicate: *const c_char, value: bool) -> c_int {
+    match (indv.as_mut(), to_str(predicate)) {
+        (Some(indv), Some(predicate)) => {
+            indv.set_bool(predicate, value);
+            FFI_OK
+        },
+        _ => FFI_ERR,
+    }
+}
+
+/// Removes predicate, returns `1` if it was absent.
+///
+/// # Safety
+/// `indv` must be a valid individual pointer, `predicate` a valid nul-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn v_individual_remove(indv: *mut Individual, predicate: *const c_char) -> c_int {
+    match (indv.as_mut(), to_str(predicate)) {
+        (Some(indv), Some(predicate)) => {
+            if indv.remove(predicate) {
+                FFI_OK
+            } else {
+                FFI_NOT_FOUND
+            }
+        },
+        _ => FFI_ERR,
+    }
+}
+
+/// Serializes individual to msgpack, returns an empty buffer on error.
+///
+/// # Safety
+/// `indv` must be a valid individual pointer.
+#[no_mangle]
+pub unsafe extern "C" fn v_individual_to_msgpack(indv: *mut Individual) -> VBuffer {
+    let indv = match indv.as_mut() {
+        Some(v) => v,
+        None => return VBuffer::null(),
+    };
+
+    indv.parse_all();
+    let mut out = Vec::new();
+    if to_msgpack(indv, &mut out).is_err() {
+        return VBuffer::null();
+    }
+
+    VBuffer::from_vec(out)
+}
+
+/// Serializes individual to JSON.
+///
+/// # Safety
+/// `indv` must be a valid individual pointer.
+#[no_mangle]
+pub unsafe extern "C" fn v_individual_to_json(indv: *mut Individual) -> *mut c_char {
+    match indv.as_mut() {
+        Some(indv) => to_c_string(&indv.parse_all().get_obj().as_json_str()),
+        None => ptr::null_mut(),
+    }
+}
+
+/// Releases a string returned by this module.
+///
+/// # Safety
+/// `s` must be null or a string returned by this module that was not freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn v_string_free(s: *mut c_char) {
+    if !s.is_null() {
+        drop(CString::from_raw(s));
+    }
+}
+
+/// Releases a buffer returned by this module.
+///
+/// # Safety
+/// `buf` must be a buffer returned by this module that was not freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn v_buffer_free(buf: VBuffer) {
+    if !buf.data.is_null() {
+        drop(Box::from_raw(ptr::slice_from_raw_parts_mut(buf.data, buf.len)));
+    }
+}
@@ -0,0 +1,399 @@
+use crate::onto::decimal::to_decimal;
+use crate::onto::individual::{Individual, IndividualObj};
+use crate::onto::resource::{Resource, Value};
+#[cfg(feature = "chrono")]
+use chrono::{DateTime, NaiveDate, NaiveDateTime};
+use rust_decimal::Decimal;
+use std::cmp::Ordering;
+use std::fmt;
+use std::str::FromStr;
+
+/// Filter expression over the predicates of an individual, parsed once and evaluated many times.
+///
+/// ```text
+/// rdf:type == 'v-s:Document' && (v-s:created > '2024-01-01' || !v-s:deleted)
+/// ```
+///
+/// A comparison is true when at least one value of the predicate satisfies it, `!=` is true when no value is equal.
+/// A bare predicate checks its existence. Literals are quoted strings, numbers, `true` and `false`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Filter {
+    And(Box<Filter>, Box<Filter>),
+    Or(Box<Filter>, Box<Filter>),
+    Not(Box<Filter>),
+    Exists(String),
+    Compare(String, CompareOp, Literal),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompareOp {
+    Eq,
+    Ne,
+    Gt,
+    Ge,
+    Lt,
+    Le,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Literal {
+    Str(String),
+    Num(String),
+    Bool(bool),
+}
+
+/// Deepest nesting of `!`, parentheses and chained `&&` / `||` a filter may have, evaluation recurses that deep.
+const MAX_DEPTH: usize = 256;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FilterError {
+    pub pos: usize,
+    pub msg: String,
+}
+
+impl fmt::Display for FilterError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "filter: {} at {}", self.msg, self.pos)
+    }
+}
+
+impl std::error::Error for FilterError {}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Ident(String),
+    Lit(Literal),
+    Op(CompareOp),
+    And,
+    Or,
+    Not,
+    LParen,
+    RParen,
+}
+
+fn is_ident_char(c: char) -> bool {
+    c.is_alphanumeric() || matches!(c, ':' | '-' | '_' | '.' | '#' | '/' | '@')
+}
+
+fn tokenize(src: &str) -> Result<Vec<(usize, Token)>, FilterError> {
+    let chars: Vec<(usize, char)> = src.char_indices().collect();
+    let mut res = Vec::new();
+    let mut i = 0;
+
+    let err = |pos: usize, msg: &str| FilterError {
+        pos,
+        msg: msg.to_owned(),
+    };
+
+    while i < chars.len() {
+        let (pos, c) = chars[i];
+        let next = chars.get(i + 1).map(|(_, c)| *c);
+
+        match c {
+            ' ' | '\t' | '\r' | '\n' => {
+                i += 1;
+            },
+            '(' => {
+                res.push((pos, Token::LParen));
+                i += 1;
+            },
+            ')' => {
+                res.push((pos, Token::RParen));
+                i += 1;
+            },
+            '&' if next == Some('&') => {
+                res.push((pos, Token::And));
+                i += 2;
+            },
+            '|' if next == Some('|') => {
+                res.push((pos, Token::Or));
+                i += 2;
+            },
+            '=' if next == Some('=') => {
+                res.push((pos, Token::Op(CompareOp::Eq)));
+                i += 2;
+            },
+            '!' if next == Some('=') => {
+                res.push((pos, Token::Op(CompareOp::Ne)));
+                i += 2;
+            },
+            '!' => {
+                res.push((pos, Token::Not));
+                i += 1;
+            },
+            '>' | '<' => {
+                let op = match (c, next == Some('=')) {
+                    ('>', true) => CompareOp::Ge,
+                    ('>', false) => CompareOp::Gt,
+                    (_, true) => CompareOp::Le,
+                    (_, false) => CompareOp::Lt,
+                };
+                res.push((pos, Token::Op(op)));
+                i += if next == Some('=') {
+                    2
+                } else {
+                    1
+                };
+            },
+            '\'' | '"' => {
+                let mut s = String::new();
+                i += 1;
+                loop {
+                    match chars.get(i) {
+                        None => return Err(err(pos, "unterminated string")),
+                        Some((_, '\\')) if i + 1 < chars.len() => {
+                            s.push(chars[i + 1].1);
+                            i += 2;
+                        },
+                        Some((_, q)) if *q == c => {
+                            i += 1;
+                            break;
+                        },
+                        Some((_, ch)) => {
+                            s.push(*ch);
+                            i += 1;
+                        },
+                    }
+                }
+                res.push((pos, Token::Lit(Literal::Str(s))));
+            },
+            c if c.is_ascii_digit() || (c == '-' && next.is_some_and(|n| n.is_ascii_digit())) => {
+                let start = i;
+                i += 1;
+                while i < chars.len() && (chars[i].1.is_ascii_digit() || chars[i].1 == '.') {
+                    i += 1;
+                }
+                let s: String = chars[start..i].iter().map(|(_, c)| *c).collect();
+                res.push((pos, Token::Lit(Literal::Num(s))));
+            },
+            c if is_ident_char(c) => {
+                let start = i;
+                while i < chars.len() && is_ident_char(chars[i].1) {
+                    i += 1;
+                }
+                let s: String = chars[start..i].iter().map(|(_, c)| *c).collect();
+                let token = match s.as_str() {
+                    "true" => Token::Lit(Literal::Bool(true)),
+                    "false" => Token::Lit(Literal::Bool(false)),
+                    _ => Token::Ident(s),
+                };
+                res.push((pos, token));
+            },
+            _ => return Err(err(pos, &format!("unexpected character [{}]", c))),
+        }
+    }
+    Ok(res)
+}
+
+struct Parser {
+    tokens: Vec<(usize, Token)>,
+    cur: usize,
+    end: usize,
+    depth: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.cur).map(|(_, t)| t)
+    }
+
+    fn pos(&self) -> usize {
+        self.tokens.get(self.cur).map(|(p, _)| *p).unwrap_or(self.end)
+    }
+
+    fn err(&self, msg: &str) -> FilterError {
+        FilterError {
+            pos: self.pos(),
+            msg: msg.to_owned(),
+        }
+    }
+
+    /// One level deeper, an error past `MAX_DEPTH`; the caller restores `depth` when the level is done.
+    fn descend(&mut self) -> Result<(), FilterError> {
+        self.depth += 1;
+        if self.depth > MAX_DEPTH {
+            return Err(self.err(&format!("nested deeper than {}", MAX_DEPTH)));
+        }
+        Ok(())
+    }
+
+    fn parse_or(&mut self) -> Result<Filter, FilterError> {
+        let depth = self.depth;
+        let mut left = self.parse_and()?;
+        while self.peek() == Some(&Token::Or) {
+            // every operator puts the expression so far one level down
+            self.descend()?;
+            self.cur += 1;
+            let right = self.parse_and()?;
+            left = Filter::Or(Box::new(left), Box::new(right));
+        }
+        self.depth = depth;
+        Ok(left)
+    }
+
+    fn parse_and(&mut self) -> Result<Filter, FilterError> {
+        let depth = self.depth;
+        let mut left = self.parse_unary()?;
+        while self.peek() == Some(&Token::And) {
+            self.descend()?;
+            self.cur += 1;
+            let right = self.parse_unary()?;
+            left = Filter::And(Box::new(left), Box::new(right));
+        }
+        self.depth = depth;
+        Ok(left)
+    }
+
+    fn parse_unary(&mut self) -> Result<Filter, FilterError> {
+        match self.peek().cloned() {
+            Some(Token::Not) => {
+                self.descend()?;
+                self.cur += 1;
+                let f = Filter::Not(Box::new(self.parse_unary()?));
+                self.depth -= 1;
+                Ok(f)
+            },
+            Some(Token::LParen) => {
+                self.descend()?;
+                self.cur += 1;
+                let f = self.parse_or()?;
+                if self.peek() != Some(&Token::RParen) {
+                    return Err(self.err("expected )"));
+                }
+                self.cur += 1;
+                self.depth -= 1;
+                Ok(f)
+            },
+            Some(Token::Ident(predicate)) => {
+                self.cur += 1;
+                if let Some(Token::Op(op)) = self.peek().cloned() {
+                    self.cur += 1;
+                    match self.peek().cloned() {
+                        Some(Token::Lit(lit)) => {
+                            self.cur += 1;
+                            Ok(Filter::Compare(predicate, op, lit))
+                        },
+                        _ => Err(self.err("expected literal")),
+                    }
+                } else {
+                    Ok(Filter::Exists(predicate))
+                }
+            },
+            _ => Err(self.err("expected predicate, ! or (")),
+        }
+    }
+}
+
+impl FromStr for Filter {
+    type Err = FilterError;
+
+    fn from_str(src: &str) -> Result<Self, Self::Err> {
+        Filter::parse(src)
+    }
+}
+
+impl Filter {
+    pub fn parse(src: &str) -> Result<Filter, FilterError> {
+        let mut parser = Parser {
+            tokens: tokenize(src)?,
+            cur: 0,
+            end: src.len(),
+            depth: 0,
+        };
+        let f = parser.parse_or()?;
+        if parser.cur < parser.tokens.len() {
+            return Err(parser.err("unexpected token"));
+        }
+        Ok(f)
+    }
+
+    pub fn eval(&self, indv: &IndividualObj) -> bool {
+        match self {
+            Filter::And(a, b) => a.eval(indv) && b.eval(indv),
+            Filter::Or(a, b) => a.eval(indv) || b.eval(indv),
+            Filter::Not(a) => !a.eval(indv),
+            Filter::Exists(predicate) => indv.get_resources().get(predicate).is_some_and(|v| !v.is_empty()),
+            Filter::Compare(predicate, CompareOp::Ne, lit) => match indv.get_resources().get(predicate) {
+                Some(values) => !values.iter().any(|r| compare(r, lit) == Some(Ordering::Equal)),
+                None => true,
+            },
+            Filter::Compare(predicate, op, lit) => match indv.get_resources().get(predicate) {
+                Some(values) => values.iter().any(|r| compare(r, lit).is_some_and(|o| op.accepts(o))),
+                None => false,
+            },
+        }
+    }
+
+    pub fn eval_individual(&self, indv: &mut Individual) -> bool {
+        indv.parse_all();
+        self.eval(indv.get_obj())
+    }
+}
+
+impl CompareOp {
+    fn accepts(&self, o: Ordering) -> bool {
+        match self {
+            CompareOp::Eq => o == Ordering::Equal,
+            CompareOp::Ne => o != Ordering::Equal,
+            CompareOp::Gt => o == Ordering::Greater,
+            CompareOp::Ge => o != Ordering::Less,
+            CompareOp::Lt => o == Ordering::Less,
+            CompareOp::Le => o != Ordering::Greater,
+        }
+    }
+}
+
+fn literal_text(lit: &Literal) -> String {
+    match lit {
+        Literal::Str(s) | Literal::Num(s) => s.to_owned(),
+        Literal::Bool(b) => b.to_string(),
+    }
+}
+
+/// Orders the resource value against the literal, None when they are not comparable.
+fn compare(r: &Resource, lit: &Literal) -> Option<Ordering> {
+    match &r.value {
+        Value::Str(s, _) | Value::Uri(s) => Some(s.as_str().cmp(literal_text(lit).as_str())),
+        Value::Bool(b) => match lit {
+            Literal::Bool(l) => Some(b.cmp(l)),
+            Literal::Str(l) => bool::from_str(l).ok().map(|l| b.cmp(&l)),
+            Literal::Num(_) => None,
+        },
+        Value::Int(i) => {
+            let l = Decimal::from_str(&literal_text(lit)).ok()?;
+            Some(Decimal::from(*i).cmp(&l))
+        },
+        Value::Num(m, e) => {
+            let l = Decimal::from_str(&literal_text(lit)).ok()?;
+            Some(to_decimal(*m, *e).ok()?.cmp(&l))
+        },
+        Value::Datetime(i) => {
+            let l = match lit {
+                Literal::Num(s) => s.parse::<i64>().ok()?,
+                Literal::Str(s) => parse_datetime(s)?,
+                Literal::Bool(_) => return None,
+            };
+            Some(i.cmp(&l))
+        },
+        Value::Binary(_) => None,
+    }
+}
+
+#[cfg(feature = "chrono")]
+pub(crate) fn parse_datetime(s: &str) -> Option<i64> {
+    if let Ok(v) = DateTime::parse_from_rfc3339(s) {
+        return Some(v.timestamp());
+    }
+    if let Ok(v) = NaiveDateTime::parse_from_str(s, "%Y-%m-%dT%H:%M:%S") {
+        return Some(v.and_utc().timestamp());
+    }
+    if let Ok(v) = NaiveDate::parse_from_str(s, "%Y-%m-%d") {
+        return v.and_hms_opt(0, 0, 0).map(|v| v.and_utc().timestamp());
+    }
+    s.parse::<i64>().ok()
+}
+
+#[cfg(not(feature = "chrono"))]
+pub(crate) fn parse_datetime(s: &str) -> Option<i64> {
+    s.parse::<i64>().ok()
+}
@@ -0,0 +1,165 @@
+use crate::onto::individual::{Individual, IndividualObj};
+use crate::onto::individual2json::{resource_data, JsonOptions};
+use crate::onto::json2individual::JsonError;
+use crate::onto::resource::Value;
+use serde_json::value::Value as JSONValue;
+use serde_json::Map;
+use std::collections::{HashMap, HashSet};
+
+/// The field of a predicate without a mapping: the local name in camelCase, `v-s:hasDocument` ->
+/// `hasDocument`, `v-s:created_by` -> `createdBy`, `http://ex.org/ns#Doc-Kind` -> `docKind`.
+pub fn camel_case(predicate: &str) -> String {
+    let local = predicate.rsplit([':', '#', '/']).next().unwrap_or(predicate);
+    let mut res = String::with_capacity(local.len());
+    let mut upper = false;
+    for c in local.chars() {
+        if c == '-' || c == '_' || c == '.' {
+            upper = !res.is_empty();
+        } else if upper {
+            res.extend(c.to_uppercase());
+            upper = false;
+        } else if res.is_empty() {
+            res.extend(c.to_lowercase());
+        } else {
+            res.push(c);
+        }
+    }
+    res
+}
+
+/// Shapes individuals into the nested JSON a GraphQL resolver returns:
+///
+/// ```text
+/// { "id": "d:doc1", "label": ["Contract"], "author": [{ "id": "d:p1", "name": ["Ivanov"] }] }
+/// ```
+///
+/// The uri is `id`, predicates become camelCase fields of value lists, values are the `data` of
+/// `as_json_opts`. Links of expanded predicates are loaded and nested up to `max_depth`; links
+/// that are not loaded, would close a cycle or lie deeper are `{ "id": uri }`.
+#[derive(Debug, Clone)]
+pub struct GraphqlShape {
+    fields: HashMap<String, String>,
+    scalars: HashSet<String>,
+    expand: HashSet<String>,
+    max_depth: usize,
+    opts: JsonOptions,
+}
+
+impl Default for GraphqlShape {
+    fn default() -> Self {
+        GraphqlShape::new()
+    }
+}
+
+impl GraphqlShape {
+    pub fn new() -> Self {
+        GraphqlShape {
+            fields: HashMap::new(),
+            scalars: HashSet::new(),
+            expand: HashSet::new(),
+            max_depth: 2,
+            opts: JsonOptions::default(),
+        }
+    }
+
+    /// Field name of a predicate instead of its camelCase name; `@` names the uri field.
+    pub fn with_field(mut self, predicate: &str, field: &str) -> Self {
+        self.fields.insert(predicate.to_owned(), field.to_owned());
+        self
+    }
+
+    /// The first value of the predicate instead of a list, `null` without values.
+    pub fn with_scalar(mut self, predicate: &str) -> Self {
+        self.scalars.insert(predicate.to_owned());
+        self
+    }
+
+    /// Links of the predicate are loaded and nested.
+    pub fn with_expand(mut self, predicate: &str) -> Self {
+        self.expand.insert(predicate.to_owned());
+        self
+    }
+
+    /// Levels of nested individuals, 2 by default; 0 writes links as `{ "id": uri }` only.
+    pub fn with_max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = max_depth;
+        self
+    }
+
+    pub fn with_json_options(mut self, opts: JsonOptions) -> Self {
+        self.opts = opts;
+        self
+    }
+
+    pub fn field(&self, predicate: &str) -> String {
+        match self.fields.get(predicate) {
+            Some(f) => f.to_owned(),
+            None if predicate == "@" => "id".to_owned(),
+            None => camel_case(predicate),
+        }
+    }
+
+    /// Shapes `obj`, loading linked individuals with `loader`. Values without a JSON form (see
+    /// `JsonOptions`) are reported, the conversion then fails.
+    pub fn shape<F>(&self, obj: &IndividualObj, mut loader: F) -> Result<JSONValue, JsonError>
+    where
+        F: FnMut(&str) -> Option<Individual>,
+    {
+        let mut errors = vec![];
+        let mut path = vec![];
+        let res = self.object(obj, &mut loader, &mut path, &mut errors);
+        if !errors.is_empty() {
+            return Err(JsonError {
+                errors,
+            });
+        }
+        Ok(res)
+    }
+
+    fn object<F>(&self, obj: &IndividualObj, loader: &mut F, path: &mut Vec<String>, errors: &mut Vec<String>) -> JSONValue
+    where
+        F: FnMut(&str) -> Option<Individual>,
+    {
+        let mut map = Map::new();
+        map.insert(self.field("@"), JSONValue::String(obj.uri.to_owned()));
+        path.push(obj.uri.to_owned());
+        for (predicate, values) in &obj.resources {
+            let mut items = vec![];
+            for r in values {
+                items.push(match &r.value {
+                    Value::Uri(u) if self.expand.contains(predicate) => self.link(u, loader, path, errors),
+                    _ => resource_data(r, self.opts).unwrap_or_else(|e| {
+                        errors.push(format!("[{}] {}: {}", obj.uri, predicate, e));
+                        JSONValue::Null
+                    }),
+                });
+            }
+            let value = if self.scalars.contains(predicate) {
+                items.into_iter().next().unwrap_or_default()
+            } else {
+                JSONValue::Array(items)
+            };
+            map.insert(self.field(predicate), value);
+        }
+        path.pop();
+        JSONValue::Object(map)
+    }
+
+    fn link<F>(&self, uri: &str, loader: &mut F, path: &mut Vec<String>, errors: &mut Vec<String>) -> JSONValue
+    where
+        F: FnMut(&str) -> Option<Individual>,
+    {
+        if path.len() <= self.max_depth && !path.iter().any(|p| p == uri) {
+            match loader(uri) {
+                Some(mut indv) => {
+                    indv.parse_all();
+                    return self.object(indv.get_obj(), loader, path, errors);
+                },
+                None => warn!("graphql shape: fail load [{}]", uri),
+            }
+        }
+        let mut map = Map::new();
+        map.insert(self.field("@"), JSONValue::String(uri.to_owned()));
+        JSONValue::Object(map)
+    }
+}
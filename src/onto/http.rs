@@ -0,0 +1,96 @@
+use crate::onto::individual::IndividualObj;
+use crate::onto::media_type::{decode, encode, negotiate, MediaError, MediaFormat};
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+
+/// Prefixes of a service: Turtle responses declare them and Turtle bodies are read into prefixed
+/// names. Registered as an axum `Extension` or an actix `app_data`.
+#[derive(Debug, Clone, Default)]
+pub struct HttpPrefixes(pub Arc<HashMap<String, String>>);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HttpError {
+    /// No format of the `Accept` header is supported, 406.
+    NotAcceptable,
+    /// 415.
+    UnsupportedMediaType { media_type: String },
+    /// The body can not be read as an individual, 400.
+    Body { msg: String },
+    /// The individual can not be written in the negotiated format, 500.
+    Encode { msg: String },
+}
+
+impl HttpError {
+    pub fn status(&self) -> u16 {
+        match self {
+            HttpError::NotAcceptable => 406,
+            HttpError::UnsupportedMediaType {
+                ..
+            } => 415,
+            HttpError::Body {
+                ..
+            } => 400,
+            HttpError::Encode {
+                ..
+            } => 500,
+        }
+    }
+}
+
+impl fmt::Display for HttpError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            HttpError::NotAcceptable => write!(f, "none of the accepted media types is supported"),
+            HttpError::UnsupportedMediaType {
+                media_type,
+            } => write!(f, "unsupported media type [{}]", media_type),
+            HttpError::Body {
+                msg,
+            } => write!(f, "invalid body: {}", msg),
+            HttpError::Encode {
+                msg,
+            } => write!(f, "fail encode: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for HttpError {}
+
+/// The response format for an `Accept` header among `MediaFormat::encodable()`, JSON without header.
+pub fn accept_format(accept: Option<&str>) -> Result<MediaFormat, HttpError> {
+    negotiate(accept, &MediaFormat::encodable()).ok_or(HttpError::NotAcceptable)
+}
+
+/// The format of a request body, JSON without `Content-Type`.
+pub fn content_format(content_type: Option<&str>) -> Result<MediaFormat, HttpError> {
+    match content_type {
+        None => Ok(MediaFormat::Json),
+        Some(ct) => MediaFormat::from_media_type(ct).filter(|f| f.can_decode()).ok_or_else(|| HttpError::UnsupportedMediaType {
+            media_type: ct.to_owned(),
+        }),
+    }
+}
+
+pub fn write_body(obj: &IndividualObj, format: MediaFormat, prefixes: &HttpPrefixes) -> Result<Vec<u8>, HttpError> {
+    encode(obj, format, &prefixes.0).map_err(|e| HttpError::Encode {
+        msg: e.to_string(),
+    })
+}
+
+/// The individual of a body; of Turtle and N-Triples the first subject.
+pub fn read_body(body: &[u8], format: MediaFormat, prefixes: &HttpPrefixes) -> Result<IndividualObj, HttpError> {
+    match decode(body, format, &prefixes.0) {
+        Ok(objs) => objs.into_iter().next().ok_or_else(|| HttpError::Body {
+            msg: "no individual".to_owned(),
+        }),
+        Err(MediaError::Unsupported {
+            media_type,
+        }) => Err(HttpError::UnsupportedMediaType {
+            media_type,
+        }),
+        Err(e) => Err(HttpError::Body {
+            msg: e.to_string(),
+        }),
+    }
+}
@@ -0,0 +1,52 @@
+use crate::onto::http::{accept_format, content_format, read_body, write_body, HttpError, HttpPrefixes};
+use crate::onto::individual::IndividualObj;
+use actix_web::body::BoxBody;
+use actix_web::dev::Payload;
+use actix_web::http::{header, StatusCode};
+use actix_web::web::Bytes;
+use actix_web::{FromRequest, HttpRequest, HttpResponse, Responder, ResponseError};
+use std::future::Future;
+use std::pin::Pin;
+
+impl ResponseError for HttpError {
+    fn status_code(&self) -> StatusCode {
+        StatusCode::from_u16(self.status()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR)
+    }
+}
+
+fn prefixes(req: &HttpRequest) -> HttpPrefixes {
+    req.app_data::<HttpPrefixes>().cloned().unwrap_or_default()
+}
+
+/// In the format negotiated from the `Accept` header, 406 if no format is acceptable; Turtle
+/// declares the `HttpPrefixes` of the app data.
+impl Responder for IndividualObj {
+    type Body = BoxBody;
+
+    fn respond_to(self, req: &HttpRequest) -> HttpResponse<Self::Body> {
+        let accept = req.headers().get(header::ACCEPT).and_then(|v| v.to_str().ok());
+        let res = accept_format(accept).and_then(|format| Ok((format, write_body(&self, format, &prefixes(req))?)));
+        match res {
+            Ok((format, body)) => HttpResponse::Ok().content_type(format.media_type()).body(body),
+            Err(e) => e.error_response(),
+        }
+    }
+}
+
+/// Reads the body in the format of its `Content-Type`.
+impl FromRequest for IndividualObj {
+    type Error = HttpError;
+    type Future = Pin<Box<dyn Future<Output = Result<Self, Self::Error>>>>;
+
+    fn from_request(req: &HttpRequest, payload: &mut Payload) -> Self::Future {
+        let req = req.clone();
+        let body = Bytes::from_request(&req, payload);
+        Box::pin(async move {
+            let format = content_format(req.headers().get(header::CONTENT_TYPE).and_then(|v| v.to_str().ok()))?;
+            let body = body.await.map_err(|e| HttpError::Body {
+                msg: e.to_string(),
+            })?;
+            read_body(&body, format, &prefixes(&req))
+        })
+    }
+}
@@ -0,0 +1,79 @@
+use crate::onto::http::{accept_format, content_format, read_body, write_body, HttpError, HttpPrefixes};
+use crate::onto::individual::IndividualObj;
+use crate::onto::media_type::MediaFormat;
+use axum::body::Bytes;
+use axum::extract::{FromRequest, FromRequestParts, Request};
+use axum::http::request::Parts;
+use axum::http::{header, StatusCode};
+use axum::response::{IntoResponse, Response};
+
+impl IntoResponse for HttpError {
+    fn into_response(self) -> Response {
+        let status = StatusCode::from_u16(self.status()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
+        (status, self.to_string()).into_response()
+    }
+}
+
+/// Extracts the response format of a request from its `Accept` header, 406 if no format is
+/// acceptable, and the `HttpPrefixes` extension:
+///
+/// ```text
+/// async fn get(accept: AcceptFormat) -> Response { accept.respond(&obj) }
+/// ```
+#[derive(Debug, Clone)]
+pub struct AcceptFormat {
+    pub format: MediaFormat,
+    pub prefixes: HttpPrefixes,
+}
+
+impl AcceptFormat {
+    pub fn respond(&self, obj: &IndividualObj) -> Response {
+        match write_body(obj, self.format, &self.prefixes) {
+            Ok(body) => ([(header::CONTENT_TYPE, self.format.media_type())], body).into_response(),
+            Err(e) => e.into_response(),
+        }
+    }
+}
+
+impl<S> FromRequestParts<S> for AcceptFormat
+where
+    S: Send + Sync,
+{
+    type Rejection = HttpError;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        let accept = parts.headers.get(header::ACCEPT).and_then(|v| v.to_str().ok());
+        Ok(AcceptFormat {
+            format: accept_format(accept)?,
+            prefixes: parts.extensions.get::<HttpPrefixes>().cloned().unwrap_or_default(),
+        })
+    }
+}
+
+/// JSON; handlers that negotiate take an `AcceptFormat`.
+impl IntoResponse for IndividualObj {
+    fn into_response(self) -> Response {
+        AcceptFormat {
+            format: MediaFormat::Json,
+            prefixes: HttpPrefixes::default(),
+        }
+        .respond(&self)
+    }
+}
+
+/// Reads the body in the format of its `Content-Type`.
+impl<S> FromRequest<S> for IndividualObj
+where
+    S: Send + Sync,
+{
+    type Rejection = HttpError;
+
+    async fn from_request(req: Request, state: &S) -> Result<Self, Self::Rejection> {
+        let format = content_format(req.headers().get(header::CONTENT_TYPE).and_then(|v| v.to_str().ok()))?;
+        let prefixes = req.extensions().get::<HttpPrefixes>().cloned().unwrap_or_default();
+        let body = Bytes::from_request(req, state).await.map_err(|e| HttpError::Body {
+            msg: e.body_text(),
+        })?;
+        read_body(&body, format, &prefixes)
+    }
+}
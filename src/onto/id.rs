@@ -0,0 +1,93 @@
+use ulid::Ulid;
+use uuid::Uuid;
+
+pub const DEFAULT_PREFIX: &str = "d:";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum IdMode {
+    /// Random UUID v4, the platform default.
+    #[default]
+    Random,
+    /// UUID v7, ids generated later sort after earlier ones.
+    Sortable,
+    /// ULID, time sortable and shorter (26 characters).
+    Ulid,
+}
+
+/// Builds uris for new individuals as `<prefix><id>`, e.g. `d:0190b0a47c2e7b3a9f8e6c1d2b3a4f5e`.
+#[derive(Debug, Clone)]
+pub struct IdGenerator {
+    prefix: String,
+    mode: IdMode,
+}
+
+impl Default for IdGenerator {
+    fn default() -> Self {
+        IdGenerator {
+            prefix: DEFAULT_PREFIX.to_owned(),
+            mode: IdMode::Random,
+        }
+    }
+}
+
+impl IdGenerator {
+    pub fn new(prefix: &str, mode: IdMode) -> Self {
+        IdGenerator {
+            prefix: prefix.to_owned(),
+            mode,
+        }
+    }
+
+    pub fn with_prefix(mut self, prefix: &str) -> Self {
+        self.prefix = prefix.to_owned();
+        self
+    }
+
+    pub fn with_mode(mut self, mode: IdMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    pub fn get_prefix(&self) -> &str {
+        &self.prefix
+    }
+
+    pub fn get_mode(&self) -> IdMode {
+        self.mode
+    }
+
+    pub fn generate(&self) -> String {
+        let id = match self.mode {
+            IdMode::Random => Uuid::new_v4().simple().to_string(),
+            IdMode::Sortable => Uuid::now_v7().simple().to_string(),
+            IdMode::Ulid => Ulid::new().to_string().to_lowercase(),
+        };
+        format!("{}{}", self.prefix, id)
+    }
+}
+
+/// `d:` + random UUID v4.
+pub fn new_uri() -> String {
+    IdGenerator::default().generate()
+}
+
+/// `d:` + UUID v7, lexicographic order follows creation time.
+pub fn new_sortable_uri() -> String {
+    IdGenerator::new(DEFAULT_PREFIX, IdMode::Sortable).generate()
+}
+
+pub fn new_ulid_uri() -> String {
+    IdGenerator::new(DEFAULT_PREFIX, IdMode::Ulid).generate()
+}
+
+/// Extracts the creation time (unix milliseconds) from an id generated in `Sortable` or `Ulid` mode.
+pub fn get_timestamp_ms(uri: &str, prefix: &str) -> Option<u64> {
+    let id = uri.strip_prefix(prefix)?;
+    if id.len() == 26 {
+        return Ulid::from_string(&id.to_uppercase()).ok().map(|u| u.timestamp_ms());
+    }
+
+    let u = Uuid::try_parse(id).ok()?;
+    let (secs, nanos) = u.get_timestamp()?.to_unix();
+    Some(secs * 1000 + (nanos / 1_000_000) as u64)
+}
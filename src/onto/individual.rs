@@ -1,30 +1,82 @@
+#[cfg(feature = "std")]
+use crate::onto::aliases::predicate_aliases;
 use crate::onto::datatype::{DataType, Lang};
-use crate::onto::parser::{parse_to_predicate, RawType};
+#[cfg(feature = "chrono")]
+use crate::onto::datetime::{parse_datetime_local, to_utc, DatetimeError};
+#[cfg(feature = "std")]
+use crate::onto::decimal::{to_decimal, DecimalConfig, DecimalError, Rounding};
+use crate::onto::iri::normalize_iri;
+use crate::onto::parser::{parse_to_predicate, RawType, MSGPACK_MAGIC_HEADER};
 use crate::onto::resource::{Resource, Value};
-use chrono::offset::LocalResult::Single;
-use chrono::{DateTime, Local, NaiveDateTime, TimeZone};
-use num::FromPrimitive;
-use num_traits::pow;
+use alloc::borrow::{Cow, ToOwned};
+use alloc::string::{String, ToString};
+use alloc::sync::Arc;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt;
+use core::mem;
+use indexmap::map::{self, IndexMap};
+#[cfg(feature = "std")]
 use rust_decimal::Decimal;
-use std::collections::HashMap;
-use std::fmt;
+#[cfg(feature = "std")]
 use std::io::{Error, ErrorKind};
-use std::ops::Sub;
-use std::str::FromStr;
+
+/// Without std the maps of the model are hashbrown maps.
+#[cfg(not(feature = "std"))]
+pub(crate) use hashbrown::{DefaultHashBuilder as RandomState, HashMap, HashSet};
+#[cfg(feature = "std")]
+pub(crate) use std::collections::{hash_map::RandomState, HashMap, HashSet};
+
+pub const TEMPLATE_WILDCARD: &str = "*";
 
 #[derive(Eq, PartialEq, Debug, Clone)]
 pub enum IndividualError {
     None,
     ParseError,
+    /// The individual has no value of `predicate`.
+    MissingPredicate {
+        uri: String,
+        predicate: String,
+    },
+    /// The first value of `predicate` is not of the datatype asked for.
+    WrongType {
+        uri: String,
+        predicate: String,
+        expected: DataType,
+        found: DataType,
+    },
+}
+
+impl fmt::Display for IndividualError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            IndividualError::None => write!(f, "no error"),
+            IndividualError::ParseError => write!(f, "parse error"),
+            IndividualError::MissingPredicate {
+                uri,
+                predicate,
+            } => write!(f, "{}: missing {}", uri, predicate),
+            IndividualError::WrongType {
+                uri,
+                predicate,
+                expected,
+                found,
+            } => write!(f, "{}: {} is {:?}, expected {:?}", uri, predicate, found, expected),
+        }
+    }
 }
 
-#[derive(Debug)]
+#[cfg(feature = "std")]
+impl std::error::Error for IndividualError {}
+
 pub struct IndividualObj {
     pub(crate) uri: String,
-    pub(crate) resources: HashMap<String, Vec<Resource>>,
+    /// Predicates in the order they were added, serializers write them in this order.
+    pub(crate) resources: IndexMap<String, Vec<Resource>, RandomState>,
+    /// Rights mask by predicate for field-level access control, see `onto::rights`.
+    pub(crate) rights: HashMap<String, u8>,
 }
 
-#[derive(Debug)]
 pub struct RawObj {
     pub data: Vec<u8>,
     pub(crate) cur: u64,
@@ -62,10 +114,22 @@ impl RawObj {
     }
 }
 
-#[derive(Debug)]
 pub struct Individual {
     pub(crate) obj: IndividualObj,
     pub(crate) raw: RawObj,
+    pub(crate) dirty: bool,
+    pub(crate) observers: Observers,
+    /// Predicates changed since parsing or the last `clear_dirty`.
+    pub(crate) changed: HashSet<String>,
+}
+
+/// Called with the object and the predicate, see `Individual::on_set`.
+pub type ObserverFn = dyn Fn(&mut IndividualObj, &str) + Send + Sync;
+
+#[derive(Clone, Default)]
+pub(crate) struct Observers {
+    on_set: Vec<Arc<ObserverFn>>,
+    on_remove: Vec<Arc<ObserverFn>>,
 }
 
 impl Default for Individual {
@@ -73,15 +137,27 @@ impl Default for Individual {
         Individual {
             obj: IndividualObj::default(),
             raw: RawObj::new_empty(),
+            dirty: false,
+            observers: Observers::default(),
+            changed: HashSet::new(),
         }
     }
 }
 
+impl From<IndividualObj> for Individual {
+    fn from(obj: IndividualObj) -> Self {
+        Individual::from_obj(obj)
+    }
+}
+
 impl Individual {
     pub fn new_raw(raw: RawObj) -> Self {
         Individual {
             obj: IndividualObj::default(),
             raw,
+            dirty: false,
+            observers: Observers::default(),
+            changed: HashSet::new(),
         }
     }
 
@@ -89,6 +165,7 @@ impl Individual {
         let mut new_obj = IndividualObj {
             uri: obj.uri.to_owned(),
             resources: Default::default(),
+            rights: obj.rights.clone(),
         };
 
         for (predicate, resources) in obj.resources.iter() {
@@ -108,12 +185,73 @@ impl Individual {
                 cur_predicates: 0,
                 raw_type: RawType::Cbor,
             },
+            dirty: true,
+            observers: Observers::default(),
+            changed: HashSet::new(),
         }
     }
 
+    /// Takes ownership of `obj` without copying its values; the individual has no raw buffer yet.
+    pub fn from_obj(obj: IndividualObj) -> Self {
+        Individual {
+            obj,
+            raw: RawObj::new_empty(),
+            dirty: true,
+            observers: Observers::default(),
+            changed: HashSet::new(),
+        }
+    }
+
+    /// An individual whose `raw` is already the msgpack or CBOR encoding of `obj`, e.g. both were kept
+    /// by a cache. Nothing is parsed and `get_clean_raw` returns `raw`; the caller vouches that they match.
+    pub fn from_obj_with_raw(obj: IndividualObj, raw: Vec<u8>) -> Self {
+        let mut raw = RawObj::new(raw);
+        raw.raw_type = match raw.data.first() {
+            Some(&MSGPACK_MAGIC_HEADER) => RawType::Msgpack,
+            Some(_) => RawType::Cbor,
+            None => RawType::Unknown,
+        };
+        raw.cur = raw.data.len() as u64;
+        Individual {
+            obj,
+            raw,
+            dirty: false,
+            observers: Observers::default(),
+            changed: HashSet::new(),
+        }
+    }
+
+    /// The decoded object, predicates not parsed yet are parsed first.
+    pub fn into_obj(mut self) -> IndividualObj {
+        self.parse_all();
+        self.obj
+    }
+
+    /// Like `into_obj`, also returns the raw buffer; it is None if the individual was changed after loading.
+    pub fn into_parts(mut self) -> (IndividualObj, Option<Vec<u8>>) {
+        self.parse_all();
+        let raw = if self.dirty || self.raw.data.is_empty() {
+            None
+        } else {
+            Some(self.raw.data)
+        };
+        (self.obj, raw)
+    }
+
+    /// Mutable object and the raw buffer at once, e.g. to patch values while copying parts of the original
+    /// encoding. All predicates are parsed first and the individual counts as changed afterwards.
+    pub fn split_mut(&mut self) -> (&mut IndividualObj, &[u8]) {
+        self.parse_all();
+        self.dirty = true;
+        self.changed.extend(self.obj.resources.keys().cloned());
+        (&mut self.obj, &self.raw.data)
+    }
+
     pub fn reset(&mut self) {
         self.obj.resources.clear();
+        self.obj.rights.clear();
         self.raw.reset();
+        self.changed.clear();
     }
 
     pub fn is_empty(&self) -> bool {
@@ -124,162 +262,456 @@ impl Individual {
         &self.obj
     }
 
+    pub(crate) fn obj_mut(&mut self) -> &mut IndividualObj {
+        self.dirty = true;
+        &mut self.obj
+    }
+
+    /// True once the individual was changed after being loaded from its raw buffer.
+    pub fn is_dirty(&self) -> bool {
+        self.dirty
+    }
+
+    /// Calls `f` after values of a predicate were added or changed through this individual, with
+    /// the object and the predicate. `f` may change the object without being called again, e.g.
+    /// `indv.on_set(|obj, p| if p != "v-s:edited" { obj.set_datetime("v-s:edited", now()) })`.
+    /// Parsing, `split_mut` and `get_first_resource_mut` are not reported.
+    pub fn on_set<F>(&mut self, f: F) -> &mut Self
+    where
+        F: Fn(&mut IndividualObj, &str) + Send + Sync + 'static,
+    {
+        self.observers.on_set.push(Arc::new(f));
+        self
+    }
+
+    /// Calls `f` after the last value of a predicate was removed through this individual.
+    pub fn on_remove<F>(&mut self, f: F) -> &mut Self
+    where
+        F: Fn(&mut IndividualObj, &str) + Send + Sync + 'static,
+    {
+        self.observers.on_remove.push(Arc::new(f));
+        self
+    }
+
+    pub fn clear_observers(&mut self) {
+        self.observers = Observers::default();
+    }
+
+    /// Predicates changed through this individual since it was parsed or `clear_dirty` was called,
+    /// e.g. to put only these into an update message. Removed predicates stay in the set.
+    /// `split_mut` counts all predicates as changed.
+    pub fn changed_predicates(&self) -> &HashSet<String> {
+        &self.changed
+    }
+
+    /// Forgets the changes, e.g. once the individual was stored. The raw buffer no longer describing
+    /// the individual is dropped, so `get_clean_raw` stays None until `set_raw`.
+    pub fn clear_dirty(&mut self) {
+        if self.dirty {
+            self.parse_all();
+            self.raw = RawObj::new_empty();
+            self.dirty = false;
+        }
+        self.changed.clear();
+    }
+
+    /// Records a change of `predicate` and reports it to the observers: `on_set` while it has values,
+    /// else `on_remove`.
+    pub(crate) fn modified(&mut self, predicate: &str) {
+        if !self.changed.contains(predicate) {
+            self.changed.insert(predicate.to_owned());
+        }
+        let observers = if self.obj.resources.get(predicate).is_some_and(|v| !v.is_empty()) {
+            &self.observers.on_set
+        } else {
+            &self.observers.on_remove
+        };
+        for f in observers.iter() {
+            f(&mut self.obj, predicate);
+        }
+    }
+
+    /// Runs `f` without observers and without recording changes, for parsers filling the individual
+    /// through its setters.
+    #[cfg(feature = "json")]
+    pub(crate) fn unobserved<R>(&mut self, f: impl FnOnce(&mut Individual) -> R) -> R {
+        let observers = mem::take(&mut self.observers);
+        let changed = mem::take(&mut self.changed);
+        let res = f(self);
+        self.observers = observers;
+        self.changed = changed;
+        res
+    }
+
+    /// Runs `f` on the fully parsed object and reports the predicates whose values it changed, as
+    /// `modified`; the individual is marked modified when `f` changed its values, uri or rights.
+    pub(crate) fn modify_any<R>(&mut self, f: impl FnOnce(&mut IndividualObj) -> R) -> R {
+        self.parse_all();
+
+        let before: IndexMap<String, Vec<Resource>, RandomState> =
+            self.obj.resources.iter().map(|(p, v)| (p.to_owned(), v.iter().map(|r| r.get_copy()).collect())).collect();
+        let (uri, rights) = (self.obj.uri.clone(), self.obj.rights.clone());
+        let res = f(&mut self.obj);
+        let mut changed: Vec<String> =
+            before.iter().filter(|(p, v)| self.obj.resources.get(*p).map_or(!v.is_empty(), |now| now != *v)).map(|(p, _)| p.to_owned()).collect();
+        changed.extend(self.obj.resources.iter().filter(|(p, v)| !v.is_empty() && !before.contains_key(*p)).map(|(p, _)| p.to_owned()));
+        if !changed.is_empty() || self.obj.uri != uri || self.obj.rights != rights {
+            self.dirty = true;
+        }
+        for predicate in changed.iter() {
+            self.modified(predicate);
+        }
+        res
+    }
+
+    /// The original msgpack or CBOR buffer, while it still describes the individual.
+    pub fn get_clean_raw(&self) -> Option<&[u8]> {
+        if self.dirty || self.raw.data.is_empty() {
+            None
+        } else {
+            Some(&self.raw.data)
+        }
+    }
+
     pub fn remove(&mut self, predicate: &str) -> bool {
-        self.obj.remove(predicate)
+        let res = self.obj_mut().remove(predicate);
+        if res {
+            self.modified(predicate);
+        }
+        res
+    }
+
+    /// The individual counts as modified once the value is handed out, and the predicate as changed.
+    pub fn get_first_resource_mut(&mut self, predicate: &str) -> Option<&mut Resource> {
+        if self.values_of(predicate).is_empty() {
+            return None;
+        }
+        if !self.changed.contains(predicate) {
+            self.changed.insert(predicate.to_owned());
+        }
+        self.obj_mut().get_first_resource_mut(predicate)
+    }
+
+    /// E.g. `indv.update_value("v-s:counter", 0, |v| if let Value::Int(i) = v { *i += 1 })`.
+    pub fn update_value<F>(&mut self, predicate: &str, idx: usize, f: F) -> bool
+    where
+        F: FnOnce(&mut Value),
+    {
+        if self.values_of(predicate).len() <= idx {
+            return false;
+        }
+        let res = self.obj_mut().update_value(predicate, idx, f);
+        if res {
+            self.modified(predicate);
+        }
+        res
     }
 
     pub fn clear(&mut self, predicate: &str) {
-        self.obj.clear(predicate);
+        self.obj_mut().clear(predicate);
+        self.modified(predicate);
+    }
+
+    pub fn remove_predicates(&mut self, predicates: &[&str]) -> Vec<String> {
+        self.parse_all();
+        let removed = self.obj_mut().remove_predicates(predicates);
+        for predicate in removed.iter() {
+            self.modified(predicate);
+        }
+        removed
+    }
+
+    pub fn rename_predicate(&mut self, old: &str, new: &str) -> bool {
+        self.parse_all();
+        let res = self.obj_mut().rename_predicate(old, new);
+        if res {
+            self.modified(old);
+            self.modified(new);
+        }
+        res
+    }
+
+    pub fn retain_predicates<F>(&mut self, f: F) -> Vec<String>
+    where
+        F: FnMut(&str, &[Resource]) -> bool,
+    {
+        self.parse_all();
+        let removed = self.obj_mut().retain_predicates(f);
+        for predicate in removed.iter() {
+            self.modified(predicate);
+        }
+        removed
+    }
+
+    pub fn project(&mut self, predicates: &[&str]) -> IndividualObj {
+        self.parse_all();
+        self.obj.project(predicates)
+    }
+
+    pub fn retain(&mut self, predicates: &[&str]) -> Vec<String> {
+        self.parse_all();
+        let removed = self.obj_mut().retain(predicates);
+        for predicate in removed.iter() {
+            self.modified(predicate);
+        }
+        removed
+    }
+
+    pub fn matches_template(&mut self, template: &IndividualObj) -> bool {
+        self.parse_all();
+        self.obj.matches_template(template)
+    }
+
+    pub fn add_unique(&mut self, predicate: &str, r: Resource) -> bool {
+        self.is_exists(predicate);
+        let res = self.obj_mut().add_unique(predicate, r);
+        if res {
+            self.modified(predicate);
+        }
+        res
+    }
+
+    pub fn add_unique_uri(&mut self, predicate: &str, s: &str) -> Result<bool, String> {
+        let v = normalize_iri(s)?;
+        Ok(self.add_unique(predicate, Resource::new_uri(&v)))
+    }
+
+    pub fn add_unique_string(&mut self, predicate: &str, s: &str, lang: Lang) -> bool {
+        self.is_exists(predicate);
+        let res = self.obj_mut().add_unique_string(predicate, s, lang);
+        if res {
+            self.modified(predicate);
+        }
+        res
+    }
+
+    pub fn add_unique_integer(&mut self, predicate: &str, i: i64) -> bool {
+        self.is_exists(predicate);
+        let res = self.obj_mut().add_unique_integer(predicate, i);
+        if res {
+            self.modified(predicate);
+        }
+        res
+    }
+
+    pub fn add_unique_decimal_d(&mut self, predicate: &str, mantissa: i64, exponent: i64) -> bool {
+        self.is_exists(predicate);
+        let res = self.obj_mut().add_unique_decimal_d(predicate, mantissa, exponent);
+        if res {
+            self.modified(predicate);
+        }
+        res
+    }
+
+    pub fn add_unique_datetime(&mut self, predicate: &str, i: i64) -> bool {
+        self.is_exists(predicate);
+        let res = self.obj_mut().add_unique_datetime(predicate, i);
+        if res {
+            self.modified(predicate);
+        }
+        res
+    }
+
+    pub fn add_unique_bool(&mut self, predicate: &str, b: bool) -> bool {
+        self.is_exists(predicate);
+        let res = self.obj_mut().add_unique_bool(predicate, b);
+        if res {
+            self.modified(predicate);
+        }
+        res
+    }
+
+    pub fn add_unique_binary(&mut self, predicate: &str, v: Vec<u8>) -> bool {
+        self.is_exists(predicate);
+        let res = self.obj_mut().add_unique_binary(predicate, v);
+        if res {
+            self.modified(predicate);
+        }
+        res
+    }
+
+    pub fn dedup_values(&mut self, predicate: &str) -> usize {
+        self.is_exists(predicate);
+        let count = self.obj_mut().dedup_values(predicate);
+        if count > 0 {
+            self.modified(predicate);
+        }
+        count
+    }
+
+    pub fn dedup_all_values(&mut self) -> usize {
+        self.modify_any(|obj| obj.dedup_all_values())
     }
 
     pub fn add_bool(&mut self, predicate: &str, b: bool) {
-        self.obj.add_bool(predicate, b)
+        self.obj_mut().add_bool(predicate, b);
+        self.modified(predicate);
     }
 
     pub fn set_bool(&mut self, predicate: &str, b: bool) {
-        self.obj.set_bool(predicate, b)
+        self.obj_mut().set_bool(predicate, b);
+        self.modified(predicate);
     }
 
     pub fn add_datetime(&mut self, predicate: &str, i: i64) {
-        self.obj.add_datetime(predicate, i)
+        self.obj_mut().add_datetime(predicate, i);
+        self.modified(predicate);
     }
 
+    #[cfg(feature = "chrono")]
     pub fn add_datetime_from_str(&mut self, predicate: &str, value: &str) {
-        if value.contains('Z') {
-            if let Ok(v) = DateTime::parse_from_rfc3339(value) {
-                self.add_datetime(predicate, v.timestamp());
-            } else {
-                error!("fail parse [{}] to datetime", value);
-            }
-        } else {
-            let ndt;
-            if value.len() == 10 {
-                if value.contains('.') {
-                    ndt = NaiveDateTime::parse_from_str(&(value.to_owned() + "T00:00:00"), "%d.%m.%YT%H:%M:%S");
-                } else {
-                    ndt = NaiveDateTime::parse_from_str(&(value.to_owned() + "T00:00:00"), "%Y-%m-%dT%H:%M:%S");
-                }
-            } else {
-                ndt = NaiveDateTime::parse_from_str(value, "%Y-%m-%dT%H:%M:%S")
-            }
-
-            if let Ok(v) = ndt {
-                if let Single(offset) = Local.offset_from_local_datetime(&v) {
-                    self.add_datetime(predicate, v.sub(offset).and_utc().timestamp());
-                } else {
-                    self.add_datetime(predicate, v.and_utc().timestamp());
-                }
-            } else {
-                error!("fail parse [{}] to datetime", value);
-            }
+        if let Err(e) = self.try_add_datetime_from_str(predicate, value) {
+            error!("{}", e);
         }
     }
 
+    /// Values with a timezone (`Z`, `±hh:mm`) may have signed or more than four digit years,
+    /// values without one are local time.
+    #[cfg(feature = "chrono")]
+    pub fn try_add_datetime_from_str(&mut self, predicate: &str, value: &str) -> Result<(), DatetimeError> {
+        self.add_datetime(predicate, parse_datetime_local(value)?);
+        Ok(())
+    }
+
     pub fn set_datetime(&mut self, predicate: &str, i: i64) {
-        self.obj.set_datetime(predicate, i)
+        self.obj_mut().set_datetime(predicate, i);
+        self.modified(predicate);
     }
 
     pub fn add_binary(&mut self, predicate: &str, v: Vec<u8>) {
-        self.obj.add_binary(predicate, v)
+        self.obj_mut().add_binary(predicate, v);
+        self.modified(predicate);
     }
 
     pub fn set_binary(&mut self, predicate: &str, v: Vec<u8>) {
-        self.obj.set_binary(predicate, v)
+        self.obj_mut().set_binary(predicate, v);
+        self.modified(predicate);
     }
 
     pub fn add_integer(&mut self, predicate: &str, i: i64) {
-        self.obj.add_integer(predicate, i)
+        self.obj_mut().add_integer(predicate, i);
+        self.modified(predicate);
+    }
+
+    pub fn add_resources(&mut self, predicate: &str, v: &[Resource]) {
+        self.obj_mut().add_resources(predicate, v);
+        self.modified(predicate);
     }
 
     pub fn set_resources(&mut self, predicate: &str, v: &[Resource]) {
-        self.remove(predicate);
+        let obj = self.obj_mut();
+        obj.remove(predicate);
         for el in v.iter() {
             match el.rtype {
-                DataType::String => self.add_string(predicate, el.get_str(), el.get_lang()),
-                DataType::Uri => self.add_uri(predicate, el.get_uri()),
-                DataType::Datetime => self.add_datetime(predicate, el.get_datetime()),
-                DataType::Integer => self.add_integer(predicate, el.get_int()),
+                DataType::String => obj.add_string(predicate, el.get_str(), el.get_lang()),
+                DataType::Uri => obj.add_uri_unchecked(predicate, el.get_uri()),
+                DataType::Datetime => obj.add_datetime(predicate, el.get_datetime()),
+                DataType::Integer => obj.add_integer(predicate, el.get_int()),
                 DataType::Decimal => {
                     let d = el.get_num();
-                    self.add_decimal_d(predicate, d.0, d.1);
+                    obj.add_decimal_d(predicate, d.0, d.1);
                 },
-                DataType::Boolean => self.add_bool(predicate, el.get_bool()),
+                DataType::Boolean => obj.add_bool(predicate, el.get_bool()),
                 DataType::Binary => {},
             }
         }
+        self.modified(predicate);
     }
 
     pub fn set_integer(&mut self, predicate: &str, i: i64) {
-        self.obj.set_integer(predicate, i)
+        self.obj_mut().set_integer(predicate, i);
+        self.modified(predicate);
     }
 
     pub fn add_decimal_d(&mut self, predicate: &str, mantissa: i64, exponent: i64) {
-        self.obj.add_decimal_d(predicate, mantissa, exponent)
+        self.obj_mut().add_decimal_d(predicate, mantissa, exponent);
+        self.modified(predicate);
     }
 
+    #[cfg(feature = "std")]
     pub fn add_decimal_from_str(&mut self, predicate: &str, value: &str) {
-        if let Ok(v) = Decimal::from_str(value) {
-            let exp = -(v.scale() as i32);
-            if let Ok(m) = value.replace('.', "").parse::<i64>() {
-                self.add_decimal_d(predicate, m, exp as i64);
-            }
-        } else {
-            error!("fail parse [{}] to decimal", value);
+        if let Err(e) = self.try_add_decimal_from_str(predicate, value, &DecimalConfig::default()) {
+            error!("predicate [{}]: {}", predicate, e);
         }
     }
 
+    #[cfg(feature = "std")]
+    /// Rounds to `cfg.max_scale`; a mantissa beyond i64 is an error, not truncated.
+    pub fn try_add_decimal_from_str(&mut self, predicate: &str, value: &str, cfg: &DecimalConfig) -> Result<(), DecimalError> {
+        let (m, e) = cfg.parse(value)?;
+        self.add_decimal_d(predicate, m, e);
+        Ok(())
+    }
+
     pub fn add_decimal_from_i64(&mut self, predicate: &str, value: i64) {
         self.add_decimal_d(predicate, value, 0);
     }
 
+    #[cfg(feature = "std")]
     pub fn add_decimal_from_f64(&mut self, predicate: &str, value: f64) {
-        if let Some(v) = Decimal::from_f64(value) {
-            let exp = v.scale() as usize;
-            let p: i64 = pow(10, exp);
-            let mantissa: f64 = value * p as f64;
-            self.add_decimal_d(predicate, mantissa as i64, -(exp as i64));
-        } else {
-            error!("fail parse [{}] to decimal", value);
+        match DecimalConfig::default().from_f64(value) {
+            Ok((m, e)) => self.add_decimal_d(predicate, m, e),
+            Err(e) => error!("predicate [{}]: {}", predicate, e),
         }
     }
 
+    #[cfg(feature = "std")]
     pub fn set_decimal_from_f64(&mut self, predicate: &str, value: f64) {
-        if let Some(v) = Decimal::from_f64(value) {
-            let exp = v.scale() as usize;
-            let p: i64 = pow(10, exp);
-            let mantissa: f64 = value * p as f64;
-            self.set_decimal_d(predicate, mantissa as i64, -(exp as i64));
-        } else {
-            error!("fail parse [{}] to decimal", value);
+        match DecimalConfig::default().from_f64(value) {
+            Ok((m, e)) => self.set_decimal_d(predicate, m, e),
+            Err(e) => error!("predicate [{}]: {}", predicate, e),
         }
     }
 
     pub fn set_decimal_d(&mut self, predicate: &str, mantissa: i64, exponent: i64) {
-        self.obj.set_decimal_d(predicate, mantissa, exponent)
+        self.obj_mut().set_decimal_d(predicate, mantissa, exponent);
+        self.modified(predicate);
+    }
+
+    /// See `IndividualObj::add_uri`; a malformed uri leaves the individual unmodified.
+    pub fn add_uri(&mut self, predicate: &str, s: &str) -> Result<(), String> {
+        let v = normalize_iri(s)?;
+        self.add_uri_unchecked(predicate, &v);
+        Ok(())
+    }
+
+    pub fn set_uri(&mut self, predicate: &str, s: &str) -> Result<(), String> {
+        let v = normalize_iri(s)?;
+        self.set_uri_unchecked(predicate, &v);
+        Ok(())
     }
 
-    pub fn add_uri(&mut self, predicate: &str, s: &str) {
-        self.obj.add_uri(predicate, s)
+    pub fn add_uri_unchecked(&mut self, predicate: &str, s: &str) {
+        self.obj_mut().add_uri_unchecked(predicate, s);
+        self.modified(predicate);
     }
 
-    pub fn set_uri(&mut self, predicate: &str, s: &str) {
-        self.obj.set_uri(predicate, s)
+    pub fn set_uri_unchecked(&mut self, predicate: &str, s: &str) {
+        self.obj_mut().set_uri_unchecked(predicate, s);
+        self.modified(predicate);
     }
 
-    pub fn set_uris(&mut self, predicate: &str, ss: Vec<String>) {
-        self.obj.set_uris(predicate, ss)
+    pub fn set_uris(&mut self, predicate: &str, ss: Vec<String>) -> Result<(), String> {
+        let normalized = ss.iter().map(|s| normalize_iri(s)).collect::<Result<Vec<String>, String>>()?;
+        self.obj_mut().set_uris(predicate, normalized)?;
+        self.modified(predicate);
+        Ok(())
     }
 
     pub fn add_string(&mut self, predicate: &str, s: &str, lang: Lang) {
-        self.obj.add_string(predicate, s, lang)
+        self.obj_mut().add_string(predicate, s, lang);
+        self.modified(predicate);
     }
 
     pub fn set_string(&mut self, predicate: &str, s: &str, lang: Lang) {
-        self.obj.set_string(predicate, s, lang)
+        self.obj_mut().set_string(predicate, s, lang);
+        self.modified(predicate);
     }
 
     pub fn set_raw(&mut self, data: &[u8]) {
         self.raw.data = data.to_vec();
+        self.dirty = false;
+        self.changed.clear();
     }
 
     pub fn get_raw_len(&self) -> usize {
@@ -291,15 +723,23 @@ impl Individual {
     }
 
     pub fn set_id(&mut self, id: &str) -> &mut Self {
-        self.obj.uri = id.to_owned();
+        self.obj_mut().uri = id.to_owned();
         self
     }
 
+    /// Like `set_id`, but normalizes the uri and rejects malformed ones.
+    pub fn try_set_id(&mut self, id: &str) -> Result<&mut Self, String> {
+        self.obj_mut().uri = normalize_iri(id)?;
+        Ok(self)
+    }
+
     pub fn get_id(&self) -> &str {
         &self.obj.uri
     }
 
     pub fn is_exists(&mut self, predicate: &str) -> bool {
+        let predicate = self.resolve_alias(predicate);
+        let predicate: &str = &predicate;
         for _ in 0..2 {
             match self.obj.resources.get(predicate) {
                 Some(v) => {
@@ -321,6 +761,8 @@ impl Individual {
     }
 
     pub fn any_exists(&mut self, predicate: &str, values: &[&str]) -> bool {
+        let predicate = self.resolve_alias(predicate);
+        let predicate: &str = &predicate;
         for _ in 0..2 {
             match self.obj.resources.get(predicate) {
                 Some(v) => {
@@ -356,6 +798,8 @@ impl Individual {
     }
 
     pub fn any_exists_v(&mut self, predicate: &str, values: &[String]) -> bool {
+        let predicate = self.resolve_alias(predicate);
+        let predicate: &str = &predicate;
         for _ in 0..2 {
             match self.obj.resources.get(predicate) {
                 Some(v) => {
@@ -391,6 +835,8 @@ impl Individual {
     }
 
     pub fn is_exists_bool(&mut self, predicate: &str, value: bool) -> bool {
+        let predicate = self.resolve_alias(predicate);
+        let predicate: &str = &predicate;
         for _ in 0..2 {
             match self.obj.resources.get(predicate) {
                 Some(v) => {
@@ -418,6 +864,8 @@ impl Individual {
     }
 
     pub fn get_resources(&mut self, predicate: &str) -> Option<Vec<Resource>> {
+        let predicate = self.resolve_alias(predicate);
+        let predicate: &str = &predicate;
         for _ in 0..2 {
             match self.obj.resources.get(predicate) {
                 Some(v) => {
@@ -439,6 +887,8 @@ impl Individual {
     }
 
     pub fn get_literals(&mut self, predicate: &str) -> Option<Vec<String>> {
+        let predicate = self.resolve_alias(predicate);
+        let predicate: &str = &predicate;
         for _ in 0..2 {
             match self.obj.resources.get(predicate) {
                 Some(v) => {
@@ -487,6 +937,7 @@ impl Individual {
         })
     }
 
+    #[cfg(feature = "std")]
     pub fn get_first_literal_or_err(&mut self, predicate: &str) -> Result<String, std::io::Error> {
         if let Some(v) = self.get_first_literal(predicate) {
             Ok(v)
@@ -496,6 +947,8 @@ impl Individual {
     }
 
     pub fn get_first_literal(&mut self, predicate: &str) -> Option<String> {
+        let predicate = self.resolve_alias(predicate);
+        let predicate: &str = &predicate;
         for _ in 0..2 {
             match self.obj.resources.get(predicate) {
                 Some(v) => match &v[0].value {
@@ -525,6 +978,8 @@ impl Individual {
     }
 
     pub fn get_first_literal_with_lang(&mut self, predicate: &str, lang: &[Lang]) -> Option<String> {
+        let predicate = self.resolve_alias(predicate);
+        let predicate: &str = &predicate;
         for _ in 0..2 {
             match self.obj.resources.get(predicate) {
                 Some(v) => {
@@ -560,6 +1015,8 @@ impl Individual {
     }
 
     pub fn get_first_bool(&mut self, predicate: &str) -> Option<bool> {
+        let predicate = self.resolve_alias(predicate);
+        let predicate: &str = &predicate;
         for _ in 0..2 {
             match self.obj.resources.get(predicate) {
                 Some(v) => match &v[0].value {
@@ -586,6 +1043,8 @@ impl Individual {
     }
 
     pub fn get_first_binobj(&mut self, predicate: &str) -> Option<Vec<u8>> {
+        let predicate = self.resolve_alias(predicate);
+        let predicate: &str = &predicate;
         for _ in 0..2 {
             match self.obj.resources.get(predicate) {
                 Some(v) => match &v[0].value {
@@ -612,6 +1071,8 @@ impl Individual {
     }
 
     pub fn get_first_integer(&mut self, predicate: &str) -> Option<i64> {
+        let predicate = self.resolve_alias(predicate);
+        let predicate: &str = &predicate;
         for _ in 0..2 {
             match self.obj.resources.get(predicate) {
                 Some(v) => {
@@ -634,7 +1095,36 @@ impl Individual {
         None
     }
 
+    #[cfg(feature = "std")]
+    /// First decimal value with exactly `scale` fraction digits, rounded half to even (for money).
+    /// `DecimalError::Overflow` if the value has no form with `scale` digits in a `Decimal`.
+    pub fn get_first_decimal_with_scale(&mut self, predicate: &str, scale: u32) -> Result<Option<Decimal>, DecimalError> {
+        match self.get_first_decimal_with(predicate, &DecimalConfig::new(scale, Rounding::HalfEven))? {
+            Some(d) => {
+                // rescale stops at the largest scale the mantissa can hold
+                let mut res = d;
+                res.rescale(scale);
+                if res.scale() != scale {
+                    return Err(DecimalError::Overflow(format!("{} with scale {}", d, scale)));
+                }
+                Ok(Some(res))
+            },
+            None => Ok(None),
+        }
+    }
+
+    #[cfg(feature = "std")]
+    /// First decimal value rounded to at most `cfg.max_scale` fraction digits.
+    pub fn get_first_decimal_with(&mut self, predicate: &str, cfg: &DecimalConfig) -> Result<Option<Decimal>, DecimalError> {
+        match self.get_first_number(predicate) {
+            Some((m, e)) => cfg.to_decimal(m, e).map(Some),
+            None => Ok(None),
+        }
+    }
+
     pub fn get_first_number(&mut self, predicate: &str) -> Option<(i64, i64)> {
+        let predicate = self.resolve_alias(predicate);
+        let predicate: &str = &predicate;
         for _ in 0..2 {
             match self.obj.resources.get(predicate) {
                 Some(v) => {
@@ -658,6 +1148,8 @@ impl Individual {
     }
 
     pub fn get_first_datetime(&mut self, predicate: &str) -> Option<i64> {
+        let predicate = self.resolve_alias(predicate);
+        let predicate: &str = &predicate;
         for _ in 0..2 {
             match self.obj.resources.get(predicate) {
                 Some(v) => {
@@ -680,7 +1172,10 @@ impl Individual {
         None
     }
 
+    #[cfg(feature = "std")]
     pub fn get_first_float(&mut self, predicate: &str) -> Option<f64> {
+        let predicate = self.resolve_alias(predicate);
+        let predicate: &str = &predicate;
         for _ in 0..2 {
             match self.obj.resources.get(predicate) {
                 Some(v) => {
@@ -701,10 +1196,181 @@ impl Individual {
         None
     }
 
+    /// The first value of `predicate` converted by `f`, the errors name the individual and the predicate.
+    fn try_first<T>(&mut self, predicate: &str, expected: DataType, f: impl Fn(&Value) -> Option<T>) -> Result<T, IndividualError> {
+        let first = self.values_of(predicate).first().map(|r| (f(&r.value), r.rtype.clone()));
+        match first {
+            Some((Some(v), _)) => Ok(v),
+            Some((None, found)) => Err(IndividualError::WrongType {
+                uri: self.get_id().to_owned(),
+                predicate: predicate.to_owned(),
+                expected,
+                found,
+            }),
+            None => Err(IndividualError::MissingPredicate {
+                uri: self.get_id().to_owned(),
+                predicate: predicate.to_owned(),
+            }),
+        }
+    }
+
+    /// As `get_first_literal`, with `MissingPredicate` / `WrongType` instead of None.
+    pub fn try_get_first_literal(&mut self, predicate: &str) -> Result<String, IndividualError> {
+        self.try_first(predicate, DataType::String, |v| match v {
+            Value::Str(s, _) | Value::Uri(s) => Some(s.to_owned()),
+            _ => None,
+        })
+    }
+
+    pub fn try_get_first_uri(&mut self, predicate: &str) -> Result<String, IndividualError> {
+        self.try_first(predicate, DataType::Uri, |v| match v {
+            Value::Uri(s) => Some(s.to_owned()),
+            _ => None,
+        })
+    }
+
+    pub fn try_get_first_bool(&mut self, predicate: &str) -> Result<bool, IndividualError> {
+        self.try_first(predicate, DataType::Boolean, |v| match v {
+            Value::Bool(b) => Some(*b),
+            _ => None,
+        })
+    }
+
+    pub fn try_get_first_binobj(&mut self, predicate: &str) -> Result<Vec<u8>, IndividualError> {
+        self.try_first(predicate, DataType::Binary, |v| match v {
+            Value::Binary(b) => Some(b.clone()),
+            _ => None,
+        })
+    }
+
+    pub fn try_get_first_integer(&mut self, predicate: &str) -> Result<i64, IndividualError> {
+        self.try_first(predicate, DataType::Integer, |v| match v {
+            Value::Int(i) => Some(*i),
+            _ => None,
+        })
+    }
+
+    /// Decimal as (mantissa, exponent).
+    pub fn try_get_first_number(&mut self, predicate: &str) -> Result<(i64, i64), IndividualError> {
+        self.try_first(predicate, DataType::Decimal, |v| match v {
+            Value::Num(m, e) => Some((*m, *e)),
+            _ => None,
+        })
+    }
+
+    pub fn try_get_first_datetime(&mut self, predicate: &str) -> Result<i64, IndividualError> {
+        self.try_first(predicate, DataType::Datetime, |v| match v {
+            Value::Datetime(t) => Some(*t),
+            _ => None,
+        })
+    }
+
+    /// `predicate`, or when the individual has no values of it the first of its aliases (see
+    /// `aliases::set_predicate_aliases`) that has values.
+    #[cfg(feature = "std")]
+    fn resolve_alias<'p>(&mut self, predicate: &'p str) -> Cow<'p, str> {
+        let Some(aliases) = predicate_aliases() else {
+            return Cow::Borrowed(predicate);
+        };
+        let alternatives = aliases.get(predicate);
+        if alternatives.is_empty() || !self.stored_values(predicate).is_empty() {
+            return Cow::Borrowed(predicate);
+        }
+        match alternatives.iter().find(|alias| !self.stored_values(alias).is_empty()) {
+            Some(alias) => Cow::Owned(alias.to_owned()),
+            None => Cow::Borrowed(predicate),
+        }
+    }
+
+    /// Aliases are set for the process, without std there are none.
+    #[cfg(not(feature = "std"))]
+    fn resolve_alias<'p>(&mut self, predicate: &'p str) -> Cow<'p, str> {
+        Cow::Borrowed(predicate)
+    }
+
+    /// Values of `predicate` or of its alias, see `resolve_alias`.
+    fn values_of(&mut self, predicate: &str) -> &[Resource] {
+        let predicate = self.resolve_alias(predicate);
+        self.stored_values(&predicate)
+    }
+
+    /// Values of `predicate`, the raw buffer is parsed up to it if needed; empty if absent.
+    fn stored_values(&mut self, predicate: &str) -> &[Resource] {
+        if !self.obj.resources.contains_key(predicate) && self.raw.cur < self.raw.data.len() as u64 {
+            parse_to_predicate(predicate, self);
+        }
+        self.obj.resources.get(predicate).map_or(&[], |v| v.as_slice())
+    }
+
+    fn collect_values<'a, T>(&'a mut self, predicate: &str, f: impl Fn(&'a Value) -> Option<T>) -> Vec<T> {
+        self.values_of(predicate).iter().filter_map(|r| f(&r.value)).collect()
+    }
+
+    /// All integer values of `predicate`, values of other types are skipped (as in every `get_all_*`).
+    pub fn get_all_integers(&mut self, predicate: &str) -> Vec<i64> {
+        self.collect_values(predicate, |v| match v {
+            Value::Int(i) => Some(*i),
+            _ => None,
+        })
+    }
+
+    /// Decimals as (mantissa, exponent).
+    pub fn get_all_numbers(&mut self, predicate: &str) -> Vec<(i64, i64)> {
+        self.collect_values(predicate, |v| match v {
+            Value::Num(m, e) => Some((*m, *e)),
+            _ => None,
+        })
+    }
+
+    pub fn get_all_datetimes(&mut self, predicate: &str) -> Vec<i64> {
+        self.collect_values(predicate, |v| match v {
+            Value::Datetime(t) => Some(*t),
+            _ => None,
+        })
+    }
+
+    pub fn get_all_bools(&mut self, predicate: &str) -> Vec<bool> {
+        self.collect_values(predicate, |v| match v {
+            Value::Bool(b) => Some(*b),
+            _ => None,
+        })
+    }
+
+    pub fn get_all_uris(&mut self, predicate: &str) -> Vec<&str> {
+        self.collect_values(predicate, |v| match v {
+            Value::Uri(s) => Some(s.as_str()),
+            _ => None,
+        })
+    }
+
+    /// String values in any language.
+    pub fn get_all_strings(&mut self, predicate: &str) -> Vec<&str> {
+        self.collect_values(predicate, |v| match v {
+            Value::Str(s, _) => Some(s.as_str()),
+            _ => None,
+        })
+    }
+
+    /// String values in `lang`, `Lang::none()` selects those without a language.
+    pub fn get_all_strings_with_lang(&mut self, predicate: &str, lang: &Lang) -> Vec<&str> {
+        self.collect_values(predicate, |v| match v {
+            Value::Str(s, l) if l == lang => Some(s.as_str()),
+            _ => None,
+        })
+    }
+
+    pub fn get_all_binaries(&mut self, predicate: &str) -> Vec<&[u8]> {
+        self.collect_values(predicate, |v| match v {
+            Value::Binary(b) => Some(b.as_slice()),
+            _ => None,
+        })
+    }
+
     pub fn parse_all(&mut self) -> &mut Individual {
         while self.raw.cur < self.raw.data.len() as u64 {
-            // next parse
-            if !parse_to_predicate("?", self) {
+            // next parse, bytes left after the last predicate of a malformed buffer end it too
+            let cur = self.raw.cur;
+            if !parse_to_predicate("?", self) || self.raw.cur == cur {
                 break;
             }
         }
@@ -714,19 +1380,22 @@ impl Individual {
 
     pub fn apply_predicate_as_set(&mut self, predicate: &str, new_data: &mut Individual) {
         if let Some(v) = new_data.obj.resources.get(predicate) {
-            self.obj.set_resources(predicate, v);
+            self.obj_mut().set_resources(predicate, v);
+            self.modified(predicate);
         }
     }
 
     pub fn apply_predicate_as_add_unique(&mut self, predicate: &str, new_data: &mut Individual) {
         if let Some(v) = new_data.obj.resources.get(predicate) {
-            self.obj.add_unique_resources(predicate, v);
+            self.obj_mut().add_unique_resources(predicate, v);
+            self.modified(predicate);
         }
     }
 
     pub fn apply_predicate_as_remove(&mut self, predicate: &str, new_data: &mut Individual) {
         if let Some(exclude) = new_data.obj.resources.get(predicate) {
-            self.obj.exclude_and_set_resources(predicate, exclude);
+            self.obj_mut().exclude_and_set_resources(predicate, exclude);
+            self.modified(predicate);
         }
     }
 
@@ -764,6 +1433,60 @@ impl Individual {
         res
     }
 
+    /// Walks a dot-separated property path, e.g. `v-s:hasApplication.v-s:hasForm.rdfs:label`.
+    /// Every segment except the last one must hold URI links, the referenced individuals are loaded with `resolver`.
+    /// Returns the values of the last segment collected over all reached individuals.
+    pub fn query_path<F>(&mut self, path: &str, mut resolver: F) -> Vec<Resource>
+    where
+        F: FnMut(&str) -> Option<Individual>,
+    {
+        let segments: Vec<&str> = path.split('.').filter(|s| !s.is_empty()).collect();
+        let (last, links) = match segments.split_last() {
+            Some(v) => v,
+            None => return Vec::new(),
+        };
+
+        let mut uris: Vec<String> = Vec::new();
+        let mut front: Vec<Individual> = Vec::new();
+
+        for (idx, predicate) in links.iter().enumerate() {
+            uris.clear();
+            if idx == 0 {
+                collect_uris(self.get_resources(predicate), &mut uris);
+            } else {
+                for indv in front.iter_mut() {
+                    collect_uris(indv.get_resources(predicate), &mut uris);
+                }
+            }
+
+            front.clear();
+            for uri in uris.iter() {
+                match resolver(uri) {
+                    Some(indv) => front.push(indv),
+                    None => {
+                        warn!("query_path: fail load [{}], path [{}]", uri, path);
+                    },
+                }
+            }
+
+            if front.is_empty() {
+                return Vec::new();
+            }
+        }
+
+        if links.is_empty() {
+            return self.get_resources(last).unwrap_or_default();
+        }
+
+        let mut res = Vec::new();
+        for indv in front.iter_mut() {
+            if let Some(v) = indv.get_resources(last) {
+                res.extend(v);
+            }
+        }
+        res
+    }
+
     pub fn compare(&self, b: &Individual, ignore_predicates: Vec<&str>) -> bool {
         if self.obj.uri != b.obj.uri {
             return false;
@@ -806,58 +1529,162 @@ impl Individual {
         true
     }
 
+    #[cfg(feature = "chrono")]
     pub fn set_datetime_from_str(&mut self, predicate: &str, value: &str) {
-        if value.contains('Z') {
-            if let Ok(v) = DateTime::parse_from_rfc3339(value) {
-                self.set_datetime(predicate, v.timestamp());
-            } else {
-                error!("fail parse [{}] to datetime", value);
-            }
-        } else {
-            let ndt;
-            if value.len() == 10 {
-                if value.contains('.') {
-                    ndt = NaiveDateTime::parse_from_str(&(value.to_owned() + "T00:00:00"), "%d.%m.%YT%H:%M:%S");
-                } else {
-                    ndt = NaiveDateTime::parse_from_str(&(value.to_owned() + "T00:00:00"), "%Y-%m-%dT%H:%M:%S");
-                }
-            } else {
-                ndt = NaiveDateTime::parse_from_str(value, "%Y-%m-%dT%H:%M:%S")
-            }
-
-            if let Ok(v) = ndt {
-                if let Single(offset) = Local.offset_from_local_datetime(&v) {
-                    self.set_datetime(predicate, v.sub(offset).and_utc().timestamp());
-                } else {
-                    self.set_datetime(predicate, v.and_utc().timestamp());
-                }
-            } else {
-                error!("fail parse [{}] to datetime", value);
-            }
+        if let Err(e) = self.try_set_datetime_from_str(predicate, value) {
+            error!("{}", e);
         }
     }
 
+    #[cfg(feature = "chrono")]
+    pub fn try_set_datetime_from_str(&mut self, predicate: &str, value: &str) -> Result<(), DatetimeError> {
+        self.set_datetime(predicate, parse_datetime_local(value)?);
+        Ok(())
+    }
+
     // Add missing set_decimal_from_str method
+    #[cfg(feature = "std")]
     pub fn set_decimal_from_str(&mut self, predicate: &str, value: &str) {
-        if let Ok(v) = Decimal::from_str(value) {
-            let exp = -(v.scale() as i32);
-            if let Ok(m) = value.replace('.', "").parse::<i64>() {
-                self.set_decimal_d(predicate, m, exp as i64);
-            }
-        } else {
-            error!("fail parse [{}] to decimal", value);
+        if let Err(e) = self.try_set_decimal_from_str(predicate, value, &DecimalConfig::default()) {
+            error!("predicate [{}]: {}", predicate, e);
         }
     }
 
+    #[cfg(feature = "std")]
+    pub fn try_set_decimal_from_str(&mut self, predicate: &str, value: &str, cfg: &DecimalConfig) -> Result<(), DecimalError> {
+        let (m, e) = cfg.parse(value)?;
+        self.set_decimal_d(predicate, m, e);
+        Ok(())
+    }
+
     // Add missing set_decimal_from_i64 method
     pub fn set_decimal_from_i64(&mut self, predicate: &str, value: i64) {
         self.set_decimal_d(predicate, value, 0);
     }
 }
 
+fn collect_uris(src: Option<Vec<Resource>>, dest: &mut Vec<String>) {
+    for r in src.unwrap_or_default() {
+        if let Value::Uri(uri) = r.value {
+            if !dest.contains(&uri) {
+                dest.push(uri);
+            }
+        }
+    }
+}
+
+/// One line: `d:doc1 {rdf:type: 1 value, rdfs:label: 2 values}`.
+impl fmt::Display for IndividualObj {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} {{", self.uri)?;
+        for (idx, (predicate, values)) in self.resources.iter().enumerate() {
+            let sep = if idx == 0 {
+                ""
+            } else {
+                ", "
+            };
+            let noun = if values.len() == 1 {
+                "value"
+            } else {
+                "values"
+            };
+            write!(f, "{}{}: {} {}", sep, predicate, values.len(), noun)?;
+        }
+        write!(f, "}}")
+    }
+}
+
+/// Values in Turtle-like notation, `{:?}` on one line, `{:#?}` one predicate per line.
+impl fmt::Debug for IndividualObj {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let (open, sep, close) = if f.alternate() {
+            ("\n    ", "\n    ", "\n")
+        } else {
+            ("", ", ", "")
+        };
+        if self.resources.is_empty() {
+            return write!(f, "{} {{}}", self.uri);
+        }
+        write!(f, "{} {{{}", self.uri, open)?;
+        for (idx, (predicate, values)) in self.resources.iter().enumerate() {
+            if idx > 0 {
+                write!(f, "{}", sep)?;
+            }
+            write!(f, "{}: [", predicate)?;
+            for (n, r) in values.iter().enumerate() {
+                if n > 0 {
+                    write!(f, ", ")?;
+                }
+                fmt_value(&r.value, f)?;
+            }
+            write!(f, "]")?;
+        }
+        write!(f, "{}}}", close)
+    }
+}
+
+/// A value as `fmt_value` writes it, for `format!`.
+#[cfg(feature = "std")]
+pub(crate) struct ValueFmt<'a>(pub &'a Value);
+
+#[cfg(feature = "std")]
+impl fmt::Display for ValueFmt<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt_value(self.0, f)
+    }
+}
+
+pub(crate) fn fmt_value(v: &Value, f: &mut fmt::Formatter) -> fmt::Result {
+    match v {
+        Value::Int(i) => write!(f, "{}", i),
+        Value::Bool(b) => write!(f, "{}", b),
+        Value::Uri(u) => write!(f, "{}", u),
+        Value::Str(s, l) if l.is_some() => write!(f, "{:?}@{}", s, l.to_string().to_lowercase()),
+        Value::Str(s, _) => write!(f, "{:?}", s),
+        #[cfg(feature = "std")]
+        Value::Num(m, e) => match to_decimal(*m, *e) {
+            Ok(d) => write!(f, "{}", d),
+            Err(_) => write!(f, "{}e{}", m, e),
+        },
+        #[cfg(not(feature = "std"))]
+        Value::Num(m, e) => write!(f, "{}e{}", m, e),
+        Value::Binary(b) => write!(f, "<{} bytes>", b.len()),
+        #[cfg(feature = "chrono")]
+        Value::Datetime(t) => match to_utc(*t) {
+            Ok(d) => write!(f, "{:?}", d),
+            Err(_) => write!(f, "datetime({})", t),
+        },
+        #[cfg(not(feature = "chrono"))]
+        Value::Datetime(t) => write!(f, "datetime({})", t),
+    }
+}
+
+impl fmt::Debug for RawObj {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("RawObj")
+            .field("len", &self.data.len())
+            .field("raw_type", &self.raw_type)
+            .field("cur", &self.cur)
+            .field("predicates", &format_args!("{}/{}", self.cur_predicates, self.len_predicates))
+            .finish()
+    }
+}
+
+impl fmt::Debug for Individual {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Debug::fmt(&self.obj, f)?;
+        let state = if self.dirty {
+            ", modified"
+        } else {
+            ""
+        };
+        write!(f, " (raw {} bytes{})", self.raw.data.len(), state)
+    }
+}
+
 impl fmt::Display for Individual {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "uri={}, \n {:#?}", self.obj.uri, self.obj.resources)
+        fmt::Display::fmt(&self.obj, f)
     }
 }
 
@@ -865,7 +1692,8 @@ impl Default for IndividualObj {
     fn default() -> Self {
         IndividualObj {
             uri: "".to_string(),
-            resources: HashMap::new(),
+            resources: IndexMap::default(),
+            rights: HashMap::new(),
         }
     }
 }
@@ -881,12 +1709,34 @@ impl IndividualObj {
     //        }
     //    }
 
-    pub fn get_resources(&self) -> &HashMap<String, Vec<Resource>> {
+    pub fn get_resources(&self) -> &IndexMap<String, Vec<Resource>, RandomState> {
         &self.resources
     }
 
+    pub fn get_first_resource_mut(&mut self, predicate: &str) -> Option<&mut Resource> {
+        self.resources.get_mut(predicate).and_then(|values| values.first_mut())
+    }
+
+    /// Changes the value at `idx` in place, its position and order stay. If `f` switches to another
+    /// variant, `rtype` follows it. False if there is no such value.
+    pub fn update_value<F>(&mut self, predicate: &str, idx: usize, f: F) -> bool
+    where
+        F: FnOnce(&mut Value),
+    {
+        let Some(r) = self.resources.get_mut(predicate).and_then(|values| values.get_mut(idx)) else {
+            return false;
+        };
+        let before = mem::discriminant(&r.value);
+        f(&mut r.value);
+        if mem::discriminant(&r.value) != before {
+            r.rtype = r.value.data_type();
+        }
+        true
+    }
+
     pub fn remove(&mut self, predicate: &str) -> bool {
-        self.resources.remove(predicate).is_some()
+        self.rights.remove(predicate);
+        self.resources.shift_remove(predicate).is_some()
     }
 
     pub fn clear(&mut self, predicate: &str) {
@@ -966,12 +1816,135 @@ impl IndividualObj {
         }
 
         if new_values.is_empty() {
-            self.resources.remove(predicate);
+            self.resources.shift_remove(predicate);
         } else {
             self.resources.insert(predicate.to_owned(), new_values);
         }
     }
 
+    pub fn remove_predicates(&mut self, predicates: &[&str]) -> Vec<String> {
+        let mut removed = vec![];
+        for predicate in predicates {
+            self.rights.remove(*predicate);
+            if self.resources.shift_remove(*predicate).is_some() {
+                removed.push((*predicate).to_owned());
+            }
+        }
+        removed
+    }
+
+    pub fn rename_predicate(&mut self, old: &str, new: &str) -> bool {
+        if old == new {
+            return self.resources.contains_key(old);
+        }
+
+        let Some((idx, _, moved)) = self.resources.shift_remove_full(old) else {
+            return false;
+        };
+        // the moved values keep their rights, merged values need both masks
+        if let Some(mask) = self.rights.remove(old) {
+            *self.rights.entry(new.to_owned()).or_default() |= mask;
+        }
+        match self.resources.get_mut(new) {
+            Some(values) => {
+                for mut el in moved {
+                    el.order = values.len() as u16;
+                    values.push(el);
+                }
+            },
+            // the renamed predicate keeps its place
+            None => {
+                self.resources.shift_insert(idx, new.to_owned(), moved);
+            },
+        }
+        true
+    }
+
+    pub fn retain_predicates<F>(&mut self, mut f: F) -> Vec<String>
+    where
+        F: FnMut(&str, &[Resource]) -> bool,
+    {
+        let mut removed = vec![];
+        self.resources.retain(|predicate, values| {
+            if f(predicate, values) {
+                true
+            } else {
+                removed.push(predicate.to_owned());
+                false
+            }
+        });
+        for predicate in removed.iter() {
+            self.rights.remove(predicate);
+        }
+        removed
+    }
+
+    pub fn project(&self, predicates: &[&str]) -> IndividualObj {
+        let mut res = IndividualObj {
+            uri: self.uri.to_owned(),
+            resources: IndexMap::default(),
+            rights: HashMap::new(),
+        };
+
+        for predicate in predicates {
+            if let Some(values) = self.resources.get(*predicate) {
+                res.resources.insert((*predicate).to_owned(), values.iter().map(|el| el.get_copy()).collect());
+            }
+            if let Some(mask) = self.rights.get(*predicate) {
+                res.rights.insert((*predicate).to_owned(), *mask);
+            }
+        }
+        res
+    }
+
+    pub fn retain(&mut self, predicates: &[&str]) -> Vec<String> {
+        self.retain_predicates(|predicate, _| predicates.contains(&predicate))
+    }
+
+    /// Adds a template value matching any value of the given type, see `matches_template`.
+    pub fn add_wildcard(&mut self, predicate: &str, rtype: DataType) {
+        let values = self.resources.entry(predicate.to_owned()).or_default();
+        values.push(Resource {
+            rtype,
+            order: values.len() as u16,
+            value: Value::Uri(TEMPLATE_WILDCARD.to_owned()),
+        });
+    }
+
+    /// Checks that every predicate of `template` is present here and each template value is matched:
+    /// an empty value list only requires the predicate to exist, a `*` value (see `add_wildcard`) matches any value of its type,
+    /// other values must be present as is. The template uri is compared unless it is empty or `*`.
+    pub fn matches_template(&self, template: &IndividualObj) -> bool {
+        if !template.uri.is_empty() && template.uri != TEMPLATE_WILDCARD && template.uri != self.uri {
+            return false;
+        }
+
+        for (predicate, expected) in template.resources.iter() {
+            let values = match self.resources.get(predicate) {
+                Some(v) if !v.is_empty() => v,
+                _ => return false,
+            };
+
+            for t in expected.iter() {
+                let is_wildcard = match &t.value {
+                    Value::Uri(s) | Value::Str(s, _) => s == TEMPLATE_WILDCARD,
+                    _ => false,
+                };
+
+                let found = if is_wildcard {
+                    values.iter().any(|el| el.rtype == t.rtype)
+                } else {
+                    values.contains(t)
+                };
+
+                if !found {
+                    return false;
+                }
+            }
+        }
+        true
+    }
+
     pub fn add_bool(&mut self, predicate: &str, b: bool) {
         let values = self.resources.entry(predicate.to_owned()).or_default();
         values.push(Resource {
@@ -1067,7 +2040,15 @@ impl IndividualObj {
         });
     }
 
-    pub fn add_uri(&mut self, predicate: &str, s: &str) {
+    /// Adds a normalized uri value, a malformed uri is an error and nothing is added.
+    pub fn add_uri(&mut self, predicate: &str, s: &str) -> Result<(), String> {
+        let v = normalize_iri(s)?;
+        self.add_uri_unchecked(predicate, &v);
+        Ok(())
+    }
+
+    /// Adds the value as is, without validation and normalization.
+    pub fn add_uri_unchecked(&mut self, predicate: &str, s: &str) {
         let values = self.resources.entry(predicate.to_owned()).or_default();
         values.push(Resource {
             rtype: DataType::Uri,
@@ -1076,7 +2057,14 @@ impl IndividualObj {
         });
     }
 
-    pub fn set_uri(&mut self, predicate: &str, s: &str) {
+    /// Replaces the values by a normalized uri, a malformed uri is an error and the predicate is left unchanged.
+    pub fn set_uri(&mut self, predicate: &str, s: &str) -> Result<(), String> {
+        let v = normalize_iri(s)?;
+        self.set_uri_unchecked(predicate, &v);
+        Ok(())
+    }
+
+    pub fn set_uri_unchecked(&mut self, predicate: &str, s: &str) {
         let values = self.resources.entry(predicate.to_owned()).or_default();
         values.clear();
         values.push(Resource {
@@ -1086,16 +2074,19 @@ impl IndividualObj {
         });
     }
 
-    pub fn set_uris(&mut self, predicate: &str, ss: Vec<String>) {
+    /// Replaces the values by the normalized uris; if one is malformed, the predicate is left unchanged.
+    pub fn set_uris(&mut self, predicate: &str, ss: Vec<String>) -> Result<(), String> {
+        let normalized = ss.iter().map(|s| normalize_iri(s)).collect::<Result<Vec<String>, String>>()?;
         let values = self.resources.entry(predicate.to_owned()).or_default();
         values.clear();
-        for s in ss {
+        for v in normalized {
             values.push(Resource {
                 rtype: DataType::Uri,
                 order: 0,
-                value: Value::Uri(s.to_owned()),
+                value: Value::Uri(v),
             });
         }
+        Ok(())
     }
 
     pub fn add_string(&mut self, predicate: &str, s: &str, lang: Lang) {
@@ -1116,4 +2107,237 @@ impl IndividualObj {
             value: Value::Str(s.to_owned(), lang),
         });
     }
+
+    /// Appends the value unless an equal one (see `Resource::semantic_eq`) is present, returns true if added.
+    pub fn add_unique(&mut self, predicate: &str, mut r: Resource) -> bool {
+        let values = self.resources.entry(predicate.to_owned()).or_default();
+        if values.iter().any(|el| el.semantic_eq(&r)) {
+            return false;
+        }
+        r.order = values.len() as u16;
+        values.push(r);
+        true
+    }
+
+    /// True if the normalized uri was added, an error if it is malformed.
+    pub fn add_unique_uri(&mut self, predicate: &str, s: &str) -> Result<bool, String> {
+        let v = normalize_iri(s)?;
+        Ok(self.add_unique(predicate, Resource::new_uri(&v)))
+    }
+
+    pub fn add_unique_string(&mut self, predicate: &str, s: &str, lang: Lang) -> bool {
+        self.add_unique(
+            predicate,
+            Resource {
+                rtype: DataType::String,
+                order: 0,
+                value: Value::Str(s.to_owned(), lang),
+            },
+        )
+    }
+
+    pub fn add_unique_integer(&mut self, predicate: &str, i: i64) -> bool {
+        self.add_unique(
+            predicate,
+            Resource {
+                rtype: DataType::Integer,
+                order: 0,
+                value: Value::Int(i),
+            },
+        )
+    }
+
+    pub fn add_unique_decimal_d(&mut self, predicate: &str, mantissa: i64, exponent: i64) -> bool {
+        self.add_unique(
+            predicate,
+            Resource {
+                rtype: DataType::Decimal,
+                order: 0,
+                value: Value::Num(mantissa, exponent),
+            },
+        )
+    }
+
+    pub fn add_unique_datetime(&mut self, predicate: &str, i: i64) -> bool {
+        self.add_unique(
+            predicate,
+            Resource {
+                rtype: DataType::Datetime,
+                order: 0,
+                value: Value::Datetime(i),
+            },
+        )
+    }
+
+    pub fn add_unique_bool(&mut self, predicate: &str, b: bool) -> bool {
+        self.add_unique(predicate, Resource::new_bool(b))
+    }
+
+    pub fn add_unique_binary(&mut self, predicate: &str, v: Vec<u8>) -> bool {
+        self.add_unique(
+            predicate,
+            Resource {
+                rtype: DataType::Binary,
+                order: 0,
+                value: Value::Binary(v),
+            },
+        )
+    }
+
+    /// Removes repeated values of the predicate keeping the first occurrence, returns the number of removed values.
+    pub fn dedup_values(&mut self, predicate: &str) -> usize {
+        let Some(values) = self.resources.get_mut(predicate) else {
+            return 0;
+        };
+
+        let before = values.len();
+        let mut unique: Vec<Resource> = Vec::with_capacity(before);
+        for el in values.drain(..) {
+            if !unique.iter().any(|u| u.semantic_eq(&el)) {
+                unique.push(el);
+            }
+        }
+        for (idx, el) in unique.iter_mut().enumerate() {
+            el.order = idx as u16;
+        }
+        *values = unique;
+        before - values.len()
+    }
+
+    pub fn dedup_all_values(&mut self) -> usize {
+        let predicates: Vec<String> = self.resources.keys().cloned().collect();
+        predicates.iter().map(|p| self.dedup_values(p)).sum()
+    }
+
+    pub fn iter(&self) -> Iter<'_> {
+        Iter {
+            inner: self.resources.iter(),
+        }
+    }
+
+    pub fn iter_mut(&mut self) -> IterMut<'_> {
+        IterMut {
+            inner: self.resources.iter_mut(),
+        }
+    }
+
+    pub fn entry(&mut self, predicate: &str) -> Entry<'_> {
+        Entry {
+            inner: self.resources.entry(predicate.to_owned()),
+        }
+    }
+}
+
+pub struct Iter<'a> {
+    inner: map::Iter<'a, String, Vec<Resource>>,
+}
+
+impl<'a> Iterator for Iter<'a> {
+    type Item = (&'a str, &'a [Resource]);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(|(k, v)| (k.as_str(), v.as_slice()))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+pub struct IterMut<'a> {
+    inner: map::IterMut<'a, String, Vec<Resource>>,
+}
+
+impl<'a> Iterator for IterMut<'a> {
+    type Item = (&'a str, &'a mut [Resource]);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(|(k, v)| (k.as_str(), v.as_mut_slice()))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<'a> IntoIterator for &'a IndividualObj {
+    type Item = (&'a str, &'a [Resource]);
+    type IntoIter = Iter<'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a mut IndividualObj {
+    type Item = (&'a str, &'a mut [Resource]);
+    type IntoIter = IterMut<'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+impl IntoIterator for IndividualObj {
+    type Item = (String, Vec<Resource>);
+    type IntoIter = map::IntoIter<String, Vec<Resource>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.resources.into_iter()
+    }
+}
+
+/// View into the values of a single predicate, obtained from [`IndividualObj::entry`].
+pub struct Entry<'a> {
+    inner: map::Entry<'a, String, Vec<Resource>>,
+}
+
+impl<'a> Entry<'a> {
+    pub fn key(&self) -> &str {
+        self.inner.key()
+    }
+
+    /// True if the predicate is absent or has no values.
+    pub fn is_vacant(&self) -> bool {
+        match &self.inner {
+            map::Entry::Occupied(e) => e.get().is_empty(),
+            map::Entry::Vacant(_) => true,
+        }
+    }
+
+    /// Appends the value, creating the predicate if needed.
+    pub fn append(self, mut r: Resource) -> &'a mut Vec<Resource> {
+        let values = self.inner.or_default();
+        r.order = values.len() as u16;
+        values.push(r);
+        values
+    }
+
+    /// Inserts the value only if the predicate is absent or has no values.
+    pub fn or_insert(self, mut r: Resource) -> &'a mut Vec<Resource> {
+        let values = self.inner.or_default();
+        if values.is_empty() {
+            r.order = 0;
+            values.push(r);
+        }
+        values
+    }
+
+    pub fn or_insert_with<F: FnOnce() -> Vec<Resource>>(self, f: F) -> &'a mut Vec<Resource> {
+        let values = self.inner.or_default();
+        if values.is_empty() {
+            *values = f();
+        }
+        values
+    }
+
+    pub fn or_default(self) -> &'a mut Vec<Resource> {
+        self.inner.or_default()
+    }
+
+    pub fn and_modify<F: FnOnce(&mut Vec<Resource>)>(self, f: F) -> Self {
+        Entry {
+            inner: self.inner.and_modify(f),
+        }
+    }
 }
@@ -0,0 +1,102 @@
+use crate::onto::cbor2individual::TagId;
+use crate::onto::individual::IndividualObj;
+use crate::onto::resource::{Resource, Value};
+use std::io::{Error, ErrorKind};
+
+const MAJOR_UINT: u8 = 0;
+const MAJOR_NINT: u8 = 1;
+const MAJOR_TEXT: u8 = 3;
+const MAJOR_ARRAY: u8 = 4;
+const MAJOR_MAP: u8 = 5;
+const MAJOR_TAG: u8 = 6;
+
+fn write_head(out: &mut Vec<u8>, major: u8, n: u64) {
+    let major = major << 5;
+    if n < 24 {
+        out.push(major | n as u8);
+    } else if n <= u8::MAX as u64 {
+        out.push(major | 24);
+        out.push(n as u8);
+    } else if n <= u16::MAX as u64 {
+        out.push(major | 25);
+        out.extend_from_slice(&(n as u16).to_be_bytes());
+    } else if n <= u32::MAX as u64 {
+        out.push(major | 26);
+        out.extend_from_slice(&(n as u32).to_be_bytes());
+    } else {
+        out.push(major | 27);
+        out.extend_from_slice(&n.to_be_bytes());
+    }
+}
+
+fn write_text(out: &mut Vec<u8>, s: &str) {
+    write_head(out, MAJOR_TEXT, s.len() as u64);
+    out.extend_from_slice(s.as_bytes());
+}
+
+/// Negative numbers are written with their absolute value as argument, the way `cbor2individual`
+/// reads them back (see `read_i64`).
+fn write_int(out: &mut Vec<u8>, i: i64) {
+    if i >= 0 {
+        write_head(out, MAJOR_UINT, i as u64);
+    } else {
+        write_head(out, MAJOR_NINT, i.unsigned_abs());
+    }
+}
+
+fn write_resource(out: &mut Vec<u8>, predicate: &str, r: &Resource) -> Result<(), Error> {
+    match &r.value {
+        Value::Bool(b) => out.push(if *b {
+            0xf5
+        } else {
+            0xf4
+        }),
+        Value::Uri(s) => {
+            write_head(out, MAJOR_TAG, TagId::Uri as u64);
+            write_text(out, s);
+        },
+        Value::Str(s, lang) => {
+            match lang.to_string() {
+                "RU" => write_head(out, MAJOR_TAG, TagId::TextRu as u64),
+                "EN" => write_head(out, MAJOR_TAG, TagId::TextEn as u64),
+                _ => {},
+            }
+            write_text(out, s);
+        },
+        Value::Int(i) => write_int(out, *i),
+        Value::Datetime(i) => {
+            write_head(out, MAJOR_TAG, TagId::EpochDateTime as u64);
+            write_int(out, *i);
+        },
+        Value::Num(m, e) => {
+            write_head(out, MAJOR_TAG, TagId::DecimalFraction as u64);
+            write_head(out, MAJOR_ARRAY, 2);
+            write_int(out, *m);
+            write_int(out, *e);
+        },
+        Value::Binary(_) => return Err(Error::new(ErrorKind::InvalidData, format!("predicate [{}], binary values have no cbor form", predicate))),
+    }
+    Ok(())
+}
+
+/// Writes the individual as the CBOR map `parse_cbor` reads: `@` with the uri, then each predicate
+/// with its value, or an array of its values. Strings keep only the `ru` and `en` languages;
+/// binary values are an error.
+pub fn to_cbor(obj: &IndividualObj, out: &mut Vec<u8>) -> Result<(), Error> {
+    let predicates: Vec<(&String, &Vec<Resource>)> = obj.resources.iter().filter(|(_, values)| !values.is_empty()).collect();
+    write_head(out, MAJOR_MAP, predicates.len() as u64 + 1);
+    write_text(out, "@");
+    write_text(out, &obj.uri);
+    for (predicate, values) in predicates {
+        write_text(out, predicate);
+        if let [r] = values.as_slice() {
+            write_resource(out, predicate, r)?;
+        } else {
+            write_head(out, MAJOR_ARRAY, values.len() as u64);
+            for r in values {
+                write_resource(out, predicate, r)?;
+            }
+        }
+    }
+    Ok(())
+}
@@ -1,14 +1,91 @@
-use crate::onto::datatype::{exponent_to_scale, DataType, Lang};
+use crate::onto::datatype::{DataType, Lang};
+use crate::onto::datetime::to_utc;
+use crate::onto::decimal::{format_scientific, to_decimal, DecimalFormat};
 use crate::onto::individual::IndividualObj;
 use crate::onto::resource::{Resource, Value};
 use base64::{Engine as _, engine::general_purpose};
-use chrono::{TimeZone, Utc};
-use rust_decimal::Decimal;
-use serde::ser::{Serialize, SerializeMap, SerializeStruct, Serializer};
+use chrono::{Datelike, SecondsFormat};
+use serde::ser::{Error, Serialize, SerializeMap, SerializeStruct, Serializer};
 use serde_json::json;
 use serde_json::value::Value as JSONValue;
 
+/// How `Value::Datetime` is written by `as_json_with`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DatetimeFormat {
+    /// chrono's `Debug` output, e.g. `2020-01-01T00:00:00Z`; the format of `as_json`.
+    /// Timestamps chrono cannot represent fail the conversion.
+    #[default]
+    Debug,
+    /// RFC 3339 in UTC, years outside 0000…9999 are an error.
+    Rfc3339,
+    EpochSeconds,
+    EpochMillis,
+}
+
+/// Value formats for `as_json_opts`, the default gives the `as_json` output.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct JsonOptions {
+    pub datetime: DatetimeFormat,
+    pub decimal: DecimalFormat,
+}
+
+impl JsonOptions {
+    pub fn with_datetime(mut self, datetime: DatetimeFormat) -> Self {
+        self.datetime = datetime;
+        self
+    }
+
+    pub fn with_decimal(mut self, decimal: DecimalFormat) -> Self {
+        self.decimal = decimal;
+        self
+    }
+}
+
+struct JsonObj<'a> {
+    obj: &'a IndividualObj,
+    opts: JsonOptions,
+}
+
+struct JsonResource<'a> {
+    r: &'a Resource,
+    opts: JsonOptions,
+}
+
+struct JsonValues<'a> {
+    values: &'a [Resource],
+    opts: JsonOptions,
+}
+
 impl IndividualObj {
+    pub fn as_json_opts(&self, opts: JsonOptions) -> JSONValue {
+        let obj = JsonObj {
+            obj: self,
+            opts,
+        };
+        match serde_json::to_value(obj) {
+            Ok(b) => b,
+            Err(e) => {
+                log_error!(uri = self.uri, format = "json"; "fail serialize to json, err={}", e);
+                json!(null)
+            },
+        }
+    }
+
+    pub fn as_json_str_opts(&self, opts: JsonOptions) -> String {
+        match self.as_json_opts(opts) {
+            JSONValue::Null => "".to_owned(),
+            b => b.to_string(),
+        }
+    }
+
+    pub fn as_json_with(&self, datetime: DatetimeFormat) -> JSONValue {
+        self.as_json_opts(JsonOptions::default().with_datetime(datetime))
+    }
+
+    pub fn as_json_str_with(&self, datetime: DatetimeFormat) -> String {
+        self.as_json_str_opts(JsonOptions::default().with_datetime(datetime))
+    }
+
     pub fn as_json_str(&self) -> String {
         if let Ok(b) = serde_json::to_value(self) {
             return b.to_string();
@@ -25,6 +102,13 @@ impl IndividualObj {
     }
 }
 
+/// The `as_json` output.
+impl From<&IndividualObj> for JSONValue {
+    fn from(obj: &IndividualObj) -> Self {
+        obj.as_json()
+    }
+}
+
 impl Serialize for IndividualObj {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
@@ -39,51 +123,125 @@ impl Serialize for IndividualObj {
     }
 }
 
+impl Serialize for JsonObj<'_> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut map = serializer.serialize_map(Some(self.obj.resources.len()))?;
+        map.serialize_entry("@", &self.obj.uri)?;
+        for (k, v) in &self.obj.resources {
+            map.serialize_entry(
+                &k,
+                &JsonValues {
+                    values: v,
+                    opts: self.opts,
+                },
+            )?;
+        }
+        map.end()
+    }
+}
+
+impl Serialize for JsonValues<'_> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.collect_seq(self.values.iter().map(|r| JsonResource {
+            r,
+            opts: self.opts,
+        }))
+    }
+}
+
+impl Serialize for JsonResource<'_> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serialize_resource(self.r, self.opts, serializer)
+    }
+}
+
 impl Serialize for Resource {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
         S: Serializer,
     {
-        let mut tup = serializer.serialize_struct("E", 0)?;
+        serialize_resource(self, JsonOptions::default(), serializer)
+    }
+}
 
-        match &self.value {
-            Value::Num(_m, _e) => {
-                tup.serialize_field("data", &self.value)?;
-            },
-            Value::Int(i) => {
-                tup.serialize_field("data", i)?;
-            },
-            Value::Datetime(i) => {
-                let dt = *i;
-                if let Some(datetime) = Utc.timestamp_opt(dt, 0).single() {
-                    tup.serialize_field("data", &format!("{:?}", datetime))?;
-                } else {
-                    error!("Invalid timestamp value: {}", dt);
-                    tup.serialize_field("data", &format!("Invalid timestamp: {}", dt))?;
-                }
-            },
-            Value::Bool(b) => {
-                tup.serialize_field("data", b)?;
-            },
-            Value::Str(s, l) => {
-                tup.serialize_field("data", s)?;
+/// The `data` of a value as written by `as_json_opts`.
+pub(crate) fn resource_data(r: &Resource, opts: JsonOptions) -> Result<JSONValue, serde_json::Error> {
+    let mut v = serde_json::to_value(JsonResource {
+        r,
+        opts,
+    })?;
+    Ok(v.get_mut("data").map(JSONValue::take).unwrap_or_default())
+}
 
-                if self.rtype == DataType::String && l.is_some() {
-                    tup.serialize_field("lang", l)?;
+fn serialize_resource<S>(r: &Resource, opts: JsonOptions, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    let mut tup = serializer.serialize_struct("E", 0)?;
+
+    match &r.value {
+        Value::Num(m, e) => match opts.decimal {
+            DecimalFormat::Plain => tup.serialize_field("data", &r.value)?,
+            DecimalFormat::Scientific => tup.serialize_field("data", &format_scientific(*m, *e))?,
+            DecimalFormat::Number => {
+                let d = to_decimal(*m, *e).map_err(S::Error::custom)?.normalize();
+                match i64::try_from(d.mantissa()) {
+                    Ok(i) if d.scale() == 0 => tup.serialize_field("data", &i)?,
+                    _ => tup.serialize_field("data", &d.to_string().parse::<f64>().map_err(S::Error::custom)?)?,
                 }
             },
-            Value::Uri(s) => {
-                tup.serialize_field("data", s)?;
-            },
-            Value::Binary(bytes) => {
-                let base64_str = general_purpose::STANDARD.encode(bytes);
-                tup.serialize_field("data", &base64_str)?;
-            },
-        }
-        tup.serialize_field("type", &self.rtype)?;
+        },
+        Value::Int(i) => {
+            tup.serialize_field("data", i)?;
+        },
+        Value::Datetime(i) => {
+            let dt = *i;
+            match opts.datetime {
+                DatetimeFormat::EpochSeconds => tup.serialize_field("data", &dt)?,
+                DatetimeFormat::EpochMillis => match dt.checked_mul(1000) {
+                    Some(ms) => tup.serialize_field("data", &ms)?,
+                    None => return Err(S::Error::custom(format!("timestamp {} out of range for milliseconds", dt))),
+                },
+                DatetimeFormat::Debug => {
+                    let datetime = to_utc(dt).map_err(S::Error::custom)?;
+                    tup.serialize_field("data", &format!("{:?}", datetime))?;
+                },
+                DatetimeFormat::Rfc3339 => match to_utc(dt).ok().filter(|d| (0..=9999).contains(&d.year())) {
+                    Some(datetime) => tup.serialize_field("data", &datetime.to_rfc3339_opts(SecondsFormat::Secs, true))?,
+                    None => return Err(S::Error::custom(format!("timestamp {} is not representable in RFC 3339", dt))),
+                },
+            }
+        },
+        Value::Bool(b) => {
+            tup.serialize_field("data", b)?;
+        },
+        Value::Str(s, l) => {
+            tup.serialize_field("data", s)?;
 
-        tup.end()
+            if r.rtype == DataType::String && l.is_some() {
+                tup.serialize_field("lang", l)?;
+            }
+        },
+        Value::Uri(s) => {
+            tup.serialize_field("data", s)?;
+        },
+        Value::Binary(bytes) => {
+            let base64_str = general_purpose::STANDARD.encode(bytes);
+            tup.serialize_field("data", &base64_str)?;
+        },
     }
+    tup.serialize_field("type", &r.rtype)?;
+
+    tup.end()
 }
 
 impl Serialize for Value {
@@ -93,8 +251,7 @@ impl Serialize for Value {
     {
         match &self {
             Value::Num(m, e) => {
-                let (num, scale) = exponent_to_scale(m, e);
-                let d = Decimal::new(num, scale);
+                let d = to_decimal(*m, *e).map_err(S::Error::custom)?;
                 serializer.serialize_str(&d.to_string())
             },
             Value::Int(i) => serializer.serialize_i64(*i),
@@ -0,0 +1,79 @@
+use crate::onto::datatype_registry::{DatatypeRegistry, XSD_NAMESPACE};
+use crate::onto::individual::IndividualObj;
+use crate::onto::resource::{Resource, Value};
+use base64::{engine::general_purpose, Engine as _};
+use serde_json::json;
+use serde_json::value::Value as JSONValue;
+use serde_json::Map;
+use std::collections::HashMap;
+
+fn add_prefix(v: &str, all_prefixes: &HashMap<String, String>, context: &mut Map<String, JSONValue>) {
+    if let Some((p, _)) = v.split_once(':') {
+        if let Some(ns) = all_prefixes.get(p) {
+            context.entry(p.to_owned()).or_insert_with(|| JSONValue::String(ns.to_owned()));
+        }
+    }
+}
+
+fn typed(value: String, iri: &str, context: &mut Map<String, JSONValue>) -> JSONValue {
+    if iri.starts_with("xsd:") {
+        context.entry("xsd").or_insert_with(|| JSONValue::String(XSD_NAMESPACE.to_owned()));
+    }
+    json!({ "@value": value, "@type": iri })
+}
+
+fn resource_to_json_ld(
+    predicate: &str,
+    r: &Resource,
+    all_prefixes: &HashMap<String, String>,
+    context: &mut Map<String, JSONValue>,
+) -> Result<JSONValue, String> {
+    Ok(match &r.value {
+        Value::Uri(s) => {
+            add_prefix(s, all_prefixes, context);
+            json!({ "@id": s })
+        },
+        Value::Str(s, lang) if lang.is_some() => json!({ "@value": s, "@language": lang.to_string().to_lowercase() }),
+        Value::Str(s, _) => json!(s),
+        Value::Int(i) => json!(i),
+        Value::Bool(b) => json!(b),
+        Value::Binary(bytes) => typed(general_purpose::STANDARD.encode(bytes), "xsd:base64Binary", context),
+        Value::Num(..) | Value::Datetime(_) => match DatatypeRegistry::builtin().datatype_of(predicate, r) {
+            Some(def) => typed(def.format(&r.value).map_err(|e| format!("predicate [{}], {}", predicate, e))?, def.iri(), context),
+            None => return Err(format!("predicate [{}], no datatype of the value", predicate)),
+        },
+    })
+}
+
+/// The individual as a compacted JSON-LD node: `@id` is the uri, the values of `rdf:type` are
+/// `@type`, uris are `{"@id": ..}` and strings with a language `{"@value": .., "@language": ..}`.
+/// Integers and booleans are JSON values, decimals, datetimes and binaries `xsd:` typed values.
+/// `@context` declares the prefixes of `all_prefixes` that are used.
+pub fn to_json_ld(obj: &IndividualObj, all_prefixes: &HashMap<String, String>) -> Result<JSONValue, String> {
+    let mut context = Map::new();
+    let mut node = Map::new();
+    add_prefix(&obj.uri, all_prefixes, &mut context);
+    node.insert("@id".to_owned(), json!(obj.uri));
+
+    for (predicate, values) in obj.resources.iter().filter(|(_, values)| !values.is_empty()) {
+        let mut out = Vec::with_capacity(values.len());
+        if predicate == "rdf:type" && values.iter().all(|r| matches!(r.value, Value::Uri(_))) {
+            for r in values {
+                add_prefix(r.get_uri(), all_prefixes, &mut context);
+                out.push(json!(r.get_uri()));
+            }
+            node.insert("@type".to_owned(), JSONValue::Array(out));
+            continue;
+        }
+        add_prefix(predicate, all_prefixes, &mut context);
+        for r in values {
+            out.push(resource_to_json_ld(predicate, r, all_prefixes, &mut context)?);
+        }
+        node.insert(predicate.to_owned(), JSONValue::Array(out));
+    }
+
+    let mut res = Map::new();
+    res.insert("@context".to_owned(), JSONValue::Object(context));
+    res.extend(node);
+    Ok(JSONValue::Object(res))
+}
@@ -1,10 +1,42 @@
 extern crate rmp as msgpack;
 use crate::onto::datatype::DataType;
+#[cfg(feature = "std")]
+use crate::onto::envelope::{Codec, Key, RawEnvelope, EXT_RIGHTS};
 use crate::onto::individual::Individual;
+#[cfg(feature = "std")]
+use crate::onto::metrics::{Failure, Measure, Metrics, Operation};
+#[cfg(feature = "std")]
+use crate::onto::migrations::{write_format_version, CURRENT_FORMAT};
+use crate::onto::parser::MSGPACK_MAGIC_HEADER;
 use crate::onto::resource::Resource;
+#[cfg(feature = "std")]
+use crate::onto::rights::encode_rights;
+use alloc::vec::Vec;
+#[cfg(not(feature = "std"))]
+use core::convert::Infallible;
 use msgpack::encode::*;
+#[cfg(feature = "std")]
 use std::io::Error;
-use std::io::Write;
+
+/// Without std individuals are only written to a `Vec`, which does not fail; the error keeps
+/// the signatures the same.
+#[cfg(not(feature = "std"))]
+#[derive(Debug)]
+pub struct Error;
+
+#[cfg(not(feature = "std"))]
+impl From<ValueWriteError<Infallible>> for Error {
+    fn from(_: ValueWriteError<Infallible>) -> Self {
+        Error
+    }
+}
+
+#[cfg(not(feature = "std"))]
+impl From<Infallible> for Error {
+    fn from(e: Infallible) -> Self {
+        match e {}
+    }
+}
 
 fn write_resource(out: &mut Vec<u8>, r: &Resource) -> Result<(), Error> {
     match r.rtype {
@@ -18,7 +50,7 @@ fn write_resource(out: &mut Vec<u8>, r: &Resource) -> Result<(), Error> {
             write_u8(out, r.rtype.clone() as u8)?;
             let data = r.get_binary();
             write_str_len(out, data.len() as u32)?;
-            out.write_all(data).map_err(ValueWriteError::InvalidDataWrite)?;
+            out.extend_from_slice(data);
         },
         DataType::Boolean => {
             write_array_len(out, 2)?;
@@ -86,7 +118,47 @@ fn write_resource(out: &mut Vec<u8>, r: &Resource) -> Result<(), Error> {
     Ok(())
 }
 
+/// An individual loaded from msgpack and not changed since is written as its original buffer.
+#[cfg(feature = "std")]
 pub fn to_msgpack(indv: &Individual, out: &mut Vec<u8>) -> Result<(), Error> {
+    write_measured(indv, out, None)
+}
+
+#[cfg(not(feature = "std"))]
+pub fn to_msgpack(indv: &Individual, out: &mut Vec<u8>) -> Result<(), Error> {
+    write_individual(indv, out)
+}
+
+/// Same as `to_msgpack`, measurements go to `metrics` instead of the global sink.
+#[cfg(feature = "std")]
+pub fn to_msgpack_with_metrics(indv: &Individual, out: &mut Vec<u8>, metrics: &dyn Metrics) -> Result<(), Error> {
+    write_measured(indv, out, Some(metrics))
+}
+
+#[cfg(feature = "std")]
+fn write_measured(indv: &Individual, out: &mut Vec<u8>, metrics: Option<&dyn Metrics>) -> Result<(), Error> {
+    let measure = Measure::start(Operation::Serialize, metrics);
+    let start = out.len();
+    match write_individual(indv, out) {
+        Ok(()) => {
+            measure.ok("msgpack", out.len() - start);
+            Ok(())
+        },
+        Err(e) => {
+            measure.fail("msgpack", Failure::Encode);
+            Err(e)
+        },
+    }
+}
+
+fn write_individual(indv: &Individual, out: &mut Vec<u8>) -> Result<(), Error> {
+    if let Some(raw) = indv.get_clean_raw() {
+        if raw[0] == MSGPACK_MAGIC_HEADER {
+            out.extend_from_slice(raw);
+            return Ok(());
+        }
+    }
+
     write_array_len(out, 2)?;
     write_str(out, &indv.obj.uri)?;
     write_map_len(out, indv.obj.resources.len() as u32)?;
@@ -101,3 +173,44 @@ pub fn to_msgpack(indv: &Individual, out: &mut Vec<u8>) -> Result<(), Error> {
 
     Ok(())
 }
+
+/// Same as `to_msgpack`, prefixed with the current format version.
+#[cfg(feature = "std")]
+pub fn to_msgpack_versioned(indv: &Individual, out: &mut Vec<u8>) -> Result<(), Error> {
+    write_format_version(CURRENT_FORMAT, out);
+    to_msgpack(indv, out)
+}
+
+/// The `EXT_RIGHTS` extension with the rights masks of the individual, if it has any.
+#[cfg(feature = "std")]
+fn rights_extensions(indv: &Individual) -> Vec<(u8, Vec<u8>)> {
+    if indv.obj.rights.is_empty() {
+        Vec::new()
+    } else {
+        vec![(EXT_RIGHTS, encode_rights(&indv.obj.rights))]
+    }
+}
+
+/// Same as `to_msgpack`, the result is wrapped in a compressed `RawEnvelope`; rights masks of
+/// predicates are kept in its `EXT_RIGHTS` extension.
+#[cfg(feature = "std")]
+pub fn to_msgpack_packed(indv: &Individual, out: &mut Vec<u8>, codec: Codec) -> Result<(), Error> {
+    let mut raw = Vec::new();
+    to_msgpack(indv, &mut raw)?;
+    let mut envelope = RawEnvelope::pack(&raw, codec).map_err(Error::other)?;
+    for (tag, data) in rights_extensions(indv) {
+        envelope = envelope.with_extension(tag, data).map_err(Error::other)?;
+    }
+    envelope.encode(out);
+    Ok(())
+}
+
+/// Same as `to_msgpack_packed`, the envelope is encrypted with `key`, `key_id` is kept in clear;
+/// the rights masks are authenticated with the header.
+#[cfg(feature = "std")]
+pub fn to_msgpack_encrypted(indv: &Individual, out: &mut Vec<u8>, codec: Codec, key_id: &str, key: &Key) -> Result<(), Error> {
+    let mut raw = Vec::new();
+    to_msgpack(indv, &mut raw)?;
+    RawEnvelope::pack_encrypted_with(&raw, codec, key_id, key, rights_extensions(indv)).map_err(Error::other)?.encode(out);
+    Ok(())
+}
@@ -1,15 +1,20 @@
+use crate::onto::blank_nodes::BlankNodeIds;
+use crate::onto::containers::{Container, RDF_NIL, SEQ_TYPE};
 use crate::onto::datatype::*;
+use crate::onto::datatype_registry::DatatypeRegistry;
+use crate::onto::decimal::{format_scientific, DecimalFormat};
 use crate::onto::individual::*;
+use crate::onto::isomorphism::is_blank_node;
+use crate::onto::links::export_order;
 use crate::onto::resource::*;
 use crate::onto::turtle_formatters_with_prefixes::TurtleFormatterWithPrefixes;
-use chrono::{TimeZone, Utc};
 use rio_api::formatter::TriplesFormatter;
 use rio_api::model::*;
-use rust_decimal::Decimal;
 use std::collections::HashMap;
 use std::io;
+use std::slice;
 
-fn from_boolean<'a>(id: &'a str, in_predicate: &'a str, v: &'a str) -> Triple<'a> {
+fn from_typed<'a>(id: &'a str, in_predicate: &'a str, v: &'a str, datatype: &'a str) -> Triple<'a> {
     let subject = NamedNode {
         iri: id,
     };
@@ -21,7 +26,7 @@ fn from_boolean<'a>(id: &'a str, in_predicate: &'a str, v: &'a str) -> Triple<'a
     let obj = Literal::Typed {
         value: v,
         datatype: NamedNode {
-            iri: "xsd:boolean",
+            iri: datatype,
         },
     };
 
@@ -32,7 +37,7 @@ fn from_boolean<'a>(id: &'a str, in_predicate: &'a str, v: &'a str) -> Triple<'a
     }
 }
 
-fn from_integer<'a>(id: &'a str, in_predicate: &'a str, v: &'a str) -> Triple<'a> {
+fn from_uri<'a>(id: &'a str, in_predicate: &'a str, v: &'a str) -> Triple<'a> {
     let subject = NamedNode {
         iri: id,
     };
@@ -41,11 +46,8 @@ fn from_integer<'a>(id: &'a str, in_predicate: &'a str, v: &'a str) -> Triple<'a
         iri: in_predicate,
     };
 
-    let obj = Literal::Typed {
-        value: v,
-        datatype: NamedNode {
-            iri: "xsd:integer",
-        },
+    let obj = NamedNode {
+        iri: v,
     };
 
     Triple {
@@ -55,7 +57,7 @@ fn from_integer<'a>(id: &'a str, in_predicate: &'a str, v: &'a str) -> Triple<'a
     }
 }
 
-fn from_decimal<'a>(id: &'a str, in_predicate: &'a str, v: &'a str) -> Triple<'a> {
+fn from_blank<'a>(id: &'a str, in_predicate: &'a str, blank: &'a str) -> Triple<'a> {
     let subject = NamedNode {
         iri: id,
     };
@@ -64,11 +66,8 @@ fn from_decimal<'a>(id: &'a str, in_predicate: &'a str, v: &'a str) -> Triple<'a
         iri: in_predicate,
     };
 
-    let obj = Literal::Typed {
-        value: v,
-        datatype: NamedNode {
-            iri: "xsd:decimal",
-        },
+    let obj = BlankNode {
+        id: blank,
     };
 
     Triple {
@@ -78,7 +77,7 @@ fn from_decimal<'a>(id: &'a str, in_predicate: &'a str, v: &'a str) -> Triple<'a
     }
 }
 
-fn from_datetime<'a>(id: &'a str, in_predicate: &'a str, v: &'a str) -> Triple<'a> {
+fn from_string<'a>(id: &'a str, in_predicate: &'a str, s: &'a str, l: &'a Lang) -> Triple<'a> {
     let subject = NamedNode {
         iri: id,
     };
@@ -87,11 +86,15 @@ fn from_datetime<'a>(id: &'a str, in_predicate: &'a str, v: &'a str) -> Triple<'
         iri: in_predicate,
     };
 
-    let obj = Literal::Typed {
-        value: v,
-        datatype: NamedNode {
-            iri: "xsd:dateTime",
-        },
+    let obj = if l.is_some() {
+        Literal::LanguageTaggedString {
+            value: s,
+            language: l.to_string(),
+        }
+    } else {
+        Literal::Simple {
+            value: s,
+        }
     };
 
     Triple {
@@ -101,50 +104,82 @@ fn from_datetime<'a>(id: &'a str, in_predicate: &'a str, v: &'a str) -> Triple<'
     }
 }
 
-fn from_uri<'a>(id: &'a str, in_predicate: &'a str, v: &'a str) -> Triple<'a> {
-    let subject = NamedNode {
-        iri: id,
-    };
+/// How `to_turtle_with` writes blank nodes, uris starting with `_:`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BlankNodeLabels<'a> {
+    /// As stored.
+    #[default]
+    Keep,
+    /// `_:b0`, `_:b1`, … in the order of the output, see `BlankNodeIds`.
+    Relabel,
+    /// Skolem IRIs under the given base, e.g. `http://example.org/.well-known/genid/b0`.
+    Skolemize(&'a str),
+}
 
-    let predicate = NamedNode {
-        iri: in_predicate,
-    };
+/// Options for `to_turtle_with`, the default gives the `to_turtle` output.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TurtleOptions<'a> {
+    pub decimal: DecimalFormat,
+    /// Datatypes of typed literals, `DatatypeRegistry::builtin()` when not set.
+    pub registry: Option<&'a DatatypeRegistry>,
+    /// Writes referenced individuals before the ones referencing them, see `links::export_order`.
+    pub dependency_order: bool,
+    pub blank_nodes: BlankNodeLabels<'a>,
+    /// Predicates whose values are written, in order, as one `rdf:List` or `rdf:Seq`.
+    pub lists: &'a [(&'a str, Container)],
+}
 
-    let obj = NamedNode {
-        iri: v,
-    };
+impl<'a> TurtleOptions<'a> {
+    pub fn with_decimal(mut self, decimal: DecimalFormat) -> Self {
+        self.decimal = decimal;
+        self
+    }
 
-    Triple {
-        subject: subject.into(),
-        predicate,
-        object: obj.into(),
+    pub fn with_registry(mut self, registry: &'a DatatypeRegistry) -> Self {
+        self.registry = Some(registry);
+        self
     }
-}
 
-fn from_string<'a>(id: &'a str, in_predicate: &'a str, s: &'a str, l: &'a Lang) -> Triple<'a> {
-    let subject = NamedNode {
-        iri: id,
-    };
+    pub fn with_dependency_order(mut self, dependency_order: bool) -> Self {
+        self.dependency_order = dependency_order;
+        self
+    }
 
-    let predicate = NamedNode {
-        iri: in_predicate,
-    };
+    pub fn with_blank_nodes(mut self, blank_nodes: BlankNodeLabels<'a>) -> Self {
+        self.blank_nodes = blank_nodes;
+        self
+    }
 
-    let obj = if l.is_some() {
-        Literal::LanguageTaggedString {
-            value: s,
-            language: l.to_string(),
-        }
-    } else {
-        Literal::Simple {
-            value: s,
+    pub fn with_lists(mut self, lists: &'a [(&'a str, Container)]) -> Self {
+        self.lists = lists;
+        self
+    }
+}
+
+/// Blank node labels of one export.
+#[derive(Default)]
+struct Blanks {
+    /// Relabeling of stored blank nodes, None keeps them.
+    stored: Option<BlankNodeIds>,
+    /// Cells of written lists while stored labels are kept.
+    cells: BlankNodeIds,
+}
+
+impl Blanks {
+    fn cell(&mut self) -> String {
+        match &mut self.stored {
+            Some(ids) => ids.fresh(),
+            None => self.cells.fresh(),
         }
-    };
+    }
+}
 
-    Triple {
-        subject: subject.into(),
-        predicate,
-        object: obj.into(),
+/// `subject predicate node`, the node being a blank node or a skolem IRI.
+fn format_node(subject: &str, predicate: &str, node: &str, formatter: &mut TurtleFormatterWithPrefixes<Vec<u8>>) -> Result<(), io::Error> {
+    if is_blank_node(node) {
+        formatter.format(&from_blank(subject, predicate, node))
+    } else {
+        formatter.format(&from_uri(subject, predicate, node))
     }
 }
 
@@ -153,21 +188,80 @@ pub fn format_resources(
     predicate: &str,
     resources: &[Resource],
     formatter: &mut TurtleFormatterWithPrefixes<Vec<u8>>,
+) -> Result<(), io::Error> {
+    format_resources_with(subject, predicate, resources, &TurtleOptions::default(), &mut Blanks::default(), formatter)
+}
+
+fn format_resources_with(
+    subject: &str,
+    predicate: &str,
+    resources: &[Resource],
+    opts: &TurtleOptions,
+    blanks: &mut Blanks,
+    formatter: &mut TurtleFormatterWithPrefixes<Vec<u8>>,
 ) -> Result<(), io::Error> {
     for r in resources {
-        format_resource(subject, predicate, r, formatter)?;
+        match (blanks.stored.as_mut(), &r.value) {
+            (Some(ids), Value::Uri(u)) if is_blank_node(u) => format_node(subject, predicate, ids.get(u), formatter)?,
+            _ => format_resource_with(subject, predicate, r, opts, formatter)?,
+        }
     }
     Ok(())
 }
 
-pub fn format_resource(subject: &str, predicate: &str, r: &Resource, formatter: &mut TurtleFormatterWithPrefixes<Vec<u8>>) -> Result<(), io::Error> {
-    match r.rtype {
-        DataType::Boolean => {
-            formatter.format(&from_boolean(subject, predicate, &r.get_bool().to_string()))?;
+/// The cells of a list or seq whose head `node` is already referenced.
+fn format_container(
+    node: &str,
+    container: Container,
+    resources: &[Resource],
+    opts: &TurtleOptions,
+    blanks: &mut Blanks,
+    formatter: &mut TurtleFormatterWithPrefixes<Vec<u8>>,
+) -> Result<(), io::Error> {
+    match container {
+        Container::Seq => {
+            formatter.format(&from_uri(node, "rdf:type", SEQ_TYPE))?;
+            for (i, r) in resources.iter().enumerate() {
+                format_resources_with(node, &format!("rdf:_{}", i + 1), slice::from_ref(r), opts, blanks, formatter)?;
+            }
         },
-        DataType::Integer => {
-            formatter.format(&from_integer(subject, predicate, &r.get_int().to_string()))?;
+        Container::List => {
+            let mut cell = node.to_owned();
+            for (i, r) in resources.iter().enumerate() {
+                format_resources_with(&cell, "rdf:first", slice::from_ref(r), opts, blanks, formatter)?;
+                if i + 1 == resources.len() {
+                    formatter.format(&from_uri(&cell, "rdf:rest", RDF_NIL))?;
+                } else {
+                    let next = blanks.cell();
+                    format_node(&cell, "rdf:rest", &next, formatter)?;
+                    cell = next;
+                }
+            }
         },
+    }
+    Ok(())
+}
+
+pub fn format_resource(subject: &str, predicate: &str, r: &Resource, formatter: &mut TurtleFormatterWithPrefixes<Vec<u8>>) -> Result<(), io::Error> {
+    format_resource_with(subject, predicate, r, &TurtleOptions::default(), formatter)
+}
+
+/// Like `format_resource`. Literals take the datatype bound to the predicate in the registry (strings
+/// without language only), `DecimalFormat::Scientific` writes decimals as `xsd:double`.
+pub fn format_resource_with(
+    subject: &str,
+    predicate: &str,
+    r: &Resource,
+    opts: &TurtleOptions,
+    formatter: &mut TurtleFormatterWithPrefixes<Vec<u8>>,
+) -> Result<(), io::Error> {
+    let invalid = |e: String| io::Error::new(io::ErrorKind::InvalidData, e);
+    let registry = match opts.registry {
+        Some(v) => v,
+        None => DatatypeRegistry::builtin(),
+    };
+
+    match r.rtype {
         DataType::Uri => {
             if !r.get_uri().contains(':') || r.get_uri().contains('/') {
                 formatter.format(&from_string(subject, predicate, r.get_str(), &Lang::none()))?;
@@ -175,24 +269,23 @@ pub fn format_resource(subject: &str, predicate: &str, r: &Resource, formatter:
                 formatter.format(&from_uri(subject, predicate, r.get_uri()))?;
             }
         },
-        DataType::String => {
+        DataType::String if r.get_lang().is_some() => {
             formatter.format(&from_string(subject, predicate, r.get_str(), &r.get_lang()))?;
         },
-        DataType::Datetime => {
-            let datetime = match Utc.timestamp_opt(r.get_datetime(), 0) {
-                chrono::LocalResult::Single(dt) => dt,
-                _ => return Err(io::Error::new(io::ErrorKind::InvalidData, format!("Invalid timestamp value: {}", r.get_datetime()))),
-            };
-
-            formatter.format(&from_datetime(subject, predicate, &format!("{:?}", datetime)))?;
-        },
-        DataType::Decimal => {
+        DataType::Decimal if opts.decimal == DecimalFormat::Scientific => {
             let (m, e) = r.get_num();
-            let c = exponent_to_scale(&m, &e);
-            let d = Decimal::new(c.0, c.1);
-            formatter.format(&from_decimal(subject, predicate, &format!("{:?}", d.to_string())))?;
+            formatter.format(&from_typed(subject, predicate, &format_scientific(m, e), "xsd:double"))?;
+        },
+        _ => match registry.datatype_of(predicate, r) {
+            Some(def) if def.iri() == "xsd:string" => {
+                formatter.format(&from_string(subject, predicate, r.get_str(), &Lang::none()))?;
+            },
+            Some(def) => {
+                let v = def.format(&r.value).map_err(invalid)?;
+                formatter.format(&from_typed(subject, predicate, &v, def.iri()))?;
+            },
+            None => {},
         },
-        _ => {},
     }
     Ok(())
 }
@@ -252,13 +345,29 @@ pub fn extract_prefixes(indvs: &[Individual], all_prefixes: &HashMap<String, Str
     used_prefixes
 }
 
-fn indv_format_to_tt(id: &str, indv: &Individual, formatter: &mut TurtleFormatterWithPrefixes<Vec<u8>>, exclude_counter: bool) -> Result<(), io::Error> {
+fn indv_format_to_tt(
+    id: &str,
+    indv: &Individual,
+    formatter: &mut TurtleFormatterWithPrefixes<Vec<u8>>,
+    exclude_counter: bool,
+    opts: &TurtleOptions,
+    blanks: &mut Blanks,
+) -> Result<(), io::Error> {
+    let mapped;
+    let id = match blanks.stored.as_mut() {
+        Some(ids) if is_blank_node(id) => {
+            mapped = ids.get(id).to_owned();
+            mapped.as_str()
+        },
+        _ => id,
+    };
     for (predicate, resources) in &indv.obj.resources {
         if predicate == "rdf:type" {
-            format_resources(id, predicate, resources, formatter)?;
+            format_resources_with(id, predicate, resources, opts, blanks, formatter)?;
             break;
         }
     }
+    let mut containers = vec![];
     for (predicate, resources) in &indv.obj.resources {
         if predicate == "rdf:type" {
             continue;
@@ -267,14 +376,21 @@ fn indv_format_to_tt(id: &str, indv: &Individual, formatter: &mut TurtleFormatte
             continue;
         }
 
-        if predicate == "?" {
-            format_resources(id, "d:unknown", resources, formatter)?;
+        if let Some((_, container)) = opts.lists.iter().find(|(p, _)| p == predicate).filter(|_| !resources.is_empty()) {
+            let node = blanks.cell();
+            format_node(id, predicate, &node, formatter)?;
+            containers.push((node, *container, resources));
+        } else if predicate == "?" {
+            format_resources_with(id, "d:unknown", resources, opts, blanks, formatter)?;
         } else if !predicate.contains(':') {
-            format_resources(id, &format!("d:{}", predicate), resources, formatter)?;
+            format_resources_with(id, &format!("d:{}", predicate), resources, opts, blanks, formatter)?;
         } else {
-            format_resources(id, predicate, resources, formatter)?;
+            format_resources_with(id, predicate, resources, opts, blanks, formatter)?;
         }
     }
+    for (node, container, resources) in containers {
+        format_container(&node, container, resources, opts, blanks, formatter)?;
+    }
 
     Ok(())
 }
@@ -283,18 +399,53 @@ pub fn to_turtle_with_counter_refs(indvs: &[&Individual], all_prefixes: &HashMap
     let used_prefixes = extract_prefixes_ref(indvs, all_prefixes);
     let mut formatter = TurtleFormatterWithPrefixes::new(Vec::default(), &used_prefixes, true);
     for indv in indvs.iter() {
-        indv_format_to_tt(indv.get_id(), indv, &mut formatter, false)?;
+        indv_format_to_tt(indv.get_id(), indv, &mut formatter, false, &TurtleOptions::default(), &mut Blanks::default())?;
     }
 
     formatter.finish()
 }
 
 pub fn to_turtle(indvs: &[Individual], all_prefixes: &HashMap<String, String>) -> Result<Vec<u8>, io::Error> {
-    let used_prefixes = extract_prefixes(indvs, all_prefixes);
+    to_turtle_with(indvs, all_prefixes, &TurtleOptions::default())
+}
+
+pub fn to_turtle_with(indvs: &[Individual], all_prefixes: &HashMap<String, String>, opts: &TurtleOptions) -> Result<Vec<u8>, io::Error> {
+    let mut used_prefixes = extract_prefixes(indvs, all_prefixes);
+    if let Some(registry) = opts.registry {
+        for indv in indvs.iter() {
+            for predicate in indv.obj.resources.keys() {
+                if let Some(def) = registry.get_bound(predicate) {
+                    collect_prefix(def.iri(), all_prefixes, &mut used_prefixes);
+                }
+            }
+        }
+    }
+    if !opts.lists.is_empty() {
+        collect_prefix("rdf:", all_prefixes, &mut used_prefixes);
+    }
     let mut formatter = TurtleFormatterWithPrefixes::new(Vec::default(), &used_prefixes, true);
+    let mut blanks = Blanks {
+        stored: match opts.blank_nodes {
+            BlankNodeLabels::Keep => None,
+            BlankNodeLabels::Relabel => Some(BlankNodeIds::new()),
+            BlankNodeLabels::Skolemize(base) => Some(BlankNodeIds::skolemizing(base)),
+        },
+        ..Default::default()
+    };
+
+    if opts.dependency_order {
+        let order = export_order(indvs.iter().map(|indv| &indv.obj));
+        for cycle in order.cycles.iter() {
+            warn!("to_turtle: reference cycle, written in input order: {}", cycle.join(", "));
+        }
+        for i in order.order {
+            indv_format_to_tt(indvs[i].get_id(), &indvs[i], &mut formatter, true, opts, &mut blanks)?;
+        }
+        return formatter.finish();
+    }
 
     for indv in indvs.iter() {
-        indv_format_to_tt(indv.get_id(), indv, &mut formatter, true)?;
+        indv_format_to_tt(indv.get_id(), indv, &mut formatter, true, opts, &mut blanks)?;
     }
 
     formatter.finish()
@@ -0,0 +1,225 @@
+use crate::onto::datatype::{DataType, Lang};
+use crate::onto::individual::{IndividualObj, RandomState};
+use crate::onto::msgpack2individual::parse_msgpack_ref;
+use crate::onto::resource::{Resource, Value};
+use alloc::borrow::Cow;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use indexmap::IndexMap;
+
+/// `Value` borrowing its strings and binaries from the buffer it was read from. Strings are
+/// borrowed unless they are not valid UTF-8 and had to be replaced. The language is kept as written,
+/// `Lang` is built by `to_owned`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ValueRef<'a> {
+    Int(i64),
+    Str(Cow<'a, str>, Option<Cow<'a, str>>),
+    Uri(Cow<'a, str>),
+    Bool(bool),
+    Num(i64, i64),
+    Binary(&'a [u8]),
+    Datetime(i64),
+}
+
+impl ValueRef<'_> {
+    pub fn to_owned(&self) -> Value {
+        match self {
+            ValueRef::Int(v) => Value::Int(*v),
+            ValueRef::Str(s, lang) => Value::Str(s.to_string(), lang.as_deref().map(Lang::new_from_str).unwrap_or_else(Lang::none)),
+            ValueRef::Uri(s) => Value::Uri(s.to_string()),
+            ValueRef::Bool(v) => Value::Bool(*v),
+            ValueRef::Num(m, e) => Value::Num(*m, *e),
+            ValueRef::Binary(v) => Value::Binary(v.to_vec()),
+            ValueRef::Datetime(v) => Value::Datetime(*v),
+        }
+    }
+}
+
+/// `Resource` borrowed from the buffer, see `ValueRef`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ResourceRef<'a> {
+    pub rtype: DataType,
+    pub value: ValueRef<'a>,
+    pub order: u16,
+}
+
+impl<'a> ResourceRef<'a> {
+    pub fn to_owned(&self) -> Resource {
+        Resource {
+            rtype: self.rtype.clone(),
+            order: self.order,
+            value: self.value.to_owned(),
+        }
+    }
+
+    pub fn get_str(&self) -> &str {
+        if let ValueRef::Str(s, _) = &self.value {
+            s
+        } else {
+            ""
+        }
+    }
+
+    pub fn get_uri(&self) -> &str {
+        if let ValueRef::Uri(s) = &self.value {
+            s
+        } else {
+            ""
+        }
+    }
+
+    /// The language as written, None for values without language.
+    pub fn get_lang(&self) -> Option<&str> {
+        if let ValueRef::Str(_, Some(l)) = &self.value {
+            Some(l)
+        } else {
+            None
+        }
+    }
+
+    pub fn get_binary(&self) -> &'a [u8] {
+        if let ValueRef::Binary(v) = self.value {
+            v
+        } else {
+            &[]
+        }
+    }
+
+    pub fn get_int(&self) -> i64 {
+        if let ValueRef::Int(v) = self.value {
+            v
+        } else {
+            0
+        }
+    }
+
+    pub fn get_datetime(&self) -> i64 {
+        if let ValueRef::Datetime(v) = self.value {
+            v
+        } else {
+            0
+        }
+    }
+
+    pub fn get_bool(&self) -> bool {
+        if let ValueRef::Bool(v) = self.value {
+            v
+        } else {
+            false
+        }
+    }
+
+    pub fn get_num(&self) -> (i64, i64) {
+        if let ValueRef::Num(m, e) = self.value {
+            (m, e)
+        } else {
+            (0, 0)
+        }
+    }
+}
+
+/// Read-only view of a msgpack individual whose predicates and values borrow from the record,
+/// nothing is copied out of it. For consumers that read a few values of many records (indexers,
+/// exporters, filters); `to_owned` gives an `IndividualObj` to modify. Envelopes, versioned records
+/// and CBOR are not borrowed, they are read by `parse_raw`.
+///
+/// ```text
+/// let indv = IndividualRef::from_msgpack(&data)?;
+/// let label = indv.get_first_literal("rdfs:label");
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct IndividualRef<'a> {
+    pub(crate) uri: Cow<'a, str>,
+    pub(crate) resources: IndexMap<Cow<'a, str>, Vec<ResourceRef<'a>>, RandomState>,
+}
+
+impl<'a> IndividualRef<'a> {
+    pub fn from_msgpack(data: &'a [u8]) -> Result<Self, String> {
+        parse_msgpack_ref(data)
+    }
+
+    pub fn get_id(&self) -> &str {
+        &self.uri
+    }
+
+    /// In record order.
+    pub fn get_predicates(&self) -> impl Iterator<Item = &str> {
+        self.resources.keys().map(|p| p.as_ref())
+    }
+
+    pub fn get_resources(&self, predicate: &str) -> Option<&[ResourceRef<'a>]> {
+        self.resources.get(predicate).map(|v| v.as_slice())
+    }
+
+    fn get_first(&self, predicate: &str) -> Option<&ValueRef<'a>> {
+        self.resources.get(predicate).and_then(|v| v.first()).map(|r| &r.value)
+    }
+
+    /// The first value if it is a string or a uri.
+    pub fn get_first_literal(&self, predicate: &str) -> Option<&str> {
+        match self.get_first(predicate)? {
+            ValueRef::Str(s, _) | ValueRef::Uri(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    /// The string and uri values.
+    pub fn get_literals(&self, predicate: &str) -> Vec<&str> {
+        self.resources
+            .get(predicate)
+            .map(|v| {
+                v.iter()
+                    .filter_map(|r| match &r.value {
+                        ValueRef::Str(s, _) | ValueRef::Uri(s) => Some(s.as_ref()),
+                        _ => None,
+                    })
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    pub fn get_first_integer(&self, predicate: &str) -> Option<i64> {
+        match self.get_first(predicate)? {
+            ValueRef::Int(v) => Some(*v),
+            _ => None,
+        }
+    }
+
+    pub fn get_first_bool(&self, predicate: &str) -> Option<bool> {
+        match self.get_first(predicate)? {
+            ValueRef::Bool(v) => Some(*v),
+            _ => None,
+        }
+    }
+
+    pub fn get_first_datetime(&self, predicate: &str) -> Option<i64> {
+        match self.get_first(predicate)? {
+            ValueRef::Datetime(v) => Some(*v),
+            _ => None,
+        }
+    }
+
+    pub fn get_first_binobj(&self, predicate: &str) -> Option<&'a [u8]> {
+        match self.get_first(predicate)? {
+            ValueRef::Binary(v) => Some(*v),
+            _ => None,
+        }
+    }
+
+    pub fn is_exists(&self, predicate: &str) -> bool {
+        self.resources.contains_key(predicate)
+    }
+
+    /// The individual with every value copied, as `parse_raw` and `parse_all` give it.
+    pub fn to_owned(&self) -> IndividualObj {
+        let mut res = IndividualObj {
+            uri: self.uri.to_string(),
+            ..Default::default()
+        };
+        res.resources.reserve(self.resources.len());
+        for (predicate, values) in self.resources.iter() {
+            res.resources.insert(predicate.to_string(), values.iter().map(|r| r.to_owned()).collect());
+        }
+        res
+    }
+}
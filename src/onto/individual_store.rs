@@ -0,0 +1,204 @@
+use crate::onto::dump::read_record;
+#[cfg(feature = "msgpack")]
+use crate::onto::dump::write_record;
+use crate::onto::individual::{Individual, RawObj};
+#[cfg(feature = "msgpack")]
+use crate::onto::individual2msgpack::to_msgpack;
+use crate::onto::op::IndividualOp;
+use crate::onto::parser::parse_raw;
+use crate::onto::resource::Value;
+use std::collections::btree_map;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+#[cfg(feature = "msgpack")]
+use std::io::Write;
+use std::io::{Error, ErrorKind, Read};
+use std::ops::Bound;
+
+const RDF_TYPE: &str = "rdf:type";
+
+/// In-memory collection of fully parsed individuals, indexed by uri (ordered, for prefix lookups) and by `rdf:type`.
+#[derive(Default)]
+pub struct IndividualStoreInMem {
+    individuals: BTreeMap<String, Individual>,
+    by_type: HashMap<String, BTreeSet<String>>,
+}
+
+fn get_types(indv: &Individual) -> Vec<String> {
+    let mut res = Vec::new();
+    if let Some(values) = indv.get_obj().get_resources().get(RDF_TYPE) {
+        for r in values.iter() {
+            if let Value::Uri(t) = &r.value {
+                res.push(t.to_owned());
+            }
+        }
+    }
+    res
+}
+
+impl IndividualStoreInMem {
+    pub fn new() -> Self {
+        IndividualStoreInMem::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.individuals.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.individuals.is_empty()
+    }
+
+    pub fn contains(&self, uri: &str) -> bool {
+        self.individuals.contains_key(uri)
+    }
+
+    /// Adds or replaces an individual, returns the replaced one.
+    pub fn insert(&mut self, mut indv: Individual) -> Option<Individual> {
+        indv.parse_all();
+        let uri = indv.get_id().to_owned();
+        let prev = self.remove(&uri);
+
+        for t in get_types(&indv) {
+            self.by_type.entry(t).or_default().insert(uri.to_owned());
+        }
+        self.individuals.insert(uri, indv);
+        prev
+    }
+
+    pub fn remove(&mut self, uri: &str) -> Option<Individual> {
+        let indv = self.individuals.remove(uri)?;
+        for t in get_types(&indv) {
+            if let Some(uris) = self.by_type.get_mut(&t) {
+                uris.remove(uri);
+                if uris.is_empty() {
+                    self.by_type.remove(&t);
+                }
+            }
+        }
+        Some(indv)
+    }
+
+    pub fn get(&self, uri: &str) -> Option<&Individual> {
+        self.individuals.get(uri)
+    }
+
+    /// Modifies an individual in place and refreshes the indexes, returns false if `uri` is not found.
+    /// Changing the uri inside `f` moves the individual to the new key.
+    pub fn update<F>(&mut self, uri: &str, f: F) -> bool
+    where
+        F: FnOnce(&mut Individual),
+    {
+        match self.remove(uri) {
+            Some(mut indv) => {
+                f(&mut indv);
+                self.insert(indv);
+                true
+            },
+            None => false,
+        }
+    }
+
+    /// Applies an update op; `SetIn` and `AddTo` on a missing individual create it.
+    pub fn apply_op(&mut self, op: &IndividualOp) -> Result<(), String> {
+        match op {
+            IndividualOp::Put(obj) => {
+                self.insert(Individual::new_from_obj(obj));
+                Ok(())
+            },
+            IndividualOp::Remove(uri) => {
+                self.remove(uri);
+                Ok(())
+            },
+            _ => {
+                let (mut indv, existed) = match self.remove(op.get_uri()) {
+                    Some(indv) => (indv, true),
+                    None => {
+                        let mut indv = Individual::default();
+                        indv.set_id(op.get_uri());
+                        (indv, false)
+                    },
+                };
+                let res = indv.apply_op(op);
+                if existed || !indv.is_empty() {
+                    self.insert(indv);
+                }
+                res
+            },
+        }
+    }
+
+    pub fn find_by_type(&self, rdf_type: &str) -> Vec<&Individual> {
+        match self.by_type.get(rdf_type) {
+            Some(uris) => uris.iter().filter_map(|uri| self.individuals.get(uri)).collect(),
+            None => Vec::new(),
+        }
+    }
+
+    pub fn find_by_prefix(&self, prefix: &str) -> Vec<&Individual> {
+        self.individuals
+            .range::<str, _>((Bound::Included(prefix), Bound::Unbounded))
+            .take_while(|(uri, _)| uri.starts_with(prefix))
+            .map(|(_, indv)| indv)
+            .collect()
+    }
+
+    pub fn types(&self) -> Vec<&str> {
+        self.by_type.keys().map(|t| t.as_str()).collect()
+    }
+
+    pub fn iter(&self) -> btree_map::Values<'_, String, Individual> {
+        self.individuals.values()
+    }
+
+    /// Writes all individuals as a sequence of records: u32 big-endian length followed by the msgpack body.
+    #[cfg(feature = "msgpack")]
+    pub fn write_msgpack<W: Write>(&self, out: &mut W) -> Result<usize, Error> {
+        let mut buf = Vec::new();
+        for indv in self.individuals.values() {
+            buf.clear();
+            to_msgpack(indv, &mut buf)?;
+            write_record(out, &buf)?;
+        }
+        Ok(self.individuals.len())
+    }
+
+    /// Reads records written by `write_msgpack`, the body of each record may be msgpack or CBOR.
+    /// Returns the number of loaded individuals.
+    pub fn read_raw<R: Read>(&mut self, src: &mut R) -> Result<usize, Error> {
+        let mut count = 0;
+        while let Some(data) = read_record(src)? {
+            let mut indv = Individual::new_raw(RawObj::new(data));
+            if parse_raw(&mut indv).is_err() {
+                return Err(Error::new(ErrorKind::InvalidData, format!("fail parse individual, record {}", count)));
+            }
+            self.insert(indv);
+            count += 1;
+        }
+        Ok(count)
+    }
+}
+
+impl<'a> IntoIterator for &'a IndividualStoreInMem {
+    type Item = &'a Individual;
+    type IntoIter = btree_map::Values<'a, String, Individual>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.individuals.values()
+    }
+}
+
+impl Extend<Individual> for IndividualStoreInMem {
+    fn extend<T: IntoIterator<Item = Individual>>(&mut self, iter: T) {
+        for indv in iter {
+            self.insert(indv);
+        }
+    }
+}
+
+impl FromIterator<Individual> for IndividualStoreInMem {
+    fn from_iter<T: IntoIterator<Item = Individual>>(iter: T) -> Self {
+        let mut store = IndividualStoreInMem::new();
+        store.extend(iter);
+        store
+    }
+}
@@ -0,0 +1,147 @@
+use crate::onto::individual::HashMap;
+use alloc::borrow::ToOwned;
+use alloc::format;
+use alloc::string::String;
+use iri_string::types::IriReferenceStr;
+use unicode_normalization::UnicodeNormalization;
+
+fn is_unreserved(b: u8) -> bool {
+    b.is_ascii_alphanumeric() || matches!(b, b'-' | b'.' | b'_' | b'~')
+}
+
+/// ASCII characters that can not appear in an IRI and are percent-encoded on normalization.
+fn must_encode(c: char) -> bool {
+    c.is_ascii_control() || matches!(c, ' ' | '"' | '<' | '>' | '\\' | '^' | '`' | '{' | '|' | '}')
+}
+
+fn hex_value(b: u8) -> Option<u8> {
+    (b as char).to_digit(16).map(|v| v as u8)
+}
+
+fn normalize_percent_encoding(src: &str) -> String {
+    let bytes = src.as_bytes();
+    let mut res = String::with_capacity(src.len());
+    let mut i = 0;
+
+    while i < bytes.len() {
+        if bytes[i] == b'%' {
+            match (bytes.get(i + 1).and_then(|b| hex_value(*b)), bytes.get(i + 2).and_then(|b| hex_value(*b))) {
+                (Some(h), Some(l)) => {
+                    let b = h * 16 + l;
+                    if is_unreserved(b) {
+                        res.push(b as char);
+                    } else {
+                        res.push_str(&format!("%{:02X}", b));
+                    }
+                    i += 3;
+                },
+                _ => {
+                    res.push_str("%25");
+                    i += 1;
+                },
+            }
+            continue;
+        }
+
+        let c = src[i..].chars().next().unwrap_or_default();
+        if must_encode(c) {
+            res.push_str(&format!("%{:02X}", c as u8));
+        } else {
+            res.push(c);
+        }
+        i += c.len_utf8();
+    }
+    res
+}
+
+fn is_blank_label(label: &str) -> bool {
+    !label.is_empty() && !label.chars().any(|c| c.is_whitespace() || must_encode(c))
+}
+
+/// Returns the length of the scheme if `s` starts with `scheme ":"`.
+fn scheme_len(s: &str) -> Option<usize> {
+    let pos = s.find(':')?;
+    let scheme = &s[..pos];
+    let mut chars = scheme.chars();
+    if !chars.next()?.is_ascii_alphabetic() {
+        return None;
+    }
+    if chars.all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.')) {
+        Some(pos)
+    } else {
+        None
+    }
+}
+
+/// Only an absolute IRI (`scheme://`) is changed, the prefix of a prefixed name such as `V-S:Foo` is case sensitive.
+fn lowercase_scheme_and_host(s: &str) -> String {
+    let Some(slen) = scheme_len(s) else {
+        return s.to_owned();
+    };
+    let Some(after) = s[slen + 1..].strip_prefix("//") else {
+        return s.to_owned();
+    };
+
+    let mut res = s[..slen].to_ascii_lowercase();
+    res.push(':');
+
+    let auth_end = after.find(['/', '?', '#']).unwrap_or(after.len());
+    let authority = &after[..auth_end];
+    let host_start = authority.rfind('@').map(|p| p + 1).unwrap_or(0);
+    let host_end = if authority[host_start..].starts_with('[') {
+        authority[host_start..].find(']').map(|p| host_start + p + 1).unwrap_or(authority.len())
+    } else {
+        authority[host_start..].rfind(':').map(|p| host_start + p).unwrap_or(authority.len())
+    };
+
+    res.push_str("//");
+    res.push_str(&authority[..host_start]);
+    res.push_str(&authority[host_start..host_end].to_lowercase());
+    res.push_str(&authority[host_end..]);
+    res.push_str(&after[auth_end..]);
+    res
+}
+
+/// Normalizes an IRI (NFC, percent-encoding, lowercase scheme and host) and checks it is a valid IRI reference.
+/// Prefixed names such as `v-s:Document` are accepted and keep the case of the prefix. Blank node labels
+/// (`_:b0`) are returned unchanged.
+pub fn normalize_iri(s: &str) -> Result<String, String> {
+    if s.is_empty() {
+        return Err("empty iri".to_owned());
+    }
+    if let Some(label) = s.strip_prefix("_:") {
+        if !is_blank_label(label) {
+            return Err(format!("invalid blank node [{}]", s));
+        }
+        return Ok(s.to_owned());
+    }
+
+    let nfc: String = s.nfc().collect();
+    let res = lowercase_scheme_and_host(&normalize_percent_encoding(&nfc));
+
+    if IriReferenceStr::new(&res).is_err() {
+        return Err(format!("invalid iri [{}]", s));
+    }
+    Ok(res)
+}
+
+pub fn is_valid_iri(s: &str) -> bool {
+    match s.strip_prefix("_:") {
+        Some(label) => is_blank_label(label),
+        None => !s.is_empty() && IriReferenceStr::new(s).is_ok(),
+    }
+}
+
+/// `iri` as a prefixed name of the longest matching namespace of `all_prefixes`, as is otherwise.
+pub fn compact_iri(iri: &str, all_prefixes: &HashMap<String, String>) -> String {
+    let mut best: Option<(&str, &str)> = None;
+    for (prefix, ns) in all_prefixes {
+        if iri.starts_with(ns.as_str()) && best.is_none_or(|(_, b)| ns.len() > b.len()) {
+            best = Some((prefix, ns));
+        }
+    }
+    match best {
+        Some((prefix, ns)) => format!("{}:{}", prefix, &iri[ns.len()..]),
+        None => iri.to_owned(),
+    }
+}
@@ -0,0 +1,195 @@
+use crate::onto::individual::IndividualObj;
+use crate::onto::resource::Value;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, HashSet};
+use std::hash::{Hash, Hasher};
+
+pub const BLANK_NODE_PREFIX: &str = "_:";
+
+pub fn is_blank_node(uri: &str) -> bool {
+    uri.starts_with(BLANK_NODE_PREFIX)
+}
+
+/// (subject, predicate, object, object is a blank node), literals are kept as a canonical text of type and value.
+type Triple = (String, String, String, bool);
+
+struct Graph {
+    triples: Vec<Triple>,
+    blanks: Vec<String>,
+}
+
+impl Graph {
+    fn new(objs: &[IndividualObj]) -> Self {
+        let mut triples = Vec::new();
+        let mut blanks = HashSet::new();
+
+        for obj in objs.iter() {
+            if is_blank_node(&obj.uri) {
+                blanks.insert(obj.uri.to_owned());
+            }
+            for (predicate, values) in obj.resources.iter() {
+                for r in values.iter() {
+                    match &r.value {
+                        Value::Uri(u) if is_blank_node(u) => {
+                            blanks.insert(u.to_owned());
+                            triples.push((obj.uri.to_owned(), predicate.to_owned(), u.to_owned(), true));
+                        },
+                        v => {
+                            triples.push((obj.uri.to_owned(), predicate.to_owned(), format!("{:?}:{:?}", r.rtype, v), false));
+                        },
+                    }
+                }
+            }
+        }
+        triples.sort();
+        triples.dedup();
+
+        let mut blanks: Vec<String> = blanks.into_iter().collect();
+        blanks.sort();
+
+        Graph {
+            triples,
+            blanks,
+        }
+    }
+
+    fn term_color(&self, term: &str, is_blank: bool, colors: &HashMap<String, u64>) -> u64 {
+        if is_blank {
+            colors.get(term).copied().unwrap_or(0)
+        } else {
+            hash_of(&term)
+        }
+    }
+
+    /// One round of color refinement: a blank node color is derived from its colored neighbourhood.
+    fn refine(&self, colors: &HashMap<String, u64>) -> HashMap<String, u64> {
+        let mut signatures: HashMap<&str, Vec<(u8, &str, u64)>> = HashMap::new();
+        for (s, p, o, o_blank) in self.triples.iter() {
+            if is_blank_node(s) {
+                let c = self.term_color(o, *o_blank, colors);
+                signatures.entry(s.as_str()).or_default().push((0, p.as_str(), c));
+            }
+            if *o_blank {
+                let c = self.term_color(s, is_blank_node(s), colors);
+                signatures.entry(o.as_str()).or_default().push((1, p.as_str(), c));
+            }
+        }
+
+        let mut res = HashMap::new();
+        for b in self.blanks.iter() {
+            let mut sig = signatures.remove(b.as_str()).unwrap_or_default();
+            sig.sort();
+            res.insert(b.to_owned(), hash_of(&(colors.get(b).copied().unwrap_or(0), sig)));
+        }
+        res
+    }
+
+    fn mapped_triples(&self, mapping: &HashMap<&str, &str>) -> Vec<Triple> {
+        let map = |t: &String| -> String {
+            match mapping.get(t.as_str()) {
+                Some(m) => (*m).to_owned(),
+                None => t.to_owned(),
+            }
+        };
+        let mut res: Vec<Triple> = self
+            .triples
+            .iter()
+            .map(|(s, p, o, o_blank)| {
+                (
+                    map(s),
+                    p.to_owned(),
+                    if *o_blank {
+                        map(o)
+                    } else {
+                        o.to_owned()
+                    },
+                    *o_blank,
+                )
+            })
+            .collect();
+        res.sort();
+        res
+    }
+}
+
+fn hash_of<T: Hash>(v: &T) -> u64 {
+    let mut h = DefaultHasher::new();
+    v.hash(&mut h);
+    h.finish()
+}
+
+fn distinct(colors: &HashMap<String, u64>) -> usize {
+    colors.values().collect::<HashSet<_>>().len()
+}
+
+fn search<'a>(
+    a: &'a Graph,
+    b: &'a Graph,
+    order: &[&'a str],
+    candidates: &HashMap<&'a str, Vec<&'a str>>,
+    mapping: &mut HashMap<&'a str, &'a str>,
+    used: &mut HashSet<&'a str>,
+) -> bool {
+    let Some((x, rest)) = order.split_first() else {
+        return a.mapped_triples(mapping) == b.triples;
+    };
+
+    for y in candidates[x].iter() {
+        if used.contains(y) {
+            continue;
+        }
+        mapping.insert(x, y);
+        used.insert(y);
+        if search(a, b, rest, candidates, mapping, used) {
+            return true;
+        }
+        mapping.remove(x);
+        used.remove(y);
+    }
+    false
+}
+
+/// Compares two sets of individuals as RDF graphs, blank nodes (uris starting with `_:`) may be renamed.
+/// Value order inside a predicate is ignored.
+pub fn isomorphic(a: &[IndividualObj], b: &[IndividualObj]) -> bool {
+    let ga = Graph::new(a);
+    let gb = Graph::new(b);
+
+    if ga.triples.len() != gb.triples.len() || ga.blanks.len() != gb.blanks.len() {
+        return false;
+    }
+    if ga.blanks.is_empty() {
+        return ga.triples == gb.triples;
+    }
+
+    let mut ca: HashMap<String, u64> = ga.blanks.iter().map(|x| (x.to_owned(), 0)).collect();
+    let mut cb: HashMap<String, u64> = gb.blanks.iter().map(|x| (x.to_owned(), 0)).collect();
+    loop {
+        let na = ga.refine(&ca);
+        let nb = gb.refine(&cb);
+        let stable = distinct(&na) == distinct(&ca) && distinct(&nb) == distinct(&cb);
+        ca = na;
+        cb = nb;
+        if stable {
+            break;
+        }
+    }
+
+    let mut sa: Vec<u64> = ca.values().copied().collect();
+    let mut sb: Vec<u64> = cb.values().copied().collect();
+    sa.sort_unstable();
+    sb.sort_unstable();
+    if sa != sb {
+        return false;
+    }
+
+    let mut candidates: HashMap<&str, Vec<&str>> = HashMap::new();
+    for x in ga.blanks.iter() {
+        candidates.insert(x.as_str(), gb.blanks.iter().filter(|y| cb[*y] == ca[x]).map(|y| y.as_str()).collect());
+    }
+
+    let mut order: Vec<&str> = ga.blanks.iter().map(|x| x.as_str()).collect();
+    order.sort_by_key(|x| (candidates[x].len(), *x));
+
+    search(&ga, &gb, &order, &candidates, &mut HashMap::new(), &mut HashSet::new())
+}
@@ -0,0 +1,137 @@
+use crate::onto::dump::{read_record, write_record};
+use crate::onto::individual_store::IndividualStoreInMem;
+use crate::onto::op::IndividualOp;
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufReader, BufWriter, Error, ErrorKind, Read, Write};
+use std::path::{Path, PathBuf};
+
+pub const SNAPSHOT_FILE: &str = "snapshot.bin";
+pub const JOURNAL_FILE: &str = "journal.log";
+
+/// Durable storage for an `IndividualStoreInMem` in a directory: a snapshot of all individuals
+/// (`write_msgpack` format) and a journal of the ops applied after it (u32 big-endian length + `IndividualOp::to_msgpack`).
+/// All ops are idempotent, so replaying ops already contained in the snapshot is harmless.
+pub struct Journal {
+    dir: PathBuf,
+    log: File,
+    snapshot_every: usize,
+    since_snapshot: usize,
+    sync: bool,
+}
+
+/// A record cut short by a crash ends the journal.
+fn read_op_record<R: Read>(src: &mut R) -> Result<Option<Vec<u8>>, Error> {
+    match read_record(src) {
+        Err(e) if e.kind() == ErrorKind::UnexpectedEof => Ok(None),
+        res => res,
+    }
+}
+
+impl Journal {
+    /// Opens the journal in `dir`, the directory is created if needed.
+    pub fn open<P: AsRef<Path>>(dir: P) -> Result<Journal, Error> {
+        let dir = dir.as_ref().to_path_buf();
+        fs::create_dir_all(&dir)?;
+        let log = OpenOptions::new().create(true).append(true).open(dir.join(JOURNAL_FILE))?;
+
+        Ok(Journal {
+            dir,
+            log,
+            snapshot_every: 0,
+            since_snapshot: 0,
+            sync: false,
+        })
+    }
+
+    /// Write a snapshot from `apply` after every `n` ops, 0 (default) disables automatic snapshots.
+    pub fn with_snapshot_every(mut self, n: usize) -> Self {
+        self.snapshot_every = n;
+        self
+    }
+
+    /// Flush every appended op to disk (fsync), slower but survives power loss.
+    pub fn with_sync(mut self, sync: bool) -> Self {
+        self.sync = sync;
+        self
+    }
+
+    pub fn get_dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Ops appended since the last snapshot.
+    pub fn pending(&self) -> usize {
+        self.since_snapshot
+    }
+
+    pub fn append(&mut self, op: &IndividualOp) -> Result<(), Error> {
+        let mut buf = Vec::new();
+        op.to_msgpack(&mut buf)?;
+
+        // one write, so a crash leaves at most a cut-off record at the end
+        let mut record = Vec::with_capacity(buf.len() + 4);
+        write_record(&mut record, &buf)?;
+        self.log.write_all(&record)?;
+        if self.sync {
+            self.log.sync_data()?;
+        }
+        self.since_snapshot += 1;
+        Ok(())
+    }
+
+    /// Journals the op, then applies it to the store; writes a snapshot when one is due.
+    pub fn apply(&mut self, store: &mut IndividualStoreInMem, op: &IndividualOp) -> Result<(), Error> {
+        self.append(op)?;
+        store.apply_op(op).map_err(|e| Error::new(ErrorKind::InvalidData, e))?;
+
+        if self.snapshot_every > 0 && self.since_snapshot >= self.snapshot_every {
+            self.snapshot(store)?;
+        }
+        Ok(())
+    }
+
+    /// Replaces the snapshot with the content of the store and empties the journal.
+    pub fn snapshot(&mut self, store: &IndividualStoreInMem) -> Result<(), Error> {
+        let tmp = self.dir.join(format!("{}.tmp", SNAPSHOT_FILE));
+        {
+            let mut out = BufWriter::new(File::create(&tmp)?);
+            store.write_msgpack(&mut out)?;
+            out.into_inner().map_err(|e| e.into_error())?.sync_all()?;
+        }
+        fs::rename(&tmp, self.dir.join(SNAPSHOT_FILE))?;
+
+        self.log.set_len(0)?;
+        self.log.sync_all()?;
+        self.since_snapshot = 0;
+        Ok(())
+    }
+
+    /// Loads the snapshot and the journaled ops into the store, returns the number of replayed ops.
+    /// A record cut off by a crash at the end of the journal is dropped.
+    pub fn replay(&mut self, store: &mut IndividualStoreInMem) -> Result<usize, Error> {
+        match File::open(self.dir.join(SNAPSHOT_FILE)) {
+            Ok(f) => {
+                store.read_raw(&mut BufReader::new(f))?;
+            },
+            Err(e) if e.kind() == ErrorKind::NotFound => {},
+            Err(e) => return Err(e),
+        }
+
+        let mut src = BufReader::new(File::open(self.dir.join(JOURNAL_FILE))?);
+        let mut count = 0;
+        let mut valid_len = 0u64;
+        while let Some(data) = read_op_record(&mut src)? {
+            let op = IndividualOp::from_msgpack(&data).map_err(|e| Error::new(ErrorKind::InvalidData, format!("journal record {}: {}", count, e)))?;
+            store.apply_op(&op).map_err(|e| Error::new(ErrorKind::InvalidData, e))?;
+            valid_len += 4 + data.len() as u64;
+            count += 1;
+        }
+
+        if valid_len < self.log.metadata()?.len() {
+            warn!("journal {:?}: drop incomplete record after {} ops", self.dir, count);
+            self.log.set_len(valid_len)?;
+        }
+        self.since_snapshot = count;
+        Ok(count)
+    }
+}
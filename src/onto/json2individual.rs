@@ -1,10 +1,66 @@
 use crate::onto::datatype::{DataType, Lang};
-use crate::onto::individual::Individual;
+use crate::onto::datatype_registry::DatatypeRegistry;
+use crate::onto::individual::{Individual, IndividualObj};
+use crate::onto::normalize::NormalizePolicy;
+use serde::de::{DeserializeSeed, Deserializer, IgnoredAny, MapAccess, SeqAccess, Visitor};
 use serde_json::value::Value as JSONValue;
 use serde_json::Map;
+use std::fmt;
+use std::io;
+
+/// Everything that was wrong with a JSON individual, in document order.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JsonError {
+    pub errors: Vec<String>,
+}
+
+impl fmt::Display for JsonError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "invalid json individual: {}", self.errors.join("; "))
+    }
+}
+
+impl std::error::Error for JsonError {}
 
 pub fn parse_json_to_individual(src: &JSONValue, dest: &mut Individual) -> bool {
-    let mut res = true;
+    log_errors(parse_json(src, dest, None))
+}
+
+/// Reads a JSON document. With the `simd` feature simd-json tokenizes it; a document simd-json
+/// refuses is read again by serde_json, which gives the error of an invalid one.
+pub fn json_from_slice(data: &[u8]) -> Result<JSONValue, serde_json::Error> {
+    #[cfg(feature = "simd")]
+    {
+        // simd-json parses in place
+        let mut buf = data.to_vec();
+        if let Ok(v) = simd_json::serde::from_slice::<JSONValue>(&mut buf) {
+            return Ok(v);
+        }
+    }
+    serde_json::from_slice(data)
+}
+
+/// Same as `parse_json_to_individual` on the text of a JSON individual, see `json_from_slice`.
+pub fn parse_json_slice_to_individual(data: &[u8], dest: &mut Individual) -> bool {
+    match json_from_slice(data) {
+        Ok(src) => parse_json_to_individual(&src, dest),
+        Err(e) => log_errors(vec![e.to_string()]),
+    }
+}
+
+fn log_errors(errors: Vec<String>) -> bool {
+    for e in &errors {
+        error!("json->individual: {}", e);
+    }
+    errors.is_empty()
+}
+
+fn parse_json(src: &JSONValue, dest: &mut Individual, registry: Option<&DatatypeRegistry>) -> Vec<String> {
+    dest.unobserved(|dest| parse_json_obj(src, dest, registry))
+}
+
+fn parse_json_obj(src: &JSONValue, dest: &mut Individual, registry: Option<&DatatypeRegistry>) -> Vec<String> {
+    let mut errors = vec![];
 
     if let Some(props) = src.as_object() {
         for (key, value) in props.iter() {
@@ -12,25 +68,202 @@ pub fn parse_json_to_individual(src: &JSONValue, dest: &mut Individual) -> bool
                 if let Some(id) = value.as_str() {
                     dest.set_id(id);
                 } else {
-                    error!("json->individual: fail get id");
-                    res = false;
+                    errors.push("fail get id".to_owned());
                 }
             } else if let Some(values) = value.as_array() {
-                if !json_to_predicate(key.trim(), values, dest) {
-                    res = false;
-                }
+                json_to_predicate(key.trim(), values, dest, registry, &mut errors);
             } else {
-                error!("json->individual: predicate [{}] must contain an array of values", key);
-                res = false;
+                errors.push(format!("predicate [{}] must contain an array of values", key));
             }
         }
     } else {
-        res = false;
+        errors.push("expected an object".to_owned());
     }
 
+    errors
+}
+
+/// Parses and normalizes string values with the given policy.
+pub fn parse_json_to_individual_with(src: &JSONValue, dest: &mut Individual, policy: &NormalizePolicy) -> bool {
+    let res = parse_json_to_individual(src, dest);
+    dest.normalize_strings(policy);
     res
 }
 
+/// Also accepts values typed with datatypes of the registry (`{"data": "+7 900 000-00-00", "type": "v-s:PhoneNumber"}`),
+/// parsed from their lexical form, and checks values of bound predicates; invalid values are logged and fail the parse.
+pub fn parse_json_to_individual_typed(src: &JSONValue, dest: &mut Individual, registry: &DatatypeRegistry) -> bool {
+    let mut errors = parse_json(src, dest, Some(registry));
+    errors.extend(dest.validate_datatypes(registry).iter().map(|e| e.to_string()));
+    log_errors(errors)
+}
+
+/// Same as `parse_json_to_individual`, but any problem fails the conversion and is kept in the error.
+impl TryFrom<&JSONValue> for IndividualObj {
+    type Error = JsonError;
+
+    fn try_from(src: &JSONValue) -> Result<Self, Self::Error> {
+        let mut indv = Individual::default();
+        let errors = parse_json(src, &mut indv, None);
+        if errors.is_empty() {
+            Ok(indv.into_obj())
+        } else {
+            Err(JsonError {
+                errors,
+            })
+        }
+    }
+}
+
+impl TryFrom<JSONValue> for IndividualObj {
+    type Error = JsonError;
+
+    fn try_from(src: JSONValue) -> Result<Self, Self::Error> {
+        IndividualObj::try_from(&src)
+    }
+}
+
+/// Reads a JSON individual from `rd` as it arrives: only one value is held as `serde_json::Value`
+/// at a time and is added to the individual before the next one is read, so a predicate with
+/// a hundred thousand values does not need the whole document in memory. Wrap unbuffered
+/// readers in a `BufReader`. Fails on a syntax error, or with every problem of the individual.
+pub fn parse_json_reader_to_individual<R: io::Read>(rd: R, dest: &mut Individual) -> Result<(), JsonError> {
+    let mut errors = vec![];
+    let mut de = serde_json::Deserializer::from_reader(rd);
+    let res = dest.unobserved(|dest| {
+        IndividualSeed {
+            dest,
+            errors: &mut errors,
+        }
+        .deserialize(&mut de)
+        .and_then(|_| de.end())
+    });
+    if let Err(e) = res {
+        errors.push(e.to_string());
+    }
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(JsonError {
+            errors,
+        })
+    }
+}
+
+struct IndividualSeed<'a> {
+    dest: &'a mut Individual,
+    errors: &'a mut Vec<String>,
+}
+
+impl<'de> DeserializeSeed<'de> for IndividualSeed<'_> {
+    type Value = ();
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<(), D::Error> {
+        deserializer.deserialize_map(self)
+    }
+}
+
+impl<'de> Visitor<'de> for IndividualSeed<'_> {
+    type Value = ();
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "an object")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<(), A::Error> {
+        while let Some(key) = map.next_key::<String>()? {
+            if key == "@" {
+                match map.next_value::<JSONValue>()? {
+                    JSONValue::String(id) => {
+                        self.dest.set_id(&id);
+                    },
+                    _ => self.errors.push("fail get id".to_owned()),
+                }
+            } else {
+                map.next_value_seed(PredicateSeed {
+                    predicate: key.trim(),
+                    dest: self.dest,
+                    errors: self.errors,
+                })?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The values of a predicate, added one by one.
+struct PredicateSeed<'a> {
+    predicate: &'a str,
+    dest: &'a mut Individual,
+    errors: &'a mut Vec<String>,
+}
+
+impl PredicateSeed<'_> {
+    fn not_array(self) {
+        self.errors.push(format!("predicate [{}] must contain an array of values", self.predicate));
+    }
+}
+
+impl<'de> DeserializeSeed<'de> for PredicateSeed<'_> {
+    type Value = ();
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<(), D::Error> {
+        deserializer.deserialize_any(self)
+    }
+}
+
+impl<'de> Visitor<'de> for PredicateSeed<'_> {
+    type Value = ();
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "an array of values")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<(), A::Error> {
+        while let Some(value) = seq.next_element::<JSONValue>()? {
+            json_to_predicate(self.predicate, std::slice::from_ref(&value), self.dest, None, self.errors);
+        }
+        Ok(())
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<(), A::Error> {
+        while map.next_entry::<IgnoredAny, IgnoredAny>()?.is_some() {}
+        self.not_array();
+        Ok(())
+    }
+
+    fn visit_str<E>(self, _v: &str) -> Result<(), E> {
+        self.not_array();
+        Ok(())
+    }
+
+    fn visit_bool<E>(self, _v: bool) -> Result<(), E> {
+        self.not_array();
+        Ok(())
+    }
+
+    fn visit_i64<E>(self, _v: i64) -> Result<(), E> {
+        self.not_array();
+        Ok(())
+    }
+
+    fn visit_u64<E>(self, _v: u64) -> Result<(), E> {
+        self.not_array();
+        Ok(())
+    }
+
+    fn visit_f64<E>(self, _v: f64) -> Result<(), E> {
+        self.not_array();
+        Ok(())
+    }
+
+    fn visit_unit<E>(self) -> Result<(), E> {
+        self.not_array();
+        Ok(())
+    }
+}
+
 fn get_datatype_from_json(val: Option<&JSONValue>) -> Result<DataType, String> {
     if val.is_none() {
         return Err("not content field type".to_owned());
@@ -74,20 +307,26 @@ fn add_string(v: &Map<String, JSONValue>, vdata: &JSONValue, predicate: &str, de
     }
 }
 
-fn json_to_predicate(predicate: &str, values: &[JSONValue], dest: &mut Individual) -> bool {
-    let mut res = true;
+fn json_to_predicate(predicate: &str, values: &[JSONValue], dest: &mut Individual, registry: Option<&DatatypeRegistry>, errors: &mut Vec<String>) {
     for val in values {
         if let Some(v) = val.as_object() {
             let vdata = v.get("data");
             if vdata.is_none() {
-                error!("json->individual: predicate [{}], value must contain [data]", predicate);
-                res = false;
+                errors.push(format!("predicate [{}], value must contain [data]", predicate));
+                continue;
+            }
+            if let Some(def) = v.get("type").and_then(|t| t.as_str()).filter(|t| DataType::new_from_str(t).is_none()).and_then(|t| registry?.get(t)) {
+                match vdata.and_then(|d| d.as_str()).ok_or_else(|| "expected a string".to_owned()).and_then(|s| def.parse(s)) {
+                    Ok(r) => dest.add_resources(predicate, &[r]),
+                    Err(e) => {
+                        errors.push(format!("predicate [{}], invalid {}: {}", predicate, def.iri(), e));
+                    },
+                }
                 continue;
             }
             let ptype = get_datatype_from_json(v.get("type"));
             if ptype.is_err() {
-                error!("json->individual: predicate [{}], invalid value", predicate);
-                res = false;
+                errors.push(format!("predicate [{}], invalid value", predicate));
                 continue;
             }
             let vdata = vdata.unwrap();
@@ -95,7 +334,9 @@ fn json_to_predicate(predicate: &str, values: &[JSONValue], dest: &mut Individua
             match ptype.unwrap() {
                 DataType::Uri => {
                     if let Some(v) = vdata.as_str() {
-                        dest.add_uri(predicate, v);
+                        if let Err(e) = dest.add_uri(predicate, v) {
+                            errors.push(format!("predicate [{}], {}", predicate, e));
+                        }
                     }
                 },
                 DataType::String => add_string(v, vdata, predicate, dest),
@@ -144,10 +385,7 @@ fn json_to_predicate(predicate: &str, values: &[JSONValue], dest: &mut Individua
                 },
             }
         } else {
-            error!("json->individual: value for predicate [{}] must contain map", predicate);
-            res = false;
+            errors.push(format!("value for predicate [{}] must contain map", predicate));
         }
     }
-
-    res
 }
@@ -0,0 +1,150 @@
+use crate::onto::graphql::camel_case;
+use crate::onto::individual::IndividualObj;
+use crate::onto::individual2json::JsonOptions;
+use crate::onto::json2individual::JsonError;
+use serde_json::value::Value as JSONValue;
+use serde_json::Map;
+use std::collections::HashMap;
+
+/// Renames predicates to the keys of a public API and back, so JSON of external APIs does not
+/// carry internal prefixes:
+///
+/// ```text
+/// {"@": "d:doc1", "v-s:created": [...]}  <->  {"id": "d:doc1", "created": [...]}
+/// ```
+///
+/// Aliases go first, then prefixes are stripped and local names camelCased. Keys are read back
+/// through the aliases and the vocabulary; other keys without a prefix get the first stripped
+/// prefix, so names that change under camelCase round-trip only through the vocabulary.
+#[derive(Debug, Clone, Default)]
+pub struct KeyTransform {
+    strip_prefixes: Vec<String>,
+    camel_case: bool,
+    /// predicate -> key
+    aliases: HashMap<String, String>,
+    vocabulary: Vec<String>,
+    /// key -> predicate, of the aliases and the vocabulary
+    reverse: HashMap<String, String>,
+}
+
+impl KeyTransform {
+    pub fn new() -> Self {
+        KeyTransform::default()
+    }
+
+    /// `v-s:created` -> `created`.
+    pub fn with_strip_prefix(mut self, prefix: &str) -> Self {
+        self.strip_prefixes.push(prefix.trim_end_matches(':').to_owned());
+        self.rebuild();
+        self
+    }
+
+    /// `v-s:created_by` -> `v-s:createdBy`, see `graphql::camel_case`.
+    pub fn with_camel_case(mut self, camel_case: bool) -> Self {
+        self.camel_case = camel_case;
+        self.rebuild();
+        self
+    }
+
+    /// A fixed key of a predicate, `@` is the uri.
+    pub fn with_alias(mut self, predicate: &str, key: &str) -> Self {
+        self.aliases.insert(predicate.to_owned(), key.to_owned());
+        self.rebuild();
+        self
+    }
+
+    /// Predicates whose keys are read back exactly, e.g. those of the ontology.
+    pub fn with_vocabulary<'a, I>(mut self, predicates: I) -> Self
+    where
+        I: IntoIterator<Item = &'a str>,
+    {
+        self.vocabulary.extend(predicates.into_iter().map(|p| p.to_owned()));
+        self.rebuild();
+        self
+    }
+
+    fn rebuild(&mut self) {
+        let mut reverse = HashMap::new();
+        for p in self.vocabulary.iter() {
+            let key = self.key_of(p);
+            if let Some(other) = reverse.insert(key.to_owned(), p.to_owned()) {
+                if other != *p {
+                    warn!("key transform: [{}] and [{}] have the same key [{}]", other, p, key);
+                }
+            }
+        }
+        for (p, key) in self.aliases.iter() {
+            reverse.insert(key.to_owned(), p.to_owned());
+        }
+        self.reverse = reverse;
+    }
+
+    pub fn key_of(&self, predicate: &str) -> String {
+        if let Some(key) = self.aliases.get(predicate) {
+            return key.to_owned();
+        }
+        if predicate == "@" {
+            return predicate.to_owned();
+        }
+        let (prefix, local) = match predicate.split_once(':') {
+            // full IRIs are kept
+            Some((_, l)) if l.starts_with("//") => return predicate.to_owned(),
+            Some((p, l)) => (Some(p), l),
+            None => (None, predicate),
+        };
+
+        let local = if self.camel_case {
+            camel_case(local)
+        } else {
+            local.to_owned()
+        };
+        match prefix {
+            Some(p) if !self.strip_prefixes.iter().any(|s| s == p) => format!("{}:{}", p, local),
+            _ => local,
+        }
+    }
+
+    pub fn predicate_of(&self, key: &str) -> String {
+        if let Some(p) = self.reverse.get(key) {
+            return p.to_owned();
+        }
+        match self.strip_prefixes.first() {
+            Some(prefix) if key != "@" && !key.contains(':') => format!("{}:{}", prefix, key),
+            _ => key.to_owned(),
+        }
+    }
+
+    /// Renames the predicates of a JSON individual to keys; of predicates with the same key only
+    /// the last is kept.
+    pub fn to_external(&self, src: &JSONValue) -> JSONValue {
+        let Some(props) = src.as_object() else {
+            return src.to_owned();
+        };
+        let mut res = Map::new();
+        for (predicate, v) in props {
+            let key = self.key_of(predicate);
+            if res.insert(key.to_owned(), v.to_owned()).is_some() {
+                warn!("key transform: more than one predicate has the key [{}]", key);
+            }
+        }
+        JSONValue::Object(res)
+    }
+
+    /// Renames the keys of a JSON individual back to predicates.
+    pub fn to_internal(&self, src: &JSONValue) -> JSONValue {
+        match src.as_object() {
+            Some(props) => JSONValue::Object(props.iter().map(|(key, v)| (self.predicate_of(key), v.to_owned())).collect()),
+            None => src.to_owned(),
+        }
+    }
+
+    /// `as_json_opts` with keys.
+    pub fn format(&self, obj: &IndividualObj, opts: JsonOptions) -> JSONValue {
+        self.to_external(&obj.as_json_opts(opts))
+    }
+
+    /// The inverse of `format`, see `TryFrom<&JSONValue> for IndividualObj`.
+    pub fn parse(&self, src: &JSONValue) -> Result<IndividualObj, JsonError> {
+        IndividualObj::try_from(&self.to_internal(src))
+    }
+}
@@ -0,0 +1,92 @@
+use crate::onto::datatype::Lang;
+use crate::onto::datatype_registry::DatatypeRegistry;
+use crate::onto::decimal::DecimalConfig;
+use crate::onto::individual::IndividualObj;
+use crate::onto::iri::{compact_iri, normalize_iri};
+use crate::onto::json2individual::JsonError;
+use crate::onto::resource::Resource;
+use base64::{engine::general_purpose, Engine as _};
+use serde_json::value::Value as JSONValue;
+use std::collections::HashMap;
+
+fn value_to_resource(v: &JSONValue, all_prefixes: &HashMap<String, String>) -> Result<Resource, String> {
+    match v {
+        JSONValue::String(s) => Ok(Resource::new_string(s, Lang::none())),
+        JSONValue::Bool(b) => Ok(Resource::new_bool(*b)),
+        JSONValue::Number(n) => match n.as_i64() {
+            Some(i) => Ok(Resource::new_integer(i)),
+            None => {
+                let (m, e) = DecimalConfig::default().from_f64(n.as_f64().unwrap_or_default()).map_err(|e| e.to_string())?;
+                Ok(Resource::new_decimal(m, e))
+            },
+        },
+        JSONValue::Object(o) => {
+            if let Some(id) = o.get("@id") {
+                return match id.as_str() {
+                    Some(id) => normalize_iri(&compact_iri(id, all_prefixes)).map(|id| Resource::new_uri(&id)),
+                    None => Err("[@id] must be a string".to_owned()),
+                };
+            }
+            let Some(value) = o.get("@value") else {
+                return Err("expected [@id] or [@value]".to_owned());
+            };
+            match (o.get("@type").and_then(|t| t.as_str()), o.get("@language").and_then(|l| l.as_str()), value) {
+                (Some(t), _, JSONValue::String(s)) if t == "xsd:base64Binary" || t == "http://www.w3.org/2001/XMLSchema#base64Binary" => {
+                    general_purpose::STANDARD.decode(s).map(Resource::new_binary).map_err(|e| e.to_string())
+                },
+                (Some(t), _, JSONValue::String(s)) => DatatypeRegistry::builtin().parse(t, s),
+                (None, Some(lang), JSONValue::String(s)) => Ok(Resource::new_string(s, Lang::new_from_str(lang))),
+                (None, None, v) if !v.is_object() && !v.is_array() => value_to_resource(v, all_prefixes),
+                _ => Err("invalid [@value]".to_owned()),
+            }
+        },
+        _ => Err("expected a value".to_owned()),
+    }
+}
+
+/// Reads a JSON-LD node in the form `to_json_ld` writes: `@id` is the uri, `@type` the values
+/// of `rdf:type`, a predicate has one value or an array of them. Full IRIs in the namespaces of
+/// `all_prefixes` become prefixed names, `@context` is not applied, prefixed names are kept as they are.
+pub fn from_json_ld(src: &JSONValue, all_prefixes: &HashMap<String, String>) -> Result<IndividualObj, JsonError> {
+    let mut errors = vec![];
+    let mut obj = IndividualObj::default();
+
+    if let Some(props) = src.as_object() {
+        for (key, value) in props.iter() {
+            match key.as_str() {
+                "@context" => {},
+                "@id" => match value.as_str() {
+                    Some(id) => obj.uri = compact_iri(id, all_prefixes),
+                    None => errors.push("fail get id".to_owned()),
+                },
+                _ => {
+                    let (predicate, as_type) = match key.as_str() {
+                        "@type" => ("rdf:type".to_owned(), true),
+                        _ => (compact_iri(key.trim(), all_prefixes), false),
+                    };
+                    for v in value.as_array().map(|a| a.as_slice()).unwrap_or(std::slice::from_ref(value)) {
+                        let r = match v.as_str() {
+                            Some(t) if as_type => normalize_iri(&compact_iri(t, all_prefixes)).map(|t| Resource::new_uri(&t)),
+                            _ if as_type => Err("[@type] must contain iris".to_owned()),
+                            _ => value_to_resource(v, all_prefixes),
+                        };
+                        match r {
+                            Ok(r) => obj.add_resources(&predicate, &[r]),
+                            Err(e) => errors.push(format!("predicate [{}], {}", predicate, e)),
+                        }
+                    }
+                },
+            }
+        }
+    } else {
+        errors.push("expected an object".to_owned());
+    }
+
+    if errors.is_empty() {
+        Ok(obj)
+    } else {
+        Err(JsonError {
+            errors,
+        })
+    }
+}
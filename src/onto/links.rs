@@ -0,0 +1,273 @@
+use crate::onto::individual::{Individual, IndividualObj};
+use crate::onto::resource::Value;
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap, HashSet};
+use std::fmt;
+
+impl IndividualObj {
+    /// Every `Value::Uri` of the individual as (predicate, uri), in predicate and value order.
+    pub fn collect_links(&self) -> Vec<(&str, &str)> {
+        let mut res = vec![];
+        for (predicate, values) in &self.resources {
+            for r in values {
+                if let Value::Uri(u) = &r.value {
+                    res.push((predicate.as_str(), u.as_str()));
+                }
+            }
+        }
+        res
+    }
+}
+
+impl Individual {
+    pub fn collect_links(&mut self) -> Vec<(&str, &str)> {
+        self.parse_all();
+        self.obj.collect_links()
+    }
+}
+
+/// Outgoing references of each individual: uri -> distinct uris it links to, in first-seen order.
+/// Every individual of the set is a key, also without links; targets may be outside the set.
+pub fn link_graph<'a, I>(indvs: I) -> HashMap<&'a str, Vec<&'a str>>
+where
+    I: IntoIterator<Item = &'a IndividualObj>,
+{
+    let mut graph: HashMap<&str, Vec<&str>> = HashMap::new();
+    for obj in indvs {
+        let targets = graph.entry(obj.uri.as_str()).or_default();
+        for (_, uri) in obj.collect_links() {
+            if !targets.contains(&uri) {
+                targets.push(uri);
+            }
+        }
+    }
+    graph
+}
+
+/// A `Value::Uri` naming an individual that is not in the checked set.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BrokenLink {
+    pub source: String,
+    pub predicate: String,
+    pub target: String,
+}
+
+impl fmt::Display for BrokenLink {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} {}: {} is missing", self.source, self.predicate, self.target)
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BrokenLinksReport {
+    /// In the order the individuals were added.
+    pub broken: Vec<BrokenLink>,
+}
+
+impl BrokenLinksReport {
+    pub fn is_ok(&self) -> bool {
+        self.broken.is_empty()
+    }
+}
+
+impl fmt::Display for BrokenLinksReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for link in self.broken.iter() {
+            writeln!(f, "{}", link)?;
+        }
+        Ok(())
+    }
+}
+
+/// Collects the individuals of a dump one by one and reports references to uris that none of them has.
+/// Targets in external namespaces (`rdf:`, `rdfs:`, `owl:`, `xsd:` by default) are not checked.
+#[derive(Debug)]
+pub struct LinkChecker {
+    known: HashSet<String>,
+    links: Vec<BrokenLink>,
+    external: Vec<String>,
+}
+
+impl Default for LinkChecker {
+    fn default() -> Self {
+        LinkChecker {
+            known: HashSet::new(),
+            links: Vec::new(),
+            external: ["rdf:", "rdfs:", "owl:", "xsd:"].iter().map(|p| (*p).to_owned()).collect(),
+        }
+    }
+}
+
+impl LinkChecker {
+    pub fn new() -> Self {
+        LinkChecker::default()
+    }
+
+    pub fn with_external_prefix(mut self, prefix: &str) -> Self {
+        self.external.push(prefix.to_owned());
+        self
+    }
+
+    pub fn add(&mut self, obj: &IndividualObj) {
+        self.known.insert(obj.uri.to_owned());
+        for (predicate, target) in obj.collect_links() {
+            if self.external.iter().any(|p| target.starts_with(p.as_str())) {
+                continue;
+            }
+            self.links.push(BrokenLink {
+                source: obj.uri.to_owned(),
+                predicate: predicate.to_owned(),
+                target: target.to_owned(),
+            });
+        }
+    }
+
+    pub fn report(&self) -> BrokenLinksReport {
+        BrokenLinksReport {
+            broken: self.links.iter().filter(|l| !self.known.contains(&l.target)).cloned().collect(),
+        }
+    }
+}
+
+/// `LinkChecker` over a whole set, `external` adds namespaces to the default ones.
+pub fn check_links<'a, I>(indvs: I, external: &[&str]) -> BrokenLinksReport
+where
+    I: IntoIterator<Item = &'a IndividualObj>,
+{
+    let mut checker = external.iter().fold(LinkChecker::new(), |c, p| c.with_external_prefix(p));
+    for obj in indvs {
+        checker.add(obj);
+    }
+    checker.report()
+}
+
+/// Order of a set for single-pass loaders, see `export_order`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ExportOrder {
+    /// Positions in the input, referenced individuals before the ones referencing them.
+    pub order: Vec<usize>,
+    /// Uris of the individuals referencing each other in a loop, in input order; each cycle is
+    /// emitted as a block in input order once everything it references outside the cycle is.
+    pub cycles: Vec<Vec<String>>,
+}
+
+impl ExportOrder {
+    pub fn is_acyclic(&self) -> bool {
+        self.cycles.is_empty()
+    }
+}
+
+/// Orders a set so that every individual comes after the individuals of the set it references;
+/// otherwise the input order is kept. References outside the set and to the individual itself are
+/// ignored, a uri given twice is referenced through its first occurrence.
+pub fn export_order<'a, I>(indvs: I) -> ExportOrder
+where
+    I: IntoIterator<Item = &'a IndividualObj>,
+{
+    let objs: Vec<&IndividualObj> = indvs.into_iter().collect();
+    let mut pos: HashMap<&str, usize> = HashMap::new();
+    for (i, obj) in objs.iter().enumerate() {
+        pos.entry(obj.uri.as_str()).or_insert(i);
+    }
+    let deps: Vec<Vec<usize>> = objs
+        .iter()
+        .enumerate()
+        .map(|(i, obj)| {
+            let mut d = vec![];
+            for (_, uri) in obj.collect_links() {
+                if let Some(&j) = pos.get(uri) {
+                    if j != i && !d.contains(&j) {
+                        d.push(j);
+                    }
+                }
+            }
+            d
+        })
+        .collect();
+
+    let comp = strongly_connected(&deps);
+    let ncomp = comp.iter().max().map_or(0, |c| c + 1);
+    let mut members: Vec<Vec<usize>> = vec![vec![]; ncomp];
+    for (i, c) in comp.iter().enumerate() {
+        members[*c].push(i);
+    }
+
+    // Kahn over the components, the ready one with the earliest member goes first
+    let mut pending = vec![0usize; ncomp];
+    let mut dependents: Vec<Vec<usize>> = vec![vec![]; ncomp];
+    for (i, d) in deps.iter().enumerate() {
+        for j in d {
+            if comp[i] != comp[*j] {
+                pending[comp[i]] += 1;
+                dependents[comp[*j]].push(comp[i]);
+            }
+        }
+    }
+    let mut ready: BinaryHeap<Reverse<(usize, usize)>> = (0..ncomp).filter(|c| pending[*c] == 0).map(|c| Reverse((members[c][0], c))).collect();
+
+    let mut res = ExportOrder::default();
+    while let Some(Reverse((_, c))) = ready.pop() {
+        if members[c].len() > 1 {
+            res.cycles.push(members[c].iter().map(|i| objs[*i].uri.to_owned()).collect());
+        }
+        res.order.extend_from_slice(&members[c]);
+        for d in &dependents[c] {
+            pending[*d] -= 1;
+            if pending[*d] == 0 {
+                ready.push(Reverse((members[*d][0], *d)));
+            }
+        }
+    }
+    res.cycles.sort_by_key(|uris| pos[uris[0].as_str()]);
+    res
+}
+
+/// Tarjan's algorithm without recursion, long reference chains are common in dumps. Returns the
+/// component of each node.
+fn strongly_connected(edges: &[Vec<usize>]) -> Vec<usize> {
+    const NONE: usize = usize::MAX;
+    let n = edges.len();
+    let (mut index, mut low, mut comp) = (vec![NONE; n], vec![0; n], vec![NONE; n]);
+    let mut on_stack = vec![false; n];
+    let (mut stack, mut work) = (vec![], vec![]);
+    let (mut next, mut ncomp) = (0, 0);
+
+    for root in 0..n {
+        if index[root] != NONE {
+            continue;
+        }
+        work.push((root, 0));
+        while let Some((v, i)) = work.pop() {
+            if i == 0 {
+                index[v] = next;
+                low[v] = next;
+                next += 1;
+                stack.push(v);
+                on_stack[v] = true;
+            }
+            if let Some(&w) = edges[v].get(i) {
+                work.push((v, i + 1));
+                if index[w] == NONE {
+                    work.push((w, 0));
+                } else if on_stack[w] {
+                    low[v] = low[v].min(index[w]);
+                }
+                continue;
+            }
+            if low[v] == index[v] {
+                while let Some(w) = stack.pop() {
+                    on_stack[w] = false;
+                    comp[w] = ncomp;
+                    if w == v {
+                        break;
+                    }
+                }
+                ncomp += 1;
+            }
+            if let Some(&(u, _)) = work.last() {
+                low[u] = low[u].min(low[v]);
+            }
+        }
+    }
+    comp
+}
@@ -0,0 +1,250 @@
+#[cfg(any(feature = "turtle", feature = "msgpack", feature = "cbor"))]
+use crate::onto::individual::Individual;
+use crate::onto::individual::IndividualObj;
+#[cfg(any(feature = "msgpack", feature = "cbor"))]
+use crate::onto::individual::RawObj;
+#[cfg(feature = "cbor")]
+use crate::onto::individual2cbor::to_cbor;
+#[cfg(feature = "json")]
+use crate::onto::individual2jsonld::to_json_ld;
+#[cfg(feature = "msgpack")]
+use crate::onto::individual2msgpack::to_msgpack;
+#[cfg(feature = "json")]
+use crate::onto::json2individual::json_from_slice;
+#[cfg(feature = "json")]
+use crate::onto::jsonld2individual::from_json_ld;
+#[cfg(feature = "turtle")]
+use crate::onto::individual2turtle::to_turtle;
+#[cfg(any(feature = "msgpack", feature = "cbor"))]
+use crate::onto::parser::{parse_raw, MSGPACK_MAGIC_HEADER};
+#[cfg(feature = "turtle")]
+use crate::onto::turtle2individual::{from_ntriples, from_turtle};
+use std::collections::HashMap;
+use std::fmt;
+
+/// Serialization formats by media type.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MediaFormat {
+    Json,
+    JsonLd,
+    Turtle,
+    NTriples,
+    Msgpack,
+    Cbor,
+}
+
+impl MediaFormat {
+    pub const ALL: [MediaFormat; 6] = [
+        MediaFormat::Json,
+        MediaFormat::JsonLd,
+        MediaFormat::Turtle,
+        MediaFormat::NTriples,
+        MediaFormat::Msgpack,
+        MediaFormat::Cbor,
+    ];
+
+    pub fn media_type(self) -> &'static str {
+        match self {
+            MediaFormat::Json => "application/json",
+            MediaFormat::JsonLd => "application/ld+json",
+            MediaFormat::Turtle => "text/turtle",
+            MediaFormat::NTriples => "application/n-triples",
+            MediaFormat::Msgpack => "application/msgpack",
+            MediaFormat::Cbor => "application/cbor",
+        }
+    }
+
+    /// The format of a media type or `Content-Type`, parameters are ignored.
+    pub fn from_media_type(s: &str) -> Option<MediaFormat> {
+        match s.split(';').next().unwrap_or_default().trim().to_ascii_lowercase().as_str() {
+            "application/json" => Some(MediaFormat::Json),
+            "application/ld+json" => Some(MediaFormat::JsonLd),
+            "text/turtle" | "application/x-turtle" => Some(MediaFormat::Turtle),
+            "application/n-triples" => Some(MediaFormat::NTriples),
+            "application/msgpack" | "application/x-msgpack" | "application/vnd.msgpack" => Some(MediaFormat::Msgpack),
+            "application/cbor" => Some(MediaFormat::Cbor),
+            _ => None,
+        }
+    }
+
+    /// `encode` writes the format in this build.
+    pub fn can_encode(self) -> bool {
+        match self {
+            MediaFormat::Json | MediaFormat::JsonLd => cfg!(feature = "json"),
+            MediaFormat::Turtle => cfg!(feature = "turtle"),
+            MediaFormat::Msgpack => cfg!(feature = "msgpack"),
+            MediaFormat::Cbor => cfg!(feature = "cbor"),
+            MediaFormat::NTriples => false,
+        }
+    }
+
+    /// `decode` reads the format in this build.
+    pub fn can_decode(self) -> bool {
+        match self {
+            MediaFormat::Json | MediaFormat::JsonLd => cfg!(feature = "json"),
+            MediaFormat::Turtle | MediaFormat::NTriples => cfg!(feature = "turtle"),
+            MediaFormat::Msgpack => cfg!(feature = "msgpack"),
+            MediaFormat::Cbor => cfg!(feature = "cbor"),
+        }
+    }
+
+    /// Formats `encode` writes, in order of preference.
+    pub fn encodable() -> Vec<MediaFormat> {
+        MediaFormat::ALL.into_iter().filter(|f| f.can_encode()).collect()
+    }
+
+    /// Formats `decode` reads.
+    pub fn decodable() -> Vec<MediaFormat> {
+        MediaFormat::ALL.into_iter().filter(|f| f.can_decode()).collect()
+    }
+}
+
+/// The offered format with the highest q-value in an `Accept` header; the most specific range
+/// of a format decides, ties go to the earlier offer. The first offer without header, None if
+/// no offer is acceptable.
+pub fn negotiate(accept: Option<&str>, offered: &[MediaFormat]) -> Option<MediaFormat> {
+    let Some(accept) = accept.filter(|a| !a.trim().is_empty()) else {
+        return offered.first().copied();
+    };
+
+    let mut best: Option<(MediaFormat, f32)> = None;
+    for format in offered.iter().copied() {
+        let main = format.media_type().split('/').next().unwrap_or_default();
+        // (specificity, q) of the most specific matching range
+        let mut matched: Option<(u8, f32)> = None;
+        for range in accept.split(',') {
+            let mut parts = range.split(';');
+            let media = parts.next().unwrap_or_default().trim().to_ascii_lowercase();
+            let specificity = match media.split_once('/') {
+                Some(("*", "*")) => 0,
+                Some((m, "*")) if m == main => 1,
+                _ if MediaFormat::from_media_type(&media) == Some(format) => 2,
+                _ => continue,
+            };
+            let q = parts.find_map(|p| p.trim().strip_prefix("q=")).and_then(|q| q.trim().parse::<f32>().ok()).unwrap_or(1.0);
+            if matched.is_none_or(|(s, _)| specificity > s) {
+                matched = Some((specificity, q));
+            }
+        }
+        if let Some((_, q)) = matched.filter(|(_, q)| *q > 0.0) {
+            if best.is_none_or(|(_, b)| q > b) {
+                best = Some((format, q));
+            }
+        }
+    }
+    best.map(|(format, _)| format)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MediaError {
+    /// No serializer or deserializer of the format in this build.
+    Unsupported { media_type: String },
+    /// The individual has no form in the format.
+    Encode { msg: String },
+    /// The body is not an individual in the format.
+    Decode { msg: String },
+}
+
+impl fmt::Display for MediaError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MediaError::Unsupported {
+                media_type,
+            } => write!(f, "unsupported media type [{}]", media_type),
+            MediaError::Encode {
+                msg,
+            } => write!(f, "fail encode: {}", msg),
+            MediaError::Decode {
+                msg,
+            } => write!(f, "fail decode: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for MediaError {}
+
+/// Writes an individual; Turtle and JSON-LD declare the prefixes of `all_prefixes` they use.
+#[cfg_attr(not(all(feature = "json", feature = "turtle")), allow(unused_variables))]
+pub fn encode(obj: &IndividualObj, format: MediaFormat, all_prefixes: &HashMap<String, String>) -> Result<Vec<u8>, MediaError> {
+    #[cfg(any(feature = "json", feature = "turtle", feature = "msgpack", feature = "cbor"))]
+    let err = |msg: String| MediaError::Encode {
+        msg,
+    };
+    match format {
+        #[cfg(feature = "json")]
+        MediaFormat::Json => match obj.as_json_str() {
+            s if s.is_empty() => Err(MediaError::Encode {
+                msg: "json".to_owned(),
+            }),
+            s => Ok(s.into_bytes()),
+        },
+        #[cfg(feature = "json")]
+        MediaFormat::JsonLd => Ok(to_json_ld(obj, all_prefixes).map_err(err)?.to_string().into_bytes()),
+        #[cfg(feature = "turtle")]
+        MediaFormat::Turtle => to_turtle(&[Individual::new_from_obj(obj)], all_prefixes).map_err(|e| err(e.to_string())),
+        #[cfg(feature = "msgpack")]
+        MediaFormat::Msgpack => {
+            let mut out = Vec::new();
+            to_msgpack(&Individual::new_from_obj(obj), &mut out).map_err(|e| err(e.to_string()))?;
+            Ok(out)
+        },
+        #[cfg(feature = "cbor")]
+        MediaFormat::Cbor => {
+            let mut out = Vec::new();
+            to_cbor(obj, &mut out).map_err(|e| err(e.to_string()))?;
+            Ok(out)
+        },
+        _ => Err(MediaError::Unsupported {
+            media_type: format.media_type().to_owned(),
+        }),
+    }
+}
+
+/// Reads the individuals of a body: one per subject for Turtle and N-Triples, with IRIs in the
+/// namespaces of `all_prefixes` as prefixed names, one otherwise.
+#[cfg_attr(not(all(feature = "json", feature = "turtle", feature = "msgpack", feature = "cbor")), allow(unused_variables))]
+pub fn decode(body: &[u8], format: MediaFormat, all_prefixes: &HashMap<String, String>) -> Result<Vec<IndividualObj>, MediaError> {
+    #[cfg(any(feature = "json", feature = "turtle", feature = "msgpack", feature = "cbor"))]
+    let err = |msg: String| MediaError::Decode {
+        msg,
+    };
+    match format {
+        #[cfg(feature = "json")]
+        MediaFormat::Json => {
+            let v = json_from_slice(body).map_err(|e| err(e.to_string()))?;
+            Ok(vec![IndividualObj::try_from(&v).map_err(|e| err(e.to_string()))?])
+        },
+        #[cfg(feature = "json")]
+        MediaFormat::JsonLd => {
+            let v = json_from_slice(body).map_err(|e| err(e.to_string()))?;
+            Ok(vec![from_json_ld(&v, all_prefixes).map_err(|e| err(e.to_string()))?])
+        },
+        #[cfg(feature = "turtle")]
+        MediaFormat::Turtle | MediaFormat::NTriples => {
+            let indvs = if format == MediaFormat::Turtle {
+                from_turtle(body, all_prefixes)
+            } else {
+                from_ntriples(body, all_prefixes)
+            };
+            Ok(indvs.map_err(|e| err(e.to_string()))?.into_iter().map(|indv| indv.into_obj()).collect())
+        },
+        // parse_raw tells msgpack and cbor apart by the first byte, which has to match the declared format
+        #[cfg(any(feature = "msgpack", feature = "cbor"))]
+        MediaFormat::Msgpack | MediaFormat::Cbor if format.can_decode() => {
+            let detected = match body.first() {
+                Some(&b) if b == MSGPACK_MAGIC_HEADER => MediaFormat::Msgpack,
+                Some(_) => MediaFormat::Cbor,
+                None => format,
+            };
+            if detected != format {
+                return Err(err(format!("{} declared, body is {}", format.media_type(), detected.media_type())));
+            }
+            let mut indv = Individual::new_raw(RawObj::new(body.to_vec()));
+            parse_raw(&mut indv).map_err(|e| err(format!("{}, code {}", format.media_type(), e)))?;
+            Ok(vec![indv.into_obj()])
+        },
+        _ => Err(MediaError::Unsupported {
+            media_type: format.media_type().to_owned(),
+        }),
+    }
+}
@@ -0,0 +1,100 @@
+use crate::onto::individual::IndividualObj;
+use crate::onto::resource::Resource;
+
+/// A predicate both sides changed incompatibly: both replaced values, or one removed the predicate
+/// while the other added values. The merge keeps `ours`.
+#[derive(Debug, PartialEq)]
+pub struct MergeConflict {
+    pub predicate: String,
+    pub base: Vec<Resource>,
+    pub ours: Vec<Resource>,
+    pub theirs: Vec<Resource>,
+}
+
+#[derive(Debug)]
+pub struct Merge3 {
+    pub merged: IndividualObj,
+    pub conflicts: Vec<MergeConflict>,
+}
+
+impl Merge3 {
+    pub fn is_clean(&self) -> bool {
+        self.conflicts.is_empty()
+    }
+}
+
+fn same_values(a: &[Resource], b: &[Resource]) -> bool {
+    a.len() == b.len() && a.iter().all(|r| b.contains(r)) && b.iter().all(|r| a.contains(r))
+}
+
+fn copies(values: &[Resource]) -> Vec<Resource> {
+    values.iter().map(|r| r.get_copy()).collect()
+}
+
+/// Values of `a` not in `b`.
+fn minus<'a>(a: &'a [Resource], b: &[Resource]) -> Vec<&'a Resource> {
+    a.iter().filter(|r| !b.contains(r)).collect()
+}
+
+/// Merges concurrent edits `ours` and `theirs` of `base` predicate by predicate. A predicate
+/// changed on one side takes that side; changed on both, the values removed by either side are
+/// removed and the values added by either side are added. Value order is not a change.
+/// The merged individual has the uri of `ours`.
+pub fn merge3(base: &IndividualObj, ours: &IndividualObj, theirs: &IndividualObj) -> Merge3 {
+    let mut merged = IndividualObj {
+        uri: ours.uri.to_owned(),
+        ..Default::default()
+    };
+    let mut conflicts = Vec::new();
+
+    let mut predicates: Vec<&String> = ours.resources.keys().collect();
+    predicates.extend(theirs.resources.keys().filter(|p| !ours.resources.contains_key(*p)));
+
+    for predicate in predicates {
+        let b = base.resources.get(predicate).map(|v| v.as_slice()).unwrap_or_default();
+        let o = ours.resources.get(predicate).map(|v| v.as_slice()).unwrap_or_default();
+        let t = theirs.resources.get(predicate).map(|v| v.as_slice()).unwrap_or_default();
+
+        let values: Vec<&Resource> = if same_values(o, t) || same_values(t, b) {
+            o.iter().collect()
+        } else if same_values(o, b) {
+            t.iter().collect()
+        } else {
+            let (added_o, added_t) = (minus(o, b), minus(t, b));
+            let replaced_o = !added_o.is_empty() && !minus(b, o).is_empty();
+            let replaced_t = !added_t.is_empty() && !minus(b, t).is_empty();
+            if (replaced_o && replaced_t) || (o.is_empty() && !added_t.is_empty()) || (t.is_empty() && !added_o.is_empty()) {
+                conflicts.push(MergeConflict {
+                    predicate: predicate.to_owned(),
+                    base: copies(b),
+                    ours: copies(o),
+                    theirs: copies(t),
+                });
+                o.iter().collect()
+            } else {
+                let removed_t = minus(b, t);
+                let mut values: Vec<&Resource> = o.iter().filter(|r| !removed_t.contains(r)).collect();
+                values.extend(added_t.into_iter().filter(|r| !o.contains(r)));
+                values
+            }
+        };
+
+        if !values.is_empty() {
+            let values = values
+                .into_iter()
+                .enumerate()
+                .map(|(idx, r)| Resource {
+                    rtype: r.rtype.clone(),
+                    order: idx as u16,
+                    value: r.value.clone(),
+                })
+                .collect();
+            merged.resources.insert(predicate.to_owned(), values);
+        }
+    }
+
+    Merge3 {
+        merged,
+        conflicts,
+    }
+}
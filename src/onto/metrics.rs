@@ -0,0 +1,100 @@
+use std::sync::{Arc, OnceLock};
+use std::time::{Duration, Instant};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Operation {
+    Parse,
+    Serialize,
+}
+
+impl Operation {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Operation::Parse => "parse",
+            Operation::Serialize => "serialize",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Failure {
+    /// The envelope could not be unpacked: unknown codec, missing key, failed decryption.
+    Envelope,
+    /// The envelope checksum does not match.
+    Corrupted,
+    /// A versioned record could not be upgraded.
+    Migration,
+    /// The msgpack or CBOR payload is malformed.
+    Decode,
+    /// Writing the output failed.
+    Encode,
+}
+
+impl Failure {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Failure::Envelope => "envelope",
+            Failure::Corrupted => "corrupted",
+            Failure::Migration => "migration",
+            Failure::Decode => "decode",
+            Failure::Encode => "encode",
+        }
+    }
+}
+
+/// Receives measurements of parse and serialize calls, e.g. to export them as Prometheus counters
+/// and histograms. `format` is `msgpack`, `cbor` or `unknown`; every method defaults to doing nothing.
+pub trait Metrics: Send + Sync {
+    /// Time spent in an operation, failed or not.
+    fn duration(&self, _op: Operation, _format: &'static str, _elapsed: Duration) {}
+
+    /// Bytes read by a successful parse (as stored, before unpacking an envelope) or written by a successful serialize.
+    fn payload_size(&self, _op: Operation, _format: &'static str, _bytes: usize) {}
+
+    fn failure(&self, _op: Operation, _format: &'static str, _kind: Failure) {}
+}
+
+static GLOBAL: OnceLock<Arc<dyn Metrics>> = OnceLock::new();
+
+/// Installs the process-wide sink used when a call is not given its own. Like `log::set_logger`,
+/// only the first call succeeds, later ones return their argument.
+pub fn set_global_metrics(metrics: Arc<dyn Metrics>) -> Result<(), Arc<dyn Metrics>> {
+    GLOBAL.set(metrics)
+}
+
+pub fn global_metrics() -> Option<&'static dyn Metrics> {
+    GLOBAL.get().map(|m| m.as_ref())
+}
+
+/// One measured operation, reported to the per-call sink or else the global one; free without a sink.
+pub(crate) struct Measure<'a> {
+    sink: Option<(&'a dyn Metrics, Instant)>,
+    op: Operation,
+}
+
+impl<'a> Measure<'a> {
+    pub(crate) fn start(op: Operation, per_call: Option<&'a dyn Metrics>) -> Self {
+        let sink = match per_call {
+            Some(m) => Some(m),
+            None => global_metrics(),
+        };
+        Measure {
+            sink: sink.map(|m| (m, Instant::now())),
+            op,
+        }
+    }
+
+    pub(crate) fn ok(self, format: &'static str, bytes: usize) {
+        if let Some((m, start)) = self.sink {
+            m.duration(self.op, format, start.elapsed());
+            m.payload_size(self.op, format, bytes);
+        }
+    }
+
+    pub(crate) fn fail(self, format: &'static str, kind: Failure) {
+        if let Some((m, start)) = self.sink {
+            m.duration(self.op, format, start.elapsed());
+            m.failure(self.op, format, kind);
+        }
+    }
+}
@@ -0,0 +1,96 @@
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// Prefix of a versioned raw individual, followed by the format version byte and the msgpack or CBOR layout.
+pub const FORMAT_MAGIC: [u8; 3] = [0xc1, b'V', b'F'];
+
+/// Records written without the prefix use the layout of this version.
+pub const UNVERSIONED_FORMAT: u8 = 1;
+
+pub const CURRENT_FORMAT: u8 = 1;
+
+pub type MigrationFn = Box<dyn Fn(&[u8]) -> Result<Vec<u8>, String> + Send + Sync>;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MigrationError {
+    Truncated,
+    /// Written by a newer version of the model.
+    UnsupportedVersion(u8),
+    /// No migration is registered from this version.
+    MissingMigration(u8),
+    Failed(u8, String),
+}
+
+impl fmt::Display for MigrationError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MigrationError::Truncated => write!(f, "format version is truncated"),
+            MigrationError::UnsupportedVersion(v) => write!(f, "format version {} is newer than {}", v, CURRENT_FORMAT),
+            MigrationError::MissingMigration(v) => write!(f, "no migration from format version {}", v),
+            MigrationError::Failed(v, e) => write!(f, "migration from format version {} failed: {}", v, e),
+        }
+    }
+}
+
+impl std::error::Error for MigrationError {}
+
+pub fn is_versioned(data: &[u8]) -> bool {
+    data.starts_with(&FORMAT_MAGIC)
+}
+
+/// Returns the format version of a raw individual and its layout without the prefix.
+pub fn split_format_version(data: &[u8]) -> Result<(u8, &[u8]), MigrationError> {
+    if !is_versioned(data) {
+        return Ok((UNVERSIONED_FORMAT, data));
+    }
+    let version = *data.get(FORMAT_MAGIC.len()).ok_or(MigrationError::Truncated)?;
+    Ok((version, &data[FORMAT_MAGIC.len() + 1..]))
+}
+
+pub fn write_format_version(version: u8, out: &mut Vec<u8>) {
+    out.extend_from_slice(&FORMAT_MAGIC);
+    out.push(version);
+}
+
+/// Upgrades raw individuals written in older layouts, one registered step per version.
+#[derive(Default)]
+pub struct Migrations {
+    steps: BTreeMap<u8, MigrationFn>,
+}
+
+impl fmt::Debug for Migrations {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Migrations").field("from", &self.steps.keys().collect::<Vec<_>>()).finish()
+    }
+}
+
+impl Migrations {
+    pub fn new() -> Self {
+        Migrations::default()
+    }
+
+    /// Registers the conversion of a layout from version `from` to `from + 1`.
+    pub fn register<F>(&mut self, from: u8, f: F) -> &mut Self
+    where
+        F: Fn(&[u8]) -> Result<Vec<u8>, String> + Send + Sync + 'static,
+    {
+        self.steps.insert(from, Box::new(f));
+        self
+    }
+
+    /// Brings a raw individual of any known version to the current layout, the result has no version prefix.
+    pub fn upgrade(&self, data: &[u8]) -> Result<Vec<u8>, MigrationError> {
+        let (mut version, body) = split_format_version(data)?;
+        if version > CURRENT_FORMAT {
+            return Err(MigrationError::UnsupportedVersion(version));
+        }
+
+        let mut res = body.to_vec();
+        while version < CURRENT_FORMAT {
+            let step = self.steps.get(&version).ok_or(MigrationError::MissingMigration(version))?;
+            res = step(&res).map_err(|e| MigrationError::Failed(version, e))?;
+            version += 1;
+        }
+        Ok(res)
+    }
+}
@@ -1,16 +1,155 @@
+#[cfg(feature = "std")]
+pub mod aliases;
+#[cfg(feature = "std")]
+pub mod audit;
+#[cfg(feature = "std")]
+pub mod blank_nodes;
+#[cfg(feature = "std")]
+pub mod cardinality;
+#[cfg(feature = "cbor")]
 pub mod cbor2individual;
+#[cfg(feature = "std")]
+pub mod codec;
+#[cfg(feature = "std")]
+pub mod collation;
+#[cfg(feature = "std")]
+pub mod containers;
+#[cfg(feature = "std")]
+pub mod crdt;
 pub mod datatype;
+#[cfg(feature = "std")]
+pub mod datatype_registry;
+#[cfg(feature = "chrono")]
+pub mod datetime;
+#[cfg(feature = "std")]
+pub mod decimal;
+#[cfg(feature = "std")]
+pub mod dump;
+#[cfg(feature = "std")]
+pub mod envelope;
+#[cfg(feature = "std")]
+pub mod expiry;
+#[cfg(feature = "std")]
+pub mod extract;
+#[cfg(feature = "fake")]
+pub mod fake;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+#[cfg(feature = "std")]
+pub mod filter;
+#[cfg(feature = "json")]
+pub mod graphql;
+#[cfg(any(feature = "axum", feature = "actix"))]
+pub mod http;
+#[cfg(feature = "actix")]
+pub mod http_actix;
+#[cfg(feature = "axum")]
+pub mod http_axum;
+#[cfg(feature = "id")]
+pub mod id;
+#[cfg(feature = "alloc")]
 pub mod individual;
+#[cfg(feature = "cbor")]
+pub mod individual2cbor;
+#[cfg(feature = "json")]
 pub mod individual2json;
+#[cfg(feature = "json")]
+pub mod individual2jsonld;
+#[cfg(feature = "msgpack")]
 pub mod individual2msgpack;
+#[cfg(feature = "turtle")]
 pub mod individual2turtle;
+#[cfg(feature = "msgpack")]
+pub mod individual_ref;
+#[cfg(feature = "std")]
+pub mod individual_store;
+#[cfg(feature = "alloc")]
+pub mod iri;
+#[cfg(feature = "std")]
+pub mod isomorphism;
+#[cfg(all(feature = "std", feature = "msgpack"))]
+pub mod journal;
+#[cfg(feature = "json")]
 pub mod json2individual;
+#[cfg(feature = "json")]
+pub mod json_keys;
+#[cfg(feature = "json")]
+pub mod jsonld2individual;
+#[cfg(feature = "std")]
+pub mod links;
+#[cfg(feature = "std")]
+pub mod media_type;
+#[cfg(feature = "std")]
+pub mod merge;
+#[cfg(feature = "std")]
+pub mod metrics;
+#[cfg(feature = "std")]
+pub mod migrations;
+#[cfg(feature = "msgpack")]
 pub mod msgpack2individual;
+#[cfg(feature = "std")]
+pub mod multilang;
+#[cfg(feature = "std")]
+pub mod namespace;
+#[cfg(feature = "std")]
+pub mod normalize;
+#[cfg(feature = "std")]
+pub mod onto_check;
+#[cfg(feature = "std")]
+pub mod onto_diff;
+#[cfg(feature = "std")]
 pub mod onto_impl;
+#[cfg(feature = "search")]
 pub mod onto_index;
+#[cfg(feature = "std")]
+pub mod onto_template;
+#[cfg(feature = "std")]
+pub mod op;
+#[cfg(feature = "alloc")]
 pub mod parser;
+#[cfg(feature = "std")]
+pub mod patch;
+#[cfg(feature = "std")]
+pub mod pipeline;
+#[cfg(feature = "std")]
+pub mod pretty;
+#[cfg(feature = "std")]
+pub mod provenance;
+#[cfg(feature = "python")]
+pub mod python;
+#[cfg(feature = "std")]
+pub mod raw_scan;
+#[cfg(feature = "redaction")]
+pub mod redaction;
+#[cfg(feature = "std")]
+pub mod reification;
 pub mod resource;
+#[cfg(feature = "std")]
+pub mod rights;
+#[cfg(feature = "redaction")]
+pub mod sample;
+#[cfg(feature = "std")]
+pub mod sanitize;
+#[cfg(feature = "turtle")]
+pub mod sparql;
+#[cfg(feature = "json")]
+pub mod sparql_results;
+#[cfg(feature = "std")]
+pub mod stats;
+#[cfg(feature = "term")]
+pub mod term;
+#[cfg(feature = "std")]
+pub mod text;
+#[cfg(feature = "std")]
+pub mod tombstone;
+#[cfg(feature = "std")]
+pub mod trace;
+#[cfg(feature = "turtle")]
+pub mod turtle2individual;
+#[cfg(feature = "turtle")]
 pub mod turtle_formatters_with_prefixes;
+#[cfg(feature = "std")]
+pub mod versioning;
 
 /// -9223372036854775808…+9223372036854775807 (64 bit).
 pub const XSD_LONG: &str = "http://www.w3.org/2001/XMLSchema#long";
@@ -1,39 +1,168 @@
+#![deny(clippy::unwrap_used, clippy::expect_used, clippy::indexing_slicing, clippy::panic)]
+
 extern crate rmp as msgpack;
 use crate::onto::datatype::*;
 use crate::onto::individual::*;
+use crate::onto::individual_ref::{IndividualRef, ResourceRef, ValueRef};
 use crate::onto::parser::*;
-use crate::onto::resource::*;
-use msgpack::decode::*;
+#[cfg(feature = "std")]
+use crate::onto::trace::{value_detail, ParseTrace, Token};
+use alloc::borrow::{Cow, ToOwned};
+use alloc::format;
+use alloc::string::String;
 use msgpack::Marker;
-use std::io::Cursor;
 
-pub fn parse_msgpack(raw: &mut RawObj) -> Result<String, i8> {
-    if raw.data.is_empty() || raw.raw_type != RawType::Msgpack {
-        return Err(-1);
+/// Single-pass cursor over a msgpack buffer. Strings are returned as slices of the buffer,
+/// nothing is copied before the value is stored.
+struct Reader<'a> {
+    buf: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn new(buf: &'a [u8], pos: usize) -> Self {
+        Reader {
+            buf,
+            pos,
+        }
+    }
+
+    fn is_eof(&self) -> bool {
+        self.pos >= self.buf.len()
+    }
+
+    fn remaining(&self) -> usize {
+        self.buf.len().saturating_sub(self.pos)
+    }
+
+    fn peek(&self) -> Option<Marker> {
+        self.buf.get(self.pos).map(|b| Marker::from_u8(*b))
+    }
+
+    fn marker(&mut self) -> Result<Marker, String> {
+        let m = self.peek().ok_or_else(|| format!("unexpected end at {}", self.pos))?;
+        self.pos += 1;
+        Ok(m)
+    }
+
+    /// The next `len` bytes; a length read from the input is checked against the buffer before use.
+    fn take(&mut self, len: usize) -> Result<&'a [u8], String> {
+        let res =
+            self.pos.checked_add(len).and_then(|end| self.buf.get(self.pos..end)).ok_or_else(|| format!("len {} exceeds the buffer at {}", len, self.pos))?;
+        self.pos += len;
+        Ok(res)
+    }
+
+    fn take_array<const N: usize>(&mut self) -> Result<[u8; N], String> {
+        let mut res = [0u8; N];
+        res.copy_from_slice(self.take(N)?);
+        Ok(res)
+    }
+
+    fn read_len(&mut self, bytes: usize) -> Result<usize, String> {
+        Ok(match bytes {
+            1 => self.take_array::<1>()?[0] as usize,
+            2 => u16::from_be_bytes(self.take_array()?) as usize,
+            _ => u32::from_be_bytes(self.take_array()?) as usize,
+        })
     }
 
-    let mut cur = Cursor::new(raw.data.as_slice());
+    fn read_int(&mut self) -> Result<i64, String> {
+        match self.marker()? {
+            Marker::FixPos(v) => Ok(i64::from(v)),
+            Marker::FixNeg(v) => Ok(i64::from(v)),
+            Marker::U8 => Ok(i64::from(self.take_array::<1>()?[0])),
+            Marker::U16 => Ok(i64::from(u16::from_be_bytes(self.take_array()?))),
+            Marker::U32 => Ok(i64::from(u32::from_be_bytes(self.take_array()?))),
+            Marker::U64 => i64::try_from(u64::from_be_bytes(self.take_array()?)).map_err(|_| "integer out of range".to_owned()),
+            Marker::I8 => Ok(i64::from(i8::from_be_bytes(self.take_array()?))),
+            Marker::I16 => Ok(i64::from(i16::from_be_bytes(self.take_array()?))),
+            Marker::I32 => Ok(i64::from(i32::from_be_bytes(self.take_array()?))),
+            Marker::I64 => Ok(i64::from_be_bytes(self.take_array()?)),
+            marker => Err(format!("expected integer, marker={:?}", marker)),
+        }
+    }
 
-    if let Ok(Marker::FixArray(size)) = read_marker(&mut cur) {
+    fn read_bool(&mut self) -> Result<bool, String> {
+        match self.marker()? {
+            Marker::True => Ok(true),
+            Marker::False => Ok(false),
+            marker => Err(format!("expected bool, marker={:?}", marker)),
+        }
+    }
+
+    /// Bytes of a string, nil is the empty string.
+    fn read_str(&mut self) -> Result<&'a [u8], String> {
+        let len = match self.marker()? {
+            Marker::FixStr(len) => len as usize,
+            Marker::Str8 => self.read_len(1)?,
+            Marker::Str16 => self.read_len(2)?,
+            Marker::Str32 => self.read_len(4)?,
+            Marker::Null => 0,
+            marker => return Err(format!("expected string, marker={:?}", marker)),
+        };
+        self.take(len)
+    }
+
+    fn read_array_len(&mut self) -> Result<usize, String> {
+        match self.marker()? {
+            Marker::FixArray(len) => Ok(len as usize),
+            Marker::Array16 => self.read_len(2),
+            Marker::Array32 => self.read_len(4),
+            marker => Err(format!("expected array, marker={:?}", marker)),
+        }
+    }
+
+    fn read_map_len(&mut self) -> Result<usize, String> {
+        match self.marker()? {
+            Marker::FixMap(len) => Ok(len as usize),
+            Marker::Map16 => self.read_len(2),
+            Marker::Map32 => self.read_len(4),
+            marker => Err(format!("expected map, marker={:?}", marker)),
+        }
+    }
+
+    /// `[uri, {predicates}]` up to the first predicate: the uri and the number of predicates.
+    fn read_header(&mut self) -> Result<(Cow<'a, str>, usize), String> {
+        let size = self.read_array_len()?;
         if size != 2 {
-            return Err(-1);
+            return Err(format!("unexpected header size {}", size));
         }
-    } else {
+        let uri = to_str(self.read_str()?);
+        let len = self.read_map_len()?;
+        Ok((uri, len))
+    }
+}
+
+/// Invalid UTF-8 is replaced, as stored data is kept readable rather than rejected. Valid input, the
+/// usual case, is checked by the faster `str::from_utf8` first.
+fn to_str(bytes: &[u8]) -> Cow<'_, str> {
+    match core::str::from_utf8(bytes) {
+        Ok(s) => Cow::Borrowed(s),
+        Err(_) => String::from_utf8_lossy(bytes),
+    }
+}
+
+pub fn parse_msgpack(raw: &mut RawObj) -> Result<String, i8> {
+    if raw.data.is_empty() || raw.raw_type != RawType::Msgpack {
         return Err(-1);
     }
 
-    // read individual URI
-    let uri = match read_string_from_msgpack(&mut cur) {
-        Ok(p) => p,
-        Err(_) => return Err(-1),
-    };
+    let (uri, len, pos) = read_header(&raw.data)?;
+    raw.len_predicates = len as u32;
+    raw.cur = pos as u64;
+    Ok(uri)
+}
 
-    if let Ok(size) = read_map_len(&mut cur) {
-        raw.len_predicates = size;
-        raw.cur = cur.position();
-        Ok(uri)
-    } else {
-        Err(-1)
+/// The uri, the number of predicates and the position of the first one.
+fn read_header(data: &[u8]) -> Result<(String, usize, usize), i8> {
+    let mut rd = Reader::new(data, 0);
+    match rd.read_header() {
+        Ok((uri, len)) => Ok((uri.into_owned(), len, rd.pos)),
+        Err(e) => {
+            log_error!(format = "msgpack", pos = rd.pos; "fail read header, err={}", e);
+            Err(-1)
+        },
     }
 }
 
@@ -42,236 +171,209 @@ pub fn parse_msgpack_to_predicate(expect_predicate: &str, iraw: &mut Individual)
         return Err("fail position of cursor".to_owned());
     }
 
-    let mut is_found = false;
-    let mut cur = Cursor::new(iraw.raw.data.as_slice());
-    cur.set_position(iraw.raw.cur);
+    let raw = &mut iraw.raw;
+    read_predicates(&raw.data, &mut raw.cur, &mut raw.cur_predicates, raw.len_predicates, &mut iraw.obj, Some(expect_predicate))
+}
+
+/// Decodes a msgpack individual borrowed from elsewhere into `obj` in one pass, see `parser::parse_raw_slice`.
+#[cfg(feature = "std")]
+pub(crate) fn parse_msgpack_slice(data: &[u8], obj: &mut IndividualObj) -> Result<(), String> {
+    let (uri, len, pos) = read_header(data).map_err(|_| "fail read header".to_owned())?;
+    obj.uri = uri;
+    let (mut cur, mut cur_predicates) = (pos as u64, 0);
+    read_predicates(data, &mut cur, &mut cur_predicates, len as u32, obj, None)
+}
+
+/// Decodes a msgpack individual into a view borrowing from `data`, see `IndividualRef`.
+pub(crate) fn parse_msgpack_ref(data: &[u8]) -> Result<IndividualRef<'_>, String> {
+    let mut rd = Reader::new(data, 0);
+    let (uri, len) = rd.read_header().map_err(|e| format!("fail read header, err={}", e))?;
+    let mut res = IndividualRef {
+        uri,
+        ..Default::default()
+    };
+    res.resources.reserve(len.min(rd.remaining() / 2));
 
-    for i in iraw.raw.cur_predicates..iraw.raw.len_predicates {
-        let predicate = match read_string_from_msgpack(&mut cur) {
-            Ok(p) => p,
+    for _ in 0..len {
+        let predicate = to_str(rd.read_str().map_err(|e| format!("fail read predicate, err={}", e))?);
+        let size = rd.read_array_len().map_err(|e| format!("parsing {}", e))?;
+        if size > 0 {
+            let values = res.resources.entry(predicate).or_default();
+            values.reserve(size.min(rd.remaining() / 3));
+            for _ in 0..size {
+                let value = read_value(&mut rd, values.len() as u16)?;
+                values.push(value);
+            }
+        }
+    }
+    Ok(res)
+}
+
+/// Reads a msgpack individual token by token, see `trace::trace_raw`.
+#[cfg(feature = "std")]
+pub(crate) fn trace_msgpack(data: &[u8]) -> ParseTrace {
+    let mut trace = ParseTrace::new(RawType::Msgpack.as_str());
+    let mut rd = Reader::new(data, 0);
+    let mut predicate = String::new();
+    if let Err(e) = trace_tokens(&mut rd, &mut predicate, &mut trace) {
+        trace.push(rd.pos as u64, Token::Error, &predicate, e);
+    }
+    trace
+}
+
+#[cfg(feature = "std")]
+fn trace_tokens(rd: &mut Reader, predicate: &mut String, trace: &mut ParseTrace) -> Result<(), String> {
+    let at = rd.pos as u64;
+    let size = rd.read_array_len()?;
+    trace.push(at, Token::Header, "", format!("array of {}", size));
+    if size != 2 {
+        return Err(format!("unexpected header size {}", size));
+    }
+    let at = rd.pos as u64;
+    let uri = to_str(rd.read_str()?);
+    trace.push(at, Token::Uri, "", uri.into_owned());
+    let at = rd.pos as u64;
+    let len = rd.read_map_len()?;
+    trace.push(at, Token::Predicates, "", format!("{} predicates", len));
+
+    for _ in 0..len {
+        let at = rd.pos as u64;
+        *predicate = to_str(rd.read_str().map_err(|e| format!("fail read predicate, err={}", e))?).into_owned();
+        trace.push(at, Token::Predicate, predicate, String::new());
+        let at = rd.pos as u64;
+        let size = rd.read_array_len()?;
+        trace.push(at, Token::Values, predicate, format!("{} values", size));
+        for i in 0..size {
+            let at = rd.pos as u64;
+            let value = read_value(rd, i as u16)?;
+            trace.push(at, Token::Value, predicate, value_detail(&value.to_owned()));
+        }
+    }
+    predicate.clear();
+
+    if !rd.is_eof() {
+        trace.push(rd.pos as u64, Token::Trailing, "", format!("{} bytes", rd.remaining()));
+    }
+    Ok(())
+}
+
+/// Reads predicates from `cur` until `expect_predicate` has been read, or to the end.
+fn read_predicates(
+    data: &[u8],
+    cur: &mut u64,
+    cur_predicates: &mut u32,
+    len_predicates: u32,
+    obj: &mut IndividualObj,
+    expect_predicate: Option<&str>,
+) -> Result<(), String> {
+    let mut rd = Reader::new(data, *cur as usize);
+    if *cur_predicates == 0 {
+        // a predicate takes at least 2 bytes, the reservation is bounded by the buffer
+        obj.resources.reserve((len_predicates as usize).min(rd.remaining() / 2));
+    }
+
+    for i in *cur_predicates..len_predicates {
+        if rd.is_eof() {
+            *cur = rd.pos as u64;
+            return Err(String::default());
+        }
+        let key = match rd.read_str() {
+            Ok(key) => key,
             Err(e) => {
-                iraw.raw.cur = cur.position();
-                if e == -1 {
-                    return Err("fail read_string_from_msgpack".to_owned());
-                } else {
-                    return Err(String::default());
-                }
+                *cur = rd.pos as u64;
+                return Err(format!("fail read predicate, err={}", e));
             },
         };
 
-        iraw.raw.cur_predicates = i;
+        // the predicate asked for is known to be valid, other keys are checked once
+        let (is_found, predicate) = match expect_predicate {
+            Some(expect) if key == expect.as_bytes() => (true, Cow::Borrowed(expect)),
+            _ => (false, to_str(key)),
+        };
 
-        if predicate == expect_predicate {
-            is_found = true;
-        }
+        let size = rd.read_array_len().map_err(|e| format!("parsing {}", e))?;
+        if size > 0 {
+            // one lookup per predicate, the values are pushed to its vector directly
+            let values = obj.resources.entry(predicate.into_owned()).or_default();
 
-        match read_array_len(&mut cur) {
-            Ok(size) => {
-                for _i_values in 0..size {
-                    match read_marker(&mut cur) {
-                        Ok(v) => match v {
-                            Marker::FixArray(size) => {
-                                if size != 2 && size != 3 {
-                                    return Err(format!("parsing values, unexpected array size, len={:?}", size));
-                                }
-
-                                let v_type: u8 = if let Ok(t) = read_int(&mut cur) {
-                                    t
-                                } else {
-                                    return Err("parsing type".to_owned());
-                                };
-
-                                if size == 2 {
-                                    if v_type == DataType::Boolean as u8 {
-                                        match read_bool(&mut cur) {
-                                            Ok(res) => iraw.obj.add_bool(&predicate, res),
-                                            Err(e) => return Err(format!("value: expected {}, err={:?}", v_type, e)),
-                                        }
-                                    } else if v_type == DataType::Datetime as u8 {
-                                        match read_int(&mut cur) {
-                                            Ok(res) => iraw.obj.add_datetime(&predicate, res),
-                                            Err(e) => return Err(format!("value: expected {}, err={:?}", v_type, e)),
-                                        }
-                                    } else if v_type == DataType::Integer as u8 {
-                                        match read_int(&mut cur) {
-                                            Ok(res) => iraw.obj.add_integer(&predicate, res),
-                                            Err(e) => return Err(format!("value: expected {}, err={:?}", v_type, e)),
-                                        }
-                                    } else if v_type == DataType::Uri as u8 {
-                                        match read_string_from_msgpack(&mut cur) {
-                                            Ok(res) => iraw.obj.add_uri(&predicate, &res),
-                                            Err(e) => return Err(format!("value: expected {}, err={:?}", v_type, e)),
-                                        }
-                                    } else if v_type == DataType::Binary as u8 {
-                                        let values = iraw.obj.resources.entry(predicate.to_owned()).or_default();
-                                        if !read_raw_into_resources(&mut cur, values) {
-                                            return Err("value: fail read raw".to_owned());
-                                        }
-                                    } else if v_type == DataType::String as u8 {
-                                        match read_string_from_msgpack(&mut cur) {
-                                            Ok(res) => iraw.obj.add_string(&predicate, &res, Lang::none()),
-                                            Err(e) => return Err(format!("value: expected {}, err={:?}", v_type, e)),
-                                        };
-                                    } else {
-                                        return Err(format!("msgpack:unknown type {}", v_type));
-                                    }
-                                } else if size == 3 {
-                                    if v_type == DataType::Decimal as u8 {
-                                        match read_int(&mut cur) {
-                                            Ok(mantissa) => match read_int(&mut cur) {
-                                                Ok(exponent) => iraw.obj.add_decimal_d(&predicate, mantissa, exponent),
-                                                Err(e) => {
-                                                    return Err(format!("value: fail read exponent, err={:?}", e));
-                                                },
-                                            },
-                                            Err(e) => return Err(format!("value: fail read mantissa, err={:?}", e)),
-                                        }
-                                    } else if v_type == DataType::String as u8 {
-                                        match read_string_from_msgpack(&mut cur) {
-                                            Ok(str) => {
-                                                let lang = match read_marker(&mut cur.clone()) {
-                                                    Ok(v) => match v {
-                                                        Marker::I8 | Marker::U8 | Marker::FixPos(_) => match read_int(&mut cur) {
-                                                            Ok(res) => Lang::new_from_i64(res),
-                                                            Err(e) => {
-                                                                return Err(format!("value: fail read lang from int, err={:?}", e));
-                                                            },
-                                                        },
-                                                        _ => match read_string_from_msgpack(&mut cur) {
-                                                            Ok(res) => Lang::new_from_str(&res),
-                                                            Err(e) => {
-                                                                return Err(format!("value: fail read lang from string, err={:?}", e));
-                                                            },
-                                                        },
-                                                    },
-                                                    _ => {
-                                                        return Err("value: fail read lang".to_string());
-                                                    },
-                                                };
-
-                                                iraw.obj.add_string(&predicate, &str, lang);
-                                            },
-                                            Err(e) => return Err(format!("value: expected {}, err={:?}", v_type, e)),
-                                        }
-                                    }
-                                }
-                            },
-                            marker => return Err(format!("parsing values: unexpected marker={:?}", marker)),
-                        },
-                        Err(e) => return Err(format!("parsing values: err={:?}", e)),
-                    }
-                }
-            },
-            Err(e) => return Err(format!("parsing {:?}", e)),
+            // a value takes at least 3 bytes, the reservation is bounded by the buffer
+            values.reserve_exact(size.min(rd.remaining() / 3));
+            for _ in 0..size {
+                let value = read_value(&mut rd, values.len() as u16)?;
+                values.push(value.to_owned());
+            }
         }
 
+        *cur_predicates = i + 1;
+
         if is_found {
-            iraw.raw.cur = cur.position();
+            *cur = rd.pos as u64;
             return Ok(());
         }
     }
 
-    iraw.raw.cur = cur.position();
+    *cur = rd.pos as u64;
     Ok(())
 }
 
-fn read_raw_into_resources(cur: &mut Cursor<&[u8]>, values: &mut Vec<Resource>) -> bool {
-    let m_pos = cur.position();
-    let size: u32;
-
-    if let Ok(v) = read_marker(cur) {
-        match v {
-            Marker::FixStr(s) => {
-                size = u32::from(s);
-                cur.set_position(m_pos);
-            },
-            Marker::Str8 | Marker::Str16 | Marker::Str32 => {
-                cur.set_position(m_pos);
-
-                match read_str_len(cur) {
-                    Ok(s) => size = s,
-                    Err(e) => {
-                        error!("fail read str len , err={:?}", e);
-                        return false;
-                    },
-                }
-                cur.set_position(m_pos);
-            },
-            _marker => return false,
-        }
-    } else {
-        return false;
-    }
+/// Reads one `[type, value]` or `[type, value, lang / exponent]` array, borrowing strings from the buffer.
+fn read_value<'a>(rd: &mut Reader<'a>, order: u16) -> Result<ResourceRef<'a>, String> {
+    let size = match rd.marker().map_err(|e| format!("parsing values: err={}", e))? {
+        Marker::FixArray(size) if size == 2 || size == 3 => size,
+        Marker::FixArray(size) => return Err(format!("parsing values, unexpected array size, len={:?}", size)),
+        marker => return Err(format!("parsing values: unexpected marker={:?}", marker)),
+    };
 
-    let mut out = vec![0u8; size as usize];
+    let v_type = rd.read_int().ok().and_then(|t| u8::try_from(t).ok()).ok_or_else(|| "parsing type".to_owned())?;
+    let expected = |e: String| format!("value: expected {}, err={}", v_type, e);
 
-    match read_str(cur, &mut out) {
-        Ok(v) => {
-            values.push(Resource {
+    let (rtype, value) = if size == 2 {
+        if v_type == DataType::Boolean as u8 {
+            (DataType::Boolean, ValueRef::Bool(rd.read_bool().map_err(expected)?))
+        } else if v_type == DataType::Datetime as u8 {
+            (DataType::Datetime, ValueRef::Datetime(rd.read_int().map_err(expected)?))
+        } else if v_type == DataType::Integer as u8 {
+            (DataType::Integer, ValueRef::Int(rd.read_int().map_err(expected)?))
+        } else if v_type == DataType::Uri as u8 {
+            (DataType::Uri, ValueRef::Uri(to_str(rd.read_str().map_err(expected)?)))
+        } else if v_type == DataType::Binary as u8 {
+            if rd.peek() == Some(Marker::Null) {
+                return Err("value: fail read raw".to_owned());
+            }
+            let data = rd.read_str().map_err(|e| format!("value: fail read raw, err={}", e))?;
+            return Ok(ResourceRef {
                 rtype: DataType::Binary,
                 order: 0,
-                value: Value::Binary(v.as_bytes().to_vec()),
+                value: ValueRef::Binary(data),
             });
-            true
-        },
-        Err(e) => {
-            if let DecodeStringError::InvalidUtf8(buf, _err) = e {
-                values.push(Resource {
-                    rtype: DataType::Binary,
-                    order: 0,
-                    value: Value::Binary(buf.to_vec()),
-                });
-                return true;
-            }
-            false
-        },
-    }
-}
-
-fn read_string_from_msgpack(cur: &mut Cursor<&[u8]>) -> Result<String, i64> {
-    let m_pos = cur.position();
-    let size: u32;
-
-    if let Ok(v) = read_marker(cur) {
-        match v {
-            Marker::FixStr(s) => {
-                size = u32::from(s);
-                cur.set_position(m_pos);
-            },
-            Marker::Str8 | Marker::Str16 | Marker::Str32 => {
-                cur.set_position(m_pos);
-                match read_str_len(cur) {
-                    Ok(s) => size = s,
-                    Err(e) => {
-                        error!("fail read str len , err={:?}", e);
-                        return Err(-1);
-                    },
-                }
-                cur.set_position(m_pos);
-            },
-            Marker::Null => {
-                //cur.set_position(m_pos);
-                return Ok("".to_string());
-            },
-            marker => {
-                error!("marker={:?}", marker);
-                return Err(-1);
-            },
+        } else if v_type == DataType::String as u8 {
+            (DataType::String, ValueRef::Str(to_str(rd.read_str().map_err(expected)?), None))
+        } else {
+            return Err(format!("msgpack:unknown type {}", v_type));
         }
+    } else if v_type == DataType::Decimal as u8 {
+        let mantissa = rd.read_int().map_err(|e| format!("value: fail read mantissa, err={}", e))?;
+        let exponent = rd.read_int().map_err(|e| format!("value: fail read exponent, err={}", e))?;
+        (DataType::Decimal, ValueRef::Num(mantissa, exponent))
+    } else if v_type == DataType::String as u8 {
+        let s = to_str(rd.read_str().map_err(expected)?);
+        let lang = match rd.peek() {
+            Some(Marker::I8 | Marker::U8 | Marker::FixPos(_)) => match rd.read_int().map_err(|e| format!("value: fail read lang from int, err={}", e))? {
+                1 => Some(Cow::Borrowed("RU")),
+                2 => Some(Cow::Borrowed("EN")),
+                _ => None,
+            },
+            Some(_) => Some(to_str(rd.read_str().map_err(|e| format!("value: fail read lang from string, err={}", e))?)),
+            None => return Err("value: fail read lang".to_owned()),
+        };
+        (DataType::String, ValueRef::Str(s, lang))
     } else {
-        return Err(-2);
-    }
+        return Err(format!("msgpack:unknown type {}", v_type));
+    };
 
-    let mut out = vec![0u8; size as usize];
-    match read_str(cur, &mut out) {
-        Ok(v) => Ok(v.to_string()),
-        Err(e) => {
-            if let DecodeStringError::InvalidUtf8(buf, _err) = e {
-                let res = String::from_utf8_lossy(buf);
-                return Ok(res.to_string());
-            }
-            error!("fail read str, err={:?}", e);
-            Err(-1)
-        },
-    }
+    Ok(ResourceRef {
+        rtype,
+        order,
+        value,
+    })
 }
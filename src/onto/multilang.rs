@@ -0,0 +1,153 @@
+use crate::onto::datatype::Lang;
+use crate::onto::individual::{Individual, IndividualObj};
+use crate::onto::resource::{Resource, Value};
+
+/// All language variants of a string predicate, one value per language, in insertion order.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MultiLangStr {
+    entries: Vec<(Lang, String)>,
+}
+
+impl MultiLangStr {
+    pub fn new() -> Self {
+        MultiLangStr::default()
+    }
+
+    /// Sets the variant of `lang`, returns the replaced one.
+    pub fn insert(&mut self, s: &str, lang: Lang) -> Option<String> {
+        match self.entries.iter_mut().find(|(l, _)| *l == lang) {
+            Some((_, v)) => Some(std::mem::replace(v, s.to_owned())),
+            None => {
+                self.entries.push((lang, s.to_owned()));
+                None
+            },
+        }
+    }
+
+    pub fn remove(&mut self, lang: &Lang) -> Option<String> {
+        let pos = self.entries.iter().position(|(l, _)| l == lang)?;
+        Some(self.entries.remove(pos).1)
+    }
+
+    pub fn get_lang(&self, lang: &Lang) -> Option<&str> {
+        self.entries.iter().find(|(l, _)| l == lang).map(|(_, s)| s.as_str())
+    }
+
+    /// The variant of the first language in `langs` that is present.
+    pub fn get(&self, langs: &[Lang]) -> Option<&str> {
+        langs.iter().find_map(|l| self.get_lang(l))
+    }
+
+    /// Like `get`, falls back to the first variant.
+    pub fn get_or_first(&self, langs: &[Lang]) -> Option<&str> {
+        self.get(langs).or_else(|| self.entries.first().map(|(_, s)| s.as_str()))
+    }
+
+    pub fn langs(&self) -> impl Iterator<Item = &Lang> {
+        self.entries.iter().map(|(l, _)| l)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&Lang, &str)> {
+        self.entries.iter().map(|(l, s)| (l, s.as_str()))
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn to_resources(&self) -> Vec<Resource> {
+        let mut res: Vec<Resource> = self.entries.iter().map(|(l, s)| Resource::new_string(s, l.clone())).collect();
+        for (idx, r) in res.iter_mut().enumerate() {
+            r.order = idx as u16;
+        }
+        res
+    }
+}
+
+impl FromIterator<(String, Lang)> for MultiLangStr {
+    fn from_iter<T: IntoIterator<Item = (String, Lang)>>(iter: T) -> Self {
+        let mut res = MultiLangStr::new();
+        for (s, l) in iter {
+            res.insert(&s, l);
+        }
+        res
+    }
+}
+
+/// Languages of an HTTP `Accept-Language` header, most preferred first. Entries with `q=0` and `*` are dropped;
+/// a regional tag is followed by its primary language (`en-US` -> `EN-US`, `EN`), as stored values mostly carry that.
+pub fn parse_accept_language(header: &str) -> Vec<Lang> {
+    let mut ranked: Vec<(u16, &str)> = Vec::new();
+    for item in header.split(',') {
+        let mut parts = item.split(';').map(|p| p.trim());
+        let tag = match parts.next() {
+            Some(t) if !t.is_empty() && t != "*" => t,
+            _ => continue,
+        };
+        let mut q = 1000;
+        for param in parts {
+            if let Some(v) = param.strip_prefix("q=") {
+                q = v.trim().parse::<f32>().map(|v| (v.clamp(0.0, 1.0) * 1000.0).round() as u16).unwrap_or(0);
+            }
+        }
+        if q > 0 {
+            ranked.push((q, tag));
+        }
+    }
+    // stable, equal weights keep the header order
+    ranked.sort_by_key(|(q, _)| std::cmp::Reverse(*q));
+
+    let mut res: Vec<Lang> = Vec::new();
+    for (_, tag) in ranked {
+        let primary = tag.split('-').next().unwrap_or(tag);
+        for l in [tag, primary] {
+            let l = Lang::new_from_str(l);
+            if l.is_some() && !res.contains(&l) {
+                res.push(l);
+            }
+        }
+    }
+    res
+}
+
+impl IndividualObj {
+    /// String values of the predicate by language; of several values in one language the first is kept.
+    pub fn get_multilang(&self, predicate: &str) -> MultiLangStr {
+        let mut res = MultiLangStr::new();
+        for r in self.resources.get(predicate).into_iter().flatten() {
+            if let Value::Str(s, l) = &r.value {
+                if res.get_lang(l).is_none() {
+                    res.insert(s, l.clone());
+                }
+            }
+        }
+        res
+    }
+
+    pub fn set_multilang(&mut self, predicate: &str, v: &MultiLangStr) {
+        self.set_resources(predicate, &v.to_resources());
+    }
+}
+
+impl Individual {
+    pub fn get_multilang(&mut self, predicate: &str) -> MultiLangStr {
+        self.parse_all();
+        self.obj.get_multilang(predicate)
+    }
+
+    pub fn set_multilang(&mut self, predicate: &str, v: &MultiLangStr) {
+        self.obj_mut().set_multilang(predicate, v);
+        self.modified(predicate);
+    }
+
+    /// The value in the most preferred language, else the value without language, else the first one.
+    /// `langs` is usually the result of `parse_accept_language`.
+    pub fn get_localized(&mut self, predicate: &str, langs: &[Lang]) -> Option<String> {
+        let values = self.get_multilang(predicate);
+        values.get(langs).or_else(|| values.get_lang(&Lang::none())).or_else(|| values.get_or_first(&[])).map(|s| s.to_owned())
+    }
+}
@@ -0,0 +1,78 @@
+use crate::onto::individual::{Individual, IndividualObj};
+use crate::onto::resource::Value;
+use indexmap::IndexMap;
+use std::mem;
+
+/// `term` with the namespace `old` replaced by `new`, None if it is not in `old`.
+fn rewrite(term: &str, old: &str, new: &str) -> Option<String> {
+    term.strip_prefix(old).map(|rest| format!("{}{}", new, rest))
+}
+
+impl IndividualObj {
+    /// Moves every term of the namespace `old` to `new`: the individual uri, predicates and `Value::Uri`
+    /// values. Namespaces are plain prefixes, `v-s:` or `http://example.org/ns#`, so include the separator.
+    /// Values of a predicate that lands on an existing one are appended to it. Returns the number of
+    /// rewritten terms.
+    pub fn migrate_namespace(&mut self, old: &str, new: &str) -> usize {
+        if old.is_empty() || old == new {
+            return 0;
+        }
+
+        let mut count = 0;
+        if let Some(uri) = rewrite(&self.uri, old, new) {
+            self.uri = uri;
+            count += 1;
+        }
+
+        for values in self.resources.values_mut() {
+            for r in values.iter_mut() {
+                if let Value::Uri(u) = &mut r.value {
+                    if let Some(n) = rewrite(u, old, new) {
+                        *u = n;
+                        count += 1;
+                    }
+                }
+            }
+        }
+
+        if self.resources.keys().any(|k| k.starts_with(old)) {
+            let resources = mem::take(&mut self.resources);
+            let mut migrated = IndexMap::with_capacity(resources.len());
+            for (predicate, values) in resources {
+                let predicate = match rewrite(&predicate, old, new) {
+                    Some(p) => {
+                        count += 1;
+                        p
+                    },
+                    None => predicate,
+                };
+                let dest: &mut Vec<_> = migrated.entry(predicate).or_default();
+                for mut r in values {
+                    r.order = dest.len() as u16;
+                    dest.push(r);
+                }
+            }
+            self.resources = migrated;
+        }
+        count
+    }
+}
+
+impl Individual {
+    pub fn migrate_namespace(&mut self, old: &str, new: &str) -> usize {
+        self.modify_any(|obj| obj.migrate_namespace(old, new))
+    }
+}
+
+/// `migrate_namespace` over a stream of individuals, e.g. read one by one from a dump; lazy, the
+/// individuals are yielded in input order.
+pub fn migrate_namespace_all<I>(indvs: I, old: &str, new: &str) -> impl Iterator<Item = Individual>
+where
+    I: IntoIterator<Item = Individual>,
+{
+    let (old, new) = (old.to_owned(), new.to_owned());
+    indvs.into_iter().map(move |mut indv| {
+        indv.migrate_namespace(&old, &new);
+        indv
+    })
+}
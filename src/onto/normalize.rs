@@ -0,0 +1,158 @@
+use crate::onto::datatype::Lang;
+use crate::onto::individual::{Individual, IndividualObj};
+use crate::onto::onto_impl::Onto;
+use crate::onto::resource::Value;
+use crate::onto::XSD_NORMALIZED_STRING;
+use std::collections::HashMap;
+use unicode_normalization::{is_nfc, UnicodeNormalization};
+
+/// Normalization applied to string values.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct StringPolicy {
+    pub nfc: bool,
+    pub trim: bool,
+    pub collapse_whitespace: bool,
+}
+
+impl StringPolicy {
+    pub fn none() -> Self {
+        StringPolicy::default()
+    }
+
+    pub fn nfc() -> Self {
+        StringPolicy {
+            nfc: true,
+            ..Default::default()
+        }
+    }
+
+    /// NFC, trimmed, every whitespace run replaced by one space, as for `xsd:normalizedString`.
+    pub fn normalized_string() -> Self {
+        StringPolicy {
+            nfc: true,
+            trim: true,
+            collapse_whitespace: true,
+        }
+    }
+
+    pub fn is_none(&self) -> bool {
+        !self.nfc && !self.trim && !self.collapse_whitespace
+    }
+
+    /// Returns None if the string is already normalized.
+    pub fn apply(&self, s: &str) -> Option<String> {
+        let mut res = if self.nfc && !is_nfc(s) {
+            Some(s.nfc().collect::<String>())
+        } else {
+            None
+        };
+
+        if self.collapse_whitespace {
+            let src = res.as_deref().unwrap_or(s);
+            let trimmed = if self.trim {
+                src.trim()
+            } else {
+                src
+            };
+            let mut collapsed = String::with_capacity(trimmed.len());
+            let mut in_ws = false;
+            for c in trimmed.chars() {
+                if c.is_whitespace() {
+                    if !in_ws {
+                        collapsed.push(' ');
+                    }
+                    in_ws = true;
+                } else {
+                    collapsed.push(c);
+                    in_ws = false;
+                }
+            }
+            if collapsed != src {
+                res = Some(collapsed);
+            }
+        } else if self.trim {
+            let src = res.as_deref().unwrap_or(s);
+            if src.trim().len() != src.len() {
+                res = Some(src.trim().to_owned());
+            }
+        }
+
+        res
+    }
+}
+
+/// String policy per predicate with a fallback for the others.
+#[derive(Debug, Clone, Default)]
+pub struct NormalizePolicy {
+    pub default: StringPolicy,
+    pub predicates: HashMap<String, StringPolicy>,
+}
+
+impl NormalizePolicy {
+    pub fn new(default: StringPolicy) -> Self {
+        NormalizePolicy {
+            default,
+            predicates: HashMap::new(),
+        }
+    }
+
+    pub fn with_predicate(mut self, predicate: &str, policy: StringPolicy) -> Self {
+        self.predicates.insert(predicate.to_owned(), policy);
+        self
+    }
+
+    /// Uses `StringPolicy::normalized_string` for every property with range `xsd:normalizedString`.
+    pub fn from_onto(onto: &Onto, default: StringPolicy) -> Self {
+        let mut res = NormalizePolicy::new(default);
+        for (property, ranges) in onto.ranges.iter() {
+            if ranges.iter().any(|r| r == "xsd:normalizedString" || r == XSD_NORMALIZED_STRING) {
+                res.predicates.insert(property.to_owned(), StringPolicy::normalized_string());
+            }
+        }
+        res
+    }
+
+    pub fn get(&self, predicate: &str) -> &StringPolicy {
+        self.predicates.get(predicate).unwrap_or(&self.default)
+    }
+}
+
+impl IndividualObj {
+    /// Applies the policy to all string values, returns the number of changed values.
+    pub fn normalize_strings(&mut self, policy: &NormalizePolicy) -> usize {
+        let mut count = 0;
+        for (predicate, values) in self.resources.iter_mut() {
+            let p = policy.get(predicate);
+            if p.is_none() {
+                continue;
+            }
+            for r in values.iter_mut() {
+                if let Value::Str(s, _) = &mut r.value {
+                    if let Some(n) = p.apply(s) {
+                        *s = n;
+                        count += 1;
+                    }
+                }
+            }
+        }
+        count
+    }
+
+    pub fn add_string_with(&mut self, predicate: &str, s: &str, lang: Lang, policy: &StringPolicy) {
+        match policy.apply(s) {
+            Some(n) => self.add_string(predicate, &n, lang),
+            None => self.add_string(predicate, s, lang),
+        }
+    }
+}
+
+impl Individual {
+    pub fn normalize_strings(&mut self, policy: &NormalizePolicy) -> usize {
+        self.modify_any(|obj| obj.normalize_strings(policy))
+    }
+
+    pub fn add_string_with(&mut self, predicate: &str, s: &str, lang: Lang, policy: &StringPolicy) {
+        self.obj_mut().add_string_with(predicate, s, lang, policy);
+        self.modified(predicate);
+    }
+}
@@ -0,0 +1,214 @@
+use crate::onto::individual::Individual;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OntoIssue {
+    /// `rdfs:subClassOf` names a class that is not defined.
+    DanglingSuperClass {
+        class: String,
+        super_class: String,
+    },
+    UndefinedRange {
+        property: String,
+        class: String,
+    },
+    UndefinedDomain {
+        property: String,
+        class: String,
+    },
+    /// The property is defined more than once with different `rdfs:range` values.
+    ConflictingRanges {
+        property: String,
+        ranges: Vec<Vec<String>>,
+    },
+    /// Classes of a `rdfs:subClassOf` cycle, starting with the smallest uri.
+    SubClassCycle(Vec<String>),
+}
+
+impl fmt::Display for OntoIssue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            OntoIssue::DanglingSuperClass {
+                class,
+                super_class,
+            } => write!(f, "{}: superclass {} is not defined", class, super_class),
+            OntoIssue::UndefinedRange {
+                property,
+                class,
+            } => write!(f, "{}: range {} is not defined", property, class),
+            OntoIssue::UndefinedDomain {
+                property,
+                class,
+            } => write!(f, "{}: domain {} is not defined", property, class),
+            OntoIssue::ConflictingRanges {
+                property,
+                ranges,
+            } => write!(f, "{}: defined with different ranges {:?}", property, ranges),
+            OntoIssue::SubClassCycle(classes) => write!(f, "subClassOf cycle: {} -> {}", classes.join(" -> "), classes[0]),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct OntoReport {
+    pub issues: Vec<OntoIssue>,
+}
+
+impl OntoReport {
+    pub fn is_ok(&self) -> bool {
+        self.issues.is_empty()
+    }
+}
+
+impl fmt::Display for OntoReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for issue in self.issues.iter() {
+            writeln!(f, "{}", issue)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Default)]
+struct PropertyDef {
+    ranges: Vec<Vec<String>>,
+    domains: BTreeSet<String>,
+}
+
+/// Collects class and property definitions like `Onto::update` and reports inconsistencies between them.
+/// Terms of external vocabularies (`rdf:`, `rdfs:`, `owl:`, `xsd:` by default) count as defined.
+#[derive(Debug)]
+pub struct OntoChecker {
+    classes: BTreeMap<String, BTreeSet<String>>,
+    properties: BTreeMap<String, PropertyDef>,
+    external: Vec<String>,
+}
+
+impl Default for OntoChecker {
+    fn default() -> Self {
+        OntoChecker {
+            classes: BTreeMap::new(),
+            properties: BTreeMap::new(),
+            external: ["rdf:", "rdfs:", "owl:", "xsd:"].iter().map(|p| (*p).to_owned()).collect(),
+        }
+    }
+}
+
+impl OntoChecker {
+    pub fn new() -> Self {
+        OntoChecker::default()
+    }
+
+    pub fn with_external_prefix(mut self, prefix: &str) -> Self {
+        self.external.push(prefix.to_owned());
+        self
+    }
+
+    pub fn add(&mut self, indv: &mut Individual) {
+        let vtype = match indv.get_first_literal("rdf:type") {
+            Some(t) => t,
+            None => return,
+        };
+
+        if vtype == "owl:Class" || vtype == "rdfs:Class" || vtype == "rdfs:Datatype" {
+            let supers = indv.get_literals("rdfs:subClassOf").unwrap_or_default();
+            self.classes.entry(indv.get_id().to_owned()).or_default().extend(supers);
+        } else if vtype == "rdf:Property" || vtype == "owl:ObjectProperty" || vtype == "owl:DatatypeProperty" {
+            let mut ranges = indv.get_literals("rdfs:range").unwrap_or_default();
+            ranges.sort();
+            let domains = indv.get_literals("rdfs:domain").unwrap_or_default();
+            let def = self.properties.entry(indv.get_id().to_owned()).or_default();
+            def.ranges.push(ranges);
+            def.domains.extend(domains);
+        }
+    }
+
+    fn is_defined(&self, class: &str) -> bool {
+        self.classes.contains_key(class) || self.external.iter().any(|p| class.starts_with(p.as_str()))
+    }
+
+    pub fn report(&self) -> OntoReport {
+        let mut issues = Vec::new();
+
+        for (class, supers) in self.classes.iter() {
+            for super_class in supers.iter().filter(|s| !self.is_defined(s)) {
+                issues.push(OntoIssue::DanglingSuperClass {
+                    class: class.to_owned(),
+                    super_class: super_class.to_owned(),
+                });
+            }
+        }
+
+        for (property, def) in self.properties.iter() {
+            let ranges: BTreeSet<&Vec<String>> = def.ranges.iter().collect();
+            if ranges.len() > 1 {
+                issues.push(OntoIssue::ConflictingRanges {
+                    property: property.to_owned(),
+                    ranges: ranges.into_iter().cloned().collect(),
+                });
+            }
+            let used: BTreeSet<&String> = def.ranges.iter().flatten().collect();
+            for class in used.into_iter().filter(|c| !self.is_defined(c)) {
+                issues.push(OntoIssue::UndefinedRange {
+                    property: property.to_owned(),
+                    class: class.to_owned(),
+                });
+            }
+            for class in def.domains.iter().filter(|c| !self.is_defined(c)) {
+                issues.push(OntoIssue::UndefinedDomain {
+                    property: property.to_owned(),
+                    class: class.to_owned(),
+                });
+            }
+        }
+
+        issues.extend(self.find_cycles().into_iter().map(OntoIssue::SubClassCycle));
+        OntoReport {
+            issues,
+        }
+    }
+
+    /// Depth-first search over subClassOf edges, every back edge closes a cycle on the current path.
+    fn find_cycles(&self) -> BTreeSet<Vec<String>> {
+        let mut cycles = BTreeSet::new();
+        let mut done: BTreeSet<&str> = BTreeSet::new();
+
+        for start in self.classes.keys() {
+            if done.contains(start.as_str()) {
+                continue;
+            }
+            let mut path: Vec<&str> = vec![start];
+            let mut iters = vec![self.supers(start)];
+            while let Some(it) = iters.last_mut() {
+                match it.next() {
+                    Some(next) => {
+                        if let Some(pos) = path.iter().position(|c| *c == next) {
+                            let mut cycle: Vec<String> = path[pos..].iter().map(|c| (*c).to_owned()).collect();
+                            let min = (0..cycle.len()).min_by_key(|i| &cycle[*i]).unwrap_or(0);
+                            cycle.rotate_left(min);
+                            cycles.insert(cycle);
+                        } else if !done.contains(next) {
+                            path.push(next);
+                            iters.push(self.supers(next));
+                        }
+                    },
+                    None => {
+                        iters.pop();
+                        if let Some(c) = path.pop() {
+                            done.insert(c);
+                        }
+                    },
+                }
+            }
+        }
+        cycles
+    }
+
+    fn supers<'a>(&'a self, class: &str) -> Box<dyn Iterator<Item = &'a str> + 'a> {
+        match self.classes.get(class) {
+            Some(supers) => Box::new(supers.iter().map(|s| s.as_str())),
+            None => Box::new(std::iter::empty()),
+        }
+    }
+}
@@ -0,0 +1,198 @@
+use crate::onto::onto_impl::{Onto, RelType};
+#[cfg(feature = "json")]
+use serde_json::json;
+#[cfg(feature = "json")]
+use serde_json::value::Value as JSONValue;
+use std::collections::BTreeSet;
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TermKind {
+    Class,
+    Property,
+}
+
+impl TermKind {
+    fn of(vtype: &str) -> TermKind {
+        if vtype == "owl:Class" || vtype == "rdfs:Class" || vtype == "rdfs:Datatype" {
+            TermKind::Class
+        } else {
+            TermKind::Property
+        }
+    }
+}
+
+/// Values of one aspect of a term that appear only in the old or only in the new ontology.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieldChange {
+    /// `rdf:type`, `rdfs:subClassOf`, `rdfs:subPropertyOf`, `rdfs:range` or `owl:maxCardinality`.
+    pub field: &'static str,
+    pub removed: Vec<String>,
+    pub added: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ModifiedTerm {
+    pub uri: String,
+    pub changes: Vec<FieldChange>,
+}
+
+/// Difference between two ontologies, every list is sorted by uri.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct OntoDiff {
+    pub added_classes: Vec<String>,
+    pub removed_classes: Vec<String>,
+    pub modified_classes: Vec<ModifiedTerm>,
+    pub added_properties: Vec<String>,
+    pub removed_properties: Vec<String>,
+    pub modified_properties: Vec<ModifiedTerm>,
+}
+
+fn terms(onto: &Onto, kind: TermKind) -> BTreeSet<&str> {
+    onto.kinds.iter().filter(|(_, vtype)| TermKind::of(vtype) == kind).map(|(uri, _)| uri.as_str()).collect()
+}
+
+fn direct_supers(onto: &Onto, uri: &str) -> BTreeSet<String> {
+    match onto.relations.get(uri) {
+        Some(rels) => rels.iter().filter(|(_, t)| **t == RelType::Super).map(|(x, _)| x.to_owned()).collect(),
+        None => BTreeSet::new(),
+    }
+}
+
+fn compare(field: &'static str, old: BTreeSet<String>, new: BTreeSet<String>, changes: &mut Vec<FieldChange>) {
+    if old != new {
+        changes.push(FieldChange {
+            field,
+            removed: old.difference(&new).cloned().collect(),
+            added: new.difference(&old).cloned().collect(),
+        });
+    }
+}
+
+fn modified_term(old: &Onto, new: &Onto, uri: &str, kind: TermKind) -> Option<ModifiedTerm> {
+    let mut changes = Vec::new();
+    let single = |v: Option<&String>| v.cloned().into_iter().collect::<BTreeSet<String>>();
+    compare("rdf:type", single(old.kinds.get(uri)), single(new.kinds.get(uri)), &mut changes);
+
+    let supers = if kind == TermKind::Class {
+        "rdfs:subClassOf"
+    } else {
+        "rdfs:subPropertyOf"
+    };
+    compare(supers, direct_supers(old, uri), direct_supers(new, uri), &mut changes);
+
+    if kind == TermKind::Property {
+        let ranges = |onto: &Onto| onto.get_ranges(uri).iter().cloned().collect();
+        compare("rdfs:range", ranges(old), ranges(new), &mut changes);
+        let max = |onto: &Onto| onto.get_max_cardinality(uri).map(|m| m.to_string()).into_iter().collect();
+        compare("owl:maxCardinality", max(old), max(new), &mut changes);
+    }
+
+    if changes.is_empty() {
+        None
+    } else {
+        Some(ModifiedTerm {
+            uri: uri.to_owned(),
+            changes,
+        })
+    }
+}
+
+fn diff_terms(old: &Onto, new: &Onto, kind: TermKind) -> (Vec<String>, Vec<String>, Vec<ModifiedTerm>) {
+    let (a, b) = (terms(old, kind), terms(new, kind));
+    let added = b.difference(&a).map(|s| (*s).to_owned()).collect();
+    let removed = a.difference(&b).map(|s| (*s).to_owned()).collect();
+    let modified = a.intersection(&b).filter_map(|uri| modified_term(old, new, uri, kind)).collect();
+    (added, removed, modified)
+}
+
+impl Onto {
+    /// Classes and properties added, removed or changed between `old` and `new`, for reviewing an upgrade.
+    pub fn diff(old: &Onto, new: &Onto) -> OntoDiff {
+        let (added_classes, removed_classes, modified_classes) = diff_terms(old, new, TermKind::Class);
+        let (added_properties, removed_properties, modified_properties) = diff_terms(old, new, TermKind::Property);
+        OntoDiff {
+            added_classes,
+            removed_classes,
+            modified_classes,
+            added_properties,
+            removed_properties,
+            modified_properties,
+        }
+    }
+}
+
+#[cfg(feature = "json")]
+fn modified_to_json(terms: &[ModifiedTerm]) -> JSONValue {
+    let terms: Vec<JSONValue> = terms
+        .iter()
+        .map(|t| {
+            let changes: Vec<JSONValue> = t.changes.iter().map(|c| json!({ "field": c.field, "removed": c.removed, "added": c.added })).collect();
+            json!({ "uri": t.uri, "changes": changes })
+        })
+        .collect();
+    JSONValue::Array(terms)
+}
+
+impl OntoDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added_classes.is_empty()
+            && self.removed_classes.is_empty()
+            && self.modified_classes.is_empty()
+            && self.added_properties.is_empty()
+            && self.removed_properties.is_empty()
+            && self.modified_properties.is_empty()
+    }
+
+    /// Changelog as Turtle comments, can be put in front of the new ontology file.
+    pub fn changelog(&self) -> String {
+        self.to_string()
+    }
+
+    #[cfg(feature = "json")]
+    pub fn to_json(&self) -> JSONValue {
+        json!({
+            "classes": {
+                "added": self.added_classes,
+                "removed": self.removed_classes,
+                "modified": modified_to_json(&self.modified_classes),
+            },
+            "properties": {
+                "added": self.added_properties,
+                "removed": self.removed_properties,
+                "modified": modified_to_json(&self.modified_properties),
+            },
+        })
+    }
+}
+
+fn write_section(f: &mut fmt::Formatter, title: &str, added: &[String], removed: &[String], modified: &[ModifiedTerm]) -> fmt::Result {
+    if added.is_empty() && removed.is_empty() && modified.is_empty() {
+        return Ok(());
+    }
+    writeln!(f, "# {}:", title)?;
+    for uri in added {
+        writeln!(f, "#   + {}", uri)?;
+    }
+    for uri in removed {
+        writeln!(f, "#   - {}", uri)?;
+    }
+    for t in modified {
+        writeln!(f, "#   ~ {}", t.uri)?;
+        for c in t.changes.iter() {
+            let values: Vec<String> = c.removed.iter().map(|v| format!("-{}", v)).chain(c.added.iter().map(|v| format!("+{}", v))).collect();
+            writeln!(f, "#       {} {}", c.field, values.join(" "))?;
+        }
+    }
+    Ok(())
+}
+
+impl fmt::Display for OntoDiff {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.is_empty() {
+            return writeln!(f, "# no changes");
+        }
+        write_section(f, "classes", &self.added_classes, &self.removed_classes, &self.modified_classes)?;
+        write_section(f, "properties", &self.added_properties, &self.removed_properties, &self.modified_properties)
+    }
+}
@@ -1,19 +1,57 @@
+use crate::onto::datatype::Lang;
 use crate::onto::individual::*;
+use crate::onto::multilang::MultiLangStr;
+use crate::onto::onto_template::ClassTemplate;
+use arc_swap::ArcSwap;
 use std::collections::{HashMap, HashSet};
 use std::fmt;
+use std::sync::Arc;
 
 // TODO: Load ontology individuals to have them by hand at runtime.
 
-#[derive(Eq, PartialEq, Debug)]
+#[derive(Eq, PartialEq, Debug, Clone)]
 pub enum RelType {
     Sub,
     Super,
 }
 
-#[derive(Debug, Default)]
+#[derive(Debug, Default, Clone)]
 pub struct Onto {
     pub relations: HashMap<String, HashMap<String, RelType>>,
     pub prefixes: HashMap<String, String>,
+    pub ranges: HashMap<String, Vec<String>>,
+    pub max_cardinality: HashMap<String, usize>,
+    /// `rdf:type` of every defined class and property.
+    pub kinds: HashMap<String, String>,
+    /// Terms marked `owl:deprecated true`.
+    pub deprecated: HashSet<String>,
+    /// `owl:equivalentProperty` values of every individual given to `update`, see `PredicateAliases::from_onto`.
+    pub equivalent_properties: HashMap<String, Vec<String>>,
+    /// Defaults and required predicates per class (or restriction), see `instantiate`.
+    pub templates: HashMap<String, ClassTemplate>,
+    /// `rdfs:label` of every individual given to `update`, see `get_label`.
+    pub labels: HashMap<String, MultiLangStr>,
+    deprecation_warning: DeprecationWarning,
+}
+
+/// Called with the individual uri and the deprecated predicate.
+pub type DeprecationCallback = dyn Fn(&str, &str) + Send + Sync;
+
+#[derive(Clone, Default)]
+struct DeprecationWarning(Option<Arc<DeprecationCallback>>);
+
+impl fmt::Debug for DeprecationWarning {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "DeprecationWarning({})",
+            if self.0.is_some() {
+                "set"
+            } else {
+                "none"
+            }
+        )
+    }
 }
 
 impl fmt::Display for Onto {
@@ -24,6 +62,30 @@ impl fmt::Display for Onto {
 
 impl Onto {
     pub fn update(&mut self, indv: &mut Individual) -> bool {
+        if indv.any_exists("rdf:type", &["owl:FunctionalProperty"]) {
+            self.set_max_cardinality(indv.get_id(), 1);
+        }
+
+        if indv.get_first_bool("owl:deprecated") == Some(true) {
+            self.deprecated.insert(indv.get_id().to_owned());
+        } else {
+            self.deprecated.remove(indv.get_id());
+        }
+
+        let equivalent = indv.get_literals("owl:equivalentProperty").unwrap_or_default();
+        if equivalent.is_empty() {
+            self.equivalent_properties.remove(indv.get_id());
+        } else {
+            self.equivalent_properties.insert(indv.get_id().to_owned(), equivalent);
+        }
+
+        let labels = indv.get_multilang("rdfs:label");
+        if labels.is_empty() {
+            self.labels.remove(indv.get_id());
+        } else {
+            self.labels.insert(indv.get_id().to_owned(), labels);
+        }
+
         if let Some(vtype) = indv.get_first_literal("rdf:type") {
             if vtype == "owl:Class"
                 || vtype == "rdfs:Class"
@@ -40,6 +102,8 @@ impl Onto {
                     Vec::new()
                 };
 
+                self.kinds.insert(indv.obj.uri.clone(), vtype.clone());
+
                 // if subs.len() > 0 {
                 let onto_el = self.relations.entry(indv.obj.uri.clone()).or_default();
 
@@ -53,7 +117,30 @@ impl Onto {
                     let rel_type = onto_el.entry(indv.obj.uri.clone()).or_insert(RelType::Sub);
                     *rel_type = RelType::Sub;
                 }
+
+                if vtype == "rdf:Property" || vtype == "owl:ObjectProperty" || vtype == "owl:DatatypeProperty" {
+                    if let Some(ranges) = indv.get_literals("rdfs:range") {
+                        self.ranges.insert(indv.obj.uri.clone(), ranges);
+                    }
+                }
             //}
+            } else if vtype == "owl:Restriction" || vtype == "v-ui:PropertySpecification" {
+                let property = if vtype == "owl:Restriction" {
+                    indv.get_first_literal("owl:onProperty")
+                } else {
+                    indv.get_first_literal("v-ui:forProperty")
+                };
+                let max = indv
+                    .get_first_integer("owl:maxCardinality")
+                    .or_else(|| indv.get_first_integer("owl:cardinality"))
+                    .or_else(|| indv.get_first_integer("v-ui:maxCardinality"));
+
+                if let (Some(property), Some(max)) = (&property, max) {
+                    self.set_max_cardinality(property, max.max(0) as usize);
+                }
+                if let Some(property) = property {
+                    self.update_template(indv, &property, vtype == "owl:Restriction");
+                }
             } else if vtype == "owl:Ontology" {
                 if let Some(full_url) = indv.get_first_literal("v-s:fullUrl") {
                     debug!("prefix : {} -> {}", indv.get_id(), full_url);
@@ -66,10 +153,9 @@ impl Onto {
         true
     }
 
-    pub fn is_some_entered(&mut self, el: &str, subs: &[&str]) -> bool {
+    pub fn is_some_entered(&self, el: &str, subs: &[&str]) -> bool {
         for sub in subs {
-            if self.relations.contains_key(*sub) {
-                let onto_el = self.relations.entry((*sub).to_string()).or_default();
+            if let Some(onto_el) = self.relations.get(*sub) {
                 if let Some(rtype) = onto_el.get(el) {
                     if *rtype == RelType::Sub {
                         return true;
@@ -81,13 +167,12 @@ impl Onto {
         false
     }
 
-    pub fn is_some_entered_it<'a, I>(&mut self, el: &str, subs: I) -> bool
+    pub fn is_some_entered_it<'a, I>(&self, el: &str, subs: I) -> bool
     where
         I: Iterator<Item = &'a String>,
     {
         for sub in subs {
-            if self.relations.contains_key(sub) {
-                let onto_el = self.relations.entry((*sub).to_string()).or_default();
+            if let Some(onto_el) = self.relations.get(sub) {
                 if let Some(rtype) = onto_el.get(el) {
                     if *rtype == RelType::Sub {
                         return true;
@@ -149,4 +234,95 @@ impl Onto {
     pub fn get_full_prefix(&self, short_prefix: &str) -> Option<&String> {
         self.prefixes.get(short_prefix)
     }
+
+    /// Restrictions are not bound to their classes: the smallest maxCardinality declared for a property applies everywhere.
+    fn set_max_cardinality(&mut self, property: &str, max: usize) {
+        let v = self.max_cardinality.entry(property.to_owned()).or_insert(max);
+        *v = (*v).min(max);
+    }
+
+    pub fn get_max_cardinality(&self, property: &str) -> Option<usize> {
+        self.max_cardinality.get(property).copied()
+    }
+
+    pub fn is_deprecated(&self, uri: &str) -> bool {
+        self.deprecated.contains(uri)
+    }
+
+    /// Callback invoked by `warn_deprecated` (and so by `try_add`) when a deprecated predicate is added to an individual.
+    pub fn set_deprecation_warning<F>(&mut self, f: F)
+    where
+        F: Fn(&str, &str) + Send + Sync + 'static,
+    {
+        self.deprecation_warning = DeprecationWarning(Some(Arc::new(f)));
+    }
+
+    /// Reports the use of a deprecated predicate to the callback, returns true if it is deprecated.
+    pub fn warn_deprecated(&self, uri: &str, predicate: &str) -> bool {
+        if !self.is_deprecated(predicate) {
+            return false;
+        }
+        if let Some(f) = &self.deprecation_warning.0 {
+            f(uri, predicate);
+        }
+        true
+    }
+
+    pub fn get_ranges(&self, property: &str) -> &[String] {
+        self.ranges.get(property).map(|v| v.as_slice()).unwrap_or_default()
+    }
+
+    /// The label of a term in `lang`, else the one without language, else the first one.
+    pub fn get_label(&self, uri: &str, lang: &Lang) -> Option<&str> {
+        let labels = self.labels.get(uri)?;
+        labels.get_lang(lang).or_else(|| labels.get_lang(&Lang::none())).or_else(|| labels.get_or_first(&[]))
+    }
+}
+
+/// Shared ontology handle. Readers get an `Arc<Onto>` snapshot without locking,
+/// a reload publishes a new ontology atomically; snapshots taken before stay valid.
+#[derive(Debug, Clone, Default)]
+pub struct ArcOnto {
+    inner: Arc<ArcSwap<Onto>>,
 }
+
+impl ArcOnto {
+    pub fn new(onto: Onto) -> Self {
+        ArcOnto {
+            inner: Arc::new(ArcSwap::from_pointee(onto)),
+        }
+    }
+
+    /// The current ontology.
+    pub fn load(&self) -> Arc<Onto> {
+        self.inner.load_full()
+    }
+
+    pub fn store(&self, onto: Onto) {
+        self.inner.store(Arc::new(onto));
+    }
+
+    /// Publishes a new ontology, returns the previous one.
+    pub fn swap(&self, onto: Onto) -> Arc<Onto> {
+        self.inner.swap(Arc::new(onto))
+    }
+
+    /// Applies `f` to a copy of the current ontology and publishes the result.
+    /// `f` may be called again if another update was published meanwhile.
+    pub fn update<F>(&self, mut f: F)
+    where
+        F: FnMut(&mut Onto),
+    {
+        self.inner.rcu(|cur| {
+            let mut onto = Onto::clone(cur);
+            f(&mut onto);
+            onto
+        });
+    }
+}
+
+const _: fn() = || {
+    fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<Onto>();
+    assert_send_sync::<ArcOnto>();
+};
@@ -1,9 +1,11 @@
 use bincode::{deserialize_from, serialize_into, ErrorKind};
+use rustc_hash::FxBuildHasher;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fs;
 use std::fs::rename;
 use std::fs::{File, OpenOptions};
+use std::hash::BuildHasher;
 use std::io::{BufReader, BufWriter};
 use std::path::Path;
 use std::time::SystemTime;
@@ -71,4 +73,212 @@ impl OntoIndex {
         self.data.insert(key.to_owned(), val.to_owned());
         self.dump()
     }
+
+    /// Read-only copy for the lookup path, build it after loading and share it between readers.
+    pub fn freeze(&self) -> FrozenOntoIndex {
+        FrozenOntoIndex::new(self.data.iter().map(|(k, v)| (k.as_str(), *v)))
+    }
+}
+
+fn hash_key(key: &str) -> u64 {
+    FxBuildHasher.hash_one(key)
+}
+
+/// A key hashed once, for keys looked up on every request (predicates, classes).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PrehashedKey {
+    hash: u64,
+    key: String,
+}
+
+impl PrehashedKey {
+    pub fn new(key: &str) -> Self {
+        PrehashedKey {
+            hash: hash_key(key),
+            key: key.to_owned(),
+        }
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.key
+    }
+}
+
+/// Immutable open-addressing table filled at build time, keeps the hash of every key
+/// so a probe compares hashes before strings; at most half of the slots are used.
+#[derive(Debug, Clone)]
+pub struct FrozenOntoIndex {
+    slots: Vec<Option<(u64, Box<str>, i64)>>,
+    len: usize,
+    prefixes: UriPrefixTrie,
+}
+
+impl FrozenOntoIndex {
+    pub fn new<'a, I>(entries: I) -> Self
+    where
+        I: IntoIterator<Item = (&'a str, i64)>,
+    {
+        let entries: Vec<(&str, i64)> = entries.into_iter().collect();
+        let mut slots: Vec<Option<(u64, Box<str>, i64)>> = vec![None; (entries.len() * 2).next_power_of_two()];
+        let mask = slots.len() - 1;
+        let mut len = 0;
+        let mut prefixes = UriPrefixTrie::default();
+
+        for (key, val) in entries {
+            prefixes.insert(key);
+            let hash = hash_key(key);
+            let mut i = hash as usize & mask;
+            loop {
+                match &mut slots[i] {
+                    Some((h, k, v)) if *h == hash && **k == *key => {
+                        *v = val;
+                        break;
+                    },
+                    Some(_) => i = (i + 1) & mask,
+                    slot @ None => {
+                        *slot = Some((hash, key.into(), val));
+                        len += 1;
+                        break;
+                    },
+                }
+            }
+        }
+
+        FrozenOntoIndex {
+            slots,
+            len,
+            prefixes,
+        }
+    }
+
+    fn lookup(&self, hash: u64, key: &str) -> Option<i64> {
+        let mask = self.slots.len() - 1;
+        let mut i = hash as usize & mask;
+        loop {
+            match &self.slots[i] {
+                Some((h, k, v)) if *h == hash && **k == *key => return Some(*v),
+                Some(_) => i = (i + 1) & mask,
+                None => return None,
+            }
+        }
+    }
+
+    pub fn get(&self, key: &str) -> Option<i64> {
+        self.lookup(hash_key(key), key)
+    }
+
+    /// Same as `get` without hashing the key again.
+    pub fn get_prehashed(&self, key: &PrehashedKey) -> Option<i64> {
+        self.lookup(key.hash, &key.key)
+    }
+
+    pub fn contains(&self, key: &str) -> bool {
+        self.get(key).is_some()
+    }
+
+    /// Keys starting with `prefix`, sorted, e.g. all terms of the `v-s:` namespace.
+    pub fn find_by_uri_prefix(&self, prefix: &str) -> Vec<&str> {
+        self.prefixes.find_by_prefix(prefix)
+    }
+
+    /// True if some key starts with `prefix`, in O(prefix length).
+    pub fn has_uri_prefix(&self, prefix: &str) -> bool {
+        self.prefixes.has_prefix(prefix)
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
 }
+
+#[derive(Debug, Clone, Default)]
+struct TrieNode {
+    /// Sorted by byte, so a depth-first walk yields keys in lexicographic order.
+    children: Vec<(u8, usize)>,
+    key: Option<usize>,
+}
+
+/// Byte trie over uris: a prefix is located in O(prefix length), then only its subtree is visited.
+#[derive(Debug, Clone)]
+pub struct UriPrefixTrie {
+    nodes: Vec<TrieNode>,
+    keys: Vec<Box<str>>,
+}
+
+impl Default for UriPrefixTrie {
+    fn default() -> Self {
+        UriPrefixTrie {
+            nodes: vec![TrieNode::default()],
+            keys: Vec::new(),
+        }
+    }
+}
+
+impl UriPrefixTrie {
+    pub fn new() -> Self {
+        UriPrefixTrie::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.keys.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+
+    pub fn insert(&mut self, key: &str) {
+        let mut node = 0;
+        for b in key.bytes() {
+            node = match self.nodes[node].children.binary_search_by_key(&b, |(c, _)| *c) {
+                Ok(pos) => self.nodes[node].children[pos].1,
+                Err(pos) => {
+                    let next = self.nodes.len();
+                    self.nodes.push(TrieNode::default());
+                    self.nodes[node].children.insert(pos, (b, next));
+                    next
+                },
+            };
+        }
+        if self.nodes[node].key.is_none() {
+            self.nodes[node].key = Some(self.keys.len());
+            self.keys.push(key.into());
+        }
+    }
+
+    fn find_node(&self, prefix: &str) -> Option<usize> {
+        let mut node = 0;
+        for b in prefix.bytes() {
+            let children = &self.nodes[node].children;
+            node = children[children.binary_search_by_key(&b, |(c, _)| *c).ok()?].1;
+        }
+        Some(node)
+    }
+
+    pub fn has_prefix(&self, prefix: &str) -> bool {
+        // every node lies on the path to at least one key
+        self.find_node(prefix).is_some() && !self.keys.is_empty()
+    }
+
+    pub fn find_by_prefix(&self, prefix: &str) -> Vec<&str> {
+        let mut res = Vec::new();
+        let mut stack: Vec<usize> = self.find_node(prefix).into_iter().collect();
+        while let Some(node) = stack.pop() {
+            if let Some(k) = self.nodes[node].key {
+                res.push(&*self.keys[k]);
+            }
+            stack.extend(self.nodes[node].children.iter().rev().map(|(_, next)| *next));
+        }
+        res
+    }
+}
+
+const _: fn() = || {
+    fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<OntoIndex>();
+    assert_send_sync::<FrozenOntoIndex>();
+};
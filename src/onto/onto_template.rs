@@ -0,0 +1,95 @@
+use crate::onto::individual::{Individual, IndividualObj};
+use crate::onto::onto_impl::{Onto, RelType};
+use crate::onto::resource::Resource;
+use std::collections::{BTreeMap, BTreeSet, HashSet, VecDeque};
+
+/// Defaults and required predicates declared for a class, by `v-ui:PropertySpecification`
+/// (`v-ui:forClass`) or by an `owl:Restriction` the class is a `rdfs:subClassOf`.
+#[derive(Debug, Default)]
+pub struct ClassTemplate {
+    pub defaults: BTreeMap<String, Vec<Resource>>,
+    /// Predicates with a minCardinality of at least 1.
+    pub required: BTreeSet<String>,
+}
+
+impl Clone for ClassTemplate {
+    fn clone(&self) -> Self {
+        ClassTemplate {
+            defaults: self.defaults.iter().map(|(p, v)| (p.to_owned(), v.iter().map(|r| r.get_copy()).collect())).collect(),
+            required: self.required.clone(),
+        }
+    }
+}
+
+impl Onto {
+    /// Called by `update` for `owl:Restriction` and `v-ui:PropertySpecification` individuals.
+    pub(crate) fn update_template(&mut self, indv: &mut Individual, property: &str, is_restriction: bool) {
+        let min = indv
+            .get_first_integer("owl:minCardinality")
+            .or_else(|| indv.get_first_integer("owl:cardinality"))
+            .or_else(|| indv.get_first_integer("v-ui:minCardinality"))
+            .unwrap_or(0);
+        let defaults = indv.get_resources("v-ui:defaultValue").unwrap_or_default();
+        if min < 1 && defaults.is_empty() {
+            return;
+        }
+
+        let classes = if is_restriction {
+            vec![indv.get_id().to_owned()]
+        } else {
+            indv.get_literals("v-ui:forClass").unwrap_or_default()
+        };
+
+        for class in classes {
+            let template = self.templates.entry(class).or_default();
+            if min >= 1 {
+                template.required.insert(property.to_owned());
+            }
+            if !defaults.is_empty() {
+                template.defaults.insert(property.to_owned(), defaults.iter().map(|r| r.get_copy()).collect());
+            }
+        }
+    }
+
+    /// The class and its superclasses, nearest first.
+    fn class_lineage(&self, class_uri: &str) -> Vec<String> {
+        let mut res = Vec::new();
+        let mut seen = HashSet::new();
+        let mut queue = VecDeque::from([class_uri.to_owned()]);
+        while let Some(class) = queue.pop_front() {
+            if !seen.insert(class.clone()) {
+                continue;
+            }
+            if let Some(rels) = self.relations.get(&class) {
+                let mut supers: Vec<&String> = rels.iter().filter(|(_, t)| **t == RelType::Super).map(|(x, _)| x).collect();
+                supers.sort();
+                queue.extend(supers.into_iter().cloned());
+            }
+            res.push(class);
+        }
+        res
+    }
+
+    /// New individual of the class without uri: `rdf:type`, the declared defaults and empty required predicates.
+    /// Templates of superclasses apply too, a default declared closer to the class wins.
+    pub fn instantiate(&self, class_uri: &str) -> IndividualObj {
+        let mut obj = IndividualObj::default();
+        obj.add_uri_unchecked("rdf:type", class_uri);
+
+        let mut required = BTreeSet::new();
+        for class in self.class_lineage(class_uri) {
+            if let Some(template) = self.templates.get(&class) {
+                for (predicate, values) in template.defaults.iter() {
+                    if !obj.resources.contains_key(predicate) {
+                        obj.add_resources(predicate, values);
+                    }
+                }
+                required.extend(template.required.iter());
+            }
+        }
+        for predicate in required {
+            obj.resources.entry(predicate.to_owned()).or_default();
+        }
+        obj
+    }
+}
@@ -0,0 +1,126 @@
+#[cfg(feature = "msgpack")]
+use crate::onto::individual::RawObj;
+use crate::onto::individual::{Individual, IndividualObj};
+#[cfg(feature = "msgpack")]
+use crate::onto::individual2msgpack::to_msgpack;
+#[cfg(feature = "msgpack")]
+use crate::onto::parser::parse_raw;
+
+/// An update of one individual, as carried by update messages.
+/// The codes are the ones used by the platform api for the same commands.
+#[derive(Debug)]
+pub enum IndividualOp {
+    /// Replaces the whole individual.
+    Put(IndividualObj),
+    /// Deletes the individual with this uri.
+    Remove(String),
+    /// Replaces the values of every predicate present in the delta.
+    SetIn(IndividualObj),
+    /// Adds the values of the delta that are not present yet.
+    AddTo(IndividualObj),
+    /// Removes the values of the delta, predicates left without values are dropped.
+    RemoveFrom(IndividualObj),
+}
+
+pub const OP_PUT: u8 = 1;
+pub const OP_SET_IN: u8 = 45;
+pub const OP_ADD_TO: u8 = 47;
+pub const OP_REMOVE_FROM: u8 = 48;
+pub const OP_REMOVE: u8 = 51;
+
+impl IndividualOp {
+    pub fn code(&self) -> u8 {
+        match self {
+            IndividualOp::Put(_) => OP_PUT,
+            IndividualOp::Remove(_) => OP_REMOVE,
+            IndividualOp::SetIn(_) => OP_SET_IN,
+            IndividualOp::AddTo(_) => OP_ADD_TO,
+            IndividualOp::RemoveFrom(_) => OP_REMOVE_FROM,
+        }
+    }
+
+    pub fn get_uri(&self) -> &str {
+        match self {
+            IndividualOp::Remove(uri) => uri,
+            IndividualOp::Put(obj) | IndividualOp::SetIn(obj) | IndividualOp::AddTo(obj) | IndividualOp::RemoveFrom(obj) => &obj.uri,
+        }
+    }
+
+    /// Writes the op code followed by the individual (only its uri for `Remove`) in msgpack.
+    #[cfg(feature = "msgpack")]
+    pub fn to_msgpack(&self, out: &mut Vec<u8>) -> Result<(), std::io::Error> {
+        out.push(self.code());
+        match self {
+            IndividualOp::Remove(uri) => {
+                let mut indv = Individual::default();
+                indv.set_id(uri);
+                to_msgpack(&indv, out)
+            },
+            IndividualOp::Put(obj) | IndividualOp::SetIn(obj) | IndividualOp::AddTo(obj) | IndividualOp::RemoveFrom(obj) => {
+                to_msgpack(&Individual::new_from_obj(obj), out)
+            },
+        }
+    }
+
+    #[cfg(feature = "msgpack")]
+    pub fn from_msgpack(data: &[u8]) -> Result<IndividualOp, String> {
+        let (code, raw) = data.split_first().ok_or("empty op")?;
+
+        let mut indv = Individual::new_raw(RawObj::new(raw.to_vec()));
+        if parse_raw(&mut indv).is_err() {
+            return Err(format!("fail parse individual of op {}", code));
+        }
+        indv.parse_all();
+        let obj = std::mem::take(&mut indv.obj);
+
+        match *code {
+            OP_PUT => Ok(IndividualOp::Put(obj)),
+            OP_REMOVE => Ok(IndividualOp::Remove(obj.uri)),
+            OP_SET_IN => Ok(IndividualOp::SetIn(obj)),
+            OP_ADD_TO => Ok(IndividualOp::AddTo(obj)),
+            OP_REMOVE_FROM => Ok(IndividualOp::RemoveFrom(obj)),
+            c => Err(format!("unknown op code {}", c)),
+        }
+    }
+}
+
+/// Applies the op to the individual. `Put` may target an empty individual, other ops must have the same uri.
+pub fn apply_op(obj: &mut IndividualObj, op: &IndividualOp) -> Result<(), String> {
+    let uri = op.get_uri();
+    if obj.uri != uri && !(matches!(op, IndividualOp::Put(_)) && obj.uri.is_empty()) {
+        return Err(format!("op for [{}] applied to [{}]", uri, obj.uri));
+    }
+
+    match op {
+        IndividualOp::Put(src) => {
+            obj.resources.clear();
+            for (predicate, values) in src.resources.iter() {
+                obj.set_resources(predicate, values);
+            }
+            obj.uri = src.uri.to_owned();
+        },
+        IndividualOp::Remove(_) => obj.resources.clear(),
+        IndividualOp::SetIn(delta) => {
+            for (predicate, values) in delta.resources.iter() {
+                obj.set_resources(predicate, values);
+            }
+        },
+        IndividualOp::AddTo(delta) => {
+            for (predicate, values) in delta.resources.iter() {
+                obj.add_unique_resources(predicate, values);
+            }
+        },
+        IndividualOp::RemoveFrom(delta) => {
+            for (predicate, values) in delta.resources.iter() {
+                obj.exclude_and_set_resources(predicate, values);
+            }
+        },
+    }
+    Ok(())
+}
+
+impl Individual {
+    pub fn apply_op(&mut self, op: &IndividualOp) -> Result<(), String> {
+        self.modify_any(|obj| apply_op(obj, op))
+    }
+}
@@ -1,6 +1,17 @@
+#[cfg(feature = "cbor")]
 use crate::onto::cbor2individual::{parse_cbor, parse_cbor_to_predicate};
+#[cfg(feature = "std")]
+use crate::onto::envelope::{is_envelope, EnvelopeError, KeyProvider, RawEnvelope, DEFAULT_MAX_INNER_LEN, EXT_RIGHTS};
 use crate::onto::individual::*;
+#[cfg(feature = "std")]
+use crate::onto::metrics::{Failure, Measure, Metrics, Operation};
+#[cfg(feature = "std")]
+use crate::onto::migrations::{is_versioned, Migrations};
+#[cfg(feature = "msgpack")]
 use crate::onto::msgpack2individual::*;
+#[cfg(feature = "std")]
+use crate::onto::rights::decode_rights;
+use alloc::string::String;
 
 #[derive(Eq, PartialEq, Debug)]
 pub enum RawType {
@@ -10,31 +21,172 @@ pub enum RawType {
     Unknown,
 }
 
-pub fn parse_to_predicate(expect_predicate: &str, iraw: &mut Individual) -> bool {
-    if iraw.raw.raw_type == RawType::Msgpack {
-        if let Err(e) = parse_msgpack_to_predicate(expect_predicate, iraw) {
-            if !e.is_empty() {
-                error!("parse for [{}], err={}", expect_predicate, e);
-            }
-            return false;
+impl RawType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RawType::Cbor => "cbor",
+            RawType::Json => "json",
+            RawType::Msgpack => "msgpack",
+            RawType::Unknown => "unknown",
         }
-        return true;
-    } else if iraw.raw.raw_type == RawType::Cbor {
-        if let Err(e) = parse_cbor_to_predicate(expect_predicate, iraw) {
-            if !e.is_empty() {
-                error!("parse for [{}], err={}", expect_predicate, e);
-            }
-            return false;
+    }
+}
+
+pub fn parse_to_predicate(expect_predicate: &str, iraw: &mut Individual) -> bool {
+    let res: Result<(), String> = match iraw.raw.raw_type {
+        #[cfg(feature = "msgpack")]
+        RawType::Msgpack => parse_msgpack_to_predicate(expect_predicate, iraw),
+        #[cfg(feature = "cbor")]
+        RawType::Cbor => parse_cbor_to_predicate(expect_predicate, iraw),
+        _ => Err(String::default()),
+    };
+
+    if let Err(e) = res {
+        if !e.is_empty() {
+            log_error!(uri = iraw.get_id(), predicate = expect_predicate, format = iraw.raw.raw_type.as_str(); "fail parse predicate, err={}", e);
         }
-        return true;
+        return false;
     }
+    true
+}
+
+pub(crate) const MSGPACK_MAGIC_HEADER: u8 = 146;
 
-    false
+/// `parse_raw` error: the envelope checksum does not match, the stored data is damaged.
+pub const PARSE_ERR_CORRUPTED: i8 = -3;
+
+/// Optional inputs of `parse_raw_with`.
+#[cfg(feature = "std")]
+#[derive(Default, Clone, Copy)]
+pub struct ParseOptions<'a> {
+    /// Gives the key for the key id of an encrypted envelope.
+    pub keys: Option<&'a KeyProvider>,
+    /// Upgrades records written in an older format version.
+    pub migrations: Option<&'a Migrations>,
+    /// Receives the measurements of this call instead of the global sink.
+    pub metrics: Option<&'a dyn Metrics>,
+    /// Largest unpacked envelope, `DEFAULT_MAX_INNER_LEN` when not set.
+    pub max_inner_len: Option<usize>,
 }
 
-const MSGPACK_MAGIC_HEADER: u8 = 146;
+#[cfg(feature = "std")]
+pub fn parse_raw(iraw: &mut Individual) -> Result<(), i8> {
+    parse_raw_with(iraw, &ParseOptions::default())
+}
 
+/// Without std only plain msgpack and CBOR records are read, envelopes and versioned records are not.
+#[cfg(not(feature = "std"))]
 pub fn parse_raw(iraw: &mut Individual) -> Result<(), i8> {
+    parse_record(iraw)
+}
+
+/// Same as `parse_raw`, encrypted envelopes are decrypted with the key returned by `keys` for the envelope key id.
+#[cfg(feature = "std")]
+pub fn parse_raw_with_keys(iraw: &mut Individual, keys: &KeyProvider) -> Result<(), i8> {
+    parse_raw_with(
+        iraw,
+        &ParseOptions {
+            keys: Some(keys),
+            ..Default::default()
+        },
+    )
+}
+
+#[cfg(feature = "std")]
+pub fn parse_raw_with(iraw: &mut Individual, opts: &ParseOptions) -> Result<(), i8> {
+    let measure = Measure::start(Operation::Parse, opts.metrics);
+    let size = iraw.raw.data.len();
+    let res = parse_raw_measured(iraw, opts);
+    let format = iraw.raw.raw_type.as_str();
+    match res {
+        Ok(()) => {
+            measure.ok(format, size);
+            Ok(())
+        },
+        Err((code, kind)) => {
+            measure.fail(format, kind);
+            Err(code)
+        },
+    }
+}
+
+/// Parses a record borrowed from storage, e.g. a memory-mapped LMDB page, without copying it into
+/// the individual first: msgpack is decoded from `data` in one pass and the individual keeps no raw
+/// buffer, so a later `to_msgpack` encodes it again. Envelopes, versioned records and CBOR are
+/// copied and parsed as by `parse_raw`, then fully.
+#[cfg(feature = "std")]
+pub fn parse_raw_slice(data: &[u8], iraw: &mut Individual) -> Result<(), i8> {
+    #[cfg(feature = "msgpack")]
+    if data.first() == Some(&MSGPACK_MAGIC_HEADER) {
+        let measure = Measure::start(Operation::Parse, None);
+        iraw.raw = RawObj::new_empty();
+        iraw.reset();
+        return match parse_msgpack_slice(data, &mut iraw.obj) {
+            Ok(()) => {
+                measure.ok(RawType::Msgpack.as_str(), data.len());
+                Ok(())
+            },
+            Err(e) => {
+                let e = if e.is_empty() {
+                    "truncated".to_owned()
+                } else {
+                    e
+                };
+                log_error!(uri = iraw.get_id(), format = RawType::Msgpack.as_str(); "fail parse, err={}", e);
+                measure.fail(RawType::Msgpack.as_str(), Failure::Decode);
+                Err(-1)
+            },
+        };
+    }
+
+    iraw.raw = RawObj::new(data.to_vec());
+    parse_raw(iraw)?;
+    iraw.parse_all();
+    Ok(())
+}
+
+#[cfg(feature = "std")]
+fn parse_raw_measured(iraw: &mut Individual, opts: &ParseOptions) -> Result<(), (i8, Failure)> {
+    let mut rights = None;
+    if is_envelope(&iraw.raw.data) {
+        let unpacked = RawEnvelope::decode(&iraw.raw.data).and_then(|envelope| {
+            rights = envelope.extension(EXT_RIGHTS).map(decode_rights).transpose()?;
+            envelope.unpack_limited(opts.keys.unwrap_or(&|_| None), opts.max_inner_len.unwrap_or(DEFAULT_MAX_INNER_LEN))
+        });
+        match unpacked {
+            Ok(data) => iraw.raw.data = data,
+            Err(e) => {
+                log_error!(format = "envelope"; "parse_raw: {}", e);
+                return match e {
+                    EnvelopeError::Corrupted {
+                        ..
+                    } => Err((PARSE_ERR_CORRUPTED, Failure::Corrupted)),
+                    _ => Err((-1, Failure::Envelope)),
+                };
+            },
+        }
+    }
+
+    if is_versioned(&iraw.raw.data) {
+        let no_migrations = Migrations::default();
+        match opts.migrations.unwrap_or(&no_migrations).upgrade(&iraw.raw.data) {
+            Ok(data) => iraw.raw.data = data,
+            Err(e) => {
+                log_error!(format = "versioned"; "parse_raw: {}", e);
+                return Err((-1, Failure::Migration));
+            },
+        }
+    }
+
+    parse_record(iraw).map_err(|code| (code, Failure::Decode))?;
+    if let Some(rights) = rights {
+        iraw.obj.rights = rights;
+    }
+    Ok(())
+}
+
+/// Reads the header of the msgpack or CBOR record in the raw buffer, the predicates are read on demand.
+fn parse_record(iraw: &mut Individual) -> Result<(), i8> {
     if iraw.raw.data.is_empty() {
         return Ok(());
     }
@@ -50,16 +202,14 @@ pub fn parse_raw(iraw: &mut Individual) -> Result<(), i8> {
         iraw.raw.raw_type = RawType::Cbor;
     }
 
-    let res = if iraw.raw.raw_type == RawType::Msgpack {
-        parse_msgpack(&mut iraw.raw)
-    } else {
-        parse_cbor(&mut iraw.raw)
+    let res: Result<String, i8> = match iraw.raw.raw_type {
+        #[cfg(feature = "msgpack")]
+        RawType::Msgpack => parse_msgpack(&mut iraw.raw),
+        #[cfg(feature = "cbor")]
+        RawType::Cbor => parse_cbor(&mut iraw.raw),
+        _ => Err(-1),
     };
 
-    if let Ok(uri) = res {
-        iraw.obj.uri = uri;
-        return Ok(());
-    }
-
-    Err(-1)
+    iraw.obj.uri = res.map_err(|_| -1)?;
+    Ok(())
 }
@@ -0,0 +1,284 @@
+use crate::onto::individual::{Individual, IndividualObj};
+use crate::onto::individual_store::IndividualStoreInMem;
+use crate::onto::op::{apply_op, IndividualOp};
+use crate::onto::versioning::VersionConflict;
+#[cfg(feature = "msgpack")]
+use rmp::{decode, encode};
+use std::collections::HashMap;
+use std::fmt;
+
+/// One change of a bundle: an update op, the deltas it must be applied after, and optionally
+/// the version (`v-s:updateCounter`) the client edited.
+#[derive(Debug)]
+pub struct IndividualDelta {
+    pub id: String,
+    pub op: IndividualOp,
+    pub depends_on: Vec<String>,
+    pub base_version: Option<i64>,
+}
+
+impl IndividualDelta {
+    pub fn new(id: &str, op: IndividualOp) -> Self {
+        IndividualDelta {
+            id: id.to_owned(),
+            op,
+            depends_on: Vec::new(),
+            base_version: None,
+        }
+    }
+
+    pub fn with_depends_on(mut self, id: &str) -> Self {
+        self.depends_on.push(id.to_owned());
+        self
+    }
+
+    /// Rejects the bundle if the individual is no longer at `version` when the delta applies.
+    pub fn with_base_version(mut self, version: i64) -> Self {
+        self.base_version = Some(version);
+        self
+    }
+}
+
+/// A batch of changes of an offline client, applied all or nothing. Deltas apply in bundle
+/// order, except that a delta waits for the deltas it depends on.
+#[derive(Debug, Default)]
+pub struct PatchBundle {
+    pub id: String,
+    pub deltas: Vec<IndividualDelta>,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum PatchError {
+    DuplicateDelta(String),
+    UnknownDependency {
+        delta: String,
+        depends_on: String,
+    },
+    /// The delta depends on itself through other deltas.
+    DependencyCycle(String),
+    Conflict {
+        delta: String,
+        conflict: VersionConflict,
+    },
+    Op {
+        delta: String,
+        msg: String,
+    },
+    Decode(String),
+}
+
+impl fmt::Display for PatchError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PatchError::DuplicateDelta(id) => write!(f, "duplicate delta [{}]", id),
+            PatchError::UnknownDependency {
+                delta,
+                depends_on,
+            } => write!(f, "delta [{}] depends on unknown delta [{}]", delta, depends_on),
+            PatchError::DependencyCycle(id) => write!(f, "dependency cycle at delta [{}]", id),
+            PatchError::Conflict {
+                delta,
+                conflict,
+            } => write!(f, "delta [{}]: {}", delta, conflict),
+            PatchError::Op {
+                delta,
+                msg,
+            } => write!(f, "delta [{}]: {}", delta, msg),
+            PatchError::Decode(msg) => write!(f, "fail decode patch bundle: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for PatchError {}
+
+impl PatchBundle {
+    pub fn new(id: &str) -> Self {
+        PatchBundle {
+            id: id.to_owned(),
+            deltas: Vec::new(),
+        }
+    }
+
+    pub fn with_delta(mut self, delta: IndividualDelta) -> Self {
+        self.deltas.push(delta);
+        self
+    }
+
+    /// Checks ids and dependencies, returns the indexes of the deltas in application order.
+    pub fn validate(&self) -> Result<Vec<usize>, PatchError> {
+        let mut index: HashMap<&str, usize> = HashMap::new();
+        for (idx, delta) in self.deltas.iter().enumerate() {
+            if index.insert(&delta.id, idx).is_some() {
+                return Err(PatchError::DuplicateDelta(delta.id.to_owned()));
+            }
+        }
+        for delta in self.deltas.iter() {
+            if let Some(dep) = delta.depends_on.iter().find(|dep| !index.contains_key(dep.as_str())) {
+                return Err(PatchError::UnknownDependency {
+                    delta: delta.id.to_owned(),
+                    depends_on: dep.to_owned(),
+                });
+            }
+        }
+
+        // the first delta in bundle order whose dependencies are applied goes next
+        let mut order = Vec::with_capacity(self.deltas.len());
+        let mut applied = vec![false; self.deltas.len()];
+        while order.len() < self.deltas.len() {
+            let next = (0..self.deltas.len()).find(|idx| !applied[*idx] && self.deltas[*idx].depends_on.iter().all(|dep| applied[index[dep.as_str()]]));
+            match next {
+                Some(idx) => {
+                    applied[idx] = true;
+                    order.push(idx);
+                },
+                None => {
+                    let stuck = applied.iter().position(|a| !a).unwrap_or_default();
+                    return Err(PatchError::DependencyCycle(self.deltas[stuck].id.to_owned()));
+                },
+            }
+        }
+        Ok(order)
+    }
+
+    /// Applies the bundle to the store, the store is left unchanged on error.
+    pub fn apply(&self, store: &mut IndividualStoreInMem) -> Result<(), PatchError> {
+        let order = self.validate()?;
+
+        // individuals touched by the bundle, None once removed
+        let mut staged: HashMap<String, Option<IndividualObj>> = HashMap::new();
+        for idx in order {
+            let delta = &self.deltas[idx];
+            let uri = delta.op.get_uri();
+            let current = staged.entry(uri.to_owned()).or_insert_with(|| store.get(uri).map(|indv| Individual::new_from_obj(indv.get_obj()).into_obj()));
+
+            if let Some(version) = delta.base_version {
+                let res = match current {
+                    Some(obj) => obj.expect_version(version),
+                    None => IndividualObj {
+                        uri: uri.to_owned(),
+                        ..Default::default()
+                    }
+                    .expect_version(version),
+                };
+                res.map_err(|conflict| PatchError::Conflict {
+                    delta: delta.id.to_owned(),
+                    conflict,
+                })?;
+            }
+
+            match &delta.op {
+                IndividualOp::Remove(_) => *current = None,
+                op => {
+                    let mut obj = current.take().unwrap_or_else(|| IndividualObj {
+                        uri: uri.to_owned(),
+                        ..Default::default()
+                    });
+                    apply_op(&mut obj, op).map_err(|msg| PatchError::Op {
+                        delta: delta.id.to_owned(),
+                        msg,
+                    })?;
+                    // as in `IndividualStoreInMem::apply_op`, an op leaving a new individual empty does not create it
+                    *current = (!obj.resources.is_empty() || store.contains(uri)).then_some(obj);
+                },
+            }
+        }
+
+        for (uri, obj) in staged {
+            match obj {
+                Some(obj) => {
+                    store.insert(Individual::from_obj(obj));
+                },
+                None => {
+                    store.remove(&uri);
+                },
+            }
+        }
+        Ok(())
+    }
+
+    /// Writes the bundle as a msgpack array `[id, [[delta id, [depends on], base version or nil, op], ...]]`,
+    /// the op as binary in `IndividualOp::to_msgpack` form.
+    #[cfg(feature = "msgpack")]
+    pub fn to_msgpack(&self, out: &mut Vec<u8>) -> Result<(), std::io::Error> {
+        encode::write_array_len(out, 2)?;
+        encode::write_str(out, &self.id)?;
+        encode::write_array_len(out, self.deltas.len() as u32)?;
+        let mut op = Vec::new();
+        for delta in self.deltas.iter() {
+            encode::write_array_len(out, 4)?;
+            encode::write_str(out, &delta.id)?;
+            encode::write_array_len(out, delta.depends_on.len() as u32)?;
+            for dep in delta.depends_on.iter() {
+                encode::write_str(out, dep)?;
+            }
+            match delta.base_version {
+                Some(v) => {
+                    encode::write_sint(out, v)?;
+                },
+                None => encode::write_nil(out)?,
+            }
+            op.clear();
+            delta.op.to_msgpack(&mut op)?;
+            encode::write_bin(out, &op)?;
+        }
+        Ok(())
+    }
+
+    #[cfg(feature = "msgpack")]
+    pub fn from_msgpack(data: &[u8]) -> Result<PatchBundle, PatchError> {
+        let rd = &mut &data[..];
+        expect_array(rd, 2)?;
+        let mut bundle = PatchBundle::new(&read_string(rd)?);
+        let count = decode::read_array_len(rd).map_err(decode_err)?;
+        for _ in 0..count {
+            expect_array(rd, 4)?;
+            let id = read_string(rd)?;
+            let deps = decode::read_array_len(rd).map_err(decode_err)?;
+            let depends_on = (0..deps).map(|_| read_string(rd)).collect::<Result<Vec<String>, PatchError>>()?;
+            let base_version = if rd.first() == Some(&0xc0) {
+                *rd = &rd[1..];
+                None
+            } else {
+                Some(decode::read_int::<i64, _>(rd).map_err(decode_err)?)
+            };
+            let len = decode::read_bin_len(rd).map_err(decode_err)?;
+            let op = IndividualOp::from_msgpack(take(rd, len)?).map_err(PatchError::Decode)?;
+            bundle.deltas.push(IndividualDelta {
+                id,
+                op,
+                depends_on,
+                base_version,
+            });
+        }
+        Ok(bundle)
+    }
+}
+
+#[cfg(feature = "msgpack")]
+fn decode_err<E: fmt::Display>(e: E) -> PatchError {
+    PatchError::Decode(e.to_string())
+}
+
+#[cfg(feature = "msgpack")]
+fn expect_array(rd: &mut &[u8], len: u32) -> Result<(), PatchError> {
+    match decode::read_array_len(rd).map_err(decode_err)? {
+        l if l == len => Ok(()),
+        l => Err(PatchError::Decode(format!("array of {} items, expected {}", l, len))),
+    }
+}
+
+#[cfg(feature = "msgpack")]
+fn take<'a>(rd: &mut &'a [u8], len: u32) -> Result<&'a [u8], PatchError> {
+    if rd.len() < len as usize {
+        return Err(PatchError::Decode("truncated".to_owned()));
+    }
+    let (head, tail) = rd.split_at(len as usize);
+    *rd = tail;
+    Ok(head)
+}
+
+#[cfg(feature = "msgpack")]
+fn read_string(rd: &mut &[u8]) -> Result<String, PatchError> {
+    let len = decode::read_str_len(rd).map_err(decode_err)?;
+    String::from_utf8(take(rd, len)?.to_vec()).map_err(decode_err)
+}
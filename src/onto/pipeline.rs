@@ -0,0 +1,276 @@
+use crate::onto::datatype::DataType;
+use crate::onto::datatype_registry::DatatypeRegistry;
+use crate::onto::individual::{Individual, IndividualObj};
+use crate::onto::normalize::NormalizePolicy;
+use crate::onto::onto_impl::Onto;
+#[cfg(feature = "redaction")]
+use crate::onto::redaction::RedactionPolicy;
+use crate::onto::resource::Value;
+use crate::onto::sanitize::SanitizePolicy;
+use std::fmt;
+use std::sync::Arc;
+
+/// One step of a `Pipeline`.
+pub trait Stage: Send + Sync {
+    /// Name in reports and errors.
+    fn name(&self) -> &str;
+
+    /// Changes `obj` in place and returns the number of changes, or rejects it with the reasons.
+    fn apply(&self, obj: &mut IndividualObj) -> Result<usize, Vec<String>>;
+}
+
+/// An individual rejected by a stage.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PipelineError {
+    pub uri: String,
+    pub stage: String,
+    pub errors: Vec<String>,
+}
+
+impl fmt::Display for PipelineError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "[{}]: rejected by {}: {}", self.uri, self.stage, self.errors.join("; "))
+    }
+}
+
+impl std::error::Error for PipelineError {}
+
+/// Changes made by each stage, in the order of the pipeline.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PipelineReport {
+    pub stages: Vec<(String, usize)>,
+}
+
+impl PipelineReport {
+    pub fn changes(&self) -> usize {
+        self.stages.iter().map(|(_, n)| n).sum()
+    }
+}
+
+/// Stages applied in order to individuals before they are stored, so consumer services share one
+/// ingest definition:
+///
+/// ```text
+/// let pipeline = Pipeline::new()
+///     .with_stage(Arc::new(Normalize(NormalizePolicy::new(StringPolicy::normalized_string()))))
+///     .with_stage(Arc::new(CoerceTypes(registry)))
+///     .with_stage(Arc::new(Dedup))
+///     .with_stage(Arc::new(Validate::new().with_onto(onto)));
+/// pipeline.run_individual(&mut indv)?;
+/// ```
+///
+/// A rejected individual keeps the changes of the stages before the one rejecting it.
+#[derive(Clone, Default)]
+pub struct Pipeline {
+    stages: Vec<Arc<dyn Stage>>,
+}
+
+impl fmt::Debug for Pipeline {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_list().entries(self.stages.iter().map(|s| s.name())).finish()
+    }
+}
+
+impl Pipeline {
+    pub fn new() -> Self {
+        Pipeline::default()
+    }
+
+    pub fn with_stage(mut self, stage: Arc<dyn Stage>) -> Self {
+        self.stages.push(stage);
+        self
+    }
+
+    /// A stage given as a closure.
+    pub fn with_fn<F>(self, name: &str, f: F) -> Self
+    where
+        F: Fn(&mut IndividualObj) -> Result<usize, Vec<String>> + Send + Sync + 'static,
+    {
+        self.with_stage(Arc::new(FnStage {
+            name: name.to_owned(),
+            f,
+        }))
+    }
+
+    pub fn run(&self, obj: &mut IndividualObj) -> Result<PipelineReport, PipelineError> {
+        let mut report = PipelineReport::default();
+        for stage in self.stages.iter() {
+            match stage.apply(obj) {
+                Ok(n) => report.stages.push((stage.name().to_owned(), n)),
+                Err(errors) => {
+                    return Err(PipelineError {
+                        uri: obj.uri.clone(),
+                        stage: stage.name().to_owned(),
+                        errors,
+                    })
+                },
+            }
+        }
+        Ok(report)
+    }
+
+    /// Same after `parse_all()`; the individual is marked modified when a stage changed it, and its
+    /// observers get the changed predicates.
+    pub fn run_individual(&self, indv: &mut Individual) -> Result<PipelineReport, PipelineError> {
+        indv.modify_any(|obj| self.run(obj))
+    }
+}
+
+struct FnStage<F> {
+    name: String,
+    f: F,
+}
+
+impl<F> Stage for FnStage<F>
+where
+    F: Fn(&mut IndividualObj) -> Result<usize, Vec<String>> + Send + Sync,
+{
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn apply(&self, obj: &mut IndividualObj) -> Result<usize, Vec<String>> {
+        (self.f)(obj)
+    }
+}
+
+/// Trims and normalizes string values, see `normalize_strings`.
+pub struct Normalize(pub NormalizePolicy);
+
+impl Stage for Normalize {
+    fn name(&self) -> &str {
+        "normalize"
+    }
+
+    fn apply(&self, obj: &mut IndividualObj) -> Result<usize, Vec<String>> {
+        Ok(obj.normalize_strings(&self.0))
+    }
+}
+
+/// Cleans rich-text values, counts the changed predicates.
+pub struct Sanitize(pub SanitizePolicy);
+
+impl Stage for Sanitize {
+    fn name(&self) -> &str {
+        "sanitize"
+    }
+
+    fn apply(&self, obj: &mut IndividualObj) -> Result<usize, Vec<String>> {
+        Ok(self.0.apply(obj).len())
+    }
+}
+
+/// Converts string values of predicates bound to a non-string datatype of the registry, e.g. "42"
+/// of a predicate ranged `xsd:integer`, to values of that datatype. Strings that do not parse
+/// reject the individual.
+pub struct CoerceTypes(pub DatatypeRegistry);
+
+impl Stage for CoerceTypes {
+    fn name(&self) -> &str {
+        "coerce"
+    }
+
+    fn apply(&self, obj: &mut IndividualObj) -> Result<usize, Vec<String>> {
+        let (mut count, mut errors) = (0, Vec::new());
+        for (predicate, values) in obj.resources.iter_mut() {
+            let Some(def) = self.0.get_bound(predicate).filter(|def| *def.base() != DataType::String) else {
+                continue;
+            };
+            for r in values.iter_mut() {
+                let Value::Str(s, _) = &r.value else {
+                    continue;
+                };
+                match def.parse(s) {
+                    Ok(mut parsed) => {
+                        parsed.order = r.order;
+                        *r = parsed;
+                        count += 1;
+                    },
+                    Err(e) => errors.push(format!("predicate [{}], {:?} is not {}: {}", predicate, s, def.iri(), e)),
+                }
+            }
+        }
+        if errors.is_empty() {
+            Ok(count)
+        } else {
+            Err(errors)
+        }
+    }
+}
+
+/// Removes repeated values of every predicate, see `dedup_values`.
+pub struct Dedup;
+
+impl Stage for Dedup {
+    fn name(&self) -> &str {
+        "dedup"
+    }
+
+    fn apply(&self, obj: &mut IndividualObj) -> Result<usize, Vec<String>> {
+        let predicates: Vec<String> = obj.resources.keys().cloned().collect();
+        Ok(predicates.iter().map(|p| obj.dedup_values(p)).sum())
+    }
+}
+
+/// Rejects individuals breaking the cardinality and ranges of the ontology (`check_cardinality`,
+/// `check_ranges`) or the checks of the datatype registry (`validate_datatypes`). Changes nothing.
+#[derive(Clone, Default)]
+pub struct Validate {
+    onto: Option<Arc<Onto>>,
+    registry: Option<Arc<DatatypeRegistry>>,
+}
+
+impl Validate {
+    pub fn new() -> Self {
+        Validate::default()
+    }
+
+    pub fn with_onto(mut self, onto: Arc<Onto>) -> Self {
+        self.onto = Some(onto);
+        self
+    }
+
+    pub fn with_registry(mut self, registry: Arc<DatatypeRegistry>) -> Self {
+        self.registry = Some(registry);
+        self
+    }
+}
+
+impl Stage for Validate {
+    fn name(&self) -> &str {
+        "validate"
+    }
+
+    fn apply(&self, obj: &mut IndividualObj) -> Result<usize, Vec<String>> {
+        let mut errors = Vec::new();
+        if let Some(onto) = &self.onto {
+            errors.extend(obj.check_cardinality(onto).iter().map(|e| e.to_string()));
+            errors.extend(obj.check_ranges(onto).iter().map(|e| e.to_string()));
+        }
+        if let Some(registry) = &self.registry {
+            errors.extend(obj.validate_datatypes(registry).iter().map(|e| e.to_string()));
+        }
+        if errors.is_empty() {
+            Ok(0)
+        } else {
+            Err(errors)
+        }
+    }
+}
+
+/// Replaces the individual by its redacted copy, counts the redacted predicates.
+#[cfg(feature = "redaction")]
+pub struct Redact(pub RedactionPolicy);
+
+#[cfg(feature = "redaction")]
+impl Stage for Redact {
+    fn name(&self) -> &str {
+        "redact"
+    }
+
+    fn apply(&self, obj: &mut IndividualObj) -> Result<usize, Vec<String>> {
+        let (redacted, report) = self.0.apply(obj);
+        *obj = redacted;
+        Ok(report.dropped.len() + report.hashed.len() + report.masked.len())
+    }
+}
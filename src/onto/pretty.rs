@@ -0,0 +1,57 @@
+use crate::onto::datatype::Lang;
+use crate::onto::individual::{Individual, IndividualObj, ValueFmt};
+use crate::onto::onto_impl::Onto;
+use crate::onto::resource::Value;
+use std::fmt::Write;
+
+/// A term as `"Документ" (v-s:Document)`, the uri alone if the ontology has no label for it.
+fn term(onto: &Onto, uri: &str, lang: &Lang) -> String {
+    match onto.get_label(uri, lang) {
+        Some(label) => format!("{:?} ({})", label, uri),
+        None => uri.to_owned(),
+    }
+}
+
+impl IndividualObj {
+    /// Human-readable block for logs and debugging, with the labels of the ontology in `lang`
+    /// instead of bare uris, one predicate per line:
+    ///
+    /// ```text
+    /// d:doc1 "Договор поставки"
+    ///   rdf:type: "Документ" (v-s:Document)
+    ///   "Автор" (v-s:author): "Иванов И.И." (d:person1)
+    ///   rdfs:label: "Договор поставки"@ru, "Supply contract"@en
+    /// ```
+    ///
+    /// The first line has the label of the individual itself. Links resolve to labels of
+    /// individuals the ontology knows (`Onto::get_label`), other values are written as by `{:?}`.
+    pub fn pretty(&self, onto: &Onto, lang: &Lang) -> String {
+        let mut res = self.uri.to_owned();
+        let labels = self.get_multilang("rdfs:label");
+        if let Some(label) = labels.get_lang(lang).or_else(|| labels.get_lang(&Lang::none())).or_else(|| labels.get_or_first(&[])) {
+            let _ = write!(res, " {:?}", label);
+        }
+        for (predicate, values) in self.resources.iter() {
+            let _ = write!(res, "\n  {}: ", term(onto, predicate, lang));
+            for (idx, r) in values.iter().enumerate() {
+                if idx > 0 {
+                    res.push_str(", ");
+                }
+                match &r.value {
+                    Value::Uri(uri) => res.push_str(&term(onto, uri, lang)),
+                    v => {
+                        let _ = write!(res, "{}", ValueFmt(v));
+                    },
+                }
+            }
+        }
+        res
+    }
+}
+
+impl Individual {
+    pub fn pretty(&mut self, onto: &Onto, lang: &Lang) -> String {
+        self.parse_all();
+        self.get_obj().pretty(onto, lang)
+    }
+}
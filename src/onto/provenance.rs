@@ -0,0 +1,161 @@
+use crate::onto::individual::{Individual, IndividualObj};
+use crate::onto::iri::normalize_iri;
+use std::collections::HashMap;
+
+pub const PROV_PREFIX: &str = "prov";
+pub const PROV_NS: &str = "http://www.w3.org/ns/prov#";
+
+pub const WAS_DERIVED_FROM: &str = "prov:wasDerivedFrom";
+pub const WAS_GENERATED_BY: &str = "prov:wasGeneratedBy";
+pub const GENERATED_AT_TIME: &str = "prov:generatedAtTime";
+pub const WAS_ATTRIBUTED_TO: &str = "prov:wasAttributedTo";
+pub const ACTIVITY_TYPE: &str = "prov:Activity";
+pub const ENDED_AT_TIME: &str = "prov:endedAtTime";
+pub const WAS_ASSOCIATED_WITH: &str = "prov:wasAssociatedWith";
+
+/// PROV-O lineage of individuals produced by one activity: an import, a migration, a report run.
+///
+/// ```text
+/// let prov = Provenance::new("d:import_2024_05").with_generated_at(now).with_agent("cfg:Importer");
+/// let copy = prov.derive(src.get_obj(), "d:copy1")?;
+/// to_turtle(&prov.annotate_all(indvs)?, &prefixes)
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Provenance {
+    activity: String,
+    generated_at: Option<i64>,
+    agent: Option<String>,
+    derived_from: Vec<String>,
+}
+
+impl Provenance {
+    /// `activity` is the uri of the `prov:Activity` the individuals are generated by.
+    pub fn new(activity: &str) -> Self {
+        Provenance {
+            activity: activity.to_owned(),
+            ..Default::default()
+        }
+    }
+
+    /// `prov:generatedAtTime`, in seconds.
+    pub fn with_generated_at(mut self, timestamp: i64) -> Self {
+        self.generated_at = Some(timestamp);
+        self
+    }
+
+    /// `prov:wasAttributedTo` of the individuals and `prov:wasAssociatedWith` of the activity.
+    pub fn with_agent(mut self, agent: &str) -> Self {
+        self.agent = Some(agent.to_owned());
+        self
+    }
+
+    /// Source of every annotated individual, in addition to the one of `derive`.
+    pub fn with_derived_from(mut self, uri: &str) -> Self {
+        self.derived_from.push(uri.to_owned());
+        self
+    }
+
+    /// The same with normalized uris, an error names the first malformed one.
+    fn normalized(&self) -> Result<Provenance, String> {
+        Ok(Provenance {
+            activity: normalize_iri(&self.activity)?,
+            generated_at: self.generated_at,
+            agent: self.agent.as_deref().map(normalize_iri).transpose()?,
+            derived_from: self.derived_from.iter().map(|uri| normalize_iri(uri)).collect::<Result<_, _>>()?,
+        })
+    }
+
+    /// Sets the activity, time and agent of `obj`; sources are added to the ones it has. A malformed
+    /// uri of the provenance is an error and `obj` is left unchanged.
+    pub fn annotate(&self, obj: &mut IndividualObj) -> Result<(), String> {
+        self.normalized()?.write(obj);
+        Ok(())
+    }
+
+    fn write(&self, obj: &mut IndividualObj) {
+        obj.set_uri_unchecked(WAS_GENERATED_BY, &self.activity);
+        match self.generated_at {
+            Some(t) => obj.set_datetime(GENERATED_AT_TIME, t),
+            None => {
+                obj.remove(GENERATED_AT_TIME);
+            },
+        }
+        match &self.agent {
+            Some(agent) => obj.set_uri_unchecked(WAS_ATTRIBUTED_TO, agent),
+            None => {
+                obj.remove(WAS_ATTRIBUTED_TO);
+            },
+        }
+        for uri in self.derived_from.iter() {
+            add_derived_from(obj, uri);
+        }
+    }
+
+    /// A copy of `src` as `uri`, derived from `src`; the provenance of `src` is not copied.
+    pub fn derive(&self, src: &IndividualObj, uri: &str) -> Result<IndividualObj, String> {
+        let prov = self.normalized()?;
+        let mut obj = IndividualObj {
+            uri: uri.to_owned(),
+            ..Default::default()
+        };
+        for (predicate, values) in src.resources.iter() {
+            if !predicate.starts_with("prov:") {
+                obj.resources.insert(predicate.to_owned(), values.iter().map(|r| r.get_copy()).collect());
+            }
+        }
+        prov.write(&mut obj);
+        add_derived_from(&mut obj, &src.uri);
+        Ok(obj)
+    }
+
+    /// The `prov:Activity` individual, ended at the generation time.
+    pub fn activity(&self) -> Result<IndividualObj, String> {
+        let prov = self.normalized()?;
+        let mut obj = IndividualObj {
+            uri: prov.activity.to_owned(),
+            ..Default::default()
+        };
+        obj.set_uri_unchecked("rdf:type", ACTIVITY_TYPE);
+        if let Some(t) = prov.generated_at {
+            obj.set_datetime(ENDED_AT_TIME, t);
+        }
+        if let Some(agent) = &prov.agent {
+            obj.set_uri_unchecked(WAS_ASSOCIATED_WITH, agent);
+        }
+        Ok(obj)
+    }
+
+    /// Annotates the individuals of an export and appends the activity.
+    pub fn annotate_all(&self, indvs: Vec<Individual>) -> Result<Vec<Individual>, String> {
+        let prov = self.normalized()?;
+        let mut res: Vec<Individual> = indvs
+            .into_iter()
+            .map(|mut indv| {
+                indv.modify_any(|obj| prov.write(obj));
+                indv
+            })
+            .collect();
+        res.push(Individual::from_obj(prov.activity()?));
+        Ok(res)
+    }
+}
+
+fn add_derived_from(obj: &mut IndividualObj, uri: &str) {
+    let known = obj.resources.get(WAS_DERIVED_FROM).is_some_and(|values| values.iter().any(|r| r.get_uri() == uri));
+    if !known {
+        obj.add_uri_unchecked(WAS_DERIVED_FROM, uri);
+    }
+}
+
+impl Individual {
+    pub fn annotate_provenance(&mut self, prov: &Provenance) -> Result<(), String> {
+        let prov = prov.normalized()?;
+        self.modify_any(|obj| prov.write(obj));
+        Ok(())
+    }
+}
+
+/// Adds the `prov` prefix, for Turtle exports of annotated individuals.
+pub fn add_prov_prefix(all_prefixes: &mut HashMap<String, String>) {
+    all_prefixes.entry(PROV_PREFIX.to_owned()).or_insert_with(|| PROV_NS.to_owned());
+}
@@ -0,0 +1,229 @@
+// The pyo3 0.22 method macros expand into conversions recent clippy reports as useless.
+#![allow(clippy::useless_conversion)]
+
+use crate::onto::datatype::Lang;
+use crate::onto::decimal::to_decimal;
+use crate::onto::individual::{Individual, RawObj};
+use crate::onto::individual2cbor::to_cbor;
+use crate::onto::individual2msgpack::to_msgpack;
+use crate::onto::individual2turtle::to_turtle;
+use crate::onto::json2individual::{json_from_slice, parse_json_to_individual};
+use crate::onto::parser::parse_raw;
+use crate::onto::resource::{Resource, Value};
+use chrono::{DateTime, TimeZone, Utc};
+use pyo3::exceptions::{PyKeyError, PyTypeError, PyValueError};
+use pyo3::prelude::*;
+use pyo3::types::{PyBool, PyBytes, PyFloat, PyInt, PyList, PyString, PyTuple};
+use rust_decimal::Decimal;
+use std::collections::HashMap;
+use std::str::FromStr;
+
+#[pyclass(name = "Individual")]
+pub struct PyIndividual {
+    indv: Individual,
+}
+
+fn resource_to_py(py: Python<'_>, r: &Resource) -> PyObject {
+    match &r.value {
+        Value::Int(i) => i.into_py(py),
+        Value::Bool(b) => b.into_py(py),
+        Value::Uri(s) => s.into_py(py),
+        Value::Str(s, l) => {
+            if l.is_some() {
+                (s, l.to_string()).into_py(py)
+            } else {
+                s.into_py(py)
+            }
+        },
+        Value::Num(m, e) => match to_decimal(*m, *e) {
+            Ok(d) => d.into_py(py),
+            Err(_) => (*m as f64 * 10f64.powf(*e as f64)).into_py(py),
+        },
+        Value::Datetime(i) => match Utc.timestamp_opt(*i, 0).single() {
+            Some(dt) => dt.into_py(py),
+            None => i.into_py(py),
+        },
+        Value::Binary(v) => PyBytes::new_bound(py, v).into_py(py),
+    }
+}
+
+fn add_py_value(indv: &mut Individual, predicate: &str, v: &Bound<'_, PyAny>) -> PyResult<()> {
+    if v.is_instance_of::<PyBool>() {
+        indv.add_bool(predicate, v.extract()?);
+    } else if v.is_instance_of::<PyInt>() {
+        indv.add_integer(predicate, v.extract()?);
+    } else if v.is_instance_of::<PyFloat>() {
+        indv.add_decimal_from_f64(predicate, v.extract()?);
+    } else if let Ok(s) = v.downcast::<PyString>() {
+        indv.add_string(predicate, s.to_str()?, Lang::none());
+    } else if let Ok(b) = v.downcast::<PyBytes>() {
+        indv.add_binary(predicate, b.as_bytes().to_vec());
+    } else if let Ok(t) = v.downcast::<PyTuple>() {
+        let (s, l): (String, String) = t.extract()?;
+        indv.add_string(predicate, &s, Lang::new_from_str(&l));
+    } else if let Ok(dt) = v.extract::<DateTime<Utc>>() {
+        indv.add_datetime(predicate, dt.timestamp());
+    } else if let Ok(d) = v.extract::<Decimal>() {
+        indv.add_decimal_from_str(predicate, &d.to_string());
+    } else {
+        return Err(PyTypeError::new_err(format!("unsupported value type for predicate [{}]", predicate)));
+    }
+    Ok(())
+}
+
+fn from_raw(data: &[u8]) -> PyResult<PyIndividual> {
+    let mut indv = Individual::new_raw(RawObj::new(data.to_vec()));
+    if parse_raw(&mut indv).is_err() {
+        return Err(PyValueError::new_err("fail parse individual"));
+    }
+    indv.parse_all();
+    Ok(PyIndividual {
+        indv,
+    })
+}
+
+#[pymethods]
+impl PyIndividual {
+    #[new]
+    #[pyo3(signature = (uri = None))]
+    fn new(uri: Option<&str>) -> Self {
+        let mut indv = Individual::default();
+        if let Some(uri) = uri {
+            indv.set_id(uri);
+        }
+        PyIndividual {
+            indv,
+        }
+    }
+
+    /// Parses a msgpack or CBOR buffer, the format is detected by its header.
+    #[staticmethod]
+    fn from_raw(data: &[u8]) -> PyResult<Self> {
+        from_raw(data)
+    }
+
+    #[staticmethod]
+    fn from_msgpack(data: &[u8]) -> PyResult<Self> {
+        from_raw(data)
+    }
+
+    #[staticmethod]
+    fn from_cbor(data: &[u8]) -> PyResult<Self> {
+        from_raw(data)
+    }
+
+    #[staticmethod]
+    fn from_json(data: &str) -> PyResult<Self> {
+        let src = json_from_slice(data.as_bytes()).map_err(|e| PyValueError::new_err(e.to_string()))?;
+        let mut indv = Individual::default();
+        if !parse_json_to_individual(&src, &mut indv) {
+            return Err(PyValueError::new_err("fail parse individual"));
+        }
+        Ok(PyIndividual {
+            indv,
+        })
+    }
+
+    #[getter]
+    fn get_uri(&self) -> &str {
+        self.indv.get_id()
+    }
+
+    #[setter]
+    fn set_uri(&mut self, uri: &str) {
+        self.indv.set_id(uri);
+    }
+
+    fn keys(&self) -> Vec<String> {
+        self.indv.get_predicates_nm()
+    }
+
+    fn items(&self, py: Python<'_>) -> Vec<(String, PyObject)> {
+        self.indv.obj.resources.iter().map(|(k, v)| (k.to_owned(), PyList::new_bound(py, v.iter().map(|r| resource_to_py(py, r))).into_py(py))).collect()
+    }
+
+    fn __len__(&self) -> usize {
+        self.indv.obj.resources.len()
+    }
+
+    fn __contains__(&self, predicate: &str) -> bool {
+        self.indv.obj.resources.contains_key(predicate)
+    }
+
+    fn __getitem__(&self, py: Python<'_>, predicate: &str) -> PyResult<PyObject> {
+        match self.indv.obj.resources.get(predicate) {
+            Some(v) => Ok(PyList::new_bound(py, v.iter().map(|r| resource_to_py(py, r))).into_py(py)),
+            None => Err(PyKeyError::new_err(predicate.to_owned())),
+        }
+    }
+
+    fn __setitem__(&mut self, predicate: &str, values: &Bound<'_, PyAny>) -> PyResult<()> {
+        self.indv.remove(predicate);
+        if let Ok(list) = values.downcast::<PyList>() {
+            for v in list.iter() {
+                add_py_value(&mut self.indv, predicate, &v)?;
+            }
+        } else {
+            add_py_value(&mut self.indv, predicate, values)?;
+        }
+        Ok(())
+    }
+
+    fn __delitem__(&mut self, predicate: &str) -> PyResult<()> {
+        if self.indv.remove(predicate) {
+            Ok(())
+        } else {
+            Err(PyKeyError::new_err(predicate.to_owned()))
+        }
+    }
+
+    fn add_uri(&mut self, predicate: &str, value: &str) -> PyResult<()> {
+        self.indv.add_uri(predicate, value).map_err(PyValueError::new_err)
+    }
+
+    #[pyo3(signature = (predicate, value, lang = None))]
+    fn add_string(&mut self, predicate: &str, value: &str, lang: Option<&str>) {
+        self.indv.add_string(predicate, value, lang.map(Lang::new_from_str).unwrap_or_else(Lang::none));
+    }
+
+    fn add_decimal(&mut self, predicate: &str, value: &str) -> PyResult<()> {
+        if Decimal::from_str(value).is_err() {
+            return Err(PyValueError::new_err(format!("fail parse [{}] to decimal", value)));
+        }
+        self.indv.add_decimal_from_str(predicate, value);
+        Ok(())
+    }
+
+    fn to_msgpack<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyBytes>> {
+        let mut out = Vec::new();
+        to_msgpack(&self.indv, &mut out).map_err(|e| PyValueError::new_err(e.to_string()))?;
+        Ok(PyBytes::new_bound(py, &out))
+    }
+
+    fn to_cbor<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyBytes>> {
+        let mut out = Vec::new();
+        to_cbor(self.indv.get_obj(), &mut out).map_err(|e| PyValueError::new_err(e.to_string()))?;
+        Ok(PyBytes::new_bound(py, &out))
+    }
+
+    fn to_json(&self) -> String {
+        self.indv.get_obj().as_json_str()
+    }
+
+    #[pyo3(signature = (prefixes = None))]
+    fn to_turtle(&self, prefixes: Option<HashMap<String, String>>) -> PyResult<String> {
+        let indvs = [Individual::new_from_obj(self.indv.get_obj())];
+        let out = to_turtle(&indvs, &prefixes.unwrap_or_default()).map_err(|e| PyValueError::new_err(e.to_string()))?;
+        String::from_utf8(out).map_err(|e| PyValueError::new_err(e.to_string()))
+    }
+
+    fn __repr__(&self) -> String {
+        format!("Individual({}, {} predicates)", self.indv.get_id(), self.indv.obj.resources.len())
+    }
+}
+
+#[pymodule]
+fn v_individual_model(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<PyIndividual>()?;
+    Ok(())
+}
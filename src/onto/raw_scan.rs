@@ -0,0 +1,348 @@
+use crate::onto::datatype::DataType;
+use crate::onto::individual::Individual;
+use crate::onto::parser::MSGPACK_MAGIC_HEADER;
+use crate::onto::resource::Value;
+use std::ops::Range;
+
+const MAX_DEPTH: usize = 64;
+
+fn read_be(data: &[u8], pos: usize, n: usize) -> Option<u64> {
+    let bytes = data.get(pos..pos + n)?;
+    Some(bytes.iter().fold(0u64, |acc, b| (acc << 8) | *b as u64))
+}
+
+/// Returns the position after the msgpack value starting at `pos`.
+pub(crate) fn msgpack_skip(data: &[u8], pos: usize) -> Option<usize> {
+    msgpack_skip_depth(data, pos, 0)
+}
+
+fn msgpack_skip_depth(data: &[u8], pos: usize, depth: usize) -> Option<usize> {
+    if depth > MAX_DEPTH {
+        return None;
+    }
+
+    let m = *data.get(pos)?;
+    let (header, payload, items) = match m {
+        0x00..=0x7f | 0xe0..=0xff | 0xc0 | 0xc2 | 0xc3 => (1, 0, 0),
+        0x80..=0x8f => (1, 0, (m & 0x0f) as u64 * 2),
+        0x90..=0x9f => (1, 0, (m & 0x0f) as u64),
+        0xa0..=0xbf => (1, (m & 0x1f) as u64, 0),
+        0xc4 | 0xd9 => (2, read_be(data, pos + 1, 1)?, 0),
+        0xc5 | 0xda => (3, read_be(data, pos + 1, 2)?, 0),
+        0xc6 | 0xdb => (5, read_be(data, pos + 1, 4)?, 0),
+        0xc7 => (3, read_be(data, pos + 1, 1)?, 0),
+        0xc8 => (4, read_be(data, pos + 1, 2)?, 0),
+        0xc9 => (6, read_be(data, pos + 1, 4)?, 0),
+        0xca | 0xce | 0xd2 => (1, 4, 0),
+        0xcb | 0xcf | 0xd3 => (1, 8, 0),
+        0xcc | 0xd0 => (1, 1, 0),
+        0xcd | 0xd1 => (1, 2, 0),
+        0xd4 => (2, 1, 0),
+        0xd5 => (2, 2, 0),
+        0xd6 => (2, 4, 0),
+        0xd7 => (2, 8, 0),
+        0xd8 => (2, 16, 0),
+        0xdc => (3, 0, read_be(data, pos + 1, 2)?),
+        0xdd => (5, 0, read_be(data, pos + 1, 4)?),
+        0xde => (3, 0, read_be(data, pos + 1, 2)? * 2),
+        0xdf => (5, 0, read_be(data, pos + 1, 4)? * 2),
+        0xc1 => return None,
+    };
+
+    let mut end = pos + header + payload as usize;
+    if end > data.len() {
+        return None;
+    }
+    for _ in 0..items {
+        end = msgpack_skip_depth(data, end, depth + 1)?;
+    }
+    Some(end)
+}
+
+/// Reads a msgpack string at `pos`, returns its bytes and the position after it.
+pub(crate) fn msgpack_str(data: &[u8], pos: usize) -> Option<(&[u8], usize)> {
+    let m = *data.get(pos)?;
+    let (header, len) = match m {
+        0xa0..=0xbf => (1, (m & 0x1f) as usize),
+        0xd9 => (2, read_be(data, pos + 1, 1)? as usize),
+        0xda => (3, read_be(data, pos + 1, 2)? as usize),
+        0xdb => (5, read_be(data, pos + 1, 4)? as usize),
+        _ => return None,
+    };
+    let start = pos + header;
+    Some((data.get(start..start + len)?, start + len))
+}
+
+fn msgpack_map_len(data: &[u8], pos: usize) -> Option<(u64, usize)> {
+    let m = *data.get(pos)?;
+    match m {
+        0x80..=0x8f => Some(((m & 0x0f) as u64, pos + 1)),
+        0xde => Some((read_be(data, pos + 1, 2)?, pos + 3)),
+        0xdf => Some((read_be(data, pos + 1, 4)?, pos + 5)),
+        _ => None,
+    }
+}
+
+fn msgpack_array_len(data: &[u8], pos: usize) -> Option<(u64, usize)> {
+    let m = *data.get(pos)?;
+    match m {
+        0x90..=0x9f => Some(((m & 0x0f) as u64, pos + 1)),
+        0xdc => Some((read_be(data, pos + 1, 2)?, pos + 3)),
+        0xdd => Some((read_be(data, pos + 1, 4)?, pos + 5)),
+        _ => None,
+    }
+}
+
+/// Reads a msgpack integer at `pos`, returns it and the position after it.
+fn msgpack_int(data: &[u8], pos: usize) -> Option<(i64, usize)> {
+    let m = *data.get(pos)?;
+    match m {
+        0x00..=0x7f => Some((m as i64, pos + 1)),
+        0xe0..=0xff => Some((m as i8 as i64, pos + 1)),
+        0xcc..=0xcf => {
+            let n = 1 << (m - 0xcc);
+            Some((i64::try_from(read_be(data, pos + 1, n)?).ok()?, pos + 1 + n))
+        },
+        0xd0..=0xd3 => {
+            let n = 1 << (m - 0xd0);
+            let shift = 64 - 8 * n as u32;
+            Some((((read_be(data, pos + 1, n)? << shift) as i64) >> shift, pos + 1 + n))
+        },
+        _ => None,
+    }
+}
+
+/// Encodes `v` as a msgpack integer of exactly `len` bytes, None if it does not fit.
+fn msgpack_int_fixed(v: i64, len: usize) -> Option<Vec<u8>> {
+    if len == 1 {
+        return if (-32..=127).contains(&v) {
+            Some(vec![v as u8])
+        } else {
+            None
+        };
+    }
+
+    let width = len - 1;
+    let bits = 8 * width as u32;
+    if !matches!(width, 1 | 2 | 4 | 8) {
+        return None;
+    }
+
+    let (base, fits) = if v >= 0 {
+        (0xcc, bits == 64 || (v as u64) < (1 << bits))
+    } else {
+        (0xd0, bits == 64 || v >= -(1 << (bits - 1)))
+    };
+    if !fits {
+        return None;
+    }
+
+    let marker = base + width.trailing_zeros() as u8;
+    let mut res = vec![marker];
+    res.extend_from_slice(&v.to_be_bytes()[8 - width..]);
+    Some(res)
+}
+
+/// Walks the predicates of a msgpack individual, `f` gets the predicate and the range of its values.
+/// Stops when `f` returns false.
+pub(crate) fn msgpack_for_each_predicate<F>(data: &[u8], mut f: F) -> Option<()>
+where
+    F: FnMut(&[u8], Range<usize>) -> bool,
+{
+    if *data.first()? != MSGPACK_MAGIC_HEADER {
+        return None;
+    }
+    let (_, pos) = msgpack_str(data, 1)?;
+    let (len, mut pos) = msgpack_map_len(data, pos)?;
+
+    for _ in 0..len {
+        let (key, start) = msgpack_str(data, pos)?;
+        let end = msgpack_skip(data, start)?;
+        if !f(key, start..end) {
+            break;
+        }
+        pos = end;
+    }
+    Some(())
+}
+
+/// CBOR item header: major type, argument, position after the header; argument is None for indefinite length.
+fn cbor_header(data: &[u8], pos: usize) -> Option<(u8, Option<u64>, usize)> {
+    let b = *data.get(pos)?;
+    let major = b >> 5;
+    let info = b & 0x1f;
+    match info {
+        0..=23 => Some((major, Some(info as u64), pos + 1)),
+        24 => Some((major, Some(read_be(data, pos + 1, 1)?), pos + 2)),
+        25 => Some((major, Some(read_be(data, pos + 1, 2)?), pos + 3)),
+        26 => Some((major, Some(read_be(data, pos + 1, 4)?), pos + 5)),
+        27 => Some((major, Some(read_be(data, pos + 1, 8)?), pos + 9)),
+        31 if (2..=5).contains(&major) => Some((major, None, pos + 1)),
+        _ => None,
+    }
+}
+
+/// Returns the position after the CBOR item starting at `pos`.
+pub(crate) fn cbor_skip(data: &[u8], pos: usize) -> Option<usize> {
+    cbor_skip_depth(data, pos, 0)
+}
+
+fn cbor_skip_depth(data: &[u8], pos: usize, depth: usize) -> Option<usize> {
+    if depth > MAX_DEPTH {
+        return None;
+    }
+
+    let (major, arg, mut end) = cbor_header(data, pos)?;
+    match (major, arg) {
+        (0 | 1, _) => {},
+        (2 | 3, Some(len)) => end += len as usize,
+        (4, Some(n)) => {
+            for _ in 0..n {
+                end = cbor_skip_depth(data, end, depth + 1)?;
+            }
+        },
+        (5, Some(n)) => {
+            for _ in 0..n * 2 {
+                end = cbor_skip_depth(data, end, depth + 1)?;
+            }
+        },
+        (6, _) => end = cbor_skip_depth(data, end, depth + 1)?,
+        (7, _) => {},
+        (_, None) => {
+            while *data.get(end)? != 0xff {
+                end = cbor_skip_depth(data, end, depth + 1)?;
+            }
+            end += 1;
+        },
+        _ => return None,
+    }
+
+    if end > data.len() {
+        return None;
+    }
+    Some(end)
+}
+
+fn cbor_text(data: &[u8], pos: usize) -> Option<(&[u8], usize)> {
+    match cbor_header(data, pos)? {
+        (3, Some(len), start) => Some((data.get(start..start + len as usize)?, start + len as usize)),
+        _ => None,
+    }
+}
+
+/// Same as `msgpack_for_each_predicate` for a CBOR individual, the `@` entry is skipped.
+pub(crate) fn cbor_for_each_predicate<F>(data: &[u8], mut f: F) -> Option<()>
+where
+    F: FnMut(&[u8], Range<usize>) -> bool,
+{
+    let (major, len, mut pos) = cbor_header(data, 0)?;
+    if major != 5 {
+        return None;
+    }
+
+    let mut i = 0;
+    while len.map(|n| i < n).unwrap_or(*data.get(pos)? != 0xff) {
+        let (key, start) = cbor_text(data, pos)?;
+        let end = cbor_skip(data, start)?;
+        if key != b"@" && !f(key, start..end) {
+            break;
+        }
+        pos = end;
+        i += 1;
+    }
+    Some(())
+}
+
+/// Finds the encoded values of a predicate in a msgpack or CBOR individual buffer.
+pub fn find_predicate_raw(data: &[u8], predicate: &str) -> Option<Range<usize>> {
+    let mut res = None;
+    let check = |key: &[u8], range: Range<usize>| {
+        if key == predicate.as_bytes() {
+            res = Some(range);
+            false
+        } else {
+            true
+        }
+    };
+
+    if *data.first()? == MSGPACK_MAGIC_HEADER {
+        msgpack_for_each_predicate(data, check)?;
+    } else {
+        cbor_for_each_predicate(data, check)?;
+    }
+    res
+}
+
+/// Position of the integer payload of the value at `idx` in a msgpack values array, if the value has the type `rtype`.
+fn msgpack_int_slot(data: &[u8], values: Range<usize>, idx: usize, rtype: DataType) -> Option<Range<usize>> {
+    let (len, mut pos) = msgpack_array_len(data, values.start)?;
+    if idx as u64 >= len {
+        return None;
+    }
+    for _ in 0..idx {
+        pos = msgpack_skip(data, pos)?;
+    }
+
+    let (n, pos) = msgpack_array_len(data, pos)?;
+    let (t, pos) = msgpack_int(data, pos)?;
+    if n != 2 || t != rtype as i64 {
+        return None;
+    }
+    let (_, end) = msgpack_int(data, pos)?;
+    Some(pos..end)
+}
+
+/// Overwrites an integer value inside a msgpack individual buffer, the buffer length never changes.
+/// Returns false if the value is not found, has another type or `v` needs more bytes than the old value.
+pub fn patch_msgpack_int(data: &mut [u8], predicate: &str, idx: usize, rtype: DataType, v: i64) -> bool {
+    if data.first() != Some(&MSGPACK_MAGIC_HEADER) {
+        return false;
+    }
+    let Some(slot) = find_predicate_raw(data, predicate).and_then(|values| msgpack_int_slot(data, values, idx, rtype)) else {
+        return false;
+    };
+    match msgpack_int_fixed(v, slot.len()) {
+        Some(enc) => {
+            data[slot].copy_from_slice(&enc);
+            true
+        },
+        None => false,
+    }
+}
+
+impl Individual {
+    /// Encoded values of one predicate taken from the original buffer, without decoding them.
+    /// The result is a msgpack array or a CBOR item, depending on the buffer format.
+    /// Returns None if the predicate is absent or the individual was modified after loading.
+    pub fn extract_predicate_raw(&self, predicate: &str) -> Option<&[u8]> {
+        let data = self.get_clean_raw()?;
+        find_predicate_raw(data, predicate).map(|r| &data[r])
+    }
+
+    /// Updates an integer value (e.g. a counter) directly in the raw msgpack buffer, skipping the decode/encode cycle.
+    /// Works while the individual is unmodified and the new value takes no more bytes than the old one;
+    /// on false nothing is changed and the regular setters should be used.
+    pub fn patch_raw_integer(&mut self, predicate: &str, idx: usize, v: i64) -> bool {
+        self.patch_raw_int(predicate, idx, DataType::Integer, v)
+    }
+
+    pub fn patch_raw_datetime(&mut self, predicate: &str, idx: usize, v: i64) -> bool {
+        self.patch_raw_int(predicate, idx, DataType::Datetime, v)
+    }
+
+    fn patch_raw_int(&mut self, predicate: &str, idx: usize, rtype: DataType, v: i64) -> bool {
+        if self.dirty || !patch_msgpack_int(&mut self.raw.data, predicate, idx, rtype.clone(), v) {
+            return false;
+        }
+
+        // keep the already parsed value in sync with the buffer
+        if let Some(r) = self.obj.resources.get_mut(predicate).and_then(|values| values.get_mut(idx)) {
+            r.value = match rtype {
+                DataType::Datetime => Value::Datetime(v),
+                _ => Value::Int(v),
+            };
+        }
+        self.changed.insert(predicate.to_owned());
+        true
+    }
+}
@@ -0,0 +1,186 @@
+use crate::onto::datatype::{exponent_to_scale, DataType, Lang};
+use crate::onto::individual::{Individual, IndividualObj};
+use crate::onto::resource::{Resource, Value};
+use rust_decimal::Decimal;
+use sha2::{Digest, Sha256};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RedactionAction {
+    /// Remove the predicate with all its values.
+    Drop,
+    /// Replace every value by the hex SHA-256 of the policy salt and the value.
+    Hash,
+    /// Replace every character except the last `keep_last` ones by `*`.
+    Mask { keep_last: usize },
+}
+
+#[derive(Debug, Clone)]
+pub struct RedactionRule {
+    pub pattern: String,
+    pub action: RedactionAction,
+}
+
+/// Ordered set of predicate patterns, the first matching rule wins.
+/// A pattern is a predicate name where `*` matches any sequence of characters, e.g. `v-s:*`, `*:email`.
+#[derive(Debug, Clone, Default)]
+pub struct RedactionPolicy {
+    pub rules: Vec<RedactionRule>,
+    pub salt: Vec<u8>,
+}
+
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct RedactionReport {
+    pub uri: String,
+    pub dropped: Vec<String>,
+    pub hashed: Vec<String>,
+    pub masked: Vec<String>,
+}
+
+impl RedactionReport {
+    pub fn is_empty(&self) -> bool {
+        self.dropped.is_empty() && self.hashed.is_empty() && self.masked.is_empty()
+    }
+}
+
+impl RedactionPolicy {
+    pub fn new() -> Self {
+        RedactionPolicy::default()
+    }
+
+    pub fn with_salt(mut self, salt: &[u8]) -> Self {
+        self.salt = salt.to_vec();
+        self
+    }
+
+    pub fn rule(mut self, pattern: &str, action: RedactionAction) -> Self {
+        self.rules.push(RedactionRule {
+            pattern: pattern.to_owned(),
+            action,
+        });
+        self
+    }
+
+    pub fn action_for(&self, predicate: &str) -> Option<&RedactionAction> {
+        self.rules.iter().find(|r| pattern_match(&r.pattern, predicate)).map(|r| &r.action)
+    }
+
+    /// Returns a sanitized copy of the individual, with the rights of the predicates it keeps, and the
+    /// list of affected predicates.
+    pub fn apply(&self, src: &IndividualObj) -> (IndividualObj, RedactionReport) {
+        let mut dest = IndividualObj {
+            uri: src.uri.to_owned(),
+            ..Default::default()
+        };
+
+        let mut report = RedactionReport {
+            uri: src.uri.to_owned(),
+            ..Default::default()
+        };
+
+        for (predicate, values) in src.resources.iter() {
+            match self.action_for(predicate) {
+                None => {
+                    dest.resources.insert(predicate.to_owned(), values.iter().map(|el| el.get_copy()).collect());
+                },
+                Some(RedactionAction::Drop) => {
+                    report.dropped.push(predicate.to_owned());
+                },
+                Some(RedactionAction::Hash) => {
+                    dest.resources.insert(predicate.to_owned(), values.iter().map(|el| self.hash_resource(el)).collect());
+                    report.hashed.push(predicate.to_owned());
+                },
+                Some(RedactionAction::Mask {
+                    keep_last,
+                }) => {
+                    dest.resources.insert(predicate.to_owned(), values.iter().map(|el| mask_resource(el, *keep_last)).collect());
+                    report.masked.push(predicate.to_owned());
+                },
+            }
+        }
+        // field rights stay with the predicates that are kept, hashed or masked
+        dest.rights = src.rights.iter().filter(|(p, _)| !matches!(self.action_for(p), Some(RedactionAction::Drop))).map(|(p, r)| (p.to_owned(), *r)).collect();
+
+        (dest, report)
+    }
+
+    pub fn apply_to_individual(&self, src: &mut Individual) -> (IndividualObj, RedactionReport) {
+        src.parse_all();
+        self.apply(src.get_obj())
+    }
+
+    fn hash_resource(&self, r: &Resource) -> Resource {
+        let mut hasher = Sha256::new();
+        hasher.update(&self.salt);
+        match &r.value {
+            Value::Binary(v) => hasher.update(v),
+            v => hasher.update(value_to_text(v).as_bytes()),
+        }
+        let hex: String = hasher.finalize().iter().map(|b| format!("{:02x}", b)).collect();
+
+        Resource {
+            rtype: DataType::String,
+            order: r.order,
+            value: Value::Str(hex, Lang::none()),
+        }
+    }
+}
+
+fn mask_resource(r: &Resource, keep_last: usize) -> Resource {
+    let text = value_to_text(&r.value);
+    let len = text.chars().count();
+    let masked: String = text
+        .chars()
+        .enumerate()
+        .map(|(i, c)| {
+            if i + keep_last < len {
+                '*'
+            } else {
+                c
+            }
+        })
+        .collect();
+
+    Resource {
+        rtype: DataType::String,
+        order: r.order,
+        value: Value::Str(masked, r.get_lang()),
+    }
+}
+
+fn value_to_text(v: &Value) -> String {
+    match v {
+        Value::Int(i) => i.to_string(),
+        Value::Datetime(i) => i.to_string(),
+        Value::Bool(b) => b.to_string(),
+        Value::Str(s, _) => s.to_owned(),
+        Value::Uri(s) => s.to_owned(),
+        Value::Num(m, e) => {
+            let (num, scale) = exponent_to_scale(m, e);
+            Decimal::new(num, scale).to_string()
+        },
+        Value::Binary(v) => String::from_utf8_lossy(v).to_string(),
+    }
+}
+
+/// Matches `text` against a pattern where `*` stands for any sequence of characters.
+pub fn pattern_match(pattern: &str, text: &str) -> bool {
+    let parts: Vec<&str> = pattern.split('*').collect();
+    if parts.len() == 1 {
+        return pattern == text;
+    }
+
+    let first = parts[0];
+    let last = parts[parts.len() - 1];
+    if !text.starts_with(first) || text.len() < first.len() + last.len() || !text.ends_with(last) {
+        return false;
+    }
+
+    let mut rest = &text[first.len()..text.len() - last.len()];
+    for part in &parts[1..parts.len() - 1] {
+        match rest.find(part) {
+            Some(pos) => rest = &rest[pos + part.len()..],
+            None => return false,
+        }
+    }
+    true
+}
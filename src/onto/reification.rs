@@ -0,0 +1,95 @@
+use crate::onto::individual::IndividualObj;
+use crate::onto::resource::{Resource, Value};
+use std::collections::HashMap;
+use std::slice;
+
+pub const STATEMENT_TYPE: &str = "rdf:Statement";
+
+/// The `rdf:Statement` individual `uri` of the statement (subject, predicate, value). Metadata about
+/// the statement is added to it by the caller; the statement itself stays on the subject.
+pub fn reify(subject: &str, predicate: &str, value: &Resource, uri: &str) -> IndividualObj {
+    let mut st = IndividualObj {
+        uri: uri.to_owned(),
+        ..Default::default()
+    };
+    st.add_uri_unchecked("rdf:type", STATEMENT_TYPE);
+    st.add_uri_unchecked("rdf:subject", subject);
+    st.add_uri_unchecked("rdf:predicate", predicate);
+    st.add_resources("rdf:object", slice::from_ref(value));
+    st
+}
+
+/// (subject, predicate, value) of an `rdf:Statement` individual with exactly one of each, None for
+/// other individuals.
+pub fn statement_of(obj: &IndividualObj) -> Option<(&str, &str, &Resource)> {
+    let is_statement = obj.resources.get("rdf:type")?.iter().any(|r| matches!(&r.value, Value::Uri(u) if u == STATEMENT_TYPE));
+    if !is_statement {
+        return None;
+    }
+    let single = |predicate: &str| match obj.resources.get(predicate).map(|v| v.as_slice()) {
+        Some([r]) => Some(r),
+        _ => None,
+    };
+    match (single("rdf:subject")?, single("rdf:predicate")?, single("rdf:object")?) {
+        (
+            Resource {
+                value: Value::Uri(s),
+                ..
+            },
+            Resource {
+                value: Value::Uri(p),
+                ..
+            },
+            o,
+        ) => Some((s.as_str(), p.as_str(), o)),
+        _ => None,
+    }
+}
+
+impl IndividualObj {
+    /// Reifies the values for which `select(predicate, value)` is true, in predicate and value order.
+    /// The statements are `<uri>_st_1`, `<uri>_st_2`, …
+    pub fn reify_where<F>(&self, mut select: F) -> Vec<IndividualObj>
+    where
+        F: FnMut(&str, &Resource) -> bool,
+    {
+        let mut res = vec![];
+        for (predicate, values) in &self.resources {
+            for r in values {
+                if select(predicate, r) {
+                    res.push(reify(&self.uri, predicate, r, &format!("{}_st_{}", self.uri, res.len() + 1)));
+                }
+            }
+        }
+        res
+    }
+}
+
+/// The inverse of `reify` over a set: every statement individual is taken out of `objs` and its
+/// statement added to the subject, unless it is already there; a subject missing from the set is
+/// appended. Returns the statement individuals, with whatever metadata they carry.
+pub fn fold_statements(objs: &mut Vec<IndividualObj>) -> Vec<IndividualObj> {
+    let (statements, rest): (Vec<IndividualObj>, Vec<IndividualObj>) = objs.drain(..).partition(|obj| statement_of(obj).is_some());
+    *objs = rest;
+    let mut index: HashMap<String, usize> = HashMap::new();
+    for (i, obj) in objs.iter().enumerate() {
+        index.entry(obj.uri.to_owned()).or_insert(i);
+    }
+
+    for st in statements.iter() {
+        if let Some((subject, predicate, value)) = statement_of(st) {
+            let pos = *index.entry(subject.to_owned()).or_insert_with(|| {
+                objs.push(IndividualObj {
+                    uri: subject.to_owned(),
+                    ..Default::default()
+                });
+                objs.len() - 1
+            });
+            let obj = &mut objs[pos];
+            if !obj.resources.get(predicate).is_some_and(|values| values.contains(value)) {
+                obj.add_resources(predicate, slice::from_ref(value));
+            }
+        }
+    }
+    statements
+}
@@ -1,5 +1,18 @@
 use crate::onto::datatype::{DataType, Lang};
+#[cfg(feature = "chrono")]
+use crate::onto::datetime::to_utc;
+#[cfg(feature = "std")]
+use crate::onto::decimal::{to_parts_nearest, DecimalConfig, Rounding, MAX_SCALE};
+use alloc::borrow::ToOwned;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+#[cfg(feature = "chrono")]
+use chrono::{DateTime, Utc};
+use core::fmt;
 use derivative::Derivative;
+#[cfg(feature = "std")]
+use rust_decimal::Decimal;
 
 #[derive(Debug, Eq, PartialEq, Clone)]
 
@@ -13,6 +26,21 @@ pub enum Value {
     Datetime(i64),
 }
 
+impl Value {
+    /// The type a resource holding this value has.
+    pub fn data_type(&self) -> DataType {
+        match self {
+            Value::Int(_) => DataType::Integer,
+            Value::Str(..) => DataType::String,
+            Value::Uri(_) => DataType::Uri,
+            Value::Bool(_) => DataType::Boolean,
+            Value::Num(..) => DataType::Decimal,
+            Value::Binary(_) => DataType::Binary,
+            Value::Datetime(_) => DataType::Datetime,
+        }
+    }
+}
+
 #[derive(Derivative)]
 #[derivative(Debug, PartialEq)]
 pub struct Resource {
@@ -39,6 +67,56 @@ impl Resource {
         }
     }
 
+    pub fn new_string(data: &str, lang: Lang) -> Self {
+        Resource {
+            rtype: DataType::String,
+            order: 0,
+            value: Value::Str(data.to_owned(), lang),
+        }
+    }
+
+    /// String with a language tag like `ru` or `en`, an empty tag means no language.
+    pub fn new_str(data: &str, lang: &str) -> Self {
+        let lang = if lang.is_empty() {
+            Lang::none()
+        } else {
+            Lang::new_from_str(lang)
+        };
+        Resource::new_string(data, lang)
+    }
+
+    pub fn new_integer(data: i64) -> Self {
+        Resource {
+            rtype: DataType::Integer,
+            order: 0,
+            value: Value::Int(data),
+        }
+    }
+
+    pub fn new_datetime(data: i64) -> Self {
+        Resource {
+            rtype: DataType::Datetime,
+            order: 0,
+            value: Value::Datetime(data),
+        }
+    }
+
+    pub fn new_decimal(mantissa: i64, exponent: i64) -> Self {
+        Resource {
+            rtype: DataType::Decimal,
+            order: 0,
+            value: Value::Num(mantissa, exponent),
+        }
+    }
+
+    pub fn new_binary(data: Vec<u8>) -> Self {
+        Resource {
+            rtype: DataType::Binary,
+            order: 0,
+            value: Value::Binary(data),
+        }
+    }
+
     pub fn get_copy(&self) -> Self {
         Resource {
             rtype: self.rtype.clone(),
@@ -111,6 +189,15 @@ impl Resource {
         }
     }
 
+    /// Value equality: decimals are compared by value (`10e-1 == 1`), strings by text and language, order is ignored.
+    pub fn semantic_eq(&self, other: &Resource) -> bool {
+        match (&self.value, &other.value) {
+            (Value::Num(m1, e1), Value::Num(m2, e2)) => normalize_decimal(*m1, *e1) == normalize_decimal(*m2, *e2),
+            (a, b) => self.rtype == other.rtype && a == b,
+        }
+    }
+
+    #[cfg(feature = "std")]
     pub fn get_float(&self) -> f64 {
         if let Value::Num(m, e) = self.value {
             m as f64 * 10.0_f64.powf(e as f64)
@@ -120,6 +207,17 @@ impl Resource {
     }
 }
 
+fn normalize_decimal(mut m: i64, mut e: i64) -> (i64, i64) {
+    if m == 0 {
+        return (0, 0);
+    }
+    while m % 10 == 0 {
+        m /= 10;
+        e += 1;
+    }
+    (m, e)
+}
+
 impl From<Value> for i64 {
     fn from(v: Value) -> Self {
         if let Value::Int(t) = v {
@@ -139,3 +237,220 @@ impl From<Value> for bool {
         }
     }
 }
+
+impl From<i64> for Value {
+    fn from(v: i64) -> Self {
+        Value::Int(v)
+    }
+}
+
+impl From<bool> for Value {
+    fn from(v: bool) -> Self {
+        Value::Bool(v)
+    }
+}
+
+/// A string without language; URIs are built with `Value::Uri` or `Resource::new_uri`.
+impl From<&str> for Value {
+    fn from(v: &str) -> Self {
+        Value::Str(v.to_owned(), Lang::none())
+    }
+}
+
+impl From<String> for Value {
+    fn from(v: String) -> Self {
+        Value::Str(v, Lang::none())
+    }
+}
+
+impl From<Vec<u8>> for Value {
+    fn from(v: Vec<u8>) -> Self {
+        Value::Binary(v)
+    }
+}
+
+/// Whole seconds, like every datetime of an individual.
+#[cfg(feature = "chrono")]
+impl From<DateTime<Utc>> for Value {
+    fn from(v: DateTime<Utc>) -> Self {
+        Value::Datetime(v.timestamp())
+    }
+}
+
+/// See `decimal::to_parts_nearest` for mantissas that do not fit `i64`.
+#[cfg(feature = "std")]
+impl From<Decimal> for Value {
+    fn from(v: Decimal) -> Self {
+        let (m, e) = to_parts_nearest(v);
+        Value::Num(m, e)
+    }
+}
+
+impl From<Value> for Resource {
+    fn from(value: Value) -> Self {
+        Resource {
+            rtype: value.data_type(),
+            order: 0,
+            value,
+        }
+    }
+}
+
+macro_rules! resource_from {
+    ($($t:ty),*) => {
+        $(
+            impl From<$t> for Resource {
+                fn from(v: $t) -> Self {
+                    Resource::from(Value::from(v))
+                }
+            }
+        )*
+    };
+}
+
+resource_from!(i64, bool, &str, String, Vec<u8>);
+#[cfg(feature = "chrono")]
+resource_from!(DateTime<Utc>);
+#[cfg(feature = "std")]
+resource_from!(Decimal);
+
+/// Why a resource could not be converted to a native type.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConversionError {
+    /// The resource holds a value of another type.
+    Type { expected: &'static str, found: DataType },
+    /// The value does not fit the target type exactly.
+    Lossy { target: &'static str, value: String },
+}
+
+impl fmt::Display for ConversionError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ConversionError::Type {
+                expected,
+                found,
+            } => write!(f, "expected {}, found {:?}", expected, found),
+            ConversionError::Lossy {
+                target,
+                value,
+            } => write!(f, "{} does not fit {} without loss", value, target),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ConversionError {}
+
+fn type_error(expected: &'static str, r: &Resource) -> ConversionError {
+    ConversionError::Type {
+        expected,
+        found: r.value.data_type(),
+    }
+}
+
+fn lossy(target: &'static str, value: String) -> ConversionError {
+    ConversionError::Lossy {
+        target,
+        value,
+    }
+}
+
+/// Integers, and decimals with an integral value.
+impl TryFrom<&Resource> for i64 {
+    type Error = ConversionError;
+
+    fn try_from(r: &Resource) -> Result<Self, Self::Error> {
+        match r.value {
+            Value::Int(i) => Ok(i),
+            Value::Num(m, e) => {
+                let v = if m == 0 {
+                    Some(0)
+                } else if e >= 0 {
+                    u32::try_from(e).ok().and_then(|e| 10_i128.checked_pow(e)).and_then(|p| (m as i128).checked_mul(p))
+                } else {
+                    u32::try_from(-e).ok().and_then(|e| 10_i128.checked_pow(e)).filter(|p| m as i128 % p == 0).map(|p| m as i128 / p)
+                };
+                v.and_then(|v| i64::try_from(v).ok()).ok_or_else(|| lossy("i64", format!("{}e{}", m, e)))
+            },
+            _ => Err(type_error("integer", r)),
+        }
+    }
+}
+
+impl TryFrom<&Resource> for bool {
+    type Error = ConversionError;
+
+    fn try_from(r: &Resource) -> Result<Self, Self::Error> {
+        match r.value {
+            Value::Bool(b) => Ok(b),
+            _ => Err(type_error("boolean", r)),
+        }
+    }
+}
+
+/// The text of a string (without its language) or a URI.
+impl TryFrom<&Resource> for String {
+    type Error = ConversionError;
+
+    fn try_from(r: &Resource) -> Result<Self, Self::Error> {
+        match &r.value {
+            Value::Str(s, _) | Value::Uri(s) => Ok(s.to_owned()),
+            _ => Err(type_error("string", r)),
+        }
+    }
+}
+
+/// Integers and decimals, up to 28 fraction digits.
+#[cfg(feature = "std")]
+impl TryFrom<&Resource> for Decimal {
+    type Error = ConversionError;
+
+    fn try_from(r: &Resource) -> Result<Self, Self::Error> {
+        match r.value {
+            Value::Int(i) => Ok(Decimal::from(i)),
+            Value::Num(m, e) => DecimalConfig::new(MAX_SCALE, Rounding::Exact).to_decimal(m, e).map_err(|_| lossy("Decimal", format!("{}e{}", m, e))),
+            _ => Err(type_error("decimal", r)),
+        }
+    }
+}
+
+/// Integers and decimals whose shortest `f64` form reads back as the same number: `0.1` converts,
+/// a decimal with more significant digits than `f64` holds is an error.
+impl TryFrom<&Resource> for f64 {
+    type Error = ConversionError;
+
+    fn try_from(r: &Resource) -> Result<Self, Self::Error> {
+        let (m, e) = match r.value {
+            Value::Int(i) => (i, 0),
+            Value::Num(m, e) => (m, e),
+            _ => return Err(type_error("decimal", r)),
+        };
+        to_f64_exact(m, e).ok_or_else(|| match r.value {
+            Value::Int(i) => lossy("f64", format!("{}", i)),
+            _ => lossy("f64", format!("{}e{}", m, e)),
+        })
+    }
+}
+
+fn to_f64_exact(m: i64, e: i64) -> Option<f64> {
+    let f: f64 = format!("{}e{}", m, e).parse().ok().filter(|f: &f64| f.is_finite())?;
+    // shortest form that reads back as f, e.g. -1.25e1
+    let s = format!("{:e}", f);
+    let (digits, exp) = s.split_once('e')?;
+    let fraction_len = digits.split_once('.').map_or(0, |(_, fraction)| fraction.len()) as i64;
+    let back: i64 = digits.replace('.', "").parse().ok()?;
+    let exp: i64 = exp.parse().ok()?;
+    (normalize_decimal(back, exp - fraction_len) == normalize_decimal(m, e)).then_some(f)
+}
+
+#[cfg(feature = "chrono")]
+impl TryFrom<&Resource> for DateTime<Utc> {
+    type Error = ConversionError;
+
+    fn try_from(r: &Resource) -> Result<Self, Self::Error> {
+        match r.value {
+            Value::Datetime(t) => to_utc(t).map_err(|_| lossy("DateTime<Utc>", t.to_string())),
+            _ => Err(type_error("datetime", r)),
+        }
+    }
+}
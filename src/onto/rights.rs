@@ -0,0 +1,92 @@
+use crate::onto::envelope::EnvelopeError;
+use crate::onto::individual::{Individual, IndividualObj};
+use std::collections::HashMap;
+
+/// Rights bits, as in the platform ACL.
+pub const CAN_CREATE: u8 = 1;
+pub const CAN_READ: u8 = 2;
+pub const CAN_UPDATE: u8 = 4;
+pub const CAN_DELETE: u8 = 8;
+pub const FULL_RIGHTS: u8 = CAN_CREATE | CAN_READ | CAN_UPDATE | CAN_DELETE;
+
+impl IndividualObj {
+    /// Rights needed for `predicate`, checked by `filter_by_rights`.
+    pub fn set_rights(&mut self, predicate: &str, mask: u8) {
+        self.rights.insert(predicate.to_owned(), mask);
+    }
+
+    /// None if the predicate has no mask and is visible to everyone.
+    pub fn get_rights(&self, predicate: &str) -> Option<u8> {
+        self.rights.get(predicate).copied()
+    }
+
+    pub fn clear_rights(&mut self) {
+        self.rights.clear();
+    }
+
+    /// The individual as seen with `mask`: the predicates whose rights mask includes every bit of
+    /// `mask`, and the predicates without mask.
+    pub fn filter_by_rights(&self, mask: u8) -> IndividualObj {
+        let mut res = IndividualObj {
+            uri: self.uri.to_owned(),
+            ..Default::default()
+        };
+        for (predicate, values) in self.resources.iter() {
+            match self.rights.get(predicate) {
+                Some(r) if r & mask != mask => {},
+                r => {
+                    res.resources.insert(predicate.to_owned(), values.iter().map(|v| v.get_copy()).collect());
+                    if let Some(r) = r {
+                        res.rights.insert(predicate.to_owned(), *r);
+                    }
+                },
+            }
+        }
+        res
+    }
+}
+
+impl Individual {
+    pub fn set_rights(&mut self, predicate: &str, mask: u8) {
+        self.obj.set_rights(predicate, mask);
+    }
+
+    pub fn get_rights(&self, predicate: &str) -> Option<u8> {
+        self.obj.get_rights(predicate)
+    }
+
+    pub fn filter_by_rights(&mut self, mask: u8) -> IndividualObj {
+        self.parse_all();
+        self.obj.filter_by_rights(mask)
+    }
+}
+
+/// Data of the `EXT_RIGHTS` envelope extension: u16 big endian count, then per predicate sorted by
+/// name its length (u16 big endian), the name and the mask.
+pub fn encode_rights(rights: &HashMap<String, u8>) -> Vec<u8> {
+    let mut predicates: Vec<(&String, &u8)> = rights.iter().collect();
+    predicates.sort();
+    let mut res = Vec::new();
+    res.extend_from_slice(&(predicates.len() as u16).to_be_bytes());
+    for (predicate, mask) in predicates {
+        res.extend_from_slice(&(predicate.len() as u16).to_be_bytes());
+        res.extend_from_slice(predicate.as_bytes());
+        res.push(*mask);
+    }
+    res
+}
+
+pub fn decode_rights(data: &[u8]) -> Result<HashMap<String, u8>, EnvelopeError> {
+    let mut res = HashMap::new();
+    let count = data.get(0..2).map(|c| u16::from_be_bytes([c[0], c[1]])).ok_or(EnvelopeError::Truncated)?;
+    let mut pos = 2;
+    for _ in 0..count {
+        let len = data.get(pos..pos + 2).map(|l| u16::from_be_bytes([l[0], l[1]]) as usize).ok_or(EnvelopeError::Truncated)?;
+        let predicate = data.get(pos + 2..pos + 2 + len).ok_or(EnvelopeError::Truncated)?;
+        let mask = *data.get(pos + 2 + len).ok_or(EnvelopeError::Truncated)?;
+        let predicate = String::from_utf8(predicate.to_vec()).map_err(|_| EnvelopeError::InvalidExtension)?;
+        res.insert(predicate, mask);
+        pos += 3 + len;
+    }
+    Ok(res)
+}
@@ -0,0 +1,68 @@
+use crate::onto::individual::{Individual, IndividualObj};
+use crate::onto::redaction::RedactionPolicy;
+use std::collections::HashMap;
+
+/// Picks a share of the individuals of a dump by `rdf:type` (the first value, "" for none): of
+/// each type `ceil(n * ratio)` of the first `n` seen, evenly spread over the dump, so every type
+/// is in the sample with about its share, rare types with at least one individual.
+#[derive(Debug, Clone)]
+pub struct Sampler {
+    ratio: f64,
+    /// Individuals seen and taken by type.
+    counts: HashMap<String, (u64, u64)>,
+}
+
+impl Sampler {
+    /// `ratio` is limited to 0…1.
+    pub fn new(ratio: f64) -> Self {
+        Sampler {
+            ratio: if ratio.is_nan() {
+                0.0
+            } else {
+                ratio.clamp(0.0, 1.0)
+            },
+            counts: HashMap::new(),
+        }
+    }
+
+    /// Whether the individual goes to the sample.
+    pub fn take(&mut self, obj: &IndividualObj) -> bool {
+        let rtype = obj.resources.get("rdf:type").and_then(|v| v.first()).map(|r| r.get_uri()).unwrap_or_default();
+        let (seen, taken) = match self.counts.get_mut(rtype) {
+            Some(c) => c,
+            None => self.counts.entry(rtype.to_owned()).or_default(),
+        };
+        *seen += 1;
+        if (*taken as f64) < *seen as f64 * self.ratio {
+            *taken += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Individuals seen and taken by type.
+    pub fn counts(&self) -> &HashMap<String, (u64, u64)> {
+        &self.counts
+    }
+}
+
+/// A representative share of a production dump for developer environments: the individuals
+/// picked by `Sampler`, redacted by `policy`. Links to individuals not in the sample are kept,
+/// uris are not changed.
+///
+/// ```text
+/// let policy = RedactionPolicy::new().with_salt(salt).rule("v-s:email", RedactionAction::Hash).rule("v-s:birthDate", RedactionAction::Drop);
+/// for obj in sample_dump(dump, 0.01, &policy) { … }
+/// ```
+pub fn sample_dump<'a, I>(indvs: I, ratio: f64, policy: &'a RedactionPolicy) -> impl Iterator<Item = IndividualObj> + 'a
+where
+    I: IntoIterator<Item = Individual>,
+    I::IntoIter: 'a,
+{
+    let mut sampler = Sampler::new(ratio);
+    indvs.into_iter().filter_map(move |mut indv| {
+        indv.parse_all();
+        sampler.take(indv.get_obj()).then(|| policy.apply(indv.get_obj()).0)
+    })
+}
@@ -0,0 +1,375 @@
+use crate::onto::individual::{Individual, IndividualObj};
+use crate::onto::resource::Value;
+use crate::onto::text::strip_html;
+use std::sync::Arc;
+use unicode_normalization::UnicodeNormalization;
+
+/// Cleans the string values of a predicate before they are stored.
+pub trait Sanitizer: Send + Sync {
+    /// The cleaned value, None if the value is kept as is.
+    fn sanitize(&self, predicate: &str, value: &str) -> Option<String>;
+}
+
+/// Elements removed with their content.
+const DANGEROUS_ELEMENTS: [&str; 16] = [
+    "script", "style", "iframe", "object", "embed", "frame", "frameset", "template", "svg", "math", "noscript", "noembed", "noframes", "xmp", "title",
+    "textarea",
+];
+
+/// Elements kept; other tags are dropped, their content is kept.
+const ALLOWED_ELEMENTS: [&str; 45] = [
+    "a",
+    "abbr",
+    "b",
+    "blockquote",
+    "br",
+    "caption",
+    "code",
+    "col",
+    "colgroup",
+    "dd",
+    "del",
+    "div",
+    "dl",
+    "dt",
+    "em",
+    "h1",
+    "h2",
+    "h3",
+    "h4",
+    "h5",
+    "h6",
+    "hr",
+    "i",
+    "img",
+    "ins",
+    "kbd",
+    "li",
+    "mark",
+    "ol",
+    "p",
+    "pre",
+    "q",
+    "s",
+    "small",
+    "span",
+    "strong",
+    "sub",
+    "sup",
+    "table",
+    "tbody",
+    "td",
+    "th",
+    "thead",
+    "tr",
+    "ul",
+];
+
+/// Attributes kept on allowed elements.
+const ALLOWED_ATTRIBUTES: [&str; 13] = [
+    "alt", "cite", "class", "colspan", "dir", "height", "href", "lang", "rowspan", "src", "start", "title", "width",
+];
+
+/// Attributes holding a URL, kept only with a scheme of `SAFE_SCHEMES` or as relative URL.
+const URL_ATTRIBUTES: [&str; 3] = ["cite", "href", "src"];
+
+const SAFE_SCHEMES: [&str; 4] = ["http", "https", "mailto", "tel"];
+
+/// Keeps the formatting markup of rich-text fields: elements and attributes outside an allowlist are
+/// dropped, `script`, `style`, `svg`, `iframe` and other active elements with their content, links
+/// other than `http(s):`, `mailto:`, `tel:` and relative ones.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct HtmlSanitizer;
+
+/// Markdown fields: line endings as `\n`, NFC, at most one blank line in a row, no trailing
+/// blank lines, and embedded HTML sanitized by `HtmlSanitizer`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MarkdownSanitizer;
+
+/// Turns HTML into plain text, see `text::strip_html`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PlainTextSanitizer;
+
+impl Sanitizer for HtmlSanitizer {
+    fn sanitize(&self, _predicate: &str, value: &str) -> Option<String> {
+        let res = sanitize_html(value);
+        (res != value).then_some(res)
+    }
+}
+
+impl Sanitizer for MarkdownSanitizer {
+    fn sanitize(&self, _predicate: &str, value: &str) -> Option<String> {
+        let text = sanitize_html(&value.replace("\r\n", "\n").replace('\r', "\n")).nfc().collect::<String>();
+        let mut res = String::with_capacity(text.len());
+        let mut blank = 0;
+        for line in text.split('\n') {
+            if line.trim().is_empty() {
+                blank += 1;
+                continue;
+            }
+            if !res.is_empty() {
+                res.push_str(if blank > 0 {
+                    "\n\n"
+                } else {
+                    "\n"
+                });
+            }
+            blank = 0;
+            res.push_str(line);
+        }
+        (res != value).then_some(res)
+    }
+}
+
+impl Sanitizer for PlainTextSanitizer {
+    fn sanitize(&self, _predicate: &str, value: &str) -> Option<String> {
+        let res = strip_html(value);
+        (res != value).then_some(res)
+    }
+}
+
+/// The value of an attribute as the browser reads it: character references are decoded with or
+/// without the closing `;`, as are the named ones used to hide a scheme (`&colon;`, `&Tab;`, …).
+fn decode_attr(value: &str) -> String {
+    let mut res = String::with_capacity(value.len());
+    let mut rest = value;
+    while let Some(pos) = rest.find('&') {
+        res.push_str(&rest[..pos]);
+        rest = &rest[pos + 1..];
+        if let Some(num) = rest.strip_prefix('#') {
+            let (radix, digits) = match num.strip_prefix(['x', 'X']) {
+                Some(hex) => (16, hex),
+                None => (10, num),
+            };
+            let len = digits.find(|c: char| !c.is_digit(radix)).unwrap_or(digits.len());
+            if len > 0 {
+                // leading zeros do not count, `&#0000106;` is `j`
+                let code = digits[..len].trim_start_matches('0');
+                let c = u32::from_str_radix(code, radix).ok().and_then(char::from_u32).filter(|c| *c != '\0').unwrap_or('\u{fffd}');
+                res.push(c);
+                rest = &digits[len..];
+                rest = rest.strip_prefix(';').unwrap_or(rest);
+                continue;
+            }
+        }
+        let name_len = rest.find(|c: char| !c.is_ascii_alphanumeric()).unwrap_or(rest.len());
+        let decoded = match &rest[..name_len] {
+            "amp" | "AMP" => Some('&'),
+            "lt" | "LT" => Some('<'),
+            "gt" | "GT" => Some('>'),
+            "quot" | "QUOT" => Some('"'),
+            "apos" => Some('\''),
+            "colon" => Some(':'),
+            "Tab" => Some('\t'),
+            "NewLine" => Some('\n'),
+            "nbsp" => Some('\u{a0}'),
+            _ => None,
+        };
+        match decoded {
+            Some(c) => {
+                res.push(c);
+                rest = &rest[name_len..];
+                rest = rest.strip_prefix(';').unwrap_or(rest);
+            },
+            None => res.push('&'),
+        }
+    }
+    res.push_str(rest);
+    res
+}
+
+/// A URL with a scheme outside `SAFE_SCHEMES`; the value is decoded.
+fn is_unsafe_url(value: &str) -> bool {
+    // browsers ignore tabs and line breaks in URLs, and leading spaces and controls
+    let v: String = value.chars().filter(|c| !c.is_whitespace() && !c.is_control()).collect();
+    let head = &v[..v.find(['/', '?', '#']).unwrap_or(v.len())];
+    match head.split_once(':') {
+        Some((scheme, _)) => !SAFE_SCHEMES.contains(&scheme.to_ascii_lowercase().as_str()),
+        // an undecoded reference could still hide a scheme
+        None => head.contains('&'),
+    }
+}
+
+fn escape_attr(v: &str) -> String {
+    v.replace('&', "&amp;").replace('"', "&quot;").replace('<', "&lt;").replace('>', "&gt;")
+}
+
+/// End of the tag starting at `s[0] == '<'`, a `>` in quoted attribute values does not close it.
+fn tag_end(s: &str) -> Option<usize> {
+    let mut quote = None;
+    for (pos, c) in s.char_indices().skip(1) {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), _) => {},
+            (None, '"' | '\'') => quote = Some(c),
+            (None, '>') => return Some(pos),
+            _ => {},
+        }
+    }
+    None
+}
+
+fn is_name_end(c: char) -> bool {
+    c.is_whitespace() || c == '/' || c == '>'
+}
+
+/// The tag of the allowed element `name` with the allowed attributes only.
+fn clean_tag(tag: &str, name: &str) -> String {
+    let inner = tag.trim_start_matches('<').trim_end_matches('>');
+    let self_closing = inner.ends_with('/');
+    // `<svg/onload=..>`: a slash ends the name like a space
+    let name_end = inner.find(is_name_end).unwrap_or(inner.len());
+    let mut res = format!("<{}", name);
+
+    let mut rest = &inner[name_end..];
+    loop {
+        rest = rest.trim_start_matches(|c: char| c.is_whitespace() || c == '/');
+        if rest.is_empty() {
+            break;
+        }
+        let name_len = rest.find(|c: char| is_name_end(c) || c == '=').unwrap_or(rest.len()).max(1);
+        let attr = rest[..name_len].to_ascii_lowercase();
+        rest = rest[name_len..].trim_start();
+        let mut value = None;
+        if let Some(v) = rest.strip_prefix('=') {
+            let v = v.trim_start();
+            let (val, len) = match v.chars().next() {
+                Some(q @ ('"' | '\'')) => match v[1..].find(q) {
+                    Some(end) => (&v[1..end + 1], end + 2),
+                    None => (&v[1..], v.len()),
+                },
+                _ => {
+                    let end = v.find(char::is_whitespace).unwrap_or(v.len());
+                    (&v[..end], end)
+                },
+            };
+            value = Some(decode_attr(val));
+            rest = &v[len..];
+        }
+
+        if !ALLOWED_ATTRIBUTES.contains(&attr.as_str()) || (URL_ATTRIBUTES.contains(&attr.as_str()) && value.as_deref().is_some_and(is_unsafe_url)) {
+            continue;
+        }
+        res.push(' ');
+        res.push_str(&attr);
+        if let Some(v) = value {
+            res.push_str(&format!("=\"{}\"", escape_attr(&v)));
+        }
+    }
+    if self_closing {
+        res.push_str(" /");
+    }
+    res.push('>');
+    res
+}
+
+/// HTML without script, see `HtmlSanitizer`.
+pub fn sanitize_html(s: &str) -> String {
+    let mut res = String::with_capacity(s.len());
+    let mut rest = s;
+    while let Some(pos) = rest.find('<') {
+        res.push_str(&rest[..pos]);
+        rest = &rest[pos..];
+        if let Some(comment) = rest.strip_prefix("<!--") {
+            rest = comment.find("-->").map(|end| &comment[end + 3..]).unwrap_or_default();
+            continue;
+        }
+        if !rest[1..].starts_with(|c: char| c.is_ascii_alphabetic() || c == '/' || c == '!' || c == '?') {
+            // not a tag, e.g. `a < b`
+            res.push_str("&lt;");
+            rest = &rest[1..];
+            continue;
+        }
+        let Some(end) = tag_end(rest) else {
+            // an unclosed tag is dropped with the rest of the value
+            rest = "";
+            break;
+        };
+        let tag = &rest[..=end];
+        rest = &rest[end + 1..];
+
+        let name = tag[1..].trim_start_matches('/').split(is_name_end).next().unwrap_or_default().to_ascii_lowercase();
+        if !name.starts_with(|c: char| c.is_ascii_alphabetic()) {
+            // doctype, processing instruction
+            continue;
+        }
+        if DANGEROUS_ELEMENTS.contains(&name.as_str()) {
+            if !tag.starts_with("</") && !tag.ends_with("/>") {
+                let close = format!("</{}", name);
+                rest = rest.to_ascii_lowercase().find(&close).map(|pos| &rest[pos..]).unwrap_or_default();
+                rest = rest.find('>').map(|end| &rest[end + 1..]).unwrap_or_default();
+            }
+            continue;
+        }
+        if !ALLOWED_ELEMENTS.contains(&name.as_str()) {
+            continue;
+        }
+        if tag.starts_with("</") {
+            res.push_str(&format!("</{}>", name));
+        } else {
+            res.push_str(&clean_tag(tag, &name));
+        }
+    }
+    res.push_str(rest);
+    res
+}
+
+/// Sanitizers by predicate, applied on ingest to values from browsers and other untrusted clients:
+///
+/// ```text
+/// let policy = SanitizePolicy::new().with("v-s:description", Arc::new(HtmlSanitizer)).with("v-s:note", Arc::new(MarkdownSanitizer));
+/// policy.apply(&mut obj);
+/// ```
+#[derive(Clone, Default)]
+pub struct SanitizePolicy {
+    rules: Vec<(String, Arc<dyn Sanitizer>)>,
+}
+
+impl SanitizePolicy {
+    pub fn new() -> Self {
+        SanitizePolicy::default()
+    }
+
+    /// Sanitizes the values of `predicate`; a later rule for the same predicate replaces the earlier one.
+    pub fn with(mut self, predicate: &str, sanitizer: Arc<dyn Sanitizer>) -> Self {
+        self.rules.retain(|(p, _)| p != predicate);
+        self.rules.push((predicate.to_owned(), sanitizer));
+        self
+    }
+
+    pub fn get(&self, predicate: &str) -> Option<&dyn Sanitizer> {
+        self.rules.iter().find(|(p, _)| p == predicate).map(|(_, s)| s.as_ref())
+    }
+
+    /// Sanitizes the string values of the designated predicates, returns the predicates changed.
+    pub fn apply(&self, obj: &mut IndividualObj) -> Vec<String> {
+        let mut changed = Vec::new();
+        for (predicate, sanitizer) in self.rules.iter() {
+            let Some(values) = obj.resources.get_mut(predicate) else {
+                continue;
+            };
+            for r in values.iter_mut() {
+                if let Value::Str(s, _) = &mut r.value {
+                    if let Some(clean) = sanitizer.sanitize(predicate, s) {
+                        *s = clean;
+                        if !changed.contains(predicate) {
+                            changed.push(predicate.to_owned());
+                        }
+                    }
+                }
+            }
+        }
+        changed
+    }
+}
+
+impl Individual {
+    pub fn sanitize(&mut self, policy: &SanitizePolicy) -> Vec<String> {
+        self.parse_all();
+        let changed = policy.apply(self.obj_mut());
+        for predicate in changed.iter() {
+            self.modified(predicate);
+        }
+        changed
+    }
+}
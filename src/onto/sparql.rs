@@ -0,0 +1,366 @@
+use crate::onto::datatype::DataType;
+use crate::onto::datatype_registry::DatatypeRegistry;
+use crate::onto::datetime::to_utc;
+use crate::onto::filter::{parse_datetime, CompareOp, Filter, Literal};
+use crate::onto::individual::IndividualObj;
+use crate::onto::individual2turtle::extract_prefix;
+use crate::onto::resource::{Resource, Value};
+use crate::onto::turtle_formatters_with_prefixes::TurtleFormatterWithPrefixes;
+use chrono::SecondsFormat;
+use std::collections::HashMap;
+use std::fmt;
+use std::fmt::Write;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SparqlError {
+    /// A filter names a predicate that has no placeholder in the template.
+    NoVariable { predicate: String },
+    /// A template value or filter literal has no SPARQL form.
+    Value { predicate: String, msg: String },
+}
+
+impl fmt::Display for SparqlError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SparqlError::NoVariable {
+                predicate,
+            } => write!(f, "predicate [{}] has no placeholder in the template", predicate),
+            SparqlError::Value {
+                predicate,
+                msg,
+            } => write!(f, "predicate [{}]: {}", predicate, msg),
+        }
+    }
+}
+
+impl std::error::Error for SparqlError {}
+
+/// The variable name of a placeholder, `?name` with letters, digits and `_`.
+pub fn placeholder(s: &str) -> Option<&str> {
+    s.strip_prefix('?').filter(|v| !v.is_empty() && v.chars().all(|c| c.is_alphanumeric() || c == '_'))
+}
+
+fn escape(s: &str) -> String {
+    let mut res = String::with_capacity(s.len() + 2);
+    res.push('"');
+    for c in s.chars() {
+        match c {
+            '\n' => res.push_str("\\n"),
+            '\r' => res.push_str("\\r"),
+            '"' => res.push_str("\\\""),
+            '\\' => res.push_str("\\\\"),
+            c => res.push(c),
+        }
+    }
+    res.push('"');
+    res
+}
+
+fn is_pn_chars(c: char) -> bool {
+    c.is_alphanumeric() || c == '_' || c == '-' || c == '\u{b7}'
+}
+
+/// `PN_PREFIX` of the SPARQL grammar, empty for the default prefix.
+fn is_pn_prefix(s: &str) -> bool {
+    s.is_empty() || (s.starts_with(char::is_alphabetic) && !s.ends_with('.') && s.chars().all(|c| is_pn_chars(c) || c == '.'))
+}
+
+/// `PN_LOCAL` of the SPARQL grammar without backslash escapes, `%` followed by two hex digits.
+fn is_pn_local(s: &str) -> bool {
+    let mut chars = s.char_indices().peekable();
+    while let Some((pos, c)) = chars.next() {
+        let ok = match c {
+            '%' => {
+                let hex = s.get(pos + 1..pos + 3).is_some_and(|h| h.chars().all(|c| c.is_ascii_hexdigit()));
+                chars.next();
+                chars.next();
+                hex
+            },
+            ':' => true,
+            '.' => pos > 0 && chars.peek().is_some(),
+            '-' | '\u{b7}' => pos > 0,
+            c => is_pn_chars(c),
+        };
+        if !ok {
+            return false;
+        }
+    }
+    true
+}
+
+/// The characters allowed between `<` and `>` by `IRIREF`.
+fn is_iriref(s: &str) -> bool {
+    !s.is_empty() && !s.chars().any(|c| c <= ' ' || matches!(c, '<' | '>' | '"' | '{' | '}' | '|' | '^' | '`' | '\\'))
+}
+
+/// Variables for placeholders, full IRIs in `<>`, prefixed names and blank nodes as they are;
+/// anything else cannot be written into the query.
+fn term(predicate: &str, s: &str) -> Result<String, SparqlError> {
+    if let Some(v) = placeholder(s) {
+        return Ok(format!("?{}", v));
+    }
+    let valid = if s.contains("://") {
+        is_iriref(s)
+    } else if let Some(label) = s.strip_prefix("_:") {
+        !label.is_empty() && !label.starts_with(['-', '.', '\u{b7}']) && is_pn_local(label) && !label.contains([':', '%'])
+    } else {
+        s.split_once(':').is_some_and(|(prefix, local)| is_pn_prefix(prefix) && is_pn_local(local))
+    };
+    if !valid {
+        return Err(SparqlError::Value {
+            predicate: predicate.to_owned(),
+            msg: format!("[{}] is not an iri, prefixed name or blank node", s),
+        });
+    }
+    Ok(if s.contains("://") {
+        format!("<{}>", s)
+    } else {
+        s.to_owned()
+    })
+}
+
+/// Builds a SPARQL query from a template individual: its uri, predicates and values become triple
+/// patterns, `?name` in place of any of them is a variable.
+///
+/// ```text
+/// ?doc rdf:type v-s:Document ; v-s:author ?author ; v-s:created ?created .
+/// ```
+///
+/// Literals take their datatype from the builtin registry like in `to_turtle`. Filters are
+/// `onto::filter` expressions over the template predicates, compared through their placeholder;
+/// strings compared with a `Value::Uri` placeholder are uris.
+#[derive(Debug, Clone)]
+pub struct SparqlBuilder<'a> {
+    template: &'a IndividualObj,
+    filters: Vec<Filter>,
+    limit: Option<usize>,
+}
+
+impl<'a> SparqlBuilder<'a> {
+    pub fn new(template: &'a IndividualObj) -> Self {
+        SparqlBuilder {
+            template,
+            filters: Vec::new(),
+            limit: None,
+        }
+    }
+
+    /// Adds `FILTER(...)`; `v-s:created > '2024-01-01'` compares the placeholder of `v-s:created`.
+    pub fn with_filter(mut self, filter: Filter) -> Self {
+        self.filters.push(filter);
+        self
+    }
+
+    pub fn with_limit(mut self, limit: usize) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    /// Variables of the template, in order of appearance.
+    pub fn variables(&self) -> Vec<&str> {
+        let mut res = vec![];
+        let terms = self.template.resources.iter().flat_map(|(predicate, values)| {
+            std::iter::once(predicate.as_str()).chain(values.iter().filter_map(|r| match &r.value {
+                Value::Uri(s) | Value::Str(s, _) => Some(s.as_str()),
+                _ => None,
+            }))
+        });
+        for t in std::iter::once(self.template.uri.as_str()).chain(terms) {
+            if let Some(v) = placeholder(t) {
+                if !res.contains(&v) {
+                    res.push(v);
+                }
+            }
+        }
+        res
+    }
+
+    /// `SELECT` of every variable.
+    pub fn select(&self, all_prefixes: &HashMap<String, String>) -> Result<String, SparqlError> {
+        let vars: Vec<String> = self.variables().iter().map(|v| format!("?{}", v)).collect();
+        let mut q = String::new();
+        let _ = writeln!(
+            q,
+            "SELECT {} WHERE {{",
+            if vars.is_empty() {
+                "*".to_owned()
+            } else {
+                vars.join(" ")
+            }
+        );
+        self.write_where(&mut q)?;
+        q.push('}');
+        Ok(self.finish(q, all_prefixes))
+    }
+
+    /// `CONSTRUCT` of the template patterns.
+    pub fn construct(&self, all_prefixes: &HashMap<String, String>) -> Result<String, SparqlError> {
+        let mut q = String::from("CONSTRUCT {\n");
+        self.write_patterns(&mut q)?;
+        q.push_str("} WHERE {\n");
+        self.write_where(&mut q)?;
+        q.push('}');
+        Ok(self.finish(q, all_prefixes))
+    }
+
+    fn write_patterns(&self, q: &mut String) -> Result<(), SparqlError> {
+        let subject = term("@", &self.template.uri)?;
+        for (predicate, values) in &self.template.resources {
+            for r in values {
+                let _ = writeln!(q, "  {} {} {} .", subject, term(predicate, predicate)?, value_term(predicate, r)?);
+            }
+        }
+        Ok(())
+    }
+
+    fn write_where(&self, q: &mut String) -> Result<(), SparqlError> {
+        self.write_patterns(q)?;
+        for filter in self.filters.iter() {
+            let _ = writeln!(q, "  FILTER({})", self.filter_expr(filter)?);
+        }
+        Ok(())
+    }
+
+    /// The placeholder variable of a template predicate, and whether it stands for uris.
+    fn variable_of(&self, predicate: &str) -> Result<(&str, bool), SparqlError> {
+        self.template
+            .resources
+            .get(predicate)
+            .and_then(|values| {
+                values.iter().find_map(|r| match &r.value {
+                    Value::Uri(s) => placeholder(s).map(|v| (v, true)),
+                    Value::Str(s, _) => placeholder(s).map(|v| (v, false)),
+                    _ => None,
+                })
+            })
+            .ok_or_else(|| SparqlError::NoVariable {
+                predicate: predicate.to_owned(),
+            })
+    }
+
+    fn filter_expr(&self, filter: &Filter) -> Result<String, SparqlError> {
+        Ok(match filter {
+            Filter::And(a, b) => format!("({} && {})", self.filter_expr(a)?, self.filter_expr(b)?),
+            Filter::Or(a, b) => format!("({} || {})", self.filter_expr(a)?, self.filter_expr(b)?),
+            Filter::Not(a) => format!("!{}", self.filter_expr(a)?),
+            Filter::Exists(predicate) => format!("BOUND(?{})", self.variable_of(predicate)?.0),
+            Filter::Compare(predicate, op, lit) => {
+                let op = match op {
+                    CompareOp::Eq => "=",
+                    CompareOp::Ne => "!=",
+                    CompareOp::Gt => ">",
+                    CompareOp::Ge => ">=",
+                    CompareOp::Lt => "<",
+                    CompareOp::Le => "<=",
+                };
+                let (var, is_uri) = self.variable_of(predicate)?;
+                let value = match lit {
+                    Literal::Str(s) if is_uri => term(predicate, s)?,
+                    _ => literal_term(predicate, lit)?,
+                };
+                format!("?{} {} {}", var, op, value)
+            },
+        })
+    }
+
+    /// Strings of a filter written as uris.
+    fn filter_uris<'f>(&self, filter: &'f Filter, out: &mut Vec<&'f str>) {
+        match filter {
+            Filter::And(a, b) | Filter::Or(a, b) => {
+                self.filter_uris(a, out);
+                self.filter_uris(b, out);
+            },
+            Filter::Not(a) => self.filter_uris(a, out),
+            Filter::Compare(predicate, _, Literal::Str(s)) if self.variable_of(predicate).is_ok_and(|(_, is_uri)| is_uri) => out.push(s),
+            _ => {},
+        }
+    }
+
+    fn finish(&self, q: String, all_prefixes: &HashMap<String, String>) -> String {
+        let mut terms = vec![self.template.uri.as_str()];
+        for (predicate, values) in &self.template.resources {
+            terms.push(predicate);
+            for r in values {
+                if let Value::Uri(u) = &r.value {
+                    terms.push(u);
+                }
+            }
+        }
+        for filter in self.filters.iter() {
+            self.filter_uris(filter, &mut terms);
+        }
+        if q.contains("^^xsd:") {
+            terms.push("xsd:");
+        }
+
+        let mut used = HashMap::new();
+        for t in terms {
+            if placeholder(t).is_some() || !t.contains(':') {
+                continue;
+            }
+            if let Some(iri) = extract_prefix(t).and_then(|p| all_prefixes.get_key_value(p)) {
+                used.insert(iri.0.to_owned(), iri.1.to_owned());
+            }
+        }
+
+        let mut out = Vec::new();
+        if !used.is_empty() {
+            let mut formatter = TurtleFormatterWithPrefixes::new(Vec::new(), &used, false);
+            let _ = formatter.write_query_prefixes(&used);
+            out = formatter.finish().unwrap_or_default();
+        }
+        out.extend_from_slice(q.as_bytes());
+        if let Some(limit) = self.limit {
+            out.extend_from_slice(format!("\nLIMIT {}", limit).as_bytes());
+        }
+        String::from_utf8_lossy(&out).into_owned()
+    }
+}
+
+fn value_term(predicate: &str, r: &Resource) -> Result<String, SparqlError> {
+    let err = |msg: String| SparqlError::Value {
+        predicate: predicate.to_owned(),
+        msg,
+    };
+    match &r.value {
+        Value::Uri(s) => term(predicate, s),
+        Value::Str(s, _) if placeholder(s).is_some() => term(predicate, s),
+        Value::Str(s, lang) if lang.is_some() => Ok(format!("{}@{}", escape(s), lang.to_string().to_lowercase())),
+        _ => match DatatypeRegistry::builtin().datatype_of(predicate, r) {
+            Some(def) if r.rtype == DataType::String => Ok(escape(&def.format(&r.value).map_err(err)?)),
+            Some(def) => Ok(format!("{}^^{}", escape(&def.format(&r.value).map_err(err)?), def.iri())),
+            None => Err(err(format!("{:?} values have no literal form", r.rtype))),
+        },
+    }
+}
+
+/// Strings that read as dates, as `Filter::eval` reads them, become `xsd:dateTime`.
+fn literal_term(predicate: &str, lit: &Literal) -> Result<String, SparqlError> {
+    Ok(match lit {
+        Literal::Num(s) => {
+            let digits = s.strip_prefix('-').unwrap_or(s);
+            if !digits.starts_with(|c: char| c.is_ascii_digit())
+                || !digits.chars().all(|c| c.is_ascii_digit() || c == '.')
+                || digits.ends_with('.')
+                || digits.matches('.').count() > 1
+            {
+                return Err(SparqlError::Value {
+                    predicate: predicate.to_owned(),
+                    msg: format!("[{}] is not a number", s),
+                });
+            }
+            s.to_owned()
+        },
+        Literal::Bool(b) => b.to_string(),
+        Literal::Str(s) => match parse_datetime(s).filter(|_| s.parse::<i64>().is_err()) {
+            Some(ts) => {
+                let dt = to_utc(ts).map_err(|e| SparqlError::Value {
+                    predicate: predicate.to_owned(),
+                    msg: e.to_string(),
+                })?;
+                format!("\"{}\"^^xsd:dateTime", dt.to_rfc3339_opts(SecondsFormat::Secs, true))
+            },
+            None => escape(s),
+        },
+    })
+}
@@ -0,0 +1,115 @@
+use crate::onto::datatype_registry::DatatypeRegistry;
+use crate::onto::individual::{Individual, IndividualObj};
+use crate::onto::iri::compact_iri;
+use crate::onto::isomorphism::BLANK_NODE_PREFIX;
+use crate::onto::json2individual::JsonError;
+use crate::onto::resource::{Resource, Value};
+use indexmap::IndexMap;
+use serde_json::value::Value as JSONValue;
+use std::collections::HashMap;
+
+/// How `from_sparql_results` turns rows into individuals.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RowGrouping<'a> {
+    /// One individual per row, `_:r1`, `_:r2`, …, with a predicate per bound variable.
+    Rows,
+    /// One individual per value of the variable, with the distinct values of the other variables
+    /// over its rows; rows without the variable are skipped.
+    BySubject(&'a str),
+}
+
+/// The value of a binding, `{"type": "uri" | "literal" | "bnode", "value": …}`.
+fn binding_value(b: &JSONValue, all_prefixes: &HashMap<String, String>, registry: &DatatypeRegistry) -> Result<Resource, String> {
+    let value = b.get("value").and_then(|v| v.as_str()).ok_or("binding without value")?;
+    match b.get("type").and_then(|v| v.as_str()) {
+        Some("uri") => Ok(Resource::new_uri(&compact_iri(value, all_prefixes))),
+        Some("bnode") => Ok(Resource::new_uri(&format!("{}{}", BLANK_NODE_PREFIX, value))),
+        // `typed-literal` is the SPARQL 1.0 JSON form
+        Some("literal") | Some("typed-literal") => {
+            if let Some(lang) = b.get("xml:lang").and_then(|v| v.as_str()) {
+                return Ok(Resource::new_str(value, lang));
+            }
+            let def = match b.get("datatype").and_then(|v| v.as_str()) {
+                Some(dt) => match registry.get(dt) {
+                    Some(def) => Some(def),
+                    None => registry.get(&compact_iri(dt, all_prefixes)),
+                },
+                None => None,
+            };
+            match def {
+                Some(def) => def.parse(value),
+                // plain literals and unknown datatypes keep the lexical form
+                None => Ok(Resource::new_str(value, "")),
+            }
+        },
+        t => Err(format!("unknown binding type {:?}", t)),
+    }
+}
+
+fn push_distinct(obj: &mut IndividualObj, predicate: &str, r: Resource) {
+    let values = obj.resources.entry(predicate.to_owned()).or_default();
+    if !values.contains(&r) {
+        values.push(Resource {
+            order: values.len() as u16,
+            ..r
+        });
+    }
+}
+
+/// Maps `application/sparql-results+json` of a `SELECT` to individuals whose predicates are the
+/// variable names, so results can go through the usual accessors and serializers. URIs in the
+/// namespaces of `all_prefixes` become prefixed names, typed literals are parsed by the builtin
+/// registry. Every malformed row is reported, the conversion then fails.
+pub fn from_sparql_results(json: &JSONValue, grouping: RowGrouping, all_prefixes: &HashMap<String, String>) -> Result<Vec<Individual>, JsonError> {
+    let mut errors = vec![];
+    let Some(rows) = json.pointer("/results/bindings").and_then(|v| v.as_array()) else {
+        return Err(JsonError {
+            errors: vec!["expected results.bindings".to_owned()],
+        });
+    };
+
+    let registry = DatatypeRegistry::builtin();
+    let mut objs: IndexMap<String, IndividualObj> = IndexMap::new();
+    for (n, row) in rows.iter().enumerate() {
+        let Some(row) = row.as_object() else {
+            errors.push(format!("row {}: expected an object", n + 1));
+            continue;
+        };
+
+        let mut values = vec![];
+        for (var, b) in row {
+            match binding_value(b, all_prefixes, registry) {
+                Ok(r) => values.push((var.as_str(), r)),
+                Err(e) => errors.push(format!("row {}, ?{}: {}", n + 1, var, e)),
+            }
+        }
+
+        let uri = match grouping {
+            RowGrouping::Rows => format!("{}r{}", BLANK_NODE_PREFIX, n + 1),
+            RowGrouping::BySubject(var) => match values.iter().position(|(v, _)| *v == var) {
+                Some(pos) => match values.remove(pos).1.value {
+                    Value::Uri(u) => u,
+                    _ => {
+                        errors.push(format!("row {}, ?{}: expected a uri or blank node", n + 1, var));
+                        continue;
+                    },
+                },
+                None => continue,
+            },
+        };
+        let obj = objs.entry(uri.to_owned()).or_insert_with(|| IndividualObj {
+            uri,
+            ..Default::default()
+        });
+        for (var, r) in values {
+            push_distinct(obj, var, r);
+        }
+    }
+
+    if !errors.is_empty() {
+        return Err(JsonError {
+            errors,
+        });
+    }
+    Ok(objs.into_values().map(Individual::from_obj).collect())
+}
@@ -0,0 +1,171 @@
+use crate::onto::individual::{Individual, IndividualObj};
+use crate::onto::resource::Value;
+#[cfg(feature = "json")]
+use serde_json::json;
+#[cfg(feature = "json")]
+use serde_json::value::Value as JSONValue;
+use std::collections::BTreeMap;
+
+/// Use of one predicate in a dump.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PredicateStats {
+    /// Individuals having the predicate.
+    pub individuals: usize,
+    pub values: usize,
+    /// Values by datatype (`Uri`, `String`, …).
+    pub value_types: BTreeMap<String, usize>,
+    /// String values, and of them the ones with a language.
+    pub strings: usize,
+    pub strings_with_lang: usize,
+}
+
+/// Figures of a dump for planning ontology and storage changes, collected by `analyze` or by
+/// `add` one individual at a time.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DumpStats {
+    pub individuals: usize,
+    /// Individuals by `rdf:type` value, an individual with several types is counted for each;
+    /// individuals without type under "".
+    pub types: BTreeMap<String, usize>,
+    pub predicates: BTreeMap<String, PredicateStats>,
+    pub values: usize,
+    /// Values by datatype (`Uri`, `String`, …).
+    pub value_types: BTreeMap<String, usize>,
+    /// String values by language, "NONE" for values without language.
+    pub langs: BTreeMap<String, usize>,
+    /// Bytes of the stored records, of the individuals read from storage.
+    pub record_bytes: u64,
+    pub records: usize,
+    /// Bytes of the string values.
+    pub string_bytes: u64,
+}
+
+fn ratio(a: u64, b: u64) -> f64 {
+    if b == 0 {
+        0.0
+    } else {
+        a as f64 / b as f64
+    }
+}
+
+impl DumpStats {
+    pub fn new() -> Self {
+        DumpStats::default()
+    }
+
+    /// Counts a parsed individual; `record_len` is the size of its stored record, 0 if unknown.
+    pub fn add_obj(&mut self, obj: &IndividualObj, record_len: usize) {
+        self.individuals += 1;
+        if record_len > 0 {
+            self.records += 1;
+            self.record_bytes += record_len as u64;
+        }
+
+        let types: Vec<&str> = obj.resources.get("rdf:type").map(|v| v.iter().map(|r| r.get_uri()).collect()).unwrap_or_default();
+        if types.is_empty() {
+            *self.types.entry(String::new()).or_default() += 1;
+        }
+        for t in types {
+            *self.types.entry(t.to_owned()).or_default() += 1;
+        }
+
+        for (predicate, values) in obj.resources.iter() {
+            if values.is_empty() {
+                continue;
+            }
+            let p = self.predicates.entry(predicate.to_owned()).or_default();
+            p.individuals += 1;
+            p.values += values.len();
+            self.values += values.len();
+            for r in values.iter() {
+                let vtype = format!("{:?}", r.rtype);
+                *p.value_types.entry(vtype.clone()).or_default() += 1;
+                *self.value_types.entry(vtype).or_default() += 1;
+                if let Value::Str(s, lang) = &r.value {
+                    p.strings += 1;
+                    if lang.is_some() {
+                        p.strings_with_lang += 1;
+                    }
+                    self.string_bytes += s.len() as u64;
+                    *self.langs.entry(lang.to_string().to_owned()).or_default() += 1;
+                }
+            }
+        }
+    }
+
+    /// Counts an individual read from storage, with the size of its raw record.
+    pub fn add(&mut self, indv: &mut Individual) {
+        indv.parse_all();
+        self.add_obj(indv.get_obj(), indv.get_raw_len());
+    }
+
+    /// Average size of a stored record in bytes.
+    pub fn avg_record_size(&self) -> f64 {
+        ratio(self.record_bytes, self.records as u64)
+    }
+
+    pub fn avg_predicates(&self) -> f64 {
+        ratio(self.predicates.values().map(|p| p.individuals as u64).sum(), self.individuals as u64)
+    }
+
+    pub fn avg_values(&self) -> f64 {
+        ratio(self.values as u64, self.individuals as u64)
+    }
+
+    /// Average length of a string value in bytes.
+    pub fn avg_string_len(&self) -> f64 {
+        ratio(self.string_bytes, self.langs.values().sum::<usize>() as u64)
+    }
+
+    /// Share of the string values having a language, 0…1.
+    pub fn lang_coverage(&self) -> f64 {
+        let strings: usize = self.langs.values().sum();
+        ratio((strings - self.langs.get("NONE").copied().unwrap_or(0)) as u64, strings as u64)
+    }
+
+    /// The report: the counts, the averages and the predicates by frequency.
+    #[cfg(feature = "json")]
+    pub fn to_json(&self) -> JSONValue {
+        let mut predicates: Vec<(&String, &PredicateStats)> = self.predicates.iter().collect();
+        predicates.sort_by(|a, b| b.1.individuals.cmp(&a.1.individuals).then_with(|| a.0.cmp(b.0)));
+        let predicates: Vec<JSONValue> = predicates
+            .into_iter()
+            .map(|(predicate, p)| {
+                json!({
+                    "predicate": predicate,
+                    "individuals": p.individuals,
+                    "frequency": ratio(p.individuals as u64, self.individuals as u64),
+                    "values": p.values,
+                    "value_types": p.value_types,
+                    "lang_coverage": ratio(p.strings_with_lang as u64, p.strings as u64),
+                })
+            })
+            .collect();
+
+        json!({
+            "individuals": self.individuals,
+            "types": self.types,
+            "values": self.values,
+            "value_types": self.value_types,
+            "langs": self.langs,
+            "lang_coverage": self.lang_coverage(),
+            "avg_record_size": self.avg_record_size(),
+            "avg_predicates": self.avg_predicates(),
+            "avg_values": self.avg_values(),
+            "avg_string_len": self.avg_string_len(),
+            "predicates": predicates,
+        })
+    }
+}
+
+/// Statistics of the individuals of a dump, read one by one.
+pub fn analyze<I>(indvs: I) -> DumpStats
+where
+    I: IntoIterator<Item = Individual>,
+{
+    let mut stats = DumpStats::new();
+    for mut indv in indvs {
+        stats.add(&mut indv);
+    }
+    stats
+}
@@ -0,0 +1,200 @@
+use crate::onto::individual::{Individual, IndividualObj, ValueFmt};
+use crate::onto::resource::Value;
+use std::fmt;
+use std::sync::Arc;
+
+/// Individuals that `render_term` shows in place of links.
+pub trait Resolver: Send + Sync {
+    fn resolve(&self, uri: &str) -> Option<IndividualObj>;
+}
+
+impl<F> Resolver for F
+where
+    F: Fn(&str) -> Option<IndividualObj> + Send + Sync,
+{
+    fn resolve(&self, uri: &str) -> Option<IndividualObj> {
+        self(uri)
+    }
+}
+
+const RESET: &str = "\x1b[0m";
+const BOLD: &str = "\x1b[1m";
+const DIM: &str = "\x1b[2m";
+const RED: &str = "\x1b[31m";
+const GREEN: &str = "\x1b[32m";
+const YELLOW: &str = "\x1b[33m";
+const BLUE: &str = "\x1b[34m";
+const MAGENTA: &str = "\x1b[35m";
+const CYAN: &str = "\x1b[36m";
+
+/// How `render_term` writes individuals for terminals: ANSI colors, truncation of long values and
+/// expansion of links through a `Resolver`.
+#[derive(Clone)]
+pub struct TermStyle {
+    color: bool,
+    max_str_len: usize,
+    max_binary_len: usize,
+    depth: usize,
+    resolver: Option<Arc<dyn Resolver>>,
+}
+
+impl Default for TermStyle {
+    fn default() -> Self {
+        TermStyle {
+            color: true,
+            max_str_len: 80,
+            max_binary_len: 16,
+            depth: 0,
+            resolver: None,
+        }
+    }
+}
+
+impl fmt::Debug for TermStyle {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("TermStyle")
+            .field("color", &self.color)
+            .field("max_str_len", &self.max_str_len)
+            .field("max_binary_len", &self.max_binary_len)
+            .field("depth", &self.depth)
+            .field("resolver", &self.resolver.is_some())
+            .finish()
+    }
+}
+
+impl TermStyle {
+    /// Colors, strings cut after 80 characters, binaries after 16 bytes, links not expanded.
+    pub fn new() -> Self {
+        TermStyle::default()
+    }
+
+    /// ANSI colors, off for pipes and files, e.g. `with_color(std::io::stdout().is_terminal())`.
+    pub fn with_color(mut self, color: bool) -> Self {
+        self.color = color;
+        self
+    }
+
+    /// Longer strings are cut to `n` characters, 0 for no limit.
+    pub fn with_max_str_len(mut self, n: usize) -> Self {
+        self.max_str_len = n;
+        self
+    }
+
+    /// Bytes of a binary shown in hex.
+    pub fn with_max_binary_len(mut self, n: usize) -> Self {
+        self.max_binary_len = n;
+        self
+    }
+
+    /// Levels of links shown as the individuals they point to, 0 for none.
+    pub fn with_depth(mut self, depth: usize) -> Self {
+        self.depth = depth;
+        self
+    }
+
+    pub fn with_resolver(mut self, resolver: Arc<dyn Resolver>) -> Self {
+        self.resolver = Some(resolver);
+        self
+    }
+
+    fn paint(&self, out: &mut String, color: &str, text: &str) {
+        if self.color {
+            out.push_str(color);
+            out.push_str(text);
+            out.push_str(RESET);
+        } else {
+            out.push_str(text);
+        }
+    }
+
+    fn write_value(&self, out: &mut String, predicate: &str, v: &Value) {
+        match v {
+            Value::Uri(uri) if predicate == "rdf:type" => self.paint(out, &format!("{}{}", BOLD, MAGENTA), uri),
+            Value::Uri(uri) => self.paint(out, BLUE, uri),
+            Value::Str(s, lang) => {
+                let len = s.chars().count();
+                if self.max_str_len > 0 && len > self.max_str_len {
+                    let cut: String = s.chars().take(self.max_str_len).collect();
+                    self.paint(out, GREEN, &format!("{:?}…", cut));
+                } else {
+                    self.paint(out, GREEN, &format!("{:?}", s));
+                }
+                if lang.is_some() {
+                    self.paint(out, DIM, &format!("@{}", lang.to_string().to_lowercase()));
+                }
+                if self.max_str_len > 0 && len > self.max_str_len {
+                    self.paint(out, DIM, &format!(" ({} chars)", len));
+                }
+            },
+            Value::Binary(b) => {
+                let mut hex: Vec<String> = b.iter().take(self.max_binary_len).map(|b| format!("{:02x}", b)).collect();
+                if b.len() > self.max_binary_len {
+                    hex.push("…".to_owned());
+                }
+                self.paint(out, DIM, &format!("<{} bytes: {}>", b.len(), hex.join(" ")));
+            },
+            Value::Bool(_) => self.paint(out, YELLOW, &ValueFmt(v).to_string()),
+            Value::Datetime(_) => self.paint(out, RED, &ValueFmt(v).to_string()),
+            Value::Int(_) | Value::Num(_, _) => self.paint(out, CYAN, &ValueFmt(v).to_string()),
+        }
+    }
+
+    /// Writes the predicates of `obj` at `indent`, the individuals of its links below the values;
+    /// `path` are the uris being expanded, a link back to one of them is not expanded again.
+    fn write_obj(&self, out: &mut String, obj: &IndividualObj, indent: usize, path: &mut Vec<String>) {
+        path.push(obj.uri.to_owned());
+        for (predicate, values) in obj.resources.iter() {
+            out.push('\n');
+            out.push_str(&"  ".repeat(indent));
+            self.paint(out, BOLD, predicate);
+            out.push_str(": ");
+            for (idx, r) in values.iter().enumerate() {
+                if idx > 0 {
+                    out.push_str(", ");
+                }
+                self.write_value(out, predicate, &r.value);
+            }
+
+            let Some(resolver) = self.resolver.as_ref().filter(|_| path.len() <= self.depth && predicate != "rdf:type") else {
+                continue;
+            };
+            for r in values.iter() {
+                let Value::Uri(uri) = &r.value else {
+                    continue;
+                };
+                if path.contains(uri) {
+                    continue;
+                }
+                if let Some(linked) = resolver.resolve(uri) {
+                    out.push('\n');
+                    out.push_str(&"  ".repeat(indent + 1));
+                    self.paint(out, &format!("{}{}", BOLD, BLUE), uri);
+                    self.write_obj(out, &linked, indent + 2, path);
+                }
+            }
+        }
+        path.pop();
+    }
+}
+
+impl IndividualObj {
+    /// The individual for terminal output, one predicate per line, see `TermStyle`:
+    ///
+    /// ```text
+    /// let style = TermStyle::new().with_depth(1).with_resolver(Arc::new(|uri: &str| load(uri)));
+    /// println!("{}", obj.render_term(&style));
+    /// ```
+    pub fn render_term(&self, style: &TermStyle) -> String {
+        let mut out = String::new();
+        style.paint(&mut out, &format!("{}{}", BOLD, BLUE), &self.uri);
+        style.write_obj(&mut out, self, 1, &mut Vec::new());
+        out
+    }
+}
+
+impl Individual {
+    pub fn render_term(&mut self, style: &TermStyle) -> String {
+        self.parse_all();
+        self.get_obj().render_term(style)
+    }
+}
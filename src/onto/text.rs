@@ -0,0 +1,194 @@
+use crate::onto::datatype::Lang;
+use crate::onto::individual::{Individual, IndividualObj};
+use crate::onto::resource::Value;
+
+/// Which string values `collect_text` takes and how it cleans them.
+#[derive(Debug, Clone)]
+pub struct TextFilter {
+    langs: Vec<Lang>,
+    predicates: Vec<String>,
+    strip_html: bool,
+    max_value_len: usize,
+    max_len: usize,
+}
+
+impl Default for TextFilter {
+    fn default() -> Self {
+        TextFilter {
+            langs: Vec::new(),
+            predicates: Vec::new(),
+            strip_html: true,
+            max_value_len: 0,
+            max_len: 0,
+        }
+    }
+}
+
+impl TextFilter {
+    /// All string values, HTML stripped, no length limits.
+    pub fn new() -> Self {
+        TextFilter::default()
+    }
+
+    /// Languages to take, `Lang::none()` for values without language; all if empty.
+    pub fn with_langs(mut self, langs: &[Lang]) -> Self {
+        self.langs = langs.to_vec();
+        self
+    }
+
+    /// Predicates to take, in this order; all in individual order if empty.
+    pub fn with_predicates(mut self, predicates: &[&str]) -> Self {
+        self.predicates = predicates.iter().map(|p| (*p).to_owned()).collect();
+        self
+    }
+
+    pub fn with_strip_html(mut self, strip_html: bool) -> Self {
+        self.strip_html = strip_html;
+        self
+    }
+
+    /// Longer values are cut to `n` characters, 0 for no limit.
+    pub fn with_max_value_len(mut self, n: usize) -> Self {
+        self.max_value_len = n;
+        self
+    }
+
+    /// Collection stops after `n` characters in total, 0 for no limit.
+    pub fn with_max_len(mut self, n: usize) -> Self {
+        self.max_len = n;
+        self
+    }
+}
+
+const INLINE_TAGS: [&str; 14] = ["a", "abbr", "b", "code", "em", "i", "mark", "s", "small", "span", "strong", "sub", "sup", "u"];
+
+/// Text of an HTML fragment: tags, comments, `script` and `style` elements removed, entities
+/// decoded, whitespace collapsed.
+pub fn strip_html(s: &str) -> String {
+    let mut text = String::with_capacity(s.len());
+    let mut rest = s;
+    while let Some(pos) = rest.find(['<', '&']) {
+        text.push_str(&rest[..pos]);
+        rest = &rest[pos..];
+        if rest.starts_with('&') {
+            let (decoded, len) = decode_entity(rest);
+            text.push_str(&decoded);
+            rest = &rest[len..];
+        } else if let Some(comment) = rest.strip_prefix("<!--") {
+            rest = comment.find("-->").map(|end| &comment[end + 3..]).unwrap_or_default();
+        } else {
+            let end = rest.find('>').map(|end| end + 1).unwrap_or(rest.len());
+            let name =
+                rest[1..end].trim_start_matches('/').split(|c: char| c.is_whitespace() || c == '>' || c == '/').next().unwrap_or_default().to_ascii_lowercase();
+            rest = &rest[end..];
+            if name == "script" || name == "style" {
+                let close = format!("</{}", name);
+                rest = rest.to_ascii_lowercase().find(&close).map(|pos| &rest[pos..]).unwrap_or_default();
+                rest = rest.find('>').map(|end| &rest[end + 1..]).unwrap_or_default();
+            }
+            // other tags separate words
+            if !INLINE_TAGS.contains(&name.as_str()) {
+                text.push(' ');
+            }
+        }
+    }
+    text.push_str(rest);
+    text.split_whitespace().collect::<Vec<&str>>().join(" ")
+}
+
+/// The text of the entity at the start of `s` and its length, `&` as is if it is not an entity.
+fn decode_entity(s: &str) -> (String, usize) {
+    let Some(end) = s.find(';').filter(|end| *end <= 10) else {
+        return ("&".to_owned(), 1);
+    };
+    let decoded = match &s[1..end] {
+        "amp" => Some('&'),
+        "lt" => Some('<'),
+        "gt" => Some('>'),
+        "quot" => Some('"'),
+        "apos" => Some('\''),
+        "nbsp" => Some(' '),
+        e => e
+            .strip_prefix("#x")
+            .or_else(|| e.strip_prefix("#X"))
+            .map(|hex| u32::from_str_radix(hex, 16))
+            .or_else(|| e.strip_prefix('#').map(|dec| dec.parse::<u32>()))
+            .and_then(|code| code.ok())
+            .and_then(char::from_u32),
+    };
+    match decoded {
+        Some(c) => (c.to_string(), end + 1),
+        None => ("&".to_owned(), 1),
+    }
+}
+
+fn truncate_chars(s: &mut String, n: usize) {
+    if let Some((pos, _)) = s.char_indices().nth(n) {
+        s.truncate(pos);
+    }
+}
+
+impl IndividualObj {
+    /// The string values of the individual as `(predicate, text)` for full-text indexing.
+    pub fn collect_text(&self, filter: &TextFilter) -> Vec<(String, String)> {
+        let mut res = Vec::new();
+        let mut left = if filter.max_len == 0 {
+            usize::MAX
+        } else {
+            filter.max_len
+        };
+
+        let predicates: Vec<&str> = if filter.predicates.is_empty() {
+            self.resources.keys().map(|p| p.as_str()).collect()
+        } else {
+            filter.predicates.iter().map(|p| p.as_str()).collect()
+        };
+        for predicate in predicates {
+            let Some(values) = self.resources.get(predicate) else {
+                continue;
+            };
+            for r in values.iter() {
+                let Value::Str(s, lang) = &r.value else {
+                    continue;
+                };
+                if !filter.langs.is_empty() && !filter.langs.contains(lang) {
+                    continue;
+                }
+                let mut text = if filter.strip_html {
+                    strip_html(s)
+                } else {
+                    s.trim().to_owned()
+                };
+                if filter.max_value_len > 0 {
+                    truncate_chars(&mut text, filter.max_value_len);
+                }
+                if left == 0 {
+                    return res;
+                }
+                truncate_chars(&mut text, left);
+                left -= text.chars().count();
+                if !text.is_empty() {
+                    res.push((predicate.to_owned(), text));
+                }
+            }
+        }
+        res
+    }
+
+    /// The collected text as one blob, values separated by newlines.
+    pub fn collect_text_blob(&self, filter: &TextFilter) -> String {
+        self.collect_text(filter).into_iter().map(|(_, text)| text).collect::<Vec<String>>().join("\n")
+    }
+}
+
+impl Individual {
+    pub fn collect_text(&mut self, filter: &TextFilter) -> Vec<(String, String)> {
+        self.parse_all();
+        self.get_obj().collect_text(filter)
+    }
+
+    pub fn collect_text_blob(&mut self, filter: &TextFilter) -> String {
+        self.parse_all();
+        self.get_obj().collect_text_blob(filter)
+    }
+}
@@ -0,0 +1,76 @@
+use crate::onto::individual::{Individual, IndividualObj};
+use crate::onto::resource::Value;
+
+/// Boolean flag of a deleted individual.
+pub const DELETED: &str = "v-s:deleted";
+
+/// Predicates a tombstone keeps besides `v-s:deleted`: the type, for consumers that route by it,
+/// and the update counter, so replicas can order the deletion against other updates.
+pub const TOMBSTONE_PREDICATES: [&str; 2] = ["rdf:type", "v-s:updateCounter"];
+
+/// The minimal individual propagating the deletion of `uri`.
+pub fn tombstone(uri: &str) -> IndividualObj {
+    let mut obj = IndividualObj {
+        uri: uri.to_owned(),
+        ..Default::default()
+    };
+    obj.mark_deleted();
+    obj
+}
+
+impl IndividualObj {
+    /// Soft-deletes the individual, its values are kept.
+    pub fn mark_deleted(&mut self) {
+        self.set_bool(DELETED, true);
+    }
+
+    /// Undoes `mark_deleted`, returns false if the individual was not deleted.
+    pub fn restore(&mut self) -> bool {
+        self.is_deleted() && self.remove(DELETED)
+    }
+
+    pub fn is_deleted(&self) -> bool {
+        self.resources.get(DELETED).is_some_and(|values| values.iter().any(|r| r.value == Value::Bool(true)))
+    }
+
+    /// Deleted and without other predicates than the tombstone ones.
+    pub fn is_tombstone(&self) -> bool {
+        self.is_deleted() && self.resources.keys().all(|p| p == DELETED || TOMBSTONE_PREDICATES.contains(&p.as_str()))
+    }
+
+    /// The tombstone of the individual, with the values of `TOMBSTONE_PREDICATES`.
+    pub fn to_tombstone(&self) -> IndividualObj {
+        let mut obj = tombstone(&self.uri);
+        for predicate in TOMBSTONE_PREDICATES {
+            if let Some(values) = self.resources.get(predicate) {
+                obj.resources.insert(predicate.to_owned(), values.iter().map(|r| r.get_copy()).collect());
+            }
+        }
+        obj
+    }
+}
+
+impl Individual {
+    pub fn mark_deleted(&mut self) {
+        self.set_bool(DELETED, true);
+    }
+
+    pub fn restore(&mut self) -> bool {
+        self.is_deleted() && self.remove(DELETED)
+    }
+
+    pub fn is_deleted(&mut self) -> bool {
+        self.parse_all();
+        self.get_obj().is_deleted()
+    }
+
+    pub fn is_tombstone(&mut self) -> bool {
+        self.parse_all();
+        self.get_obj().is_tombstone()
+    }
+
+    pub fn to_tombstone(&mut self) -> Individual {
+        self.parse_all();
+        Individual::from_obj(self.get_obj().to_tombstone())
+    }
+}
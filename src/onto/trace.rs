@@ -0,0 +1,157 @@
+use crate::onto::envelope::is_envelope;
+use crate::onto::migrations::is_versioned;
+use crate::onto::parser::MSGPACK_MAGIC_HEADER;
+#[cfg(feature = "json")]
+use serde_json::json;
+#[cfg(feature = "json")]
+use serde_json::value::Value as JSONValue;
+use std::fmt;
+
+/// What a traced token is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Token {
+    /// The outer array (msgpack) or map (CBOR).
+    Header,
+    Uri,
+    /// The map of predicates (msgpack).
+    Predicates,
+    Predicate,
+    /// The array of values of a predicate.
+    Values,
+    Value,
+    /// Bytes after the individual.
+    Trailing,
+    /// Where the parser stopped, with its error.
+    Error,
+}
+
+impl Token {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Token::Header => "header",
+            Token::Uri => "uri",
+            Token::Predicates => "predicates",
+            Token::Predicate => "predicate",
+            Token::Values => "values",
+            Token::Value => "value",
+            Token::Trailing => "trailing",
+            Token::Error => "error",
+        }
+    }
+}
+
+/// A token of a record as the parser decoded it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TraceEvent {
+    /// Position of the token in the record.
+    pub offset: u64,
+    pub token: Token,
+    /// The predicate the token belongs to, "" for the header and the uri.
+    pub predicate: String,
+    /// The decoded value, a length or the error.
+    pub detail: String,
+}
+
+/// The tokens of one record in the order they were read, see `trace_raw`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ParseTrace {
+    /// `msgpack`, `cbor` or "" for records of other formats.
+    pub format: &'static str,
+    pub events: Vec<TraceEvent>,
+}
+
+impl ParseTrace {
+    pub fn new(format: &'static str) -> Self {
+        ParseTrace {
+            format,
+            events: Vec::new(),
+        }
+    }
+
+    pub(crate) fn push(&mut self, offset: u64, token: Token, predicate: &str, detail: String) {
+        self.events.push(TraceEvent {
+            offset,
+            token,
+            predicate: predicate.to_owned(),
+            detail,
+        });
+    }
+
+    /// The error the parser stopped with, None if the record was read to the end.
+    pub fn error(&self) -> Option<&TraceEvent> {
+        self.events.iter().find(|e| e.token == Token::Error)
+    }
+
+    #[cfg(feature = "json")]
+    pub fn to_json(&self) -> JSONValue {
+        let events: Vec<JSONValue> = self
+            .events
+            .iter()
+            .map(|e| {
+                json!({
+                    "offset": e.offset,
+                    "token": e.token.as_str(),
+                    "predicate": e.predicate,
+                    "detail": e.detail,
+                })
+            })
+            .collect();
+        json!({
+            "format": self.format,
+            "events": events,
+        })
+    }
+}
+
+/// One token per line: offset, token, predicate and detail.
+impl fmt::Display for ParseTrace {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for e in self.events.iter() {
+            writeln!(f, "{:>8} {:<10} {} {}", e.offset, e.token.as_str(), e.predicate, e.detail)?;
+        }
+        Ok(())
+    }
+}
+
+/// The detail of a `Token::Value`: datatype and value.
+#[cfg(any(feature = "msgpack", feature = "cbor"))]
+pub(crate) fn value_detail(r: &crate::onto::resource::Resource) -> String {
+    format!("{:?} {}", r.rtype, crate::onto::individual::ValueFmt(&r.value))
+}
+
+/// Decodes a stored record token by token, without building an individual, for investigating
+/// corrupted records: every token with its offset and predicate, up to the error if there is one.
+///
+/// ```text
+/// let trace = trace_raw(&data);
+/// if let Some(e) = trace.error() { eprintln!("{}", trace); }
+/// ```
+///
+/// Envelopes and versioned records are not opened, trace the body `parse_raw` would parse.
+pub fn trace_raw(data: &[u8]) -> ParseTrace {
+    let fail = |format, msg: &str| {
+        let mut trace = ParseTrace::new(format);
+        trace.push(0, Token::Error, "", msg.to_owned());
+        trace
+    };
+    if data.is_empty() {
+        return fail("", "empty record");
+    }
+    if is_envelope(data) {
+        return fail("", "envelope, trace the unpacked body");
+    }
+    if is_versioned(data) {
+        return fail("", "versioned record, trace the upgraded body");
+    }
+
+    if data.first() == Some(&MSGPACK_MAGIC_HEADER) {
+        #[cfg(feature = "msgpack")]
+        return crate::onto::msgpack2individual::trace_msgpack(data);
+        #[cfg(not(feature = "msgpack"))]
+        return fail("msgpack", "msgpack support is not built in");
+    }
+    #[cfg(feature = "cbor")]
+    return crate::onto::cbor2individual::trace_cbor(data);
+    #[cfg(not(feature = "cbor"))]
+    fail("cbor", "cbor support is not built in")
+}
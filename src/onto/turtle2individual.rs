@@ -0,0 +1,178 @@
+use crate::onto::containers::fold_lists;
+use crate::onto::datatype::Lang;
+use crate::onto::datatype_registry::DatatypeRegistry;
+use crate::onto::individual::{Individual, IndividualObj};
+use crate::onto::iri::compact_iri;
+use crate::onto::isomorphism::BLANK_NODE_PREFIX;
+use indexmap::IndexMap;
+use rio_api::model::{Literal, NamedOrBlankNode, Term, Triple};
+use rio_api::parser::TriplesParser;
+use rio_turtle::{NTriplesParser, TurtleError, TurtleParser};
+use std::collections::HashMap;
+use std::fmt;
+use std::slice;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RdfSyntax {
+    Turtle,
+    NTriples,
+}
+
+/// Options for `parse_rdf`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RdfReadOptions<'a> {
+    /// Datatypes of typed literals, `DatatypeRegistry::builtin()` when not set.
+    pub registry: Option<&'a DatatypeRegistry>,
+    /// Reads `rdf:List` and `rdf:Seq` back into ordered values, see `containers::fold_lists`.
+    pub fold_lists: bool,
+}
+
+impl<'a> RdfReadOptions<'a> {
+    pub fn with_registry(mut self, registry: &'a DatatypeRegistry) -> Self {
+        self.registry = Some(registry);
+        self
+    }
+
+    pub fn with_fold_lists(mut self, fold_lists: bool) -> Self {
+        self.fold_lists = fold_lists;
+        self
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RdfError {
+    /// The payload is not valid Turtle or N-Triples.
+    Syntax { msg: String },
+    /// A typed literal does not parse as its datatype.
+    Value { subject: String, predicate: String, msg: String },
+}
+
+impl fmt::Display for RdfError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RdfError::Syntax {
+                msg,
+            } => write!(f, "rdf syntax: {}", msg),
+            RdfError::Value {
+                subject,
+                predicate,
+                msg,
+            } => write!(f, "[{}]: predicate [{}], {}", subject, predicate, msg),
+        }
+    }
+}
+
+impl std::error::Error for RdfError {}
+
+impl From<TurtleError> for RdfError {
+    fn from(e: TurtleError) -> Self {
+        RdfError::Syntax {
+            msg: e.to_string(),
+        }
+    }
+}
+
+struct Collector<'a> {
+    objs: IndexMap<String, IndividualObj>,
+    all_prefixes: &'a HashMap<String, String>,
+    registry: &'a DatatypeRegistry,
+}
+
+impl Collector<'_> {
+    fn node(&self, n: &NamedOrBlankNode) -> String {
+        match n {
+            NamedOrBlankNode::NamedNode(n) => compact_iri(n.iri, self.all_prefixes),
+            NamedOrBlankNode::BlankNode(n) => format!("{}{}", BLANK_NODE_PREFIX, n.id),
+        }
+    }
+
+    fn add(&mut self, t: Triple) -> Result<(), RdfError> {
+        let subject = self.node(&t.subject);
+        let predicate = compact_iri(t.predicate.iri, self.all_prefixes);
+        let err = |msg: String| RdfError::Value {
+            subject: subject.to_owned(),
+            predicate: predicate.to_owned(),
+            msg,
+        };
+
+        let resource = match t.object {
+            Term::Literal(Literal::Typed {
+                value,
+                datatype,
+            }) => {
+                let def = match self.registry.get(datatype.iri) {
+                    Some(def) => Some(def),
+                    None => self.registry.get(&compact_iri(datatype.iri, self.all_prefixes)),
+                };
+                match def {
+                    Some(def) => Some(def.parse(value).map_err(err)?),
+                    // unknown datatypes keep the lexical form
+                    None => None,
+                }
+            },
+            _ => None,
+        };
+
+        let obj = self.objs.entry(subject.to_owned()).or_insert_with(|| IndividualObj {
+            uri: subject.to_owned(),
+            ..Default::default()
+        });
+        match (t.object, resource) {
+            (_, Some(r)) => obj.add_resources(&predicate, slice::from_ref(&r)),
+            (Term::NamedNode(n), _) => obj.add_uri_unchecked(&predicate, &compact_iri(n.iri, self.all_prefixes)),
+            (Term::BlankNode(n), _) => obj.add_uri_unchecked(&predicate, &format!("{}{}", BLANK_NODE_PREFIX, n.id)),
+            (
+                Term::Literal(Literal::Simple {
+                    value,
+                })
+                | Term::Literal(Literal::Typed {
+                    value,
+                    ..
+                }),
+                _,
+            ) => obj.add_string(&predicate, value, Lang::none()),
+            (
+                Term::Literal(Literal::LanguageTaggedString {
+                    value,
+                    language,
+                }),
+                _,
+            ) => obj.add_string(&predicate, value, Lang::new_from_str(language)),
+        }
+        Ok(())
+    }
+}
+
+/// Reads a Turtle or N-Triples document, e.g. the result of a SPARQL `CONSTRUCT`, into one individual
+/// per subject in order of first appearance. IRIs in the namespaces of `all_prefixes` become prefixed
+/// names, blank nodes `_:id`; typed literals are parsed by the datatype registry, literals of unknown
+/// datatypes are kept as strings.
+pub fn parse_rdf(data: &[u8], syntax: RdfSyntax, all_prefixes: &HashMap<String, String>, opts: &RdfReadOptions) -> Result<Vec<Individual>, RdfError> {
+    let registry = match opts.registry {
+        Some(v) => v,
+        None => DatatypeRegistry::builtin(),
+    };
+    let mut collector = Collector {
+        objs: IndexMap::new(),
+        all_prefixes,
+        registry,
+    };
+    match syntax {
+        RdfSyntax::Turtle => TurtleParser::new(data, None).parse_all(&mut |t| collector.add(t))?,
+        RdfSyntax::NTriples => NTriplesParser::new(data).parse_all(&mut |t| collector.add(t))?,
+    }
+
+    let mut objs: Vec<IndividualObj> = collector.objs.into_values().collect();
+    if opts.fold_lists {
+        fold_lists(&mut objs);
+    }
+    Ok(objs.into_iter().map(Individual::from_obj).collect())
+}
+
+pub fn from_turtle(data: &[u8], all_prefixes: &HashMap<String, String>) -> Result<Vec<Individual>, RdfError> {
+    parse_rdf(data, RdfSyntax::Turtle, all_prefixes, &RdfReadOptions::default())
+}
+
+pub fn from_ntriples(data: &[u8], all_prefixes: &HashMap<String, String>) -> Result<Vec<Individual>, RdfError> {
+    parse_rdf(data, RdfSyntax::NTriples, all_prefixes, &RdfReadOptions::default())
+}
@@ -0,0 +1,110 @@
+use crate::onto::individual::{Individual, IndividualObj};
+use crate::onto::iri::normalize_iri;
+use crate::onto::resource::Value;
+use std::fmt;
+
+pub const UPDATE_COUNTER: &str = "v-s:updateCounter";
+pub const CREATED: &str = "v-s:created";
+pub const EDITED: &str = "v-s:edited";
+pub const CREATOR: &str = "v-s:creator";
+pub const LAST_EDITOR: &str = "v-s:lastEditor";
+
+/// Optimistic concurrency failure: the individual was changed since the expected version was read.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VersionConflict {
+    pub uri: String,
+    pub expected: i64,
+    pub actual: i64,
+}
+
+impl fmt::Display for VersionConflict {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "version conflict on [{}]: expected {}, found {}", self.uri, self.expected, self.actual)
+    }
+}
+
+impl std::error::Error for VersionConflict {}
+
+fn first_value<'a>(obj: &'a IndividualObj, predicate: &str) -> Option<&'a Value> {
+    obj.resources.get(predicate)?.first().map(|r| &r.value)
+}
+
+impl IndividualObj {
+    /// The `v-s:updateCounter`, 0 for an individual never touched.
+    pub fn get_version(&self) -> i64 {
+        match first_value(self, UPDATE_COUNTER) {
+            Some(Value::Int(n)) => *n,
+            _ => 0,
+        }
+    }
+
+    pub fn get_created(&self) -> Option<i64> {
+        match first_value(self, CREATED) {
+            Some(Value::Datetime(t)) => Some(*t),
+            _ => None,
+        }
+    }
+
+    pub fn get_edited(&self) -> Option<i64> {
+        match first_value(self, EDITED) {
+            Some(Value::Datetime(t)) => Some(*t),
+            _ => None,
+        }
+    }
+
+    /// Records a change by `actor` at `now` (seconds): increments the update counter, sets
+    /// `v-s:edited` and `v-s:lastEditor`, and on the first change `v-s:created` and `v-s:creator`.
+    /// Returns the new version, an error and no change if `actor` is a malformed uri.
+    pub fn touch(&mut self, actor: &str, now: i64) -> Result<i64, String> {
+        let actor = normalize_iri(actor)?;
+        let version = self.get_version() + 1;
+        self.set_integer(UPDATE_COUNTER, version);
+        if self.get_created().is_none() {
+            self.set_datetime(CREATED, now);
+        }
+        if self.resources.get(CREATOR).is_none_or(|v| v.is_empty()) {
+            self.set_uri_unchecked(CREATOR, &actor);
+        }
+        self.set_datetime(EDITED, now);
+        self.set_uri_unchecked(LAST_EDITOR, &actor);
+        Ok(version)
+    }
+
+    /// Ok if the individual is still at `version`, as read before a change.
+    pub fn expect_version(&self, version: i64) -> Result<(), VersionConflict> {
+        let actual = self.get_version();
+        if actual == version {
+            Ok(())
+        } else {
+            Err(VersionConflict {
+                uri: self.uri.to_owned(),
+                expected: version,
+                actual,
+            })
+        }
+    }
+}
+
+impl Individual {
+    pub fn get_version(&mut self) -> i64 {
+        self.get_first_integer(UPDATE_COUNTER).unwrap_or(0)
+    }
+
+    pub fn get_created(&mut self) -> Option<i64> {
+        self.get_first_datetime(CREATED)
+    }
+
+    pub fn get_edited(&mut self) -> Option<i64> {
+        self.get_first_datetime(EDITED)
+    }
+
+    pub fn touch(&mut self, actor: &str, now: i64) -> Result<i64, String> {
+        let actor = normalize_iri(actor)?;
+        self.modify_any(|obj| obj.touch(&actor, now))
+    }
+
+    pub fn expect_version(&mut self, version: i64) -> Result<(), VersionConflict> {
+        self.parse_all();
+        self.get_obj().expect_version(version)
+    }
+}
@@ -0,0 +1,132 @@
+//! Malformed buffers must make the decoders fail, never panic or allocate by a length read from the input.
+#![cfg(feature = "msgpack")]
+
+use v_individual_model::onto::datatype::Lang;
+#[cfg(feature = "std")]
+use v_individual_model::onto::envelope::{self, Codec, EnvelopeError, RawEnvelope};
+use v_individual_model::onto::individual::{Individual, RawObj};
+use v_individual_model::onto::individual2msgpack::to_msgpack;
+use v_individual_model::onto::parser::parse_raw;
+
+fn sample() -> Individual {
+    let mut indv = Individual::default();
+    indv.set_id("d:corpus_1");
+    indv.add_uri("rdf:type", "v-s:Document").unwrap();
+    indv.add_string("rdfs:label", "документ", Lang::new_from_str("ru"));
+    indv.add_string("rdfs:label", "document", Lang::new_from_str("en"));
+    indv.add_string("v-s:comment", &"long text ".repeat(40), Lang::none());
+    indv.add_integer("v-s:count", -300_000);
+    indv.add_integer("v-s:count", 7);
+    indv.add_decimal_d("v-s:sum", 12345, -2);
+    indv.add_datetime("v-s:created", 1_600_000_000);
+    indv.add_bool("v-s:deleted", false);
+    indv.add_binary("v-s:blob", vec![0, 1, 2, 0xff, 0xfe]);
+    indv
+}
+
+fn msgpack_sample() -> Vec<u8> {
+    let mut buf = Vec::new();
+    to_msgpack(&sample(), &mut buf).expect("serialize sample");
+    buf
+}
+
+/// `{"@": "d:c", "v-s:n": [4([-2, 12345])], "rdfs:label": [42("текст")]}` as the cbor writer of the platform encodes it.
+#[cfg(feature = "cbor")]
+fn cbor_sample() -> Vec<u8> {
+    let mut buf = vec![0xa3, 0x61, b'@', 0x63, b'd', b':', b'c'];
+    buf.extend_from_slice(&[0x65, b'v', b'-', b's', b':', b'n', 0x81, 0xc4, 0x82, 0x21, 0x19, 0x30, 0x39]);
+    buf.extend_from_slice(&[0x6a, b'r', b'd', b'f', b's', b':', b'l', b'a', b'b', b'e', b'l', 0x81, 0xd8, 0x2a, 0x6a]);
+    buf.extend_from_slice("текст".as_bytes());
+    buf
+}
+
+/// Parses as `Individual` consumers do; the result does not matter, only that it returns.
+fn parse(buf: &[u8]) {
+    let mut indv = Individual::new_raw(RawObj::new(buf.to_vec()));
+    if parse_raw(&mut indv).is_ok() {
+        indv.parse_all();
+        let _ = indv.get_predicates();
+    }
+}
+
+fn corpus(buf: &[u8]) -> Vec<Vec<u8>> {
+    let mut res = Vec::new();
+    for len in 0..buf.len() {
+        res.push(buf[..len].to_vec());
+    }
+    for pos in 0..buf.len() {
+        for b in [0x00, 0x7f, 0x80, 0x9f, 0xc1, 0xd9, 0xda, 0xdb, 0xdc, 0xdd, 0xde, 0xdf, 0xff] {
+            let mut m = buf.to_vec();
+            m[pos] = b;
+            res.push(m);
+        }
+    }
+    res
+}
+
+#[test]
+fn msgpack_sample_parses() {
+    let mut indv = Individual::new_raw(RawObj::new(msgpack_sample()));
+    assert!(parse_raw(&mut indv).is_ok());
+    indv.parse_all();
+    assert_eq!(indv.get_id(), "d:corpus_1");
+    assert_eq!(indv.get_first_integer("v-s:count"), Some(-300_000));
+    assert_eq!(indv.get_first_bool("v-s:deleted"), Some(false));
+}
+
+#[test]
+fn msgpack_truncated_and_mutated() {
+    for buf in corpus(&msgpack_sample()) {
+        parse(&buf);
+    }
+}
+
+#[test]
+fn msgpack_oversized_lengths() {
+    // str32 and array32 headers claiming 4 GiB right after the magic header
+    let mut uri = vec![0x92, 0xdb, 0xff, 0xff, 0xff, 0xff];
+    uri.extend_from_slice(b"d:x");
+    parse(&uri);
+
+    let mut values = vec![0x92, 0xa3, b'd', b':', b'x', 0x81, 0xa3, b'v', b':', b'p', 0xdd, 0xff, 0xff, 0xff, 0xff];
+    values.extend_from_slice(&[0x92, 0x02, 0xdb, 0xff, 0xff, 0xff, 0xf0, b'a']);
+    parse(&values);
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn envelope_oversized_inner_len() {
+    let mut buf = Vec::new();
+    envelope::pack(&msgpack_sample(), Codec::None, &mut buf).unwrap();
+    let env = RawEnvelope::decode(&buf).unwrap();
+    assert_eq!(
+        env.unpack_limited(&|_| None, 16),
+        Err(EnvelopeError::TooLarge {
+            inner_len: env.inner_len,
+            max: 16
+        })
+    );
+
+    // the inner length is read before decompressing
+    buf[5..9].copy_from_slice(&[0xff, 0xff, 0xff, 0xff]);
+    assert!(matches!(envelope::unpack(&buf), Err(EnvelopeError::TooLarge { .. })));
+    let mut indv = Individual::new_raw(RawObj::new(buf));
+    assert!(parse_raw(&mut indv).is_err());
+}
+
+#[cfg(feature = "cbor")]
+#[test]
+fn cbor_truncated_and_mutated() {
+    for buf in corpus(&cbor_sample()) {
+        parse(&buf);
+    }
+}
+
+#[cfg(feature = "cbor")]
+#[test]
+fn cbor_deep_nesting() {
+    let mut buf = vec![0xa2, 0x61, b'@', 0x61, b'x', 0x61, b'p'];
+    buf.resize(buf.len() + 100_000, 0x81);
+    buf.push(0x01);
+    parse(&buf);
+}
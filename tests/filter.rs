@@ -0,0 +1,78 @@
+//! Filter expressions parse with the documented precedence, and hostile input is an error, not a stack overflow.
+#![cfg(feature = "std")]
+
+use v_individual_model::onto::filter::{CompareOp, Filter, Literal};
+use v_individual_model::onto::individual::Individual;
+
+fn exists(predicate: &str) -> Box<Filter> {
+    Box::new(Filter::Exists(predicate.to_owned()))
+}
+
+#[test]
+fn precedence() {
+    assert_eq!(Filter::parse("a || b && c").unwrap(), Filter::Or(exists("a"), Box::new(Filter::And(exists("b"), exists("c")))));
+    assert_eq!(Filter::parse("(a || b) && c").unwrap(), Filter::And(Box::new(Filter::Or(exists("a"), exists("b"))), exists("c")));
+    assert_eq!(Filter::parse("!a && b").unwrap(), Filter::And(Box::new(Filter::Not(exists("a"))), exists("b")));
+    assert_eq!(Filter::parse("a && b && c").unwrap(), Filter::And(Box::new(Filter::And(exists("a"), exists("b"))), exists("c")));
+}
+
+#[test]
+fn literals() {
+    assert_eq!(
+        Filter::parse("rdf:type == 'v-s:Document'").unwrap(),
+        Filter::Compare("rdf:type".to_owned(), CompareOp::Eq, Literal::Str("v-s:Document".to_owned()))
+    );
+    assert_eq!(Filter::parse("v-s:count >= -12.5").unwrap(), Filter::Compare("v-s:count".to_owned(), CompareOp::Ge, Literal::Num("-12.5".to_owned())));
+    assert_eq!(Filter::parse("v-s:deleted != true").unwrap(), Filter::Compare("v-s:deleted".to_owned(), CompareOp::Ne, Literal::Bool(true)));
+    assert_eq!(
+        Filter::parse(r#"rdfs:label == "a \"b\"""#).unwrap(),
+        Filter::Compare("rdfs:label".to_owned(), CompareOp::Eq, Literal::Str("a \"b\"".to_owned()))
+    );
+}
+
+#[test]
+fn errors() {
+    for (src, pos) in [
+        ("", 0),
+        ("a &&", 4),
+        ("(a", 2),
+        ("a == ", 5),
+        ("a b", 2),
+        ("a == 'b", 5),
+        ("a $ b", 2),
+        (")", 0),
+    ] {
+        let e = Filter::parse(src).unwrap_err();
+        assert_eq!(e.pos, pos, "{}: {}", src, e);
+    }
+}
+
+#[test]
+fn nesting_limit() {
+    let nested = |n: usize| format!("{}a{}", "(".repeat(n), ")".repeat(n));
+    assert!(Filter::parse(&nested(200)).is_ok());
+    assert!(Filter::parse(&nested(100_000)).unwrap_err().msg.contains("nested deeper"));
+
+    assert!(Filter::parse(&format!("{}a", "!".repeat(200))).is_ok());
+    assert!(Filter::parse(&format!("{}a", "!".repeat(100_000))).is_err());
+
+    // a chain of operators builds a tree as deep as it is long
+    let chain = |n: usize, op: &str| vec!["a"; n].join(op);
+    assert!(Filter::parse(&chain(200, " || ")).is_ok());
+    assert!(Filter::parse(&chain(100_000, " || ")).is_err());
+    assert!(Filter::parse(&chain(100_000, " && ")).is_err());
+
+    // levels left are available again
+    let src = format!("{} && {}", nested(200), nested(200));
+    assert!(Filter::parse(&src).is_ok());
+}
+
+#[test]
+fn eval_within_limit() {
+    let mut indv = Individual::default();
+    indv.add_bool("v-s:deleted", false);
+    let f = Filter::parse(&format!("{}v-s:deleted{}", "(".repeat(200), ")".repeat(200))).unwrap();
+    assert!(f.eval(indv.get_obj()));
+    let f = Filter::parse(&["v-s:missing"; 200].join(" || ")).unwrap();
+    assert!(!f.eval(indv.get_obj()));
+}
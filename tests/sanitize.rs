@@ -0,0 +1,103 @@
+//! Known XSS payloads must come out of `sanitize_html` without script.
+#![cfg(feature = "std")]
+
+use v_individual_model::onto::sanitize::sanitize_html;
+
+/// No markup that runs script is left: no event handler, script scheme or active element.
+fn assert_inert(input: &str) {
+    let out = sanitize_html(input);
+    let lower = out.to_ascii_lowercase();
+    for bad in [
+        "<script",
+        "<svg",
+        "<math",
+        "<iframe",
+        "<object",
+        "<embed",
+        "<meta",
+        "<link",
+        "<form",
+        "<template",
+        "<base",
+        "<style",
+        "javascript:",
+        "vbscript:",
+        "data:",
+    ] {
+        assert!(!lower.contains(bad), "{:?} -> {:?} contains {}", input, out, bad);
+    }
+    assert!(
+        !lower.split(|c: char| c.is_whitespace() || c == '/' || c == '<').any(|w| w.starts_with("on") && w.contains('=')),
+        "{:?} -> {:?} has an event handler",
+        input,
+        out
+    );
+}
+
+#[test]
+fn keeps_formatting() {
+    assert_eq!(sanitize_html("<p>Hello <b>world</b></p>"), "<p>Hello <b>world</b></p>");
+    assert_eq!(sanitize_html(r#"<a href="https://example.com/?a=1&amp;b=2" title='t'>x</a>"#), r#"<a href="https://example.com/?a=1&amp;b=2" title="t">x</a>"#);
+    assert_eq!(sanitize_html(r#"<a href="/docs#top">x</a>"#), r#"<a href="/docs#top">x</a>"#);
+    assert_eq!(sanitize_html("<br/>a < b"), "<br />a &lt; b");
+}
+
+#[test]
+fn drops_elements_outside_the_allowlist() {
+    assert_eq!(sanitize_html("<form action=/x><input name=a>text</form>"), "text");
+    assert_eq!(sanitize_html("<svg><circle r=1 /></svg>after"), "after");
+    assert_eq!(sanitize_html("<template><img src=x onerror=alert(1)></template>after"), "after");
+    assert_eq!(sanitize_html(r#"<meta http-equiv="refresh" content="0;url=javascript:alert(1)">"#), "");
+}
+
+#[test]
+fn slash_separated_attributes() {
+    assert_inert("<svg/onload=alert(1)>");
+    // an unquoted value runs to the next space, the browser reads the same
+    assert_eq!(sanitize_html("<img/src=x/onerror=alert(1)>"), r#"<img src="x/onerror=alert(1)">"#);
+    assert_eq!(sanitize_html("<b/onclick=alert(1)>x</b>"), "<b>x</b>");
+    assert_eq!(sanitize_html("<img/src='a.png'/onerror=alert(1)>"), r#"<img src="a.png">"#);
+}
+
+#[test]
+fn encoded_script_urls() {
+    for payload in [
+        r#"<a href="&#106;avascript:alert(1)">x</a>"#,
+        r#"<a href="&#x6A;avascript:alert(1)">x</a>"#,
+        r#"<a href="&#0000106avascript:alert(1)">x</a>"#,
+        r#"<a href="javascript&colon;alert(1)">x</a>"#,
+        r#"<a href="java&Tab;script:alert(1)">x</a>"#,
+        r#"<a href="java&#x09;script:alert(1)">x</a>"#,
+        r#"<a href=" JaVaScRiPt:alert(1)">x</a>"#,
+        r#"<a href=javascript:alert(1)>x</a>"#,
+        r#"<a href="vbscript:msgbox(1)">x</a>"#,
+        r#"<img src="data:image/svg+xml;base64,PHN2Zz4=">"#,
+        r#"<a href="&unknown;javascript:alert(1)">x</a>"#,
+    ] {
+        assert_inert(payload);
+    }
+    assert_eq!(sanitize_html(r#"<a href="&#106;avascript:alert(1)">x</a>"#), "<a>x</a>");
+}
+
+#[test]
+fn event_handlers_and_breakouts() {
+    for payload in [
+        "<img src=x onerror=alert(1)>",
+        "<IMG SRC=x ONERROR=alert(1)>",
+        "<body onload=alert(1)>",
+        r#"<img src="x" alt='"><script>alert(1)</script>'>"#,
+        "<scr<script>ipt>alert(1)</script>",
+        "<script>alert(1)</script>",
+        "<SCRIPT SRC=//x.example/x.js></SCRIPT>",
+        "<iframe src=javascript:alert(1)></iframe>",
+        "<math><mtext><a href=javascript:alert(1)>x</a></mtext></math>",
+        "<link rel=stylesheet href=javascript:alert(1)>",
+        "<base href=javascript:alert(1)//>",
+        "<div style=\"background:url(javascript:alert(1))\">x</div>",
+        "<!--><script>alert(1)</script>-->",
+        "<script",
+    ] {
+        assert_inert(payload);
+    }
+    assert_eq!(sanitize_html(r#"<img src="x" alt='"><script>'>"#), r#"<img src="x" alt="&quot;&gt;&lt;script&gt;">"#);
+}
@@ -0,0 +1,82 @@
+//! Values from filters and templates must not change the structure of the query.
+#![cfg(feature = "turtle")]
+
+use std::collections::HashMap;
+use v_individual_model::onto::datatype::Lang;
+use v_individual_model::onto::filter::Filter;
+use v_individual_model::onto::individual::Individual;
+use v_individual_model::onto::sparql::{SparqlBuilder, SparqlError};
+
+fn template() -> Individual {
+    let mut t = Individual::default();
+    t.set_id("?doc");
+    t.add_uri("rdf:type", "v-s:Document").unwrap();
+    t.add_uri_unchecked("v-s:author", "?author");
+    t.add_string("v-s:created", "?created", Lang::none());
+    t
+}
+
+fn select(t: &Individual, filter: &str) -> Result<String, SparqlError> {
+    SparqlBuilder::new(t.get_obj()).with_filter(Filter::parse(filter).expect("filter")).select(&HashMap::new())
+}
+
+#[test]
+fn uri_filters() {
+    let t = template();
+    assert!(select(&t, "v-s:author == 'd:person_1'").unwrap().contains("FILTER(?author = d:person_1)"));
+    assert!(select(&t, "v-s:author == 'http://example.com/p/1'").unwrap().contains("FILTER(?author = <http://example.com/p/1>)"));
+    assert!(select(&t, "v-s:author == 'd:a%20b'").is_ok());
+    assert!(select(&t, "v-s:author == '_:b0'").is_ok());
+}
+
+#[test]
+fn injection_through_uri_filter() {
+    let t = template();
+    for payload in [
+        "x) } UNION { ?s ?p ?o . FILTER(true",
+        "d:x) } UNION { ?s ?p ?o . FILTER(true",
+        "d:x || true",
+        "http://example.com/> } UNION { ?s ?p ?o . FILTER(<x",
+        "http://example.com/a b",
+        "http://example.com/{x}",
+        "d:x#comment",
+        "?s",
+        "d:",
+        "",
+    ] {
+        let filter = format!("v-s:author == '{}'", payload);
+        if let Ok(f) = Filter::parse(&filter) {
+            match SparqlBuilder::new(t.get_obj()).with_filter(f).select(&HashMap::new()) {
+                Err(SparqlError::Value {
+                    predicate,
+                    ..
+                }) => assert_eq!(predicate, "v-s:author"),
+                // a placeholder or the empty local name of a prefix stays a single term
+                Ok(q) if payload == "?s" || payload == "d:" => assert!(!q.contains("UNION")),
+                res => panic!("{:?} gave {:?}", payload, res),
+            }
+        }
+    }
+}
+
+#[test]
+fn string_filters_are_literals() {
+    let t = template();
+    let q = select(&t, "v-s:created == 'x\" ) } UNION { ?s ?p ?o'").unwrap();
+    assert!(q.contains(r#"FILTER(?created = "x\" ) } UNION { ?s ?p ?o")"#), "{}", q);
+}
+
+#[test]
+fn injection_through_template_values() {
+    let mut t = template();
+    t.add_uri_unchecked("v-s:link", "http://example.com/> . ?s ?p ?o . <x");
+    assert!(matches!(SparqlBuilder::new(t.get_obj()).select(&HashMap::new()), Err(SparqlError::Value { .. })));
+
+    let mut t = template();
+    t.add_uri_unchecked("v-s:p } UNION { ?s", "d:x");
+    assert!(matches!(SparqlBuilder::new(t.get_obj()).construct(&HashMap::new()), Err(SparqlError::Value { .. })));
+
+    let mut t = template();
+    t.set_id("d:x> <y");
+    assert!(SparqlBuilder::new(t.get_obj()).select(&HashMap::new()).is_err());
+}